@@ -1,4 +1,9 @@
 export type ListMapData<K, V> = [K, V][]
+/// The changes that turn one map into another, matching VecMapPatch on the server
+export type ListMapPatch<K, V> = {
+    set: ListMapData<K, V>,
+    removed: K[]
+}
 
 export default class ListMap<K, V> {
     list: ListMapData<K, V>;
@@ -42,6 +47,14 @@ export default class ListMap<K, V> {
     values(): V[] {
         return this.list.map(([k, v]) => v)
     }
+    applyPatch(patch: ListMapPatch<K, V>) {
+        for (const key of patch.removed) {
+            this.delete(key);
+        }
+        for (const [key, value] of patch.set) {
+            this.insert(key, value);
+        }
+    }
 
 
 }
\ No newline at end of file
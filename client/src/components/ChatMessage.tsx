@@ -453,6 +453,10 @@ export function translateChatMessage(
             return translate(`chatMessage.playerQuit${message.gameOver ? ".gameOver" : ""}`,
                 playerNames[message.playerIndex]
             );
+        case "playerSubstituted":
+            return translate(`chatMessage.playerSubstituted${message.bot ? ".bot" : ""}`,
+                playerNames[message.playerIndex]
+            );
         case "youDied":
             return translate("chatMessage.youDied");
         case "phaseChange":
@@ -890,6 +894,10 @@ export type ChatMessageVariant = {
     type: "playerQuit",
     playerIndex: PlayerIndex
     gameOver: boolean,
+} | {
+    type: "playerSubstituted",
+    playerIndex: PlayerIndex,
+    bot: boolean,
 } | {
     type: "phaseChange", 
     phase: PhaseState,
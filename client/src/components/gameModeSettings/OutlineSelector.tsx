@@ -33,6 +33,11 @@ export default function RoleOutlineSelector(props: RoleOutlineSelectorProps): Re
                     type: "role",
                     role: option.role
                 }
+            } else if ("customRoleSet" in option) {
+                roleOrRoleSet = {
+                    type: "customRoleSet",
+                    customRoleSet: option.customRoleSet
+                }
             } else {
                 roleOrRoleSet = {
                     type: "roleSet",
@@ -91,6 +96,11 @@ export default function RoleOutlineSelector(props: RoleOutlineSelectorProps): Re
                                         roleSet: value.roleSet
                                     }
                                     break;
+                                case "customRoleSet":
+                                    options[index] = {
+                                        customRoleSet: value.customRoleSet
+                                    }
+                                    break;
                             }
                             
                             if("winIfAny" in old)
@@ -356,12 +366,33 @@ export function RoleOrRoleSetSelector(props: Readonly<{
         getAllRoles()
     )!;
 
+    const customRoleSets = useLobbyOrGameState(
+        state => state.stateType === "lobby" ? state.customRoleSets : [],
+        ["customRoleSets"],
+        []
+    )!;
+
     const isRoleEnabled = useCallback((role: Role) => {
         return enabledRoles.includes(role)
     }, [enabledRoles])
 
     const optionsSearch: SelectOptionsSearch<string> = new Map();
 
+    // The selected set is listed too, in case it was removed from the lobby since
+    const selectedCustomRoleSet = props.roleOrRoleSet.type === "customRoleSet" ? [props.roleOrRoleSet.customRoleSet] : [];
+    [...selectedCustomRoleSet, ...customRoleSets].forEach((customRoleSet) => {
+        optionsSearch.set(JSON.stringify({type: "customRoleSet", customRoleSet}), [
+            <StyledText
+                key={0}
+                noLinks={!props.disabled}
+                className={customRoleSet.roles.every(role => !isRoleEnabled(role)) ? "keyword-disabled" : ""}
+            >
+                {translateRoleOrRoleSet({type: "customRoleSet", customRoleSet})}
+            </StyledText>,
+            translateRoleOrRoleSet({type: "customRoleSet", customRoleSet})
+        ]);
+    });
+
     ROLE_SETS.forEach((roleSet) => {
         optionsSearch.set(JSON.stringify({type: "roleSet", roleSet: roleSet}), [
             <StyledText
@@ -2,7 +2,7 @@ import { VersionConverter } from ".";
 import { GameMode, GameModeData, GameModeStorage, ShareableGameMode } from "..";
 import { Conclusion, CONCLUSIONS, INSIDER_GROUPS, InsiderGroup } from "../../../../game/gameState.d";
 import { getDefaultSettings, Settings } from "../../../../game/localStorage";
-import { RoleOutline, RoleOutlineOption, RoleSet } from "../../../../game/roleListState.d";
+import { CustomRoleSet, RoleOutline, RoleOutlineOption, RoleSet } from "../../../../game/roleListState.d";
 import { Role } from "../../../../game/roleState.d";
 import { Failure, ParseResult, ParseSuccess, Success, isFailure } from "../parse";
 import { parseName, parsePhaseTimes, parseRole, parseRoleSet } from "./initial";
@@ -220,7 +220,8 @@ function parseRoleOutlineOption(json: NonNullable<any>): ParseResult<RoleOutline
         insiderGroups?: InsiderGroup[],
         winIfAny?: Conclusion[],
         role?: Role,
-        roleSet?: RoleSet
+        roleSet?: RoleSet,
+        customRoleSet?: CustomRoleSet
     } = {}
 
 
@@ -248,6 +249,10 @@ function parseRoleOutlineOption(json: NonNullable<any>): ParseResult<RoleOutline
         const roleSetResult = parseRoleSet(json.roleSet);
         if (isFailure(roleSetResult)) return roleSetResult;
         out.roleSet = roleSetResult.value;
+    } else if ("customRoleSet" in json) {
+        const customRoleSetResult = parseCustomRoleSet(json.customRoleSet);
+        if (isFailure(customRoleSetResult)) return customRoleSetResult;
+        out.customRoleSet = customRoleSetResult.value;
     } else {
         return Failure("roleOutlineOptionNeitherRoleNorRoleSet", json);
     }
@@ -256,6 +261,28 @@ function parseRoleOutlineOption(json: NonNullable<any>): ParseResult<RoleOutline
 }
 
 
+function parseCustomRoleSet(json: NonNullable<any>): ParseResult<CustomRoleSet> {
+    if (typeof json !== "object" || Array.isArray(json)) {
+        return Failure("customRoleSetNotObject", json);
+    }
+    if (typeof json.name !== "string") {
+        return Failure("customRoleSetNameNotString", json);
+    }
+    if (!Array.isArray(json.roles)) {
+        return Failure("customRoleSetRolesNotArray", json);
+    }
+
+    const roles: ParseResult<Role>[] = json.roles.map(parseRole);
+    for (const role of roles) {
+        if (isFailure(role)) return role;
+    }
+
+    return Success({
+        name: json.name,
+        roles: roles.map(success => (success as ParseSuccess<Role>).value)
+    });
+}
+
 function parseRoleOutlineOptionWinIfAny(json: NonNullable<any>): ParseResult<Conclusion[]> {
     if (!Array.isArray(json)) {
         return Failure("winIfAnyNotArray", json);
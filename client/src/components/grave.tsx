@@ -49,6 +49,8 @@ function UnobscuredGrave(props: Readonly<{
                         return translate("role."+killer.value+".name");
                     case "roleSet":
                         return translate(killer.value);
+                    case "customRoleSet":
+                        return killer.value;
                     default:
                         return translate("grave.killer."+killer.type);
                 }
@@ -2,12 +2,16 @@ import { WikiArticleLink } from "../components/WikiArticleLink";
 import { DoomsayerGuess } from "../menu/game/gameScreenContent/AbilityMenu/RoleSpecificMenus/LargeDoomsayerMenu";
 import { AbilityInput } from "./abilityInput";
 import { PhaseType, PhaseTimes, PlayerIndex, State, Verdict, ModifierType } from "./gameState.d";
-import { ToClientPacket, ToServerPacket } from "./packet";
-import { RoleList, RoleOutline } from "./roleListState.d";
+import { LobbyVisibility, StartSettings, ToClientPacket, ToServerPacket } from "./packet";
+import { CustomRoleSet, RoleConstraint, RoleList, RoleOutline } from "./roleListState.d";
 import { Role } from "./roleState.d";
+import { ListMapData } from "../ListMap";
+import { RejoinCredentials } from "./localStorage";
 
 export type Server = {
     ws: WebSocket | null,
+    /** The last sequenced packet received, so a dropped connection can resume from it */
+    lastSequence: number | null,
 
     open(): Promise<boolean>;
     sendPacket(packets: ToServerPacket): void;
@@ -53,22 +57,37 @@ export type GameManager = {
      */
     sendHostPacket(): Promise<boolean>;
     /**
+     * Private lobbies need `credentials`, or else the password or invite code saved with the reconnect data
      * @returns A promise that will be fulfilled as true if the join was 
      *          successful and false if the join was unsuccessful
      */
-    sendRejoinPacket(roomCode: number, playerId: number): Promise<boolean>;
+    sendRejoinPacket(roomCode: number, playerId: number, lastSequence?: number, credentials?: RejoinCredentials): Promise<boolean>;
+    /**
+     * Reconnects to the game after the connection dropped, without resending the whole game
+     * @returns A promise that will be fulfilled as true if the game was rejoined
+     */
+    resumeGame(): Promise<boolean>;
+    /**
+     * @returns A promise that will be fulfilled as true if the join was 
+     *          successful and false if the join was unsuccessful
+     */
+    sendJoinPacket(roomCode: number, password?: string): Promise<boolean>;
     /**
      * @returns A promise that will be fulfilled as true if the join was 
      *          successful and false if the join was unsuccessful
      */
-    sendJoinPacket(roomCode: number): Promise<boolean>;
+    sendJoinWithInviteCodePacket(inviteCode: string, password?: string): Promise<boolean>;
+    sendAnyJoinPacket(packet: ToServerPacket & { type: "join" | "joinWithInviteCode" }): Promise<boolean>;
     sendKickPlayerPacket(playerId: number): void;
     sendSetSpectatorPacket(spectator: boolean): void;
     sendSetNamePacket(name: string): void;
     sendReadyUpPacket(ready: boolean): void;
     sendSendLobbyMessagePacket(text: string): void;
     sendSetLobbyNamePacket(name: string): void;
+    sendSetLobbyVisibilityPacket(visibility: LobbyVisibility): void;
+    sendSetLobbyStartSettingsPacket(startSettings: StartSettings): void;
     sendStartGamePacket(): Promise<boolean>;
+    sendAddBotPacket(): void;
     sendBackToLobbyPacket(): void;
     sendSetPhaseTimePacket(phase: PhaseType, time: number): void;
     sendSetPhaseTimesPacket(phaseTimeSettings: PhaseTimes): void;
@@ -85,6 +104,10 @@ export type GameManager = {
     sendSendWhisperPacket(playerIndex: number, text: string): void;
     sendEnabledRolesPacket(roles: Role[]): void;
     sendEnabledModifiersPacket(modifiers: ModifierType[]): void;
+    sendSetRoleWeightsPacket(roleWeights: ListMapData<Role, number>): void;
+    sendSetRoleConstraintsPacket(constraints: RoleConstraint[]): void;
+    sendSetCustomRoleSetsPacket(customRoleSets: CustomRoleSet[]): void;
+    sendLoadPresetPacket(name: string): void;
 
     sendAbilityInput(input: AbilityInput): void;
     sendSetDoomsayerGuess(guesses: [
@@ -107,6 +130,8 @@ export type GameManager = {
     sendHostEndGamePacket(): void;
     sendHostSkipPhase(): void;
     sendHostSetPlayerNamePacket(player_id: number, name: string): void;
+    sendHostSubstituteBotPacket(playerIndex: PlayerIndex): void;
+    sendClaimPlayerPacket(playerIndex: PlayerIndex): void;
 
     messageListener(serverMessage: ToClientPacket): void;
 
@@ -6,12 +6,13 @@ import CONFIG from "./../resources/config.json"
 import React from "react";
 import { PhaseType, PhaseTimes, Verdict, PlayerIndex } from "./gameState.d";
 import { GameManager, Server, StateListener } from "./gameManager.d";
-import { LobbyPreviewData, ToClientPacket, ToServerPacket } from "./packet";
+import { LobbyPreviewData, PROTOCOL_VERSION, ToClientPacket, ToServerPacket } from "./packet";
+import { decodeMessagePack } from "./messagePack";
 import { RoleOutline } from "./roleListState.d";
 import translate from "./lang";
 import PlayMenu from "../menu/main/PlayMenu";
 import { createGameState, createLobbyState } from "./gameState";
-import { deleteReconnectData } from "./localStorage";
+import { deleteReconnectData, loadReconnectData, RejoinCredentials, saveReconnectCredentials } from "./localStorage";
 import AudioController from "../menu/AudioController";
 import ListMap from "../ListMap";
 
@@ -196,13 +197,16 @@ export function createGameManager(): GameManager {
 
             return promise;
         },
-        sendRejoinPacket(roomCode: number, playerId: number) {
+        sendRejoinPacket(roomCode: number, playerId: number, lastSequence?: number, givenCredentials?: RejoinCredentials) {
             let completePromise: (success: boolean) => void;
             const promise = new Promise<boolean>((resolver) => {
                 completePromise = resolver;
             });
             const onJoined: StateListener = (type) => {
-                if (type === "acceptJoin") {
+                if (type === "acceptJoin" || type === "resumeAccepted") {
+                    if (givenCredentials !== undefined) {
+                        saveReconnectCredentials(givenCredentials);
+                    }
                     completePromise(true);
                     GAME_MANAGER.removeStateListener(onJoined);
                 } else if (type === "rejectJoin") {
@@ -215,22 +219,65 @@ export function createGameManager(): GameManager {
             };
             GAME_MANAGER.addStateListener(onJoined);
 
+            const reconnectData = loadReconnectData();
+            const credentials = givenCredentials ?? (reconnectData?.roomCode === roomCode ? reconnectData : undefined);
             this.server.sendPacket({
                 type: "reJoin",
                 roomCode,
-                playerId
+                playerId,
+                lastSequence,
+                password: credentials?.password,
+                inviteCode: credentials?.inviteCode
             });
 
 
             return promise;
         },
-        sendJoinPacket(roomCode: number) {
+        async resumeGame() {
+            if (this.state.stateType !== "game" || this.state.myId === null || this.server.lastSequence === null) {
+                return false;
+            }
+            const roomCode = this.state.roomCode;
+            const playerId = this.state.myId;
+            const lastSequence = this.server.lastSequence;
+            // Cleared while reconnecting so a second drop doesn't try again
+            this.server.lastSequence = null;
+
+            if (!await this.server.open()) {
+                return false;
+            }
+            const resumed = await this.sendRejoinPacket(roomCode, playerId, lastSequence);
+            if (resumed) {
+                this.server.lastSequence ??= lastSequence;
+            }
+            return resumed;
+        },
+        sendJoinPacket(roomCode: number, password?: string) {
+            return this.sendAnyJoinPacket({
+                type: "join",
+                roomCode,
+                password
+            });
+        },
+        sendJoinWithInviteCodePacket(inviteCode: string, password?: string) {
+            return this.sendAnyJoinPacket({
+                type: "joinWithInviteCode",
+                inviteCode,
+                password
+            });
+        },
+        sendAnyJoinPacket(packet) {
             let completePromise: (success: boolean) => void;
             const promise = new Promise<boolean>((resolver) => {
                 completePromise = resolver;
             });
             const onJoined: StateListener = (type) => {
                 if (type === "acceptJoin") {
+                    // Needed again to rejoin
+                    saveReconnectCredentials({
+                        password: packet.password,
+                        inviteCode: packet.type === "joinWithInviteCode" ? packet.inviteCode : undefined
+                    });
                     completePromise(true);
                     GAME_MANAGER.removeStateListener(onJoined);
                 } else if (type === "rejectJoin") {
@@ -243,10 +290,7 @@ export function createGameManager(): GameManager {
             };
             GAME_MANAGER.addStateListener(onJoined);
 
-            this.server.sendPacket({
-                type: "join",
-                roomCode
-            });
+            this.server.sendPacket(packet);
 
             return promise;
         },
@@ -290,6 +334,23 @@ export function createGameManager(): GameManager {
                 name: name
             });
         },
+        sendSetLobbyVisibilityPacket(visibility) {
+            this.server.sendPacket({
+                type: "setLobbyVisibility",
+                visibility
+            });
+        },
+        sendSetLobbyStartSettingsPacket(startSettings) {
+            this.server.sendPacket({
+                type: "setLobbyStartSettings",
+                startSettings
+            });
+        },
+        sendAddBotPacket() {
+            this.server.sendPacket({
+                type: "addBot"
+            });
+        },
         sendStartGamePacket() {
             let completePromise: (success: boolean) => void;
             let promise = new Promise<boolean>((resolver) => {
@@ -408,6 +469,30 @@ export function createGameManager(): GameManager {
                 modifiers: modifiers
             });
         },
+        sendSetRoleWeightsPacket(roleWeights) {
+            this.server.sendPacket({
+                type: "setRoleWeights",
+                roleWeights
+            });
+        },
+        sendSetRoleConstraintsPacket(constraints) {
+            this.server.sendPacket({
+                type: "setRoleConstraints",
+                constraints
+            });
+        },
+        sendSetCustomRoleSetsPacket(customRoleSets) {
+            this.server.sendPacket({
+                type: "setCustomRoleSets",
+                customRoleSets
+            });
+        },
+        sendLoadPresetPacket(name) {
+            this.server.sendPacket({
+                type: "loadPreset",
+                name
+            });
+        },
 
         sendAbilityInput(input) {
             this.server.sendPacket({
@@ -472,6 +557,18 @@ export function createGameManager(): GameManager {
                 name
             })
         },
+        sendHostSubstituteBotPacket(playerIndex) {
+            this.server.sendPacket({
+                type: "hostSubstituteBot",
+                playerIndex
+            })
+        },
+        sendClaimPlayerPacket(playerIndex) {
+            this.server.sendPacket({
+                type: "claimPlayer",
+                playerIndex
+            })
+        },
 
         messageListener(serverMessage) {
             messageListener(serverMessage);
@@ -498,11 +595,13 @@ function createServer(){
 
     let Server: Server = {
         ws: null,
+        lastSequence: null,
 
         open : () => {
             let address = CONFIG.address;
             try {
                 Server.ws = new WebSocket(address);
+                Server.ws.binaryType = "arraybuffer";
             } catch {
                 return Promise.resolve(false);
             }
@@ -520,6 +619,11 @@ function createServer(){
             ]);
 
             Server.ws.onopen = (event: Event)=>{
+                Server.sendPacket({
+                    type: "hello",
+                    protocolVersion: PROTOCOL_VERSION,
+                    capabilities: ["messagePack", "resume", "statePatches"]
+                });
                 completePromise(true);
                 console.log("Connected to server.");
             };
@@ -530,15 +634,22 @@ function createServer(){
                 if (Server.ws === null) return; // We closed it ourselves
                 Server.ws = null;
 
-                ANCHOR_CONTROLLER?.pushErrorCard({
-                    title: translate("notification.connectionFailed"), 
-                    body: ""
+                GAME_MANAGER.resumeGame().then((resumed) => {
+                    if (resumed) return;
+                    ANCHOR_CONTROLLER?.pushErrorCard({
+                        title: translate("notification.connectionFailed"), 
+                        body: ""
+                    });
+                    ANCHOR_CONTROLLER?.setContent(<StartMenu/>);
                 });
-                ANCHOR_CONTROLLER?.setContent(<StartMenu/>);
             };
-            Server.ws.onmessage = (event: MessageEvent<string>)=>{
+            Server.ws.onmessage = (event: MessageEvent<string | ArrayBuffer>)=>{
+                // Binary frames are MessagePack, once the server accepts our hello
                 GAME_MANAGER.messageListener(
-                    JSON.parse(event.data) as ToClientPacket
+                    (typeof event.data === "string" 
+                        ? JSON.parse(event.data) 
+                        : decodeMessagePack(new Uint8Array(event.data))
+                    ) as ToClientPacket
                 );
             };
             Server.ws.onerror = (event: Event) => {
@@ -566,6 +677,7 @@ function createServer(){
             
             Server.ws.close();
             Server.ws = null;
+            Server.lastSequence = null;
         }
         
     }
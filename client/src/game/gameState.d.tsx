@@ -1,8 +1,8 @@
 import { Grave } from "./graveState";
 import { ChatMessage } from "../components/ChatMessage";
 import { Role, RoleState } from "./roleState.d";
-import { RoleList } from "./roleListState.d";
-import { LobbyPreviewData } from "./packet";
+import { CustomRoleSet, RoleConstraint, RoleList } from "./roleListState.d";
+import { LobbyPreviewData, LobbyVisibility, StartSettings } from "./packet";
 import { ChatFilter } from "../menu/game/gameScreenContent/ChatMenu";
 import { ControllerID, SavedController } from "./abilityInput";
 import translate from "./lang";
@@ -27,6 +27,11 @@ export type LobbyState = {
     stateType: "lobby"
     roomCode: number,
     lobbyName: string,
+    visibility: LobbyVisibility,
+    inviteCode: string | null,
+    startSettings: StartSettings,
+    /// Seconds until the game starts by itself, while it's counting down
+    startCountdown: number | null,
 
     myId: number | null,
 
@@ -34,10 +39,28 @@ export type LobbyState = {
     phaseTimes: PhaseTimes,
     enabledRoles: Role[],
     enabledModifiers: ModifierType[],
+    roleWeights: ListMapData<Role, number>,
+    roleConstraints: RoleConstraint[],
+    customRoleSets: CustomRoleSet[],
+    presets: PresetSummary[],
+    roleListAnalysis: RoleListAnalysis | null,
 
     players: ListMap<LobbyClientID, LobbyClient>,
     chatMessages: ChatMessage[],
 }
+export type PresetSummary = {
+    name: string,
+    description: string,
+    players: number
+}
+/// Outline indices count from 0, like the role list
+export type RoleListAnalysis = {
+    unsatisfiableOutlines: number[],
+    maxCountConflicts: { outlines: number[], roles: Role[] }[],
+    samples: number,
+    failedGenerations: number,
+    instantGameOvers: number
+}
 export type LobbyClient = {
     ready: "host" | "ready" | "notReady",
     connection: ClientConnection,
@@ -60,7 +83,7 @@ export type LobbyClientType = {
     type: "spectator"
 } | PlayerClientType;
 export type PlayerClientType = {
-    type: "player",
+    type: "player" | "bot",
     name: string,
 }
 
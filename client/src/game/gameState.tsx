@@ -21,6 +21,10 @@ export function createLobbyState(): LobbyState {
         stateType: "lobby",
         roomCode: 0,
         lobbyName: "Mafia Lobby",
+        visibility: { type: "public" },
+        inviteCode: null,
+        startSettings: { autoStart: false, minPlayers: null, maxPlayers: null, idleHostTimeoutSecs: null },
+        startCountdown: null,
 
         myId: null,
 
@@ -28,6 +32,11 @@ export function createLobbyState(): LobbyState {
         phaseTimes: defaultPhaseTimes(),
         enabledRoles: [],
         enabledModifiers: [],
+        roleWeights: [],
+        roleConstraints: [],
+        customRoleSets: [],
+        presets: [],
+        roleListAnalysis: null,
 
         players: new ListMap<LobbyClientID, LobbyClient>(),
         chatMessages: [],
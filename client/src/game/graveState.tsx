@@ -29,6 +29,10 @@ export type GraveDeathCause = {
 export type GraveKiller = {
     type: "roleSet"
     value: RoleSet
+} | {
+    /// The name of one of the lobby's custom role sets
+    type: "customRoleSet"
+    value: string
 } | {
     type: "suicide"
 } | {
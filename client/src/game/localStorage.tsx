@@ -7,22 +7,49 @@ import { ContentMenu } from "../menu/game/GameScreen";
 import { ParseResult, Success } from "../components/gameModeSettings/gameMode/parse";
 
 
+/** What a private lobby needs to be rejoined, same as to be joined */
+export type RejoinCredentials = {
+    password?: string,
+    inviteCode?: string,
+}
+
 export function saveReconnectData(roomCode: number, playerId: number) {
+    const previous = loadReconnectData();
+    // Still in the same lobby, so its password and invite code still work
+    const credentials = previous?.roomCode === roomCode ? {
+        "password": previous.password,
+        "inviteCode": previous.inviteCode
+    } : {};
     localStorage.setItem(
         "reconnectData",
         JSON.stringify({
             "roomCode": roomCode,
             "playerId": playerId,
+            ...credentials,
             "lastSaveTime": Date.now()
         })
     );
 }
+export function saveReconnectCredentials(credentials: RejoinCredentials) {
+    const reconnectData = loadReconnectData();
+    if (reconnectData === null) return;
+
+    localStorage.setItem(
+        "reconnectData",
+        JSON.stringify({
+            ...reconnectData,
+            ...credentials
+        })
+    );
+}
 export function deleteReconnectData() {
     localStorage.removeItem("reconnectData");
 }
 export function loadReconnectData(): {
     roomCode: number,
     playerId: number,
+    password?: string,
+    inviteCode?: string,
     lastSaveTime: number,
 } | null {
     let dataJSON = localStorage.getItem("reconnectData");
@@ -4,11 +4,11 @@ import { ANCHOR_CONTROLLER, chatMessageToAudio } from "./../menu/Anchor";
 import GAME_MANAGER from "./../index";
 import GameScreen from "./../menu/game/GameScreen";
 import { ToClientPacket } from "./packet";
-import { GameClient, PlayerIndex, Tag } from "./gameState.d";
+import { GameClient, PlayerClientType, PlayerIndex, Tag } from "./gameState.d";
 import { Role } from "./roleState.d";
 import translate from "./lang";
 import { computePlayerKeywordData, computePlayerKeywordDataForLobby } from "../components/StyledText";
-import { deleteReconnectData, loadSettingsParsed, saveReconnectData } from "./localStorage";
+import { deleteReconnectData, loadSettingsParsed, saveReconnectCredentials, saveReconnectData } from "./localStorage";
 import { WikiArticleLink } from "../components/WikiArticleLink";
 import React from "react";
 import WikiArticle from "../components/WikiArticle";
@@ -41,6 +41,16 @@ export default function messageListener(packet: ToClientPacket){
                 });
             }
         break;
+        case "helloAccepted":
+            console.log(`Speaking protocol version ${packet.protocolVersion}`);
+        break;
+        case "sequenced":
+            GAME_MANAGER.server.lastSequence = packet.sequence;
+            messageListener(packet.packet);
+        break;
+        case "resumeAccepted":
+            console.log("Resumed game after reconnecting.");
+        break;
         case "rateLimitExceeded":
             ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rateLimitExceeded"), body: "" });
         break;
@@ -48,6 +58,16 @@ export default function messageListener(packet: ToClientPacket){
             GAME_MANAGER.setOutsideLobbyState();
             ANCHOR_CONTROLLER?.setContent(<PlayMenu/>);
         break;
+        case "lobbyCrashed":
+            ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.lobbyCrashed"), body: translate("notification.lobbyCrashed.body") });
+            deleteReconnectData();
+        break;
+        case "serverShuttingDown":
+            ANCHOR_CONTROLLER?.pushErrorCard({ 
+                title: translate("notification.serverShuttingDown"), 
+                body: translate("notification.serverShuttingDown.body", Math.ceil(packet.secondsLeft / 60)) 
+            });
+        break;
         case "forcedDisconnect":
             GAME_MANAGER.setDisconnectedState();
             ANCHOR_CONTROLLER?.setContent(<StartMenu/>);
@@ -99,6 +119,21 @@ export default function messageListener(packet: ToClientPacket){
                 case "serverBusy":
                     ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectJoin"), body: translate("notification.rejectJoin.serverBusy") });
                 break;
+                case "serverShuttingDown":
+                    ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectJoin"), body: translate("notification.rejectJoin.serverShuttingDown") });
+                break;
+                case "incompatibleVersion":
+                    ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectJoin"), body: translate("notification.rejectJoin.incompatibleVersion") });
+                break;
+                case "passwordRequired":
+                    ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectJoin"), body: translate("notification.rejectJoin.passwordRequired") });
+                break;
+                case "wrongPassword":
+                    ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectJoin"), body: translate("notification.rejectJoin.wrongPassword") });
+                break;
+                case "tooManyPasswordAttempts":
+                    ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectJoin"), body: translate("notification.rejectJoin.tooManyPasswordAttempts") });
+                break;
                 case "playerTaken":
                     ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectJoin"), body: translate("notification.rejectJoin.playerTaken") });
                 break;
@@ -131,6 +166,12 @@ export default function messageListener(packet: ToClientPacket){
                 case "tooManyCLients":
                     ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectStart"), body: translate("notification.rejectStart.tooManyClients") });
                 break;
+                case "tooFewPlayers":
+                    ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectStart"), body: translate("notification.rejectStart.tooFewPlayers") });
+                break;
+                case "tooManyPlayers":
+                    ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectStart"), body: translate("notification.rejectStart.tooManyPlayers") });
+                break;
                 default:
                     ANCHOR_CONTROLLER?.pushErrorCard({ title: translate("notification.rejectStart"), body: "" });
                     console.error(`${packet.type} message response not implemented: ${packet.reason}`);
@@ -221,11 +262,18 @@ export default function messageListener(packet: ToClientPacket){
                 // Recompute keyword data, since player names are keywords.
                 computePlayerKeywordDataForLobby(
                     Array.from(GAME_MANAGER.state.players.values())
-                        .filter(client => client.clientType.type === "player")
-                        .map(client => (client.clientType as { type: "player", name: string }).name)
+                        .filter(client => client.clientType.type !== "spectator")
+                        .map(client => (client.clientType as PlayerClientType).name)
                 );
             }
         break;
+        case "lobbyClientsPatch":
+            if(GAME_MANAGER.state.stateType === "lobby"){
+                const clients = new ListMap([...GAME_MANAGER.state.players.entries()]);
+                clients.applyPatch(packet.clients);
+                messageListener({ type: "lobbyClients", clients: clients.entries() });
+            }
+        break;
         case "hostData":
             if (GAME_MANAGER.state.stateType === "game") {
                 GAME_MANAGER.state.host = {
@@ -238,6 +286,31 @@ export default function messageListener(packet: ToClientPacket){
                 GAME_MANAGER.state.lobbyName = packet.name;
             }
         break;
+        case "lobbyVisibility":
+            if(GAME_MANAGER.state.stateType === "lobby"){
+                GAME_MANAGER.state.visibility = packet.visibility;
+            }
+            // Only hosts are told the password
+            if(packet.visibility.type === "password" && packet.visibility.password !== ""){
+                saveReconnectCredentials({ password: packet.visibility.password });
+            }
+        break;
+        case "lobbyInviteCode":
+            if(GAME_MANAGER.state.stateType === "lobby"){
+                GAME_MANAGER.state.inviteCode = packet.inviteCode;
+            }
+            saveReconnectCredentials({ inviteCode: packet.inviteCode });
+        break;
+        case "lobbyStartSettings":
+            if(GAME_MANAGER.state.stateType === "lobby"){
+                GAME_MANAGER.state.startSettings = packet.startSettings;
+            }
+        break;
+        case "startCountdown":
+            if(GAME_MANAGER.state.stateType === "lobby"){
+                GAME_MANAGER.state.startCountdown = packet.secondsLeft;
+            }
+        break;
         case "startGame": 
             if (GAME_MANAGER.state.stateType === "lobby") {
                 const isSpectator = GAME_MANAGER.state.players.get(GAME_MANAGER.state.myId!)?.clientType.type === "spectator";
@@ -323,6 +396,26 @@ export default function messageListener(packet: ToClientPacket){
             if(GAME_MANAGER.state.stateType === "lobby" || GAME_MANAGER.state.stateType === "game")
                 GAME_MANAGER.state.enabledModifiers = packet.modifiers;
         break;
+        case "roleWeights":
+            if(GAME_MANAGER.state.stateType === "lobby")
+                GAME_MANAGER.state.roleWeights = packet.roleWeights;
+        break;
+        case "roleConstraints":
+            if(GAME_MANAGER.state.stateType === "lobby")
+                GAME_MANAGER.state.roleConstraints = packet.constraints;
+        break;
+        case "customRoleSets":
+            if(GAME_MANAGER.state.stateType === "lobby")
+                GAME_MANAGER.state.customRoleSets = packet.customRoleSets;
+        break;
+        case "presets":
+            if(GAME_MANAGER.state.stateType === "lobby")
+                GAME_MANAGER.state.presets = packet.presets;
+        break;
+        case "roleListAnalysis":
+            if(GAME_MANAGER.state.stateType === "lobby")
+                GAME_MANAGER.state.roleListAnalysis = packet.analysis;
+        break;
         case "phase":
             if(GAME_MANAGER.state.stateType === "game"){
                 GAME_MANAGER.state.phaseState = packet.phase;
@@ -364,6 +457,17 @@ export default function messageListener(packet: ToClientPacket){
                 GAME_MANAGER.state.players = [...GAME_MANAGER.state.players];
             }
         break;
+        case "playerVotesPatch":
+            if(GAME_MANAGER.state.stateType === "game"){
+                const votes = new ListMap<PlayerIndex, number>(
+                    GAME_MANAGER.state.players
+                        .filter(player => player.numVoted !== 0)
+                        .map(player => [player.index, player.numVoted])
+                );
+                votes.applyPatch(packet.votesForPlayer);
+                messageListener({ type: "playerVotes", votesForPlayer: votes.entries() });
+            }
+        break;
         case "yourSendChatGroups":
             if(GAME_MANAGER.state.stateType === "game" && GAME_MANAGER.state.clientState.type === "player"){
                 GAME_MANAGER.state.clientState.sendChatGroups = [...packet.sendChatGroups];
@@ -380,6 +484,16 @@ export default function messageListener(packet: ToClientPacket){
                     packet.save.sort((a, b) => sortControllerIdCompare(a[0],b[0]));
             }
         break;
+        case "yourAllowedControllersPatch":
+            if(GAME_MANAGER.state.stateType === "game" && GAME_MANAGER.state.clientState.type === "player"){
+                const controllers = new ListMap(
+                    [...GAME_MANAGER.state.clientState.savedControllers],
+                    (a, b) => JSON.stringify(a) === JSON.stringify(b)
+                );
+                controllers.applyPatch(packet.save);
+                messageListener({ type: "yourAllowedControllers", save: controllers.entries() });
+            }
+        break;
         case "yourRoleLabels":
             if(GAME_MANAGER.state.stateType === "game"){
                 for (const player of GAME_MANAGER.state.players) {
@@ -395,6 +509,17 @@ export default function messageListener(packet: ToClientPacket){
                 GAME_MANAGER.state.players = [...GAME_MANAGER.state.players];
             }
         break;
+        case "yourRoleLabelsPatch":
+            if(GAME_MANAGER.state.stateType === "game"){
+                const roleLabels = new ListMap<PlayerIndex, Role>(
+                    GAME_MANAGER.state.players
+                        .filter(player => player.roleLabel !== null)
+                        .map(player => [player.index, player.roleLabel as Role])
+                );
+                roleLabels.applyPatch(packet.roleLabels);
+                messageListener({ type: "yourRoleLabels", roleLabels: roleLabels.entries() });
+            }
+        break;
         case "yourPlayerTags":
             if(GAME_MANAGER.state.stateType === "game"){
                 for(let i = 0; i < GAME_MANAGER.state.players.length; i++){
@@ -411,6 +536,17 @@ export default function messageListener(packet: ToClientPacket){
                 GAME_MANAGER.state.players = [...GAME_MANAGER.state.players];
             }
         break;
+        case "yourPlayerTagsPatch":
+            if(GAME_MANAGER.state.stateType === "game"){
+                const playerTags = new ListMap<PlayerIndex, Tag[]>(
+                    GAME_MANAGER.state.players
+                        .filter(player => player.playerTags.length !== 0)
+                        .map(player => [player.index, player.playerTags])
+                );
+                playerTags.applyPatch(packet.playerTags);
+                messageListener({ type: "yourPlayerTags", playerTags: playerTags.entries() });
+            }
+        break;
         case "yourWill":
             if(GAME_MANAGER.state.stateType === "game" && GAME_MANAGER.state.clientState.type === "player"){
                 GAME_MANAGER.state.clientState.will = packet.will;
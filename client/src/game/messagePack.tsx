@@ -0,0 +1,98 @@
+/// Decodes the MessagePack the server sends in binary frames into the same values JSON.parse would give.
+/// Map keys become object keys, and binary data becomes an array of bytes.
+export function decodeMessagePack(bytes: Uint8Array): unknown {
+    const reader = new MessagePackReader(bytes);
+    const value = reader.read();
+    if (reader.offset !== bytes.length) {
+        throw new Error(`MessagePack has ${bytes.length - reader.offset} trailing bytes`);
+    }
+    return value;
+}
+
+class MessagePackReader {
+    private bytes: Uint8Array;
+    private view: DataView;
+    private textDecoder = new TextDecoder();
+    offset = 0;
+
+    constructor(bytes: Uint8Array) {
+        this.bytes = bytes;
+        this.view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+    }
+
+    read(): unknown {
+        const type = this.uint8();
+
+        if (type <= 0x7f) return type;
+        if (type <= 0x8f) return this.map(type & 0x0f);
+        if (type <= 0x9f) return this.array(type & 0x0f);
+        if (type <= 0xbf) return this.string(type & 0x1f);
+        if (type >= 0xe0) return type - 0x100;
+
+        switch (type) {
+            case 0xc0: return null;
+            case 0xc2: return false;
+            case 0xc3: return true;
+            case 0xc4: return this.binary(this.uint8());
+            case 0xc5: return this.binary(this.uint16());
+            case 0xc6: return this.binary(this.uint32());
+            case 0xca: return this.advance(4, () => this.view.getFloat32(this.offset));
+            case 0xcb: return this.advance(8, () => this.view.getFloat64(this.offset));
+            case 0xcc: return this.uint8();
+            case 0xcd: return this.uint16();
+            case 0xce: return this.uint32();
+            case 0xcf: return this.advance(8, () => Number(this.view.getBigUint64(this.offset)));
+            case 0xd0: return this.advance(1, () => this.view.getInt8(this.offset));
+            case 0xd1: return this.advance(2, () => this.view.getInt16(this.offset));
+            case 0xd2: return this.advance(4, () => this.view.getInt32(this.offset));
+            case 0xd3: return this.advance(8, () => Number(this.view.getBigInt64(this.offset)));
+            case 0xd9: return this.string(this.uint8());
+            case 0xda: return this.string(this.uint16());
+            case 0xdb: return this.string(this.uint32());
+            case 0xdc: return this.array(this.uint16());
+            case 0xdd: return this.array(this.uint32());
+            case 0xde: return this.map(this.uint16());
+            case 0xdf: return this.map(this.uint32());
+            default: throw new Error(`Unsupported MessagePack type 0x${type.toString(16)}`);
+        }
+    }
+
+    private advance<T>(length: number, read: () => T): T {
+        if (this.offset + length > this.bytes.length) {
+            throw new Error("MessagePack ended early");
+        }
+        const value = read();
+        this.offset += length;
+        return value;
+    }
+    private uint8(): number {
+        return this.advance(1, () => this.view.getUint8(this.offset));
+    }
+    private uint16(): number {
+        return this.advance(2, () => this.view.getUint16(this.offset));
+    }
+    private uint32(): number {
+        return this.advance(4, () => this.view.getUint32(this.offset));
+    }
+    private string(length: number): string {
+        return this.advance(length, () => this.textDecoder.decode(this.bytes.subarray(this.offset, this.offset + length)));
+    }
+    private binary(length: number): number[] {
+        return this.advance(length, () => Array.from(this.bytes.subarray(this.offset, this.offset + length)));
+    }
+    private array(length: number): unknown[] {
+        const array = [];
+        for (let i = 0; i < length; i++) {
+            array.push(this.read());
+        }
+        return array;
+    }
+    private map(length: number): Record<string, unknown> {
+        const map: Record<string, unknown> = {};
+        for (let i = 0; i < length; i++) {
+            const key = this.read();
+            map[String(key)] = this.read();
+        }
+        return map;
+    }
+}
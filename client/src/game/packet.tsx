@@ -1,21 +1,54 @@
-import { PhaseType, PlayerIndex, Verdict, PhaseTimes, Tag, LobbyClientID, ChatGroup, PhaseState, LobbyClient, ModifierType, InsiderGroup, GameClient } from "./gameState.d"
+import { PhaseType, PlayerIndex, Verdict, PhaseTimes, Tag, LobbyClientID, ChatGroup, PhaseState, LobbyClient, ModifierType, InsiderGroup, GameClient, PresetSummary, RoleListAnalysis } from "./gameState.d"
 import { Grave } from "./graveState"
 import { ChatMessage } from "../components/ChatMessage"
-import { RoleList, RoleOutline } from "./roleListState.d"
+import { CustomRoleSet, RoleConstraint, RoleList, RoleOutline } from "./roleListState.d"
 import { Role, RoleState } from "./roleState.d"
 import { DoomsayerGuess } from "../menu/game/gameScreenContent/AbilityMenu/RoleSpecificMenus/LargeDoomsayerMenu"
 import { KiraGuess } from "../menu/game/gameScreenContent/AbilityMenu/AbilitySelectionTypes/KiraSelectionMenu"
 import { AbilityInput, ControllerID, SavedController } from "./abilityInput"
-import { ListMapData } from "../ListMap"
+import { ListMapData, ListMapPatch } from "../ListMap"
+
+/// Must match PROTOCOL_VERSION on the server
+export const PROTOCOL_VERSION = 11;
+
+export type Capability = "messagePack" | "resume" | "statePatches";
 
 export type LobbyPreviewData = {
     name: string,
     inGame : boolean,
+    passwordProtected: boolean,
     players: [LobbyClientID, string][]
 }
 
+export type LobbyVisibility = {
+    type: "public"
+} | {
+    type: "unlisted"
+} | {
+    type: "password",
+    password: string
+}
+
+/// Player counts include bots, but not spectators
+export type StartSettings = {
+    autoStart: boolean,
+    minPlayers: number | null,
+    maxPlayers: number | null,
+    idleHostTimeoutSecs: number | null
+}
+
 export type ToClientPacket = {
     type: "pong",
+} | {
+    type: "helloAccepted",
+    protocolVersion: number,
+    capabilities: Capability[]
+} | {
+    type: "sequenced",
+    sequence: number,
+    packet: ToClientPacket
+} | {
+    type: "resumeAccepted"
 } | {
     type: "hostData",
     clients: ListMapData<LobbyClientID, GameClient>
@@ -25,6 +58,11 @@ export type ToClientPacket = {
     type: "forcedOutsideLobby"
 } | {
     type: "forcedDisconnect"
+} | {
+    type: "lobbyCrashed"
+} | {
+    type: "serverShuttingDown",
+    secondsLeft: number
 } | {
     type: "lobbyList",
     lobbies: Record<number, LobbyPreviewData>,
@@ -45,9 +83,24 @@ export type ToClientPacket = {
 } | {
     type: "lobbyClients",
     clients: ListMapData<LobbyClientID, LobbyClient>
+} | {
+    type: "lobbyClientsPatch",
+    clients: ListMapPatch<LobbyClientID, LobbyClient>
 } | {
     type: "lobbyName",
     name: string
+} | {
+    type: "lobbyVisibility",
+    visibility: LobbyVisibility
+} | {
+    type: "lobbyInviteCode",
+    inviteCode: string
+} | {
+    type: "lobbyStartSettings",
+    startSettings: StartSettings
+} | {
+    type: "startCountdown",
+    secondsLeft: number | null
 } | {
     type: "yourPlayerIndex",
     playerIndex: PlayerIndex
@@ -95,6 +148,21 @@ export type ToClientPacket = {
 } | {
     type: "enabledModifiers",
     modifiers: ModifierType[]
+} | {
+    type: "roleWeights",
+    roleWeights: ListMapData<Role, number>
+} | {
+    type: "roleConstraints",
+    constraints: RoleConstraint[]
+} | {
+    type: "customRoleSets",
+    customRoleSets: CustomRoleSet[]
+} | {
+    type: "presets",
+    presets: PresetSummary[]
+} | {
+    type: "roleListAnalysis",
+    analysis: RoleListAnalysis
 } |
 // Game
 {
@@ -110,6 +178,9 @@ export type ToClientPacket = {
 } | {
     type: "playerVotes",
     votesForPlayer: ListMapData<number, number> 
+} | {
+    type: "playerVotesPatch",
+    votesForPlayer: ListMapPatch<number, number> 
 } | {
     type: "yourSendChatGroups",
     sendChatGroups: ChatGroup[]
@@ -119,12 +190,21 @@ export type ToClientPacket = {
 } | {
     type: "yourAllowedControllers",
     save: ListMapData<ControllerID, SavedController>,
+} | {
+    type: "yourAllowedControllersPatch",
+    save: ListMapPatch<ControllerID, SavedController>,
 } | {
     type: "yourRoleLabels",
     roleLabels: ListMapData<PlayerIndex, Role> 
+} | {
+    type: "yourRoleLabelsPatch",
+    roleLabels: ListMapPatch<PlayerIndex, Role> 
 } | {
     type: "yourPlayerTags",
     playerTags: ListMapData<PlayerIndex, Tag[]> 
+} | {
+    type: "yourPlayerTagsPatch",
+    playerTags: ListMapPatch<PlayerIndex, Tag[]> 
 } | {
     type: "yourWill",
     will: string
@@ -164,6 +244,10 @@ export type ToClientPacket = {
 }
 
 export type ToServerPacket = {
+    type: "hello",
+    protocolVersion: number,
+    capabilities: Capability[]
+} | {
     type: "ping",
 } | {
     type: "lobbyListRequest",
@@ -173,9 +257,17 @@ export type ToServerPacket = {
     type: "reJoin",
     roomCode: number,
     playerId: number,
+    lastSequence?: number,
+    password?: string,
+    inviteCode?: string,
 } | {
     type: "join", 
-    roomCode: number
+    roomCode: number,
+    password?: string
+} | {
+    type: "joinWithInviteCode",
+    inviteCode: string,
+    password?: string
 } | {
     type: "host",
 } | {
@@ -198,8 +290,16 @@ export type ToServerPacket = {
 } | {
     type: "setLobbyName", 
     name: string
+} | {
+    type: "setLobbyVisibility",
+    visibility: LobbyVisibility
+} | {
+    type: "setLobbyStartSettings",
+    startSettings: StartSettings
 } | {
     type: "startGame",
+} | {
+    type: "addBot",
 } | {
     type: "setRoleList", 
     roleList: RoleList,
@@ -222,6 +322,18 @@ export type ToServerPacket = {
 } | {
     type: "setEnabledModifiers",
     modifiers: ModifierType[]
+} | {
+    type: "setRoleWeights",
+    roleWeights: ListMapData<Role, number>
+} | {
+    type: "setRoleConstraints",
+    constraints: RoleConstraint[]
+} | {
+    type: "setCustomRoleSets",
+    customRoleSets: CustomRoleSet[]
+} | {
+    type: "loadPreset",
+    name: string
 } |
 // Game
 {
@@ -285,4 +397,10 @@ export type ToServerPacket = {
     type: "hostForceSetPlayerName",
     id: number,
     name: string
+} | {
+    type: "hostSubstituteBot",
+    playerIndex: PlayerIndex
+} | {
+    type: "claimPlayer",
+    playerIndex: PlayerIndex
 }
\ No newline at end of file
@@ -46,13 +46,34 @@ export function getRoleSetsFromRole(role: Role): RoleSet[] {
 
 export type RoleOutline = RoleOutlineOption[];
 
-export type RoleOutlineOption = ({
+/// A role set the host named. Outlines keep a copy of its roles.
+export type CustomRoleSet = {
+    name: string,
+    roles: Role[]
+}
+
+export type RoleOutlineOptionRoles = {
     roleSet: RoleSet
 } | {
     role: Role
-}) & {
+} | {
+    customRoleSet: CustomRoleSet
+}
+export type RoleOutlineOption = RoleOutlineOptionRoles & {
     winIfAny?: Conclusion[],
-    insiderGroups?: InsiderGroup[]
+    insiderGroups?: InsiderGroup[],
+    /// Left out when it's 1
+    weight?: number
+}
+
+export type RoleConstraint = {
+    type: "atMost",
+    count: number,
+    roles: RoleOutlineOptionRoles[]
+} | {
+    type: "requires",
+    roles: RoleOutlineOptionRoles[],
+    requires: RoleOutlineOptionRoles[]
 }
 
 export type RoleOrRoleSet = ({
@@ -61,6 +82,9 @@ export type RoleOrRoleSet = ({
 } | {
     type: "role",
     role: Role
+} | {
+    type: "customRoleSet",
+    customRoleSet: CustomRoleSet
 })
 
 
@@ -82,19 +106,38 @@ export function translateRoleOutlineOption(roleOutlineOption: RoleOutlineOption)
     if (roleOutlineOption.winIfAny) {
         out += `${translateWinCondition({ type: "gameConclusionReached", winIfAny: roleOutlineOption.winIfAny })} `
     }
-    if ("roleSet" in roleOutlineOption) {
-        out += translate(roleOutlineOption.roleSet)
-    } else {
-        out += translate("role."+roleOutlineOption.role+".name")
+    out += translateRoleOutlineOptionRoles(roleOutlineOption)
+    if (roleOutlineOption.weight !== undefined && roleOutlineOption.weight !== 1) {
+        out += ` ${translate("roleOutlineOption.weight", roleOutlineOption.weight)}`
     }
     return out;
 }
+export function translateRoleOutlineOptionRoles(roles: RoleOutlineOptionRoles): string {
+    if ("roleSet" in roles) {
+        return translate(roles.roleSet)
+    } else if ("customRoleSet" in roles) {
+        return roles.customRoleSet.name
+    } else {
+        return translate("role."+roles.role+".name")
+    }
+}
+export function translateRoleConstraint(constraint: RoleConstraint): string {
+    const list = (roles: RoleOutlineOptionRoles[]) => roles.map(translateRoleOutlineOptionRoles).join(", ");
+    switch (constraint.type) {
+        case "atMost":
+            return translate("roleConstraint.atMost", constraint.count, list(constraint.roles))
+        case "requires":
+            return translate("roleConstraint.requires", list(constraint.roles), list(constraint.requires))
+    }
+}
 export function translateRoleOrRoleSet(roleOrRoleSet: RoleOrRoleSet): string {
     switch (roleOrRoleSet.type) {
         case "roleSet":
             return translate(roleOrRoleSet.roleSet)
         case "role":
             return translate("role."+roleOrRoleSet.role+".name")
+        case "customRoleSet":
+            return roleOrRoleSet.customRoleSet.name
     }
 }
 export function getRolesFromOutline(roleOutline: RoleOutline): Role[] {
@@ -103,6 +146,8 @@ export function getRolesFromOutline(roleOutline: RoleOutline): Role[] {
 export function getRolesFromOutlineOption(roleOutlineOption: RoleOutlineOption): Role[] {
     if ("roleSet" in roleOutlineOption) {
         return getRolesFromRoleSet(roleOutlineOption.roleSet)
+    } else if ("customRoleSet" in roleOutlineOption) {
+        return roleOutlineOption.customRoleSet.roles
     } else {
         return [roleOutlineOption.role]
     }
@@ -113,6 +158,8 @@ export function getRolesFromRoleOrRoleSet(roleOrRoleSet: RoleOrRoleSet): Role[]
             return getRolesFromRoleSet(roleOrRoleSet.roleSet)
         case "role":
             return [roleOrRoleSet.role]
+        case "customRoleSet":
+            return roleOrRoleSet.customRoleSet.roles
     }
 }
 
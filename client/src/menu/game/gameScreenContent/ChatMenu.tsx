@@ -222,7 +222,7 @@ export function ChatTextInput(props: Readonly<{
                 return state.players.map(player => player.toString())
             } else if (state.stateType === "lobby") {
                 return Array.from(state.players.values())
-                    .filter(player => player.clientType.type === "player")
+                    .filter(player => player.clientType.type !== "spectator")
                     .map(player => (player.clientType as PlayerClientType).name)
             }
         }
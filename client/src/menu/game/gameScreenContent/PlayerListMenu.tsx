@@ -140,6 +140,22 @@ function PlayerCard(props: Readonly<{
     );
 
     const spectator = useSpectator();
+    const host = useGameState(
+        gameState => gameState.host !== null,
+        ["hostData"]
+    )!;
+    // Players who quit can be taken over until the game ends
+    const abandoned = useGameState(
+        gameState => {
+            if (!gameState.ticking || !gameState.players[props.playerIndex].alive) return false;
+            const last = findLast(gameState.chatMessages, message =>
+                (message.variant.type === "playerQuit" || message.variant.type === "playerSubstituted") &&
+                message.variant.playerIndex === props.playerIndex
+            );
+            return last?.variant.type === "playerQuit";
+        },
+        ["addChatMessages", "gamePlayers", "playerAlive", "gameOver"]
+    )!;
 
     return <><div 
         className={`player-card`}
@@ -172,6 +188,19 @@ function PlayerCard(props: Readonly<{
             phaseState.type === "nomination" && playerAlive && 
             <StyledText>{translate("menu.playerList.player.votes", numVoted)}</StyledText>
         }
+        {spectator && abandoned &&
+            <Button onClick={() => GAME_MANAGER.sendClaimPlayerPacket(props.playerIndex)}>
+                {translate("menu.playerList.button.claim")}
+            </Button>
+        }
+        {host && abandoned &&
+            <Button
+                onClick={() => GAME_MANAGER.sendHostSubstituteBotPacket(props.playerIndex)}
+                aria-label={translate("menu.playerList.button.substituteBot")}
+            >
+                <Icon>smart_toy</Icon>
+            </Button>
+        }
         {spectator ||
             <Button 
                 disabled={isPlayerSelf || whispersDisabled}
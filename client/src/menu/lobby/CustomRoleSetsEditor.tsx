@@ -0,0 +1,72 @@
+import React, { ReactElement, useState } from "react";
+import GAME_MANAGER from "../..";
+import translate from "../../game/lang";
+import { Button } from "../../components/Button";
+import Icon from "../../components/Icon";
+import StyledText from "../../components/StyledText";
+import { useLobbyState } from "../../components/useHooks";
+import { RoleOrRoleSetSelector } from "../../components/gameModeSettings/OutlineSelector";
+import { getRolesFromRoleOrRoleSet, RoleOrRoleSet } from "../../game/roleListState.d";
+import { Role } from "../../game/roleState.d";
+
+/// Named role sets the host makes for this lobby, usable in outlines like the built-in ones
+export default function CustomRoleSetsEditor(props: Readonly<{
+    isHost: boolean
+}>): ReactElement | null {
+    const customRoleSets = useLobbyState(
+        lobbyState => lobbyState.customRoleSets,
+        ["customRoleSets"]
+    )!;
+
+    const [name, setName] = useState("");
+    const [roles, setRoles] = useState<Role[]>([]);
+    const [roleOrRoleSet, setRoleOrRoleSet] = useState<RoleOrRoleSet>({ type: "role", role: "jester" });
+
+    if (!props.isHost && customRoleSets.length === 0) return null;
+
+    const translateRoles = (roles: Role[]) => roles.map(role => translate("role."+role+".name")).join(", ");
+
+    const create = () => {
+        GAME_MANAGER.sendSetCustomRoleSetsPacket([...customRoleSets, { name: name.trim(), roles }]);
+        setName("");
+        setRoles([]);
+    }
+
+    return <section className="chat-menu-colors selector-section">
+        <h2>{translate("menu.lobby.customRoleSets")}</h2>
+        <ul>
+            {customRoleSets.map((customRoleSet, index) =>
+                <li key={customRoleSet.name}>
+                    <StyledText>{customRoleSet.name + ": " + translateRoles(customRoleSet.roles)}</StyledText>
+                    {props.isHost && <Button onClick={() =>
+                        GAME_MANAGER.sendSetCustomRoleSetsPacket(customRoleSets.filter((_, i) => i !== index))
+                    }><Icon size="tiny">remove</Icon></Button>}
+                </li>
+            )}
+        </ul>
+        {props.isHost && <div>
+            <input type="text" value={name}
+                onChange={e => setName(e.target.value)}
+                placeholder={translate("menu.lobby.customRoleSets.namePlaceholder")}
+            />
+            <div>
+                <RoleOrRoleSetSelector
+                    roleOrRoleSet={roleOrRoleSet}
+                    onChange={setRoleOrRoleSet}
+                />
+                <Button onClick={() =>
+                    setRoles(roles => Array.from(new Set([...roles, ...getRolesFromRoleOrRoleSet(roleOrRoleSet)])))
+                }>
+                    <Icon size="tiny">add</Icon>
+                </Button>
+            </div>
+            {roles.length !== 0 && <StyledText>{translateRoles(roles)}</StyledText>}
+            <Button
+                disabled={name.trim() === "" || roles.length === 0}
+                onClick={create}
+            >
+                {translate("menu.lobby.customRoleSets.create")}
+            </Button>
+        </div>}
+    </section>
+}
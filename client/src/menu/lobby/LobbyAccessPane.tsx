@@ -0,0 +1,88 @@
+import React, { ReactElement, useEffect, useState } from "react";
+import GAME_MANAGER from "../..";
+import translate from "../../game/lang";
+import Icon from "../../components/Icon";
+import { useLobbyState } from "../../components/useHooks";
+import { CopyButton } from "../../components/ClipboardButtons";
+import { LobbyVisibility } from "../../game/packet";
+
+export default function LobbyAccessPane(props: Readonly<{
+    isHost: boolean
+}>): ReactElement {
+    const visibility = useLobbyState(
+        lobbyState => lobbyState.visibility,
+        ["lobbyVisibility"]
+    )!;
+    const inviteCode = useLobbyState(
+        lobbyState => lobbyState.inviteCode,
+        ["lobbyInviteCode"]
+    )!;
+
+    return <section className="player-list-menu-colors selector-section lobby-access-pane">
+        <h2>{translate("menu.lobby.access")}</h2>
+        {props.isHost
+            ? <VisibilitySelector visibility={visibility}/>
+            : <p>{translate(`menu.lobby.visibility.${visibility.type}`)}</p>}
+        {inviteCode !== null && <InviteLinkButton inviteCode={inviteCode}/>}
+    </section>
+}
+
+function VisibilitySelector(props: Readonly<{
+    visibility: LobbyVisibility
+}>): ReactElement {
+    const [selectedType, setSelectedType] = useState<LobbyVisibility["type"]>(props.visibility.type);
+    const [password, setPassword] = useState<string>(
+        props.visibility.type === "password" ? props.visibility.password : ""
+    );
+
+    useEffect(() => {
+        setSelectedType(props.visibility.type);
+        if (props.visibility.type === "password") {
+            setPassword(props.visibility.password);
+        }
+    }, [props.visibility]);
+
+    return <div>
+        <select
+            value={selectedType}
+            onChange={e => {
+                const type = e.target.value as LobbyVisibility["type"];
+                setSelectedType(type);
+                if (type !== "password") {
+                    GAME_MANAGER.sendSetLobbyVisibilityPacket({ type });
+                } else if (password !== "") {
+                    // Otherwise it's sent once a password is entered, since an empty one makes the lobby public
+                    GAME_MANAGER.sendSetLobbyVisibilityPacket({ type, password });
+                }
+            }}
+        >
+            {(["public", "unlisted", "password"] as const).map(type =>
+                <option key={type} value={type}>{translate(`menu.lobby.visibility.${type}`)}</option>
+            )}
+        </select>
+        {selectedType === "password" && <input
+            type="text"
+            value={password}
+            placeholder={translate("menu.lobby.field.passwordPlaceholder")}
+            onChange={e => setPassword(e.target.value)}
+            onKeyUp={e => {
+                if (e.key !== "Enter") return;
+                GAME_MANAGER.sendSetLobbyVisibilityPacket({ type: "password", password });
+            }}
+            onBlur={() => GAME_MANAGER.sendSetLobbyVisibilityPacket({ type: "password", password })}
+        />}
+    </div>
+}
+
+function InviteLinkButton(props: Readonly<{
+    inviteCode: string
+}>): ReactElement {
+    const link = new URL(window.location.href);
+    link.pathname = "/connect";
+    link.search = "";
+    link.searchParams.set("invite", props.inviteCode);
+
+    return <CopyButton text={link.toString()}>
+        <Icon>link</Icon> {translate("menu.lobby.button.copyInviteLink", props.inviteCode)}
+    </CopyButton>
+}
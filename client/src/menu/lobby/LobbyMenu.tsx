@@ -15,11 +15,17 @@ import { OutlineListSelector } from "../../components/gameModeSettings/OutlineSe
 import EnabledRoleSelector from "../../components/gameModeSettings/EnabledRoleSelector";
 import Icon from "../../components/Icon";
 import { GameModeSelector } from "../../components/gameModeSettings/GameModeSelector";
+import ServerPresetSelector from "./ServerPresetSelector";
+import RoleListAnalysisDisplay from "./RoleListAnalysisDisplay";
+import RoleGenerationRulesDisplay from "./RoleGenerationRulesDisplay";
+import CustomRoleSetsEditor from "./CustomRoleSetsEditor";
 import LobbyChatMenu from "./LobbyChatMenu";
 import { useLobbyState } from "../../components/useHooks";
 import { Button } from "../../components/Button";
 import { EnabledModifiersSelector } from "../../components/gameModeSettings/EnabledModifiersSelector";
 import LobbyNamePane from "./LobbyNamePane";
+import LobbyAccessPane from "./LobbyAccessPane";
+import LobbyStartPane from "./LobbyStartPane";
 
 export default function LobbyMenu(): ReactElement {
     const isSpectator = useLobbyState(
@@ -58,6 +64,8 @@ export default function LobbyMenu(): ReactElement {
                 ? <main>
                     <div>
                         <LobbyNamePane />
+                        <LobbyAccessPane isHost={isHost}/>
+                        <LobbyStartPane isHost={isHost}/>
                         <LobbyPlayerList />
                         <LobbyChatMenu spectator={isSpectator}/>
                     </div>
@@ -68,6 +76,8 @@ export default function LobbyMenu(): ReactElement {
                 : <main>
                     <div>
                         <LobbyNamePane />
+                        <LobbyAccessPane isHost={isHost}/>
+                        <LobbyStartPane isHost={isHost}/>
                         <LobbyPlayerList />
                     </div>
                     <div>
@@ -139,6 +149,7 @@ function LobbyMenuSettings(props: Readonly<{
                 GAME_MANAGER.sendEnabledModifiersPacket(gameMode.enabledModifiers);
             }}
         />}
+        {props.isHost === true && <ServerPresetSelector/>}
         <EnabledModifiersSelector
             disabled={!props.isHost}
             onChange={modifiers => GAME_MANAGER.sendEnabledModifiersPacket(modifiers)}
@@ -147,6 +158,9 @@ function LobbyMenuSettings(props: Readonly<{
             disabled={!props.isHost}
             onChange={pts => GAME_MANAGER.sendSetPhaseTimesPacket(pts)}
         />
+        <RoleListAnalysisDisplay/>
+        <RoleGenerationRulesDisplay isHost={props.isHost}/>
+        <CustomRoleSetsEditor isHost={props.isHost}/>
         <OutlineListSelector
             disabled={!props.isHost}
             onChangeRolePicker={(value, index) => GAME_MANAGER.sendSetRoleOutlinePacket(index, value)}
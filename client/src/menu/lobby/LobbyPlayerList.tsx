@@ -12,7 +12,7 @@ import StyledText from "../../components/StyledText";
 
 type PlayerDisplayData = {
     id: number,
-    clientType: "player" | "spectator",
+    clientType: "player" | "spectator" | "bot",
     connection: ClientConnection,
     ready: boolean | null,
     host: boolean,
@@ -25,7 +25,7 @@ export default function LobbyPlayerList(): ReactElement {
         state => {
             if (state.stateType === "lobby") {
                 return state.players.entries().map(([id, player]) => {
-                    const name = player.clientType.type === "player" ? player.clientType.name : null;
+                    const name = player.clientType.type !== "spectator" ? player.clientType.name : null;
                     return {
                         id,
                         clientType: player.clientType.type,
@@ -67,16 +67,24 @@ export default function LobbyPlayerList(): ReactElement {
         ["playersHost", "lobbyClients", "yourId", "playersReady", "hostData"]
     )!;
 
+    const inLobby = useLobbyOrGameState(
+        state => state.stateType === "lobby",
+        ["lobbyClients"]
+    )!;
+
     return <section className="player-list-menu-colors selector-section">
         <h2>{translate("menu.lobby.players")}</h2>
         <div className="lobby-player-list">
             <ol>
                 {players
-                    .filter(player => player.clientType === "player")
+                    .filter(player => player.clientType !== "spectator")
                     .map(player => <LobbyPlayerListPlayer key={player.id} player={player}/>)
                 }
             </ol>
         </div>
+        {host && inLobby && <Button onClick={() => GAME_MANAGER.sendAddBotPacket()}>
+            <Icon>smart_toy</Icon> {translate("menu.lobby.button.addBot")}
+        </Button>}
         {host && <>
             <h2>{translate("menu.hostSettings.spectators")}</h2>
             <div className="lobby-player-list">
@@ -116,6 +124,7 @@ function LobbyPlayerListPlayer(props: Readonly<{ player: PlayerDisplayData }>):
             {props.player.connection === "couldReconnect" && <Icon>signal_cellular_connected_no_internet_4_bar</Icon>}
             {props.player.connection === "disconnected" && <Icon>sentiment_very_dissatisfied</Icon>}
             {props.player.host && <Icon>shield</Icon>}
+            {props.player.clientType === "bot" && <Icon>smart_toy</Icon>}
             {props.player.ready && <Icon>check</Icon>}
             <StyledText>{props.player.displayName}</StyledText>
         </div>
@@ -123,7 +132,7 @@ function LobbyPlayerListPlayer(props: Readonly<{ player: PlayerDisplayData }>):
             {host && props.player.connection !== "disconnected" && <button 
                 onClick={() => GAME_MANAGER.sendKickPlayerPacket(props.player.id)}
             ><Icon>person_remove</Icon></button>}
-            {host && props.player.clientType === "player" && <>
+            {host && props.player.clientType !== "spectator" && <>
                 <RawButton
                     ref={renameButtonRef}
                     onClick={() => setRenameOpen(open => !open)}
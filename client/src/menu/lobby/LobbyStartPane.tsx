@@ -0,0 +1,92 @@
+import React, { ReactElement, useEffect, useState } from "react";
+import GAME_MANAGER from "../..";
+import translate from "../../game/lang";
+import CheckBox from "../../components/CheckBox";
+import { useLobbyState } from "../../components/useHooks";
+import { StartSettings } from "../../game/packet";
+
+export default function LobbyStartPane(props: Readonly<{
+    isHost: boolean
+}>): ReactElement | null {
+    const startSettings = useLobbyState(
+        lobbyState => lobbyState.startSettings,
+        ["lobbyStartSettings"]
+    )!;
+    const startCountdown = useLobbyState(
+        lobbyState => lobbyState.startCountdown,
+        ["startCountdown"]
+    )!;
+
+    const isDefault = !startSettings.autoStart
+        && startSettings.minPlayers === null
+        && startSettings.maxPlayers === null
+        && startSettings.idleHostTimeoutSecs === null;
+    if (!props.isHost && isDefault) return null;
+
+    const setStartSettings = (changed: Partial<StartSettings>) =>
+        GAME_MANAGER.sendSetLobbyStartSettingsPacket({ ...startSettings, ...changed });
+
+    return <section className="player-list-menu-colors selector-section lobby-start-pane">
+        <h2>{translate("menu.lobby.start")}</h2>
+        {startCountdown !== null && <p>{translate("menu.lobby.start.countdown", startCountdown)}</p>}
+        {props.isHost
+            ? <>
+                <label>
+                    {translate("menu.lobby.start.autoStart")}
+                    <CheckBox checked={startSettings.autoStart} onChange={autoStart => setStartSettings({ autoStart })}/>
+                </label>
+                <label>
+                    {translate("menu.lobby.start.minPlayers")}
+                    <OptionalNumberInput value={startSettings.minPlayers} max={255} onChange={minPlayers => setStartSettings({ minPlayers })}/>
+                </label>
+                <label>
+                    {translate("menu.lobby.start.maxPlayers")}
+                    <OptionalNumberInput value={startSettings.maxPlayers} max={255} onChange={maxPlayers => setStartSettings({ maxPlayers })}/>
+                </label>
+                <label>
+                    {translate("menu.lobby.start.idleHostTimeout")}
+                    <OptionalNumberInput value={startSettings.idleHostTimeoutSecs} onChange={idleHostTimeoutSecs => setStartSettings({ idleHostTimeoutSecs })}/>
+                </label>
+            </>
+            : <>
+                {startSettings.autoStart && <p>{translate("menu.lobby.start.autoStart")}</p>}
+                {startSettings.minPlayers !== null && <p>{translate("menu.lobby.start.minPlayers")} {startSettings.minPlayers}</p>}
+                {startSettings.maxPlayers !== null && <p>{translate("menu.lobby.start.maxPlayers")} {startSettings.maxPlayers}</p>}
+            </>
+        }
+    </section>
+}
+
+/// Left empty for no limit. Only sent once the host is done typing.
+function OptionalNumberInput(props: Readonly<{
+    value: number | null,
+    max?: number,
+    onChange: (value: number | null) => void
+}>): ReactElement {
+    const [text, setText] = useState<string>(props.value?.toString() ?? "");
+
+    useEffect(() => {
+        setText(props.value?.toString() ?? "");
+    }, [props.value]);
+
+    const send = () => {
+        const value = text === "" ? null : parseInt(text);
+        if (value !== null && (isNaN(value) || value < 0 || (props.max !== undefined && value > props.max))) {
+            setText(props.value?.toString() ?? "");
+        } else if (value !== props.value) {
+            props.onChange(value);
+        }
+    };
+
+    return <input
+        type="number" min="0" max={props.max} step="1"
+        value={text}
+        placeholder={translate("menu.lobby.start.noLimit")}
+        onChange={e => setText(e.target.value)}
+        onKeyUp={e => {
+            if (e.key !== "Enter") return;
+            send();
+        }}
+        onBlur={send}
+    />
+}
@@ -0,0 +1,40 @@
+import React, { ReactElement } from "react";
+import GAME_MANAGER from "../..";
+import translate from "../../game/lang";
+import { Button } from "../../components/Button";
+import { useLobbyState } from "../../components/useHooks";
+import { translateRoleConstraint } from "../../game/roleListState.d";
+
+/// Role weights and constraints come from presets, so the host can only clear them here
+export default function RoleGenerationRulesDisplay(props: Readonly<{
+    isHost: boolean
+}>): ReactElement | null {
+    const roleWeights = useLobbyState(
+        lobbyState => lobbyState.roleWeights,
+        ["roleWeights"]
+    )!;
+    const roleConstraints = useLobbyState(
+        lobbyState => lobbyState.roleConstraints,
+        ["roleConstraints"]
+    )!;
+
+    if (roleWeights.length === 0 && roleConstraints.length === 0) return null;
+
+    return <section className="chat-menu-colors selector-section">
+        <h2>{translate("menu.lobby.roleGenerationRules")}</h2>
+        <ul>
+            {roleWeights.map(([role, weight]) =>
+                <li key={role}>{translate("menu.lobby.roleGenerationRules.weight", translate("role."+role+".name"), weight)}</li>
+            )}
+            {roleConstraints.map(constraint => translateRoleConstraint(constraint)).map(constraint =>
+                <li key={constraint}>{constraint}</li>
+            )}
+        </ul>
+        {props.isHost && roleWeights.length !== 0 && <Button onClick={() => GAME_MANAGER.sendSetRoleWeightsPacket([])}>
+            {translate("menu.lobby.roleGenerationRules.clearWeights")}
+        </Button>}
+        {props.isHost && roleConstraints.length !== 0 && <Button onClick={() => GAME_MANAGER.sendSetRoleConstraintsPacket([])}>
+            {translate("menu.lobby.roleGenerationRules.clearConstraints")}
+        </Button>}
+    </section>
+}
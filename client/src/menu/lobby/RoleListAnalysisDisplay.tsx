@@ -0,0 +1,43 @@
+import React, { ReactElement } from "react";
+import translate from "../../game/lang";
+import { useLobbyState } from "../../components/useHooks";
+
+/// Why the role list won't start, or how often it'd be over before anyone plays
+export default function RoleListAnalysisDisplay(): ReactElement | null {
+    const analysis = useLobbyState(
+        lobbyState => lobbyState.roleListAnalysis,
+        ["roleListAnalysis"]
+    );
+
+    if (analysis === null || analysis === undefined) return null;
+
+    const percent = (count: number) => Math.round(100 * count / analysis.samples);
+    const outlines = (indices: number[]) => indices.map(index => index + 1).join(", ");
+
+    const problems = [
+        ...analysis.unsatisfiableOutlines.map(outline =>
+            translate("menu.lobby.roleListAnalysis.unsatisfiable", outline + 1)
+        ),
+        ...analysis.maxCountConflicts.map(conflict =>
+            translate("menu.lobby.roleListAnalysis.conflict",
+                outlines(conflict.outlines),
+                conflict.roles.map(role => translate("role."+role+".name")).join(", ")
+            )
+        ),
+    ];
+    if (analysis.samples > 0 && analysis.failedGenerations > 0) {
+        problems.push(translate("menu.lobby.roleListAnalysis.failedGenerations"));
+    }
+    if (analysis.samples > 0 && analysis.instantGameOvers > 0) {
+        problems.push(translate("menu.lobby.roleListAnalysis.instantGameOvers", percent(analysis.instantGameOvers)));
+    }
+
+    if (problems.length === 0) return null;
+
+    return <section className="chat-menu-colors selector-section">
+        <h2>{translate("menu.lobby.roleListAnalysis")}</h2>
+        <ul>
+            {problems.map(problem => <li key={problem}>{problem}</li>)}
+        </ul>
+    </section>
+}
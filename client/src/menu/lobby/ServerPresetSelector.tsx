@@ -0,0 +1,28 @@
+import React, { ReactElement } from "react";
+import GAME_MANAGER from "../..";
+import translate from "../../game/lang";
+import { Button } from "../../components/Button";
+import { useLobbyState } from "../../components/useHooks";
+
+/// Presets the server offers, which replace every setting at once
+export default function ServerPresetSelector(): ReactElement | null {
+    const presets = useLobbyState(
+        lobbyState => lobbyState.presets,
+        ["presets"]
+    )!;
+
+    if (presets.length === 0) return null;
+
+    return <section className="chat-menu-colors selector-section">
+        <h2>{translate("menu.lobby.presets")}</h2>
+        {presets.map(preset =>
+            <Button
+                key={preset.name}
+                onClick={() => GAME_MANAGER.sendLoadPresetPacket(preset.name)}
+            >
+                {preset.name} ({translate("menu.lobby.presets.players", preset.players)})
+                {preset.description !== "" && <><br/><small>{preset.description}</small></>}
+            </Button>
+        )}
+    </section>
+}
@@ -8,6 +8,7 @@ import { StateListener } from "../../game/gameManager.d";
 import { LobbyPreviewData } from "../../game/packet";
 import LobbyMenu from "../lobby/LobbyMenu";
 import PlayMenuJoinPopup from "./PlayMenuJoinPopup";
+import Icon from "../../components/Icon";
 
 export default function PlayMenu(): ReactElement {
     const { setContent: setAnchorContent } = useContext(AnchorControllerContext)!;
@@ -20,16 +21,16 @@ export default function PlayMenu(): ReactElement {
     })
 
     const joinGame = useCallback(
-        async (roomCode?: number, playerId?: number): Promise<boolean> => {
+        async (roomCode?: number, playerId?: number, password?: string): Promise<boolean> => {
             if (roomCode === undefined) return false;
         
             setAnchorContent(<LoadingScreen type="join"/>);
         
             let success: boolean;
             if (playerId === undefined) {
-                success = await GAME_MANAGER.sendJoinPacket(roomCode);
+                success = await GAME_MANAGER.sendJoinPacket(roomCode, password);
             } else {
-                success = await GAME_MANAGER.sendRejoinPacket(roomCode, playerId);
+                success = await GAME_MANAGER.sendRejoinPacket(roomCode, playerId, undefined, password === undefined ? undefined : { password });
             }
         
             if (!success) {
@@ -40,6 +41,21 @@ export default function PlayMenu(): ReactElement {
         },
         [setAnchorContent]
     );
+
+    const joinWithInviteCode = useCallback(
+        async (inviteCode: string, password?: string): Promise<boolean> => {
+            setAnchorContent(<LoadingScreen type="join"/>);
+
+            const success = await GAME_MANAGER.sendJoinWithInviteCodePacket(inviteCode, password);
+
+            if (!success) {
+                setAnchorContent(<PlayMenu/>);
+            }
+
+            return success;
+        },
+        [setAnchorContent]
+    );
     
 
     return <div className="play-menu">
@@ -67,16 +83,28 @@ export default function PlayMenu(): ReactElement {
             <div className="play-menu-center">
                 <PlayMenuTable joinGame={joinGame}/>
             </div>
-            <PlayMenuFooter joinGame={joinGame}/>
+            <PlayMenuFooter joinGame={joinGame} joinWithInviteCode={joinWithInviteCode}/>
         </div>
     </div>
 }
 
 function PlayMenuFooter(props: Readonly<{
-    joinGame: (roomCode?: number, playerId?: number) => Promise<boolean>
+    joinGame: (roomCode?: number, playerId?: number, password?: string) => Promise<boolean>,
+    joinWithInviteCode: (inviteCode: string, password?: string) => Promise<boolean>
 }>): ReactElement {
     const [roomCode, setRoomCode] = useState<number | undefined>(undefined);
     const [playerID, setPlayerID] = useState<number | undefined>(undefined);
+    const [inviteCode, setInviteCode] = useState<string>("");
+    const [password, setPassword] = useState<string>("");
+
+    const join = () => {
+        const givenPassword = password === "" ? undefined : password;
+        if (inviteCode.trim() !== "") {
+            props.joinWithInviteCode(inviteCode, givenPassword);
+        } else {
+            props.joinGame(roomCode, playerID, givenPassword);
+        }
+    }
 
     return <footer>
         <div>
@@ -96,7 +124,7 @@ function PlayMenuFooter(props: Readonly<{
                     }}}
                 onKeyUp={(e)=>{
                     if(e.key === 'Enter') {
-                        props.joinGame(roomCode);
+                        join();
                     }
                 }}
             />
@@ -119,14 +147,34 @@ function PlayMenuFooter(props: Readonly<{
                 }}
                 onKeyUp={(e)=>{
                     if(e.key === 'Enter') {
-                        props.joinGame(roomCode, playerID);
+                        join();
+                    }
+                }}
+            />
+        </div>
+        <div>
+            <label>{translate("menu.play.field.inviteCode")}</label>
+            <input type="text" value={inviteCode}
+                onChange={(e)=>setInviteCode(e.target.value)}
+                onKeyUp={(e)=>{
+                    if(e.key === 'Enter') {
+                        join();
+                    }
+                }}
+            />
+        </div>
+        <div>
+            <label>{translate("menu.play.field.password")}</label>
+            <input type="text" value={password}
+                onChange={(e)=>setPassword(e.target.value)}
+                onKeyUp={(e)=>{
+                    if(e.key === 'Enter') {
+                        join();
                     }
                 }}
             />
         </div>
-        <button onClick={()=>{
-            props.joinGame(roomCode, playerID)
-        }}>
+        <button onClick={join}>
             {translate("menu.play.button.join")}
         </button>
     </footer>
@@ -177,7 +225,7 @@ function PlayMenuTable(props: Readonly<{
                             }
                         }}>{translate("menu.play.button.join")}</button>
                     </td>
-                    <td>{lobby.name}</td>
+                    <td>{lobby.passwordProtected && <Icon size="small">lock</Icon>}{lobby.name}</td>
                     <td>
                         <div className="play-menu-lobby-player-list">
                             {lobby.players.map((player)=>{
@@ -64,6 +64,21 @@ async function routeLobby(anchorController: AnchorController, roomCode: string)
     }
 }
 
+async function routeInvite(anchorController: AnchorController, inviteCode: string) {
+    if (!await GAME_MANAGER.setOutsideLobbyState()) {
+        anchorController.setContent(<StartMenu/>);
+        return;
+    }
+
+    window.history.replaceState({}, "", '/');
+
+    if (!await GAME_MANAGER.sendJoinWithInviteCodePacket(inviteCode)) {
+        await GAME_MANAGER.setDisconnectedState();
+        anchorController.clearCoverCard();
+        anchorController.setContent(<StartMenu/>)
+    }
+}
+
 async function routeGameMode(anchorController: AnchorController, gameModeString: string) {
     window.history.replaceState({}, "", "/");
     
@@ -124,9 +139,13 @@ export default async function route(anchorController: AnchorController, url: Loc
     if (url.pathname.startsWith("/wiki")) {
         return await routeWiki(anchorController, url.pathname.substring(5));
     } else if (url.pathname.startsWith("/connect")) {
-        const roomCode = new URLSearchParams(url.search).get("code");
+        const params = new URLSearchParams(url.search);
+        const roomCode = params.get("code");
+        const inviteCode = params.get("invite");
         if (roomCode !== null) {
             return await routeLobby(anchorController, roomCode);
+        } else if (inviteCode !== null) {
+            return await routeInvite(anchorController, inviteCode);
         }
     } else if (url.pathname.startsWith("/gameMode")) {
         const gameMode = new URLSearchParams(url.search).get("mode");
@@ -0,0 +1,543 @@
+use std::{
+    collections::HashMap, env, path::Path, process::ExitCode, str::FromStr,
+    sync::{Arc, Mutex, PoisonError}, time::Duration
+};
+
+use mafia_client::{Client, ClientError, ToClientPacket, ToServerPacket};
+use mafia_server::{
+    config::Config,
+    game::{
+        ability_input::{AbilityInput, AbilitySelection, AvailableAbilitySelection},
+        chat::ChatMessageVariant,
+        phase::PhaseState,
+        role::Role,
+        role_list::{RoleList, RoleOutline, RoleOutlineOption, RoleOutlineOptionRoles, RoleSet},
+        settings::PhaseTimeSettings,
+        verdict::Verdict
+    },
+    listener::RoomCode,
+    metrics,
+    websocket_connections::websocket_listener::create_ws_server
+};
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng, SeedableRng};
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpStream, sync::watch, task::JoinSet, time::{timeout_at, Instant}};
+use vec1::vec1;
+
+/// Plays many clients against a server on this machine and reports how it held up.
+///
+/// Usage: `cargo run --release -p mafia_client --bin load_test -- --clients 200 --duration-secs 60`
+///
+/// By default the server is started in this process on `--address`, so its memory is measured
+/// together with the clients'. To test a server that's already running, give its `--url`,
+/// and `--metrics` and `--server-pid` to report its tick latency and memory.
+/// A server started here saves game records and snapshots to the working directory like any other,
+/// so run it from an empty one. Exits with failure if any client hit an error.
+#[tokio::main]
+async fn main() -> ExitCode {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let url = match &options.url {
+        Some(url) => url.clone(),
+        None => {
+            Config::set(Config { address: options.address.clone(), ..Config::default() });
+            let address = options.address.clone();
+            tokio::spawn(async move { create_ws_server(&address).await });
+            format!("ws://{}", options.address)
+        }
+    };
+    if let Err(err) = wait_for_server(&url).await {
+        eprintln!("Couldn't connect to {url}: {err}");
+        return ExitCode::FAILURE;
+    }
+
+    let ticks_before = options.tick_durations().await;
+    let stats = SharedStats::default();
+    let started = Instant::now();
+    let deadline = started.checked_add(options.duration).unwrap_or(started);
+    let mut rng = StdRng::seed_from_u64(options.seed);
+
+    let mut tasks = JoinSet::new();
+    let lobbies = options.clients.checked_div(options.lobby_size).unwrap_or(0).max(1);
+    for lobby in 0..lobbies {
+        // The clients that don't fill a whole lobby are spread over the first few
+        let size = options.clients.checked_div(lobbies).unwrap_or(0)
+            .saturating_add(usize::from(lobby < options.clients.checked_rem(lobbies).unwrap_or(0)));
+        let (room_code_sender, room_code) = watch::channel(None);
+
+        let host = SimulatedClient::new(url.clone(), rng.random(), stats.clone());
+        tasks.spawn(host.run_host(size, options.phase_secs, room_code_sender, deadline));
+        for _ in 1..size {
+            let player = SimulatedClient::new(url.clone(), rng.random(), stats.clone());
+            tasks.spawn(player.run_player(room_code.clone(), deadline));
+        }
+    }
+    while tasks.join_next().await.is_some() {}
+
+    let ticks_after = options.tick_durations().await;
+    let stats = std::mem::take(&mut *stats.lock().unwrap_or_else(PoisonError::into_inner));
+    report(&options, lobbies, started.elapsed(), &stats, ticks_before.zip(ticks_after).map(|(before, after)| after.since(&before)));
+
+    if stats.errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+const USAGE: &str = "Usage: load_test [--clients N] [--lobby-size N] [--duration-secs N] [--phase-secs N] [--seed N] \
+    [--address HOST:PORT | --url WS_URL [--metrics HOST:PORT] [--server-pid PID]]";
+/// How many role lists the host tries before giving up on starting a game
+const START_TRIES: usize = 20;
+/// Role sets the outlines after the first are picked from. The first is always a mafia killing role, so games can end.
+const ROLE_SETS: [RoleSet; 10] = [
+    RoleSet::Any, RoleSet::Town, RoleSet::TownCommon, RoleSet::TownInvestigative, RoleSet::TownProtective,
+    RoleSet::TownKilling, RoleSet::TownSupport, RoleSet::Mafia, RoleSet::MafiaSupport, RoleSet::Neutral
+];
+
+struct Options {
+    clients: usize,
+    lobby_size: usize,
+    duration: Duration,
+    /// Every phase is this long, so games finish within the test
+    phase_secs: u64,
+    seed: u64,
+    /// Where to start the server in this process, if `url` isn't given
+    address: String,
+    url: Option<String>,
+    /// Address of the server's Prometheus metrics, when it isn't in this process
+    metrics: Option<String>,
+    server_pid: Option<u32>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            clients: 100,
+            lobby_size: 10,
+            duration: Duration::from_secs(60),
+            phase_secs: 5,
+            seed: rand::random(),
+            address: "127.0.0.1:8787".to_string(),
+            url: None,
+            metrics: None,
+            server_pid: None,
+        };
+
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| format!("No value given for {flag}"))?;
+            match flag.as_str() {
+                "--clients" => options.clients = parse_value(&flag, &value)?,
+                "--lobby-size" => options.lobby_size = parse_value(&flag, &value)?,
+                "--duration-secs" => options.duration = Duration::from_secs(parse_value(&flag, &value)?),
+                "--phase-secs" => options.phase_secs = parse_value(&flag, &value)?,
+                "--seed" => options.seed = parse_value(&flag, &value)?,
+                "--address" => options.address = value,
+                "--url" => options.url = Some(value),
+                "--metrics" => options.metrics = Some(value),
+                "--server-pid" => options.server_pid = Some(parse_value(&flag, &value)?),
+                _ => return Err(format!("Unknown option: {flag}")),
+            }
+        }
+
+        if options.lobby_size < 2 || options.clients < 2 {
+            return Err("A game needs at least 2 clients".to_string());
+        }
+        if options.phase_secs == 0 {
+            return Err("Phases have to last at least a second".to_string());
+        }
+        Ok(options)
+    }
+
+    /// The server's tick durations so far, if they can be read
+    async fn tick_durations(&self) -> Option<TickDurations> {
+        let metrics = match (&self.url, &self.metrics) {
+            (None, _) => metrics::render(),
+            (Some(_), Some(address)) => fetch_metrics(address).await.ok()?,
+            (Some(_), None) => return None,
+        };
+        TickDurations::parse(&metrics)
+    }
+
+    /// Resident memory and its peak, in kilobytes
+    fn memory(&self) -> Option<(u64, u64)> {
+        let status = match (&self.url, self.server_pid) {
+            (None, _) => std::fs::read_to_string("/proc/self/status"),
+            (Some(_), Some(pid)) => std::fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("status")),
+            (Some(_), None) => return None,
+        }.ok()?;
+
+        let field = |name: &str| status.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok());
+        Some((field("VmRSS:")?, field("VmHWM:")?))
+    }
+}
+
+fn parse_value<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid value for {flag}: {value}"))
+}
+
+/// The server takes a moment to start listening when it's started in this process
+async fn wait_for_server(url: &str) -> Result<(), ClientError> {
+    let mut tries = 50;
+    loop {
+        match Client::connect(url).await {
+            Ok(mut client) => {
+                client.close().await;
+                return Ok(());
+            }
+            Err(err) if tries == 0 => return Err(err),
+            Err(_) => {
+                tries -= 1;
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+async fn fetch_metrics(address: &str) -> std::io::Result<String> {
+    let mut stream = TcpStream::connect(address).await?;
+    stream.write_all(format!("GET /metrics HTTP/1.0\r\nHost: {address}\r\n\r\n").as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response.split_once("\r\n\r\n").map(|(_, body)| body.to_string()).unwrap_or(response))
+}
+
+#[derive(Default)]
+struct Stats {
+    packets_received: u64,
+    packets_sent: u64,
+    /// From sending a chat message to seeing it come back
+    chat_latencies: Vec<Duration>,
+    games_started: u64,
+    games_finished: u64,
+    role_lists_rejected: u64,
+    errors: Vec<ClientError>,
+}
+type SharedStats = Arc<Mutex<Stats>>;
+
+/// One client, hosting or joining a lobby and playing at random
+struct SimulatedClient {
+    url: String,
+    rng: StdRng,
+    stats: SharedStats,
+    /// Chat messages sent and not seen yet, by their text
+    pending_chat: HashMap<String, Instant>,
+    chat_sent: u64,
+    /// Set when a phase starts, and cleared once the new phase's controllers have been used
+    phase_started: bool,
+}
+
+impl SimulatedClient {
+    fn new(url: String, seed: u64, stats: SharedStats) -> Self {
+        Self { url, rng: StdRng::seed_from_u64(seed), stats, pending_chat: HashMap::new(), chat_sent: 0, phase_started: false }
+    }
+
+    fn stats(&self) -> std::sync::MutexGuard<'_, Stats> {
+        self.stats.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    async fn run_host(mut self, lobby_size: usize, phase_secs: u64, room_code: watch::Sender<Option<RoomCode>>, deadline: Instant) {
+        let result = async {
+            let mut client = Client::connect(&self.url).await?;
+            let code = client.host().await?;
+            let _ = room_code.send(Some(code));
+
+            self.send(&mut client, ToServerPacket::SetPhaseTimes { phase_time_settings: PhaseTimeSettings {
+                briefing: phase_secs, obituary: phase_secs, discussion: phase_secs, nomination: phase_secs, testimony: phase_secs,
+                judgement: phase_secs, final_words: phase_secs, dusk: phase_secs, night: phase_secs,
+            }}).await?;
+            self.send(&mut client, ToServerPacket::SetEnabledRoles { roles: Role::values() }).await?;
+            self.send_lobby_message(&mut client).await?;
+
+            loop {
+                let full = self.run_until(&mut client, deadline, |client, _| {
+                    client.mirror.lobby.as_ref().is_some_and(|lobby| lobby.clients.len() >= lobby_size).then_some(())
+                }).await?;
+                if full.is_none() || !self.start_game(&mut client, lobby_size, deadline).await? {
+                    break;
+                }
+
+                let over = self.run_until(&mut client, deadline, |_, packet| matches!(packet, Some(ToClientPacket::GameOver { .. })).then_some(())).await?;
+                if over.is_none() {
+                    break;
+                }
+                self.stats().games_finished += 1;
+
+                self.send(&mut client, ToServerPacket::HostForceBackToLobby).await?;
+                if self.run_until(&mut client, deadline, |_, packet| matches!(packet, Some(ToClientPacket::BackToLobby)).then_some(())).await?.is_none() {
+                    break;
+                }
+            }
+
+            client.close().await;
+            Ok(())
+        }.await;
+        self.finish(result);
+    }
+
+    async fn run_player(mut self, mut room_code: watch::Receiver<Option<RoomCode>>, deadline: Instant) {
+        let result = async {
+            let code = match timeout_at(deadline, room_code.wait_for(Option::is_some)).await {
+                Ok(Ok(code)) => *code,
+                _ => None,
+            };
+            let Some(code) = code else {return Ok(())};
+
+            let mut client = Client::connect(&self.url).await?;
+            client.join(code, None).await?;
+            self.send_lobby_message(&mut client).await?;
+
+            self.run_until(&mut client, deadline, |_, _| None::<()>).await?;
+            client.close().await;
+            Ok(())
+        }.await;
+        self.finish(result);
+    }
+
+    fn finish(&self, result: Result<(), ClientError>) {
+        if let Err(err) = result {
+            self.stats().errors.push(err);
+        }
+    }
+
+    /// Tries random role lists until one starts a game. Returns false if none did.
+    async fn start_game(&mut self, client: &mut Client, lobby_size: usize, deadline: Instant) -> Result<bool, ClientError> {
+        for _ in 0..START_TRIES {
+            let role_list = self.random_role_list(lobby_size);
+            self.send(client, ToServerPacket::SetRoleList { role_list }).await?;
+            self.send(client, ToServerPacket::StartGame).await?;
+
+            let started = self.run_until(client, deadline, |_, packet| match packet {
+                Some(ToClientPacket::StartGame) => Some(true),
+                Some(ToClientPacket::RejectStart { .. }) => Some(false),
+                _ => None,
+            }).await?;
+            match started {
+                Some(true) => {
+                    self.stats().games_started += 1;
+                    return Ok(true);
+                }
+                Some(false) => self.stats().role_lists_rejected += 1,
+                None => return Ok(false),
+            }
+        }
+        Ok(false)
+    }
+
+    fn random_role_list(&mut self, size: usize) -> RoleList {
+        let outline = |role_set| RoleOutline { options: vec1![RoleOutlineOption {
+            roles: RoleOutlineOptionRoles::RoleSet { role_set },
+            ..Default::default()
+        }]};
+
+        RoleList(std::iter::once(outline(RoleSet::MafiaKilling))
+            .chain((1..size).map(|_| outline(ROLE_SETS.choose(&mut self.rng).cloned().unwrap_or(RoleSet::Any))))
+            .collect())
+    }
+
+    /// Plays along until `done` returns something for the client and the packet just received,
+    /// or returns `None` at the deadline
+    async fn run_until<T>(
+        &mut self, client: &mut Client, deadline: Instant,
+        mut done: impl FnMut(&Client, Option<&ToClientPacket>) -> Option<T>
+    ) -> Result<Option<T>, ClientError> {
+        if let Some(answer) = done(client, None) {
+            return Ok(Some(answer));
+        }
+        loop {
+            let packet = match timeout_at(deadline, client.recv()).await {
+                Err(_) => return Ok(None),
+                Ok(None) => return Err(ClientError::Closed),
+                Ok(Some(packet)) => packet?,
+            };
+            self.stats().packets_received += 1;
+            self.react(client, &packet).await?;
+
+            if let Some(answer) = done(client, Some(&packet)) {
+                return Ok(Some(answer));
+            }
+        }
+    }
+
+    async fn react(&mut self, client: &mut Client, packet: &ToClientPacket) -> Result<(), ClientError> {
+        match packet {
+            ToClientPacket::AddChatMessages { chat_messages } => {
+                let now = Instant::now();
+                for message in chat_messages {
+                    if let ChatMessageVariant::Normal { text, .. } | ChatMessageVariant::LobbyMessage { text, .. } = message.variant() {
+                        if let Some(sent) = self.pending_chat.remove(text) {
+                            self.stats().chat_latencies.push(now.saturating_duration_since(sent));
+                        }
+                    }
+                }
+            }
+            ToClientPacket::Phase { phase, .. } => {
+                self.phase_started = true;
+                if self.rng.random_bool(0.5) {
+                    let text = self.next_chat_text();
+                    self.send(client, ToServerPacket::SendChatMessage { text, block: false }).await?;
+                }
+                if let PhaseState::Judgement { .. } = phase {
+                    let verdict = *[Verdict::Guilty, Verdict::Innocent, Verdict::Abstain].choose(&mut self.rng).unwrap_or(&Verdict::Abstain);
+                    self.send(client, ToServerPacket::Judgement { verdict }).await?;
+                }
+                if self.rng.random_bool(0.5) {
+                    self.send(client, ToServerPacket::VoteFastForwardPhase { fast_forward: true }).await?;
+                }
+            }
+            // Controllers change right after the phase does, and again every time one is used
+            ToClientPacket::YourAllowedControllers { .. } |
+            ToClientPacket::YourAllowedControllersPatch { .. } if self.phase_started => {
+                self.phase_started = false;
+                self.use_controllers(client).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Picks a random player in every player list controller
+    async fn use_controllers(&mut self, client: &mut Client) -> Result<(), ClientError> {
+        let Some(game) = &client.mirror.game else {return Ok(())};
+        let mut inputs = Vec::new();
+        for (id, controller) in game.allowed_controllers.iter() {
+            let parameters = controller.parameters();
+            if parameters.grayed_out() {continue}
+            let AvailableAbilitySelection::PlayerList { selection } = parameters.available() else {continue};
+            if selection.max_players == Some(0) {continue}
+
+            let players: Vec<_> = selection.available_players.iter().copied().collect();
+            if let Some(target) = players.choose(&mut self.rng) {
+                inputs.push(AbilityInput::new(id.clone(), AbilitySelection::new_player_list(vec![*target])));
+            }
+        }
+
+        for ability_input in inputs {
+            self.send(client, ToServerPacket::AbilityInput { ability_input }).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_lobby_message(&mut self, client: &mut Client) -> Result<(), ClientError> {
+        let text = self.next_chat_text();
+        self.send(client, ToServerPacket::SendLobbyMessage { text }).await
+    }
+
+    /// A chat message no other client sends, so it can be told apart when it comes back
+    fn next_chat_text(&mut self) -> String {
+        self.chat_sent = self.chat_sent.saturating_add(1);
+        let text = format!("{:x} {}", self.rng.random::<u32>(), self.chat_sent);
+        self.pending_chat.insert(text.clone(), Instant::now());
+        text
+    }
+
+    async fn send(&mut self, client: &mut Client, packet: ToServerPacket) -> Result<(), ClientError> {
+        client.send(packet).await?;
+        self.stats().packets_sent += 1;
+        Ok(())
+    }
+}
+
+/// The server's tick duration histogram, read from its metrics
+struct TickDurations {
+    count: u64,
+    sum: f64,
+    /// Upper bound in seconds, and how many ticks took at most that long
+    buckets: Vec<(f64, u64)>,
+}
+
+impl TickDurations {
+    fn parse(metrics: &str) -> Option<Self> {
+        let mut count = None;
+        let mut sum = None;
+        let mut buckets = Vec::new();
+        for line in metrics.lines() {
+            if let Some(bucket) = line.strip_prefix("mafia_tick_duration_seconds_bucket{le=\"") {
+                let (bound, ticks) = bucket.split_once("\"} ")?;
+                if let (Ok(bound), Ok(ticks)) = (bound.parse(), ticks.parse()) {
+                    buckets.push((bound, ticks));
+                }
+            } else if let Some(value) = line.strip_prefix("mafia_tick_duration_seconds_sum ") {
+                sum = value.parse().ok();
+            } else if let Some(value) = line.strip_prefix("mafia_tick_duration_seconds_count ") {
+                count = value.parse().ok();
+            }
+        }
+        Some(Self { count: count?, sum: sum?, buckets })
+    }
+
+    /// Only the ticks after `earlier`
+    fn since(&self, earlier: &Self) -> Self {
+        Self {
+            count: self.count.saturating_sub(earlier.count),
+            sum: self.sum - earlier.sum,
+            buckets: self.buckets.iter().zip(earlier.buckets.iter())
+                .map(|((bound, ticks), (_, earlier_ticks))| (*bound, ticks.saturating_sub(*earlier_ticks)))
+                .collect(),
+        }
+    }
+
+    fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_secs_f64(self.sum / self.count as f64))
+    }
+
+    /// The smallest bucket bound that `quantile` of ticks finished within, or `None` if they took longer than every bucket
+    fn quantile_at_most(&self, quantile: f64) -> Option<Duration> {
+        let needed = (self.count as f64 * quantile).ceil() as u64;
+        self.buckets.iter()
+            .find(|(_, ticks)| *ticks >= needed)
+            .map(|(bound, _)| Duration::from_secs_f64(*bound))
+    }
+}
+
+fn percentile(sorted: &[Duration], quantile: f64) -> Option<Duration> {
+    let last = sorted.len().checked_sub(1)?;
+    sorted.get((last as f64 * quantile).round() as usize).copied()
+}
+
+fn report(options: &Options, lobbies: usize, elapsed: Duration, stats: &Stats, ticks: Option<TickDurations>) {
+    let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+    println!("{} clients in {lobbies} lobbies for {:.1}s", options.clients, elapsed.as_secs_f64());
+    println!("Games: {} started, {} finished, {} role lists rejected", stats.games_started, stats.games_finished, stats.role_lists_rejected);
+    println!("Packets: {} received ({:.0}/s), {} sent ({:.0}/s)",
+        stats.packets_received, stats.packets_received as f64 / seconds,
+        stats.packets_sent, stats.packets_sent as f64 / seconds
+    );
+
+    let mut latencies = stats.chat_latencies.clone();
+    latencies.sort();
+    match (percentile(&latencies, 0.5), percentile(&latencies, 0.95), percentile(&latencies, 0.99), latencies.last()) {
+        (Some(p50), Some(p95), Some(p99), Some(max)) => println!(
+            "Packet latency (chat round trip, {} samples): p50 {p50:?}, p95 {p95:?}, p99 {p99:?}, max {max:?}", latencies.len()
+        ),
+        _ => println!("Packet latency: no chat messages came back"),
+    }
+
+    match ticks.as_ref().and_then(|ticks| Some((ticks, ticks.mean()?))) {
+        Some((ticks, mean)) => println!("Tick latency ({} ticks): mean {mean:?}, p99 at most {}",
+            ticks.count,
+            ticks.quantile_at_most(0.99).map_or("over the largest bucket".to_string(), |bound| format!("{bound:?}"))
+        ),
+        None => println!("Tick latency: unknown (give --metrics for a server outside this process)"),
+    }
+
+    match options.memory() {
+        Some((resident, peak)) => println!("Memory: {} MiB resident, {} MiB peak{}",
+            resident / 1024, peak / 1024,
+            if options.url.is_none() { " (server and clients together)" } else { "" }
+        ),
+        None => println!("Memory: unknown (give --server-pid for a server outside this process)"),
+    }
+
+    if !stats.errors.is_empty() {
+        println!("{} clients failed, the first with: {}", stats.errors.len(), stats.errors.first().map(ToString::to_string).unwrap_or_default());
+    }
+}
@@ -0,0 +1,268 @@
+use std::{fmt::Display, sync::{Arc, Mutex}};
+
+use mafia_server::{
+    client_connection::PacketSequence,
+    listener::{Listener, RoomCode},
+    lobby::{lobby_access::LobbyVisibility, lobby_client::LobbyClientID},
+    packet::{RejectJoinReason, ToClientPacket, ToServerPacket},
+    vec_set::VecSet,
+    websocket_connections::protocol::{Capability, Protocol, PROTOCOL_VERSION}
+};
+
+use crate::{mirror::Mirror, transport::Transport};
+
+/// Where the client is, as far as the server has told it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    OutsideLobby,
+    InLobby { room_code: RoomCode, player_id: LobbyClientID },
+    InGame { room_code: RoomCode, player_id: LobbyClientID },
+    Disconnected,
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    WebSocket(tokio_tungstenite::tungstenite::Error),
+    Json(serde_json::Error),
+    MessagePackEncode(rmp_serde::encode::Error),
+    MessagePackDecode(rmp_serde::decode::Error),
+    /// The server turned down a hello, host or join
+    Rejected(RejectJoinReason),
+    Closed,
+}
+impl Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::WebSocket(err) => write!(f, "{err}"),
+            ClientError::Json(err) => write!(f, "{err}"),
+            ClientError::MessagePackEncode(err) => write!(f, "{err}"),
+            ClientError::MessagePackDecode(err) => write!(f, "{err}"),
+            ClientError::Rejected(reason) => write!(f, "Rejected by the server: {reason:?}"),
+            ClientError::Closed => write!(f, "Connection closed"),
+        }
+    }
+}
+impl std::error::Error for ClientError {}
+
+/// One connection to the server. Every packet received goes through [`Client::recv`],
+/// which keeps [`Client::state`] and [`Client::mirror`] up to date and answers the server's pongs.
+pub struct Client {
+    transport: Transport,
+    state: ConnectionState,
+    protocol: Protocol,
+    last_sequence: Option<PacketSequence>,
+    /// The password this client last joined with, which it needs again to rejoin
+    password: Option<String>,
+    pub mirror: Mirror,
+}
+
+impl Client {
+    /// Every capability this client understands
+    pub fn all_capabilities() -> VecSet<Capability> {
+        vec![Capability::MessagePack, Capability::Resume, Capability::StatePatches].into_iter().collect()
+    }
+
+    pub async fn connect(url: &str) -> Result<Self, ClientError> {
+        Self::with_transport(Transport::connect(url).await?, Self::all_capabilities()).await
+    }
+
+    /// Connects to a listener in this process. Something else has to tick it, e.g. [`Listener::start`].
+    pub async fn in_process(listener: Arc<Mutex<Listener>>) -> Result<Self, ClientError> {
+        Self::with_transport(Transport::in_process(listener), Self::all_capabilities()).await
+    }
+
+    /// Says hello, asking for `capabilities`
+    pub async fn with_transport(transport: Transport, capabilities: VecSet<Capability>) -> Result<Self, ClientError> {
+        let mut client = Self {
+            transport,
+            state: ConnectionState::OutsideLobby,
+            protocol: Protocol::legacy(),
+            last_sequence: None,
+            password: None,
+            mirror: Mirror::default(),
+        };
+
+        client.send(ToServerPacket::Hello { protocol_version: PROTOCOL_VERSION, capabilities }).await?;
+        client.protocol = client.wait_for(|packet| match packet {
+            ToClientPacket::HelloAccepted { protocol_version, capabilities } => Some(Ok(Protocol {
+                version: *protocol_version,
+                capabilities: capabilities.clone()
+            })),
+            ToClientPacket::RejectJoin { reason } => Some(Err(ClientError::Rejected(*reason))),
+            _ => None,
+        }).await?;
+
+        Ok(client)
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+    /// What the server agreed to in its hello
+    pub fn protocol(&self) -> &Protocol {
+        &self.protocol
+    }
+    /// The last numbered packet received, which [`Client::rejoin`] resumes from
+    pub fn last_sequence(&self) -> Option<PacketSequence> {
+        self.last_sequence
+    }
+
+    pub async fn send(&mut self, packet: ToServerPacket) -> Result<(), ClientError> {
+        if self.state == ConnectionState::Disconnected {
+            return Err(ClientError::Closed);
+        }
+        self.transport.send(&packet, self.protocol.encoding()).await
+    }
+
+    /// The next packet from the server, after it's been applied to the mirror.
+    /// Numbered packets are unwrapped. Returns `None` once the connection is closed.
+    pub async fn recv(&mut self) -> Option<Result<ToClientPacket, ClientError>> {
+        if self.state == ConnectionState::Disconnected {
+            return None;
+        }
+        let packet = match self.transport.recv().await {
+            Some(Ok(packet)) => packet,
+            Some(Err(err)) => return Some(Err(err)),
+            None => {
+                self.state = ConnectionState::Disconnected;
+                return None;
+            }
+        };
+
+        let packet = match packet {
+            ToClientPacket::Sequenced { sequence, packet } => {
+                self.last_sequence = Some(sequence);
+                *packet
+            }
+            packet => packet,
+        };
+
+        self.state = match (&packet, self.state) {
+            (ToClientPacket::AcceptJoin { room_code, in_game: true, player_id, .. }, _) => {
+                ConnectionState::InGame { room_code: *room_code, player_id: *player_id }
+            }
+            (ToClientPacket::AcceptJoin { room_code, in_game: false, player_id, .. }, _) => {
+                ConnectionState::InLobby { room_code: *room_code, player_id: *player_id }
+            }
+            (ToClientPacket::YourId { player_id }, ConnectionState::InLobby { room_code, .. }) => {
+                ConnectionState::InLobby { room_code, player_id: *player_id }
+            }
+            (ToClientPacket::StartGame, ConnectionState::InLobby { room_code, player_id }) => {
+                ConnectionState::InGame { room_code, player_id }
+            }
+            (ToClientPacket::BackToLobby, ConnectionState::InGame { room_code, player_id }) => {
+                ConnectionState::InLobby { room_code, player_id }
+            }
+            (ToClientPacket::ForcedOutsideLobby, _) => {
+                self.last_sequence = None;
+                ConnectionState::OutsideLobby
+            }
+            (ToClientPacket::ForcedDisconnect, _) => ConnectionState::Disconnected,
+            (_, state) => state,
+        };
+
+        self.mirror.apply(&packet);
+
+        // The server sends pongs until it hears a ping, and drops clients that stay quiet
+        if matches!(packet, ToClientPacket::Pong) {
+            if let Err(err) = self.send(ToServerPacket::Ping).await {
+                return Some(Err(err));
+            }
+        }
+
+        Some(Ok(packet))
+    }
+
+    /// Receives packets until `answer` returns something for one of them
+    pub async fn wait_for<T>(&mut self, mut answer: impl FnMut(&ToClientPacket) -> Option<Result<T, ClientError>>) -> Result<T, ClientError> {
+        loop {
+            let packet = self.recv().await.ok_or(ClientError::Closed)??;
+            if let Some(answer) = answer(&packet) {
+                return answer;
+            }
+        }
+    }
+
+    /// Hosts a new lobby and returns its room code
+    pub async fn host(&mut self) -> Result<RoomCode, ClientError> {
+        self.send(ToServerPacket::Host).await?;
+        self.wait_for_join().await.map(|(room_code, _)| room_code)
+    }
+
+    /// Returns the ID the lobby gave this client
+    pub async fn join(&mut self, room_code: RoomCode, password: Option<String>) -> Result<LobbyClientID, ClientError> {
+        self.password.clone_from(&password);
+        self.send(ToServerPacket::Join { room_code, password }).await?;
+        self.wait_for_join().await.map(|(_, player_id)| player_id)
+    }
+
+    pub async fn join_with_invite_code(&mut self, invite_code: String, password: Option<String>) -> Result<(RoomCode, LobbyClientID), ClientError> {
+        self.password.clone_from(&password);
+        self.send(ToServerPacket::JoinWithInviteCode { invite_code, password }).await?;
+        self.wait_for_join().await
+    }
+
+    /// Takes back a player after losing the connection. With [`Capability::Resume`], only the packets
+    /// after [`Client::last_sequence`] are sent again if the server still has them.
+    /// Private lobbies need the same password or invite code as joining them does.
+    pub async fn rejoin(
+        &mut self, room_code: RoomCode, player_id: LobbyClientID, password: Option<String>, invite_code: Option<String>
+    ) -> Result<(), ClientError> {
+        let last_sequence = self.last_sequence.filter(|_| self.protocol.has(Capability::Resume));
+        self.send(ToServerPacket::ReJoin { room_code, player_id, last_sequence, password, invite_code }).await?;
+
+        let resumed = self.wait_for(|packet| match packet {
+            ToClientPacket::AcceptJoin { .. } => Some(Ok(false)),
+            ToClientPacket::ResumeAccepted => Some(Ok(true)),
+            ToClientPacket::RejectJoin { reason } => Some(Err(ClientError::Rejected(*reason))),
+            _ => None,
+        }).await?;
+
+        // Only games are resumed, and the mirror already has everything from before the connection dropped
+        if resumed {
+            self.state = ConnectionState::InGame { room_code, player_id };
+        }
+        Ok(())
+    }
+
+    /// Rejoins the lobby or game this client was last in, over a new transport
+    pub async fn reconnect(&mut self, transport: Transport) -> Result<(), ClientError> {
+        let Some(lobby) = self.mirror.lobby.as_ref() else {
+            return Err(ClientError::Closed);
+        };
+        let (room_code, player_id, invite_code) = (lobby.room_code, lobby.player_id, lobby.invite_code.clone());
+        // Hosts are told the password even if they didn't join with it
+        let password = match &lobby.visibility {
+            LobbyVisibility::Password { password } if !password.is_empty() => Some(password.clone()),
+            _ => self.password.clone(),
+        };
+        let mut reconnected = Self::with_transport(transport, self.protocol.capabilities.clone()).await?;
+        reconnected.last_sequence = self.last_sequence;
+        reconnected.password.clone_from(&password);
+        reconnected.mirror = std::mem::take(&mut self.mirror);
+        *self = reconnected;
+
+        self.rejoin(room_code, player_id, password, invite_code).await
+    }
+
+    pub async fn leave(&mut self) -> Result<(), ClientError> {
+        self.send(ToServerPacket::Leave).await?;
+        self.state = ConnectionState::OutsideLobby;
+        self.last_sequence = None;
+        self.mirror.leave();
+        Ok(())
+    }
+
+    pub async fn close(&mut self) {
+        self.transport.close().await;
+        self.state = ConnectionState::Disconnected;
+    }
+
+    async fn wait_for_join(&mut self) -> Result<(RoomCode, LobbyClientID), ClientError> {
+        self.wait_for(|packet| match packet {
+            ToClientPacket::AcceptJoin { room_code, player_id, .. } => Some(Ok((*room_code, *player_id))),
+            ToClientPacket::RejectJoin { reason } => Some(Err(ClientError::Rejected(*reason))),
+            _ => None,
+        }).await
+    }
+}
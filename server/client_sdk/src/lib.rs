@@ -0,0 +1,31 @@
+//! A client for the game's websocket protocol, for bots, load tests and anything else
+//! that wants to play without a browser.
+//!
+//! A [`Client`] talks to a real server over a websocket ([`Client::connect`]) or to a
+//! [`Listener`](mafia_server::listener::Listener) in the same process ([`Client::in_process`]).
+//! Either way it keeps a [`Mirror`] of the lobby and game it's in, updated from every packet it receives.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), mafia_client::ClientError> {
+//! let mut client = mafia_client::Client::connect("ws://127.0.0.1:8080").await?;
+//! let room_code = client.host().await?;
+//! println!("Hosting lobby {room_code}");
+//! while let Some(packet) = client.recv().await {
+//!     println!("{:?}", packet?);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+// Arithmetic
+#![warn(clippy::arithmetic_side_effects)]
+// Panicking operations
+#![deny(clippy::unwrap_used, clippy::panic, clippy::indexing_slicing)]
+
+pub mod client;
+pub mod mirror;
+pub mod transport;
+
+pub use client::{Client, ClientError, ConnectionState};
+pub use mirror::{GameMirror, LobbyMirror, Mirror};
+pub use mafia_server::packet::{ToClientPacket, ToServerPacket};
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use mafia_server::{
+    game::{
+        ability_input::{ControllerID, SavedController},
+        chat::{ChatGroup, ChatMessage},
+        components::insider_group::InsiderGroupID,
+        grave::Grave,
+        modifiers::ModifierType,
+        phase::PhaseState,
+        player::PlayerIndex,
+        preset::PresetSummary,
+        role::{ClientRoleStateEnum, Role},
+        role_generation::RoleConstraint,
+        role_list::{CustomRoleSet, RoleList},
+        settings::PhaseTimeSettings,
+        tag::Tag,
+        verdict::Verdict,
+        GameOverReason
+    },
+    listener::RoomCode,
+    lobby::{auto_start::StartSettings, lobby_access::LobbyVisibility, lobby_client::{LobbyClient, LobbyClientID}},
+    packet::{LobbyPreviewData, ToClientPacket},
+    vec_map::VecMap,
+    vec_set::VecSet
+};
+use vec1::Vec1;
+
+/// Everything the server has told this client, kept the way the web client keeps it
+#[derive(Debug, Clone, Default)]
+pub struct Mirror {
+    /// From the last `LobbyList`
+    pub lobby_list: HashMap<RoomCode, LobbyPreviewData>,
+    pub lobby: Option<LobbyMirror>,
+    /// Only while a game is running. The lobby it's in stays in [`Mirror::lobby`].
+    pub game: Option<GameMirror>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LobbyMirror {
+    pub room_code: RoomCode,
+    pub player_id: LobbyClientID,
+    pub spectator: bool,
+    pub name: String,
+    pub visibility: LobbyVisibility,
+    pub invite_code: Option<String>,
+    pub start_settings: StartSettings,
+    /// Seconds until the lobby starts by itself, while it's counting down
+    pub start_countdown: Option<u64>,
+    pub clients: VecMap<LobbyClientID, LobbyClient>,
+    pub hosts: Vec<LobbyClientID>,
+    pub role_list: RoleList,
+    pub phase_times: PhaseTimeSettings,
+    pub enabled_roles: Vec<Role>,
+    pub enabled_modifiers: Vec<ModifierType>,
+    pub role_weights: VecMap<Role, u8>,
+    pub role_constraints: Vec<RoleConstraint>,
+    pub custom_role_sets: Vec<CustomRoleSet>,
+    pub presets: Vec<PresetSummary>,
+}
+
+impl LobbyMirror {
+    fn new(room_code: RoomCode, player_id: LobbyClientID, spectator: bool) -> Self {
+        Self {
+            room_code,
+            player_id,
+            spectator,
+            name: String::new(),
+            visibility: LobbyVisibility::default(),
+            invite_code: None,
+            start_settings: StartSettings::default(),
+            start_countdown: None,
+            clients: VecMap::new(),
+            hosts: Vec::new(),
+            role_list: RoleList::default(),
+            phase_times: PhaseTimeSettings::default(),
+            enabled_roles: Vec::new(),
+            enabled_modifiers: Vec::new(),
+            role_weights: VecMap::new(),
+            role_constraints: Vec::new(),
+            custom_role_sets: Vec::new(),
+            presets: Vec::new(),
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.hosts.contains(&self.player_id)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GameMirror {
+    pub players: Vec<String>,
+    /// `None` for spectators
+    pub my_index: Option<PlayerIndex>,
+    pub phase: Option<PhaseState>,
+    pub day_number: u8,
+    pub seconds_left: Option<u64>,
+    pub alive: Vec<bool>,
+    pub votes_for_player: VecMap<PlayerIndex, u8>,
+    pub role_state: Option<ClientRoleStateEnum>,
+    pub fellow_insiders: VecSet<PlayerIndex>,
+    pub send_chat_groups: Vec<ChatGroup>,
+    pub insider_groups: VecSet<InsiderGroupID>,
+    pub allowed_controllers: VecMap<ControllerID, SavedController>,
+    pub role_labels: VecMap<PlayerIndex, Role>,
+    pub player_tags: VecMap<PlayerIndex, Vec1<Tag>>,
+    pub will: String,
+    pub notes: Vec<String>,
+    pub crossed_out_outlines: Vec<u8>,
+    pub death_note: Option<String>,
+    pub judgement: Option<Verdict>,
+    pub fast_forward: bool,
+    pub chat_messages: Vec<ChatMessage>,
+    pub graves: Vec<Grave>,
+    pub game_over: Option<GameOverReason>,
+}
+
+impl GameMirror {
+    pub fn is_alive(&self, player: PlayerIndex) -> bool {
+        self.alive.get(usize::from(player)).copied().unwrap_or(false)
+    }
+}
+
+impl Mirror {
+    pub fn apply(&mut self, packet: &ToClientPacket) {
+        match packet {
+            ToClientPacket::Sequenced { packet, .. } => self.apply(packet),
+            ToClientPacket::LobbyList { lobbies } => self.lobby_list = lobbies.clone(),
+            ToClientPacket::AcceptJoin { room_code, in_game, player_id, spectator } => {
+                self.lobby = Some(LobbyMirror::new(*room_code, *player_id, *spectator));
+                self.game = in_game.then(GameMirror::default);
+            }
+            ToClientPacket::ForcedOutsideLobby => self.leave(),
+            ToClientPacket::StartGame => {
+                self.game = Some(GameMirror::default());
+                if let Some(lobby) = &mut self.lobby {
+                    lobby.start_countdown = None;
+                }
+            }
+            ToClientPacket::BackToLobby => self.game = None,
+            packet => {
+                if let Some(lobby) = &mut self.lobby {
+                    lobby.apply(packet);
+                }
+                if let Some(game) = &mut self.game {
+                    game.apply(packet);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.lobby = None;
+        self.game = None;
+    }
+}
+
+impl LobbyMirror {
+    fn apply(&mut self, packet: &ToClientPacket) {
+        match packet {
+            ToClientPacket::YourId { player_id } => self.player_id = *player_id,
+            ToClientPacket::LobbyName { name } => self.name = name.clone(),
+            ToClientPacket::LobbyVisibility { visibility } => self.visibility = visibility.clone(),
+            ToClientPacket::LobbyInviteCode { invite_code } => self.invite_code = Some(invite_code.clone()),
+            ToClientPacket::LobbyStartSettings { start_settings } => self.start_settings = start_settings.clone(),
+            ToClientPacket::StartCountdown { seconds_left } => self.start_countdown = *seconds_left,
+            ToClientPacket::LobbyClients { clients } => self.clients = clients.clone(),
+            ToClientPacket::LobbyClientsPatch { clients } => clients.clone().apply(&mut self.clients),
+            ToClientPacket::PlayersHost { hosts } => self.hosts = hosts.clone(),
+            ToClientPacket::RoleList { role_list } => self.role_list = role_list.clone(),
+            ToClientPacket::RoleOutline { index, role_outline } => {
+                if let Some(outline) = self.role_list.0.get_mut(usize::from(*index)) {
+                    *outline = role_outline.clone();
+                }
+            }
+            ToClientPacket::PhaseTime { phase, time } => self.phase_times.set_time_for(*phase, *time),
+            ToClientPacket::PhaseTimes { phase_time_settings } => self.phase_times = phase_time_settings.clone(),
+            ToClientPacket::EnabledRoles { roles } => self.enabled_roles = roles.clone(),
+            ToClientPacket::EnabledModifiers { modifiers } => self.enabled_modifiers = modifiers.clone(),
+            ToClientPacket::RoleWeights { role_weights } => self.role_weights = role_weights.clone(),
+            ToClientPacket::RoleConstraints { constraints } => self.role_constraints = constraints.clone(),
+            ToClientPacket::CustomRoleSets { custom_role_sets } => self.custom_role_sets = custom_role_sets.clone(),
+            ToClientPacket::Presets { presets } => self.presets = presets.clone(),
+            _ => {}
+        }
+    }
+}
+
+impl GameMirror {
+    fn apply(&mut self, packet: &ToClientPacket) {
+        match packet {
+            ToClientPacket::GamePlayers { players } => self.players = players.clone(),
+            ToClientPacket::YourPlayerIndex { player_index } => self.my_index = Some(*player_index),
+            ToClientPacket::YourFellowInsiders { fellow_insiders } => self.fellow_insiders = fellow_insiders.clone(),
+            ToClientPacket::Phase { phase, day_number } => {
+                self.phase = Some(phase.clone());
+                self.day_number = *day_number;
+                self.judgement = None;
+            }
+            ToClientPacket::PhaseTimeLeft { seconds_left } => self.seconds_left = Some(*seconds_left),
+            ToClientPacket::PlayerAlive { alive } => self.alive = alive.clone(),
+            ToClientPacket::PlayerVotes { votes_for_player } => self.votes_for_player = votes_for_player.clone(),
+            ToClientPacket::PlayerVotesPatch { votes_for_player } => votes_for_player.clone().apply(&mut self.votes_for_player),
+            ToClientPacket::YourSendChatGroups { send_chat_groups } => self.send_chat_groups = send_chat_groups.clone(),
+            ToClientPacket::YourInsiderGroups { insider_groups } => self.insider_groups = insider_groups.clone(),
+            ToClientPacket::YourAllowedControllers { save } => self.allowed_controllers = save.clone(),
+            ToClientPacket::YourAllowedControllersPatch { save } => save.clone().apply(&mut self.allowed_controllers),
+            ToClientPacket::YourRoleLabels { role_labels } => self.role_labels = role_labels.clone(),
+            ToClientPacket::YourRoleLabelsPatch { role_labels } => role_labels.clone().apply(&mut self.role_labels),
+            ToClientPacket::YourPlayerTags { player_tags } => self.player_tags = player_tags.clone(),
+            ToClientPacket::YourPlayerTagsPatch { player_tags } => player_tags.clone().apply(&mut self.player_tags),
+            ToClientPacket::YourWill { will } => self.will = will.clone(),
+            ToClientPacket::YourNotes { notes } => self.notes = notes.clone(),
+            ToClientPacket::YourCrossedOutOutlines { crossed_out_outlines } => self.crossed_out_outlines = crossed_out_outlines.clone(),
+            ToClientPacket::YourDeathNote { death_note } => self.death_note = death_note.clone(),
+            ToClientPacket::YourRoleState { role_state } => self.role_state = Some(role_state.clone()),
+            ToClientPacket::YourJudgement { verdict } => self.judgement = Some(*verdict),
+            ToClientPacket::YourVoteFastForwardPhase { fast_forward } => self.fast_forward = *fast_forward,
+            ToClientPacket::AddChatMessages { chat_messages } => self.chat_messages.extend(chat_messages.iter().cloned()),
+            ToClientPacket::AddGrave { grave } => self.graves.push(grave.clone()),
+            ToClientPacket::GameOver { reason } => self.game_over = Some(*reason),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::{net::{IpAddr, Ipv6Addr, SocketAddr}, sync::{atomic::{AtomicU64, Ordering}, Arc, Mutex}};
+
+use futures_util::{SinkExt, StreamExt};
+use mafia_server::{
+    listener::Listener,
+    packet::{ToClientPacket, ToServerPacket},
+    websocket_connections::{connection::Connection, protocol::{Capability, Encoding}, sent_state::SentState, ForceLock}
+};
+use tokio::{net::TcpStream, sync::mpsc};
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::ClientError;
+
+/// How packets get to and from the server
+pub enum Transport {
+    WebSocket(Box<WebSocketStream<MaybeTlsStream<TcpStream>>>),
+    InProcess(InProcess),
+}
+
+impl Transport {
+    /// Only `ws://` URLs, since the websocket client is built without TLS
+    pub async fn connect(url: &str) -> Result<Self, ClientError> {
+        let (stream, _) = tokio_tungstenite::connect_async(url).await.map_err(ClientError::WebSocket)?;
+        Ok(Transport::WebSocket(Box::new(stream)))
+    }
+
+    pub fn in_process(listener: Arc<Mutex<Listener>>) -> Self {
+        Transport::InProcess(InProcess::new(listener))
+    }
+
+    pub async fn send(&mut self, packet: &ToServerPacket, encoding: Encoding) -> Result<(), ClientError> {
+        let message = match encoding {
+            Encoding::Json => Message::text(serde_json::to_string(packet).map_err(ClientError::Json)?),
+            Encoding::MessagePack => Message::binary(rmp_serde::to_vec_named(packet).map_err(ClientError::MessagePackEncode)?),
+        };
+        match self {
+            Transport::WebSocket(stream) => stream.send(message).await.map_err(ClientError::WebSocket),
+            Transport::InProcess(in_process) => in_process.send(&message),
+        }
+    }
+
+    /// Returns `None` once the connection is closed
+    pub async fn recv(&mut self) -> Option<Result<ToClientPacket, ClientError>> {
+        match self {
+            Transport::WebSocket(stream) => loop {
+                let message = match stream.next().await? {
+                    Ok(message) => message,
+                    Err(err) => return Some(Err(ClientError::WebSocket(err))),
+                };
+                match message {
+                    Message::Close(_) => return None,
+                    message => if let Some(packet) = decode(&message) {
+                        return Some(packet);
+                    }
+                }
+            },
+            Transport::InProcess(in_process) => in_process.recv().await,
+        }
+    }
+
+    pub async fn close(&mut self) {
+        match self {
+            Transport::WebSocket(stream) => {
+                let _ = stream.close(None).await;
+            }
+            Transport::InProcess(in_process) => in_process.close(),
+        }
+    }
+}
+
+/// A connection to a [`Listener`] in the same process.
+/// Packets are rewritten for the negotiated protocol the same way the websocket server does before sending them.
+pub struct InProcess {
+    listener: Arc<Mutex<Listener>>,
+    connection: Connection,
+    receiver: mpsc::UnboundedReceiver<ToClientPacket>,
+    sent_state: SentState,
+    closed: bool,
+}
+
+impl InProcess {
+    fn new(listener: Arc<Mutex<Listener>>) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let connection = Connection::new(sender, next_address());
+        listener.force_lock().on_connect(&connection);
+
+        Self { listener, connection, receiver, sent_state: SentState::default(), closed: false }
+    }
+
+    fn send(&mut self, message: &Message) -> Result<(), ClientError> {
+        if self.closed {
+            return Err(ClientError::Closed);
+        }
+        self.listener.force_lock().on_message(&self.connection, message);
+        Ok(())
+    }
+
+    /// Goes through the same encoding as a websocket, so nothing works here that wouldn't work over the network
+    async fn recv(&mut self) -> Option<Result<ToClientPacket, ClientError>> {
+        loop {
+            if self.closed {
+                return None;
+            }
+            let packet = self.receiver.recv().await?;
+            let protocol = self.connection.protocol();
+
+            let Some(packet) = protocol.downgrade(packet) else {continue};
+            let packet = if protocol.has(Capability::StatePatches) {
+                let Some(packet) = self.sent_state.patch(packet) else {continue};
+                packet
+            } else {
+                packet
+            };
+
+            let Some(message) = protocol.encoding().encode(&packet) else {continue};
+            if let Some(packet) = decode(&message) {
+                return Some(packet);
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        if !self.closed {
+            self.closed = true;
+            let _ = self.listener.force_lock().on_disconnect(self.connection.clone());
+        }
+    }
+}
+
+impl Drop for InProcess {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Text frames are JSON and binary frames are MessagePack. Returns `None` for frames that aren't packets.
+fn decode(message: &Message) -> Option<Result<ToClientPacket, ClientError>> {
+    match message {
+        Message::Text(text) => Some(serde_json::from_str(text).map_err(ClientError::Json)),
+        Message::Binary(bytes) => Some(rmp_serde::from_slice(bytes).map_err(ClientError::MessagePackDecode)),
+        // Websocket pings are answered by tungstenite
+        _ => None,
+    }
+}
+
+/// The listener tells clients apart by address, so every in-process connection needs its own
+fn next_address() -> SocketAddr {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    SocketAddr::new(IpAddr::V6(Ipv6Addr::from(u128::from(NEXT.fetch_add(1, Ordering::Relaxed)))), 0)
+}
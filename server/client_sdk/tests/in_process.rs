@@ -0,0 +1,77 @@
+use std::sync::{Arc, Mutex};
+
+use mafia_client::{transport::Transport, Client, ClientError, ConnectionState, ToClientPacket, ToServerPacket};
+use mafia_server::{
+    game::role::Role,
+    listener::Listener,
+    packet::RejectJoinReason,
+    vec_set::VecSet,
+    websocket_connections::protocol::{Capability, Encoding}
+};
+
+async fn connect(listener: &Arc<Mutex<Listener>>) -> Client {
+    Client::in_process(listener.clone()).await.expect("Hello should be accepted")
+}
+
+async fn wait_for_game(client: &mut Client) {
+    client.wait_for(|packet| matches!(packet, ToClientPacket::GameInitializationComplete).then_some(Ok(())))
+        .await
+        .expect("Game should start");
+}
+
+#[tokio::test]
+async fn clients_play_against_an_in_process_listener() {
+    let listener = Arc::new(Mutex::new(Listener::new()));
+    // Games start on a tick, once their roles are dealt
+    Listener::start(listener.clone());
+
+    let mut host = connect(&listener).await;
+    assert!(host.protocol().has(Capability::StatePatches));
+    assert_eq!(host.protocol().encoding(), Encoding::MessagePack);
+
+    let room_code = host.host().await.expect("Lobby should be created");
+    assert!(matches!(host.state(), ConnectionState::InLobby { room_code: code, .. } if code == room_code));
+
+    assert!(matches!(
+        connect(&listener).await.join(room_code.wrapping_add(1), None).await,
+        Err(ClientError::Rejected(RejectJoinReason::RoomDoesntExist))
+    ));
+
+    // One player speaks JSON without patches, like an older client would
+    let mut players = vec![
+        Client::with_transport(Transport::in_process(listener.clone()), VecSet::new()).await.expect("Hello should be accepted")
+    ];
+    for _ in 0..4 {
+        players.push(connect(&listener).await);
+    }
+    for player in &mut players {
+        player.join(room_code, None).await.expect("Lobby should have room");
+    }
+
+    while host.mirror.lobby.as_ref().map(|lobby| lobby.clients.len()) != Some(6) {
+        host.recv().await.expect("Host should be connected").expect("Packet should decode");
+    }
+    assert!(host.mirror.lobby.as_ref().is_some_and(|lobby| lobby.is_host()));
+
+    host.send(ToServerPacket::SetEnabledRoles { roles: Role::values() }).await.expect("Host should be connected");
+    host.send(ToServerPacket::StartGame).await.expect("Host should be connected");
+
+    wait_for_game(&mut host).await;
+    for player in &mut players {
+        wait_for_game(player).await;
+    }
+
+    let game = host.mirror.game.as_ref().expect("Host should be in the game");
+    assert_eq!(game.players.len(), 6);
+    assert!(game.role_state.is_some());
+    assert!(matches!(host.state(), ConnectionState::InGame { .. }));
+
+    // Drop the connection, then pick up where it left off
+    assert!(host.last_sequence().is_some());
+    host.close().await;
+    assert_eq!(host.state(), ConnectionState::Disconnected);
+
+    host.reconnect(Transport::in_process(listener.clone())).await.expect("Game should be resumed");
+    assert!(matches!(host.state(), ConnectionState::InGame { room_code: code, .. } if code == room_code));
+    assert_eq!(host.mirror.game.as_ref().map(|game| game.players.len()), Some(6));
+}
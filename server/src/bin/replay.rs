@@ -0,0 +1,41 @@
+use std::{env, fs, process::ExitCode};
+
+use mafia_server::game::{components::event_journal::GameRecord, replay};
+
+/// Replays an exported game record and checks that it ends the same way it did the first time.
+/// 
+/// Usage: `cargo run --bin replay -- ./game_records/<record>.json`
+fn main() -> ExitCode {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("Usage: replay <game record file>");
+        return ExitCode::FAILURE;
+    };
+
+    let record = match fs::read_to_string(&path).map_err(|err| err.to_string())
+        .and_then(|json| serde_json::from_str::<GameRecord>(&json).map_err(|err| err.to_string()))
+    {
+        Ok(record) => record,
+        Err(err) => {
+            eprintln!("Failed to read {path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match replay::verify(&record) {
+        Ok(mismatches) if mismatches.is_empty() => {
+            println!("Replay of {path} matches the recorded outcome");
+            ExitCode::SUCCESS
+        }
+        Ok(mismatches) => {
+            eprintln!("Replay of {path} diverged from the recorded outcome:");
+            for mismatch in mismatches {
+                eprintln!("{mismatch:#?}");
+            }
+            ExitCode::FAILURE
+        }
+        Err(err) => {
+            eprintln!("Failed to replay {path}: {err:?}");
+            ExitCode::FAILURE
+        }
+    }
+}
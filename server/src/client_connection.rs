@@ -1,8 +1,10 @@
-use std::time::Duration;
+use std::{collections::VecDeque, time::Duration};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{packet::ToClientPacket, websocket_connections::connection::ClientSender};
+use crate::{config::Config, packet::ToClientPacket, websocket_connections::connection::ClientSender};
+
+pub type PacketSequence = u64;
 
 #[derive(Clone, Debug)]
 pub enum ClientConnection {
@@ -20,6 +22,46 @@ impl ClientConnection {
         }
     }
 }
+/// The packets most recently sent to a player, numbered in the order they were sent.
+/// A client that reconnects can say which number it saw last and be sent only what it missed.
+#[derive(Debug, Default)]
+pub struct SentPackets {
+    next_sequence: PacketSequence,
+    /// Already wrapped in [`ToClientPacket::Sequenced`], oldest first
+    buffer: VecDeque<ToClientPacket>,
+}
+impl SentPackets {
+    /// Numbers the packet and keeps a copy, forgetting the oldest one if the buffer is full
+    pub fn push(&mut self, packet: ToClientPacket) -> ToClientPacket {
+        let sequenced = ToClientPacket::Sequenced { sequence: self.next_sequence, packet: Box::new(packet) };
+        self.next_sequence = self.next_sequence.saturating_add(1);
+
+        let capacity = Config::get().resume_buffer_size;
+        if capacity > 0 {
+            while self.buffer.len() >= capacity {
+                self.buffer.pop_front();
+            }
+            self.buffer.push_back(sequenced.clone());
+        }
+        sequenced
+    }
+
+    /// Everything sent after `last_sequence`, or `None` if some of it was already forgotten
+    /// or `last_sequence` was never sent (e.g. it's from before the server restarted)
+    pub fn since(&self, last_sequence: PacketSequence) -> Option<Vec<ToClientPacket>> {
+        let missed = self.next_sequence.checked_sub(last_sequence.checked_add(1)?)?;
+        let missed = usize::try_from(missed).ok()?;
+        let first_missed = self.buffer.len().checked_sub(missed)?;
+
+        Some(self.buffer.iter().skip(first_missed).cloned().collect())
+    }
+
+    /// Forgets every packet but keeps counting from where it was, so nobody can resume from before now
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+}
+
 impl Serialize for ClientConnection{
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -30,4 +72,17 @@ impl Serialize for ClientConnection{
             ClientConnection::Disconnected => serializer.serialize_str("disconnected"),
         }
     }
+}
+/// Only what the client is told survives, so a connected client gets a [`ClientSender::detached`]
+impl<'de> Deserialize<'de> for ClientConnection {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de> {
+        match String::deserialize(deserializer)?.as_str() {
+            "connected" => Ok(ClientConnection::Connected(ClientSender::detached())),
+            "couldReconnect" => Ok(ClientConnection::CouldReconnect { disconnect_timer: Duration::ZERO }),
+            "disconnected" => Ok(ClientConnection::Disconnected),
+            other => Err(serde::de::Error::unknown_variant(other, &["connected", "couldReconnect", "disconnected"])),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,302 @@
+use std::{fmt::Display, net::ToSocketAddrs, path::{Path, PathBuf}, sync::OnceLock, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::EnvFilter;
+
+use crate::logging::LogFormat;
+
+pub const CONFIG_PATH: &str = "./resources/config.json";
+/// Prefix for environment variables that override the config file, e.g. `MAFIA_MAX_LOBBIES=50`
+pub const ENV_PREFIX: &str = "MAFIA_";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Server-wide settings.
+///
+/// Loaded once at startup from the config file, then environment variables, then command line flags,
+/// each one overriding the last. Every field can be left out and falls back to its default.
+///
+/// Environment variables and flags use the field name in SCREAMING_SNAKE_CASE and kebab-case,
+/// e.g. `MAFIA_MAX_LOBBIES=50` or `--max-lobbies 50`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Config {
+    pub address: String,
+    /// PEM certificate chain. If this and the key are set, the server serves `wss://` itself
+    /// and reloads them both on SIGHUP.
+    pub tls_cert_path: Option<String>,
+    /// PEM private key
+    pub tls_key_path: Option<String>,
+
+    /// Which logs to keep, in `RUST_LOG` syntax. Re-read from the config file and environment on SIGHUP.
+    pub log_filter: String,
+    pub log_format: LogFormat,
+    /// If set, logs are also written to files in this directory, one per day
+    pub log_directory: Option<String>,
+    /// If set, Prometheus metrics are served over HTTP at `/metrics` on this address
+    pub metrics_address: Option<String>,
+    /// If set, a read-only JSON API listing lobbies and server status is served over HTTP on this address
+    pub http_api_address: Option<String>,
+
+    /// Hosting a new lobby past this is rejected with `ServerBusy`
+    pub max_lobbies: usize,
+    /// Joining a lobby with this many players and spectators is rejected with `RoomFull`
+    pub max_clients_per_lobby: usize,
+
+    pub lobby_disconnect_timer_secs: u64,
+    pub game_disconnect_timer_secs: u64,
+    /// How long a lobby with auto start on counts down once everyone is ready
+    pub auto_start_countdown_secs: u64,
+    /// Chat messages allowed per second, averaged over the rate limit window
+    pub message_per_second_limit: u64,
+    pub message_rate_limit_window_secs: u64,
+    pub pong_interval_secs: u64,
+    pub frame_time_millis: u64,
+    /// How many of the latest packets are kept per player, so a player who reconnects
+    /// can be sent only what they missed. If they missed more, they're sent the whole game again.
+    pub resume_buffer_size: usize,
+    /// Wrong lobby passwords a connection can give within the wrong password window, before it has to wait
+    pub max_wrong_passwords: usize,
+    pub wrong_password_window_secs: u64,
+    /// After SIGTERM or SIGINT, how long running games get to finish before the server saves them and exits
+    pub shutdown_deadline_secs: u64,
+
+    pub max_name_length: usize,
+    pub max_server_name_length: usize,
+    pub max_role_generation_tries: u8,
+    /// If set, every `.json` preset in this directory is offered to hosts alongside the built-in ones
+    pub presets_directory: Option<String>,
+    /// Where a record of every game is written when it ends. If unset, no records are kept.
+    pub game_record_directory: Option<String>,
+    /// Where open lobbies are saved, so a restarted server can bring them back.
+    /// Lobby passwords are saved in it too, so it's only readable by the user running the server.
+    pub snapshot_path: String,
+    /// How often open lobbies are saved while the server runs. They're always saved when it stops.
+    pub snapshot_interval_secs: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            address: "0.0.0.0:8081".to_string(),
+            tls_cert_path: None,
+            tls_key_path: None,
+            log_filter: "info".to_string(),
+            log_format: LogFormat::Human,
+            log_directory: None,
+            metrics_address: None,
+            http_api_address: None,
+            max_lobbies: 1000,
+            max_clients_per_lobby: 50,
+            lobby_disconnect_timer_secs: 5,
+            game_disconnect_timer_secs: 60 * 2,
+            auto_start_countdown_secs: 10,
+            message_per_second_limit: 1,
+            message_rate_limit_window_secs: 10,
+            pong_interval_secs: 5,
+            frame_time_millis: 1000,
+            resume_buffer_size: 512,
+            max_wrong_passwords: 5,
+            wrong_password_window_secs: 60,
+            shutdown_deadline_secs: 60 * 5,
+            max_name_length: 20,
+            max_server_name_length: 20,
+            max_role_generation_tries: 250,
+            presets_directory: None,
+            game_record_directory: Some("./game_records".to_string()),
+            snapshot_path: "./snapshots/listener.json".to_string(),
+            snapshot_interval_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    UnknownKey(String),
+    MissingValue(String),
+    InvalidValue { key: String, value: String },
+    Invalid(&'static str),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "Failed to read the config file: {err}"),
+            ConfigError::Json(err) => write!(f, "Failed to parse the config file: {err}"),
+            ConfigError::UnknownKey(key) => write!(f, "Unknown config option: {key}"),
+            ConfigError::MissingValue(key) => write!(f, "No value given for config option: {key}"),
+            ConfigError::InvalidValue { key, value } => write!(f, "Invalid value for config option {key}: {value}"),
+            ConfigError::Invalid(reason) => write!(f, "Invalid config: {reason}"),
+        }
+    }
+}
+
+impl Config {
+    /// The config the server was started with, or the default config if it hasn't been set
+    pub fn get() -> &'static Config {
+        CONFIG.get_or_init(Config::default)
+    }
+
+    /// Can only be done once, before anything reads the config. Returns false if the config was already set.
+    pub fn set(config: Config) -> bool {
+        CONFIG.set(config).is_ok()
+    }
+
+    /// Reads the config file (if there is one), then applies overrides from `env_vars` and `args`, then validates it.
+    /// Also returns the environment variables that have the prefix but aren't options, since they're
+    /// only worth a warning, and logging isn't set up until the config is loaded.
+    pub fn load(
+        path: &Path,
+        env_vars: impl IntoIterator<Item = (String, String)>,
+        args: impl IntoIterator<Item = String>
+    ) -> Result<(Config, Vec<String>), ConfigError> {
+        let mut config = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(ConfigError::Json)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Config::default(),
+            Err(err) => return Err(ConfigError::Io(err)),
+        };
+
+        let mut ignored_env_vars = Vec::new();
+        for (name, value) in env_vars {
+            let Some(key) = name.strip_prefix(ENV_PREFIX) else {continue};
+            // Other programs may use the same prefix, so only a typo on the command line is fatal
+            match config.set_option(&key.to_lowercase().replace('_', "-"), &value) {
+                Err(ConfigError::UnknownKey(_)) => ignored_env_vars.push(name),
+                result => result?,
+            }
+        }
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                return Err(ConfigError::UnknownKey(arg));
+            };
+            if let Some((key, value)) = flag.split_once('=') {
+                config.set_option(key, value)?;
+            } else {
+                let value = args.next().ok_or_else(|| ConfigError::MissingValue(flag.to_string()))?;
+                config.set_option(flag, &value)?;
+            }
+        }
+
+        config.validate()?;
+        Ok((config, ignored_env_vars))
+    }
+
+    /// `key` is the kebab-case name of the field
+    fn set_option(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, ConfigError> {
+            value.parse().map_err(|_| ConfigError::InvalidValue { key: key.to_string(), value: value.to_string() })
+        }
+
+        match key {
+            "address" => self.address = value.to_string(),
+            "tls-cert-path" => self.tls_cert_path = Some(value.to_string()),
+            "tls-key-path" => self.tls_key_path = Some(value.to_string()),
+            "log-filter" => self.log_filter = value.to_string(),
+            "log-format" => self.log_format = parse(key, value)?,
+            "log-directory" => self.log_directory = Some(value.to_string()),
+            "metrics-address" => self.metrics_address = Some(value.to_string()),
+            "http-api-address" => self.http_api_address = Some(value.to_string()),
+            "max-lobbies" => self.max_lobbies = parse(key, value)?,
+            "max-clients-per-lobby" => self.max_clients_per_lobby = parse(key, value)?,
+            "lobby-disconnect-timer-secs" => self.lobby_disconnect_timer_secs = parse(key, value)?,
+            "game-disconnect-timer-secs" => self.game_disconnect_timer_secs = parse(key, value)?,
+            "auto-start-countdown-secs" => self.auto_start_countdown_secs = parse(key, value)?,
+            "message-per-second-limit" => self.message_per_second_limit = parse(key, value)?,
+            "message-rate-limit-window-secs" => self.message_rate_limit_window_secs = parse(key, value)?,
+            "pong-interval-secs" => self.pong_interval_secs = parse(key, value)?,
+            "frame-time-millis" => self.frame_time_millis = parse(key, value)?,
+            "resume-buffer-size" => self.resume_buffer_size = parse(key, value)?,
+            "max-wrong-passwords" => self.max_wrong_passwords = parse(key, value)?,
+            "wrong-password-window-secs" => self.wrong_password_window_secs = parse(key, value)?,
+            "shutdown-deadline-secs" => self.shutdown_deadline_secs = parse(key, value)?,
+            "max-name-length" => self.max_name_length = parse(key, value)?,
+            "max-server-name-length" => self.max_server_name_length = parse(key, value)?,
+            "max-role-generation-tries" => self.max_role_generation_tries = parse(key, value)?,
+            "presets-directory" => self.presets_directory = Some(value.to_string()),
+            "game-record-directory" => self.game_record_directory = Some(value.to_string()),
+            "snapshot-path" => self.snapshot_path = value.to_string(),
+            "snapshot-interval-secs" => self.snapshot_interval_secs = parse(key, value)?,
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.address.to_socket_addrs().is_err() {
+            return Err(ConfigError::InvalidValue { key: "address".to_string(), value: self.address.clone() });
+        }
+        if self.tls_cert_path.is_some() != self.tls_key_path.is_some() {
+            return Err(ConfigError::Invalid("TLS needs both a certificate path and a key path"));
+        }
+        if EnvFilter::try_new(&self.log_filter).is_err() {
+            return Err(ConfigError::InvalidValue { key: "log-filter".to_string(), value: self.log_filter.clone() });
+        }
+        if self.max_lobbies == 0 {
+            return Err(ConfigError::Invalid("max lobbies must be at least 1"));
+        }
+        if self.max_clients_per_lobby == 0 {
+            return Err(ConfigError::Invalid("max clients per lobby must be at least 1"));
+        }
+        if self.pong_interval_secs == 0 {
+            return Err(ConfigError::Invalid("pong interval must be at least 1 second"));
+        }
+        if self.max_wrong_passwords == 0 {
+            return Err(ConfigError::Invalid("max wrong passwords must be at least 1"));
+        }
+        if self.frame_time_millis == 0 {
+            return Err(ConfigError::Invalid("frame time must be at least 1 millisecond"));
+        }
+        if self.max_name_length == 0 || self.max_server_name_length == 0 {
+            return Err(ConfigError::Invalid("max name lengths must be at least 1"));
+        }
+        if self.max_role_generation_tries == 0 {
+            return Err(ConfigError::Invalid("max role generation tries must be at least 1"));
+        }
+        if self.snapshot_interval_secs == 0 {
+            return Err(ConfigError::Invalid("snapshot interval must be at least 1 second"));
+        }
+        Ok(())
+    }
+
+    pub fn lobby_disconnect_timer(&self) -> Duration {
+        Duration::from_secs(self.lobby_disconnect_timer_secs)
+    }
+    pub fn game_disconnect_timer(&self) -> Duration {
+        Duration::from_secs(self.game_disconnect_timer_secs)
+    }
+    pub fn auto_start_countdown(&self) -> Duration {
+        Duration::from_secs(self.auto_start_countdown_secs)
+    }
+    pub fn message_rate_limit_window(&self) -> Duration {
+        Duration::from_secs(self.message_rate_limit_window_secs)
+    }
+    /// How many chat messages a client can send within the rate limit window
+    pub fn messages_per_rate_limit_window(&self) -> u64 {
+        self.message_rate_limit_window_secs.saturating_mul(self.message_per_second_limit)
+    }
+    pub fn pong_interval(&self) -> Duration {
+        Duration::from_secs(self.pong_interval_secs)
+    }
+    pub fn frame_time(&self) -> Duration {
+        Duration::from_millis(self.frame_time_millis)
+    }
+    pub fn wrong_password_window(&self) -> Duration {
+        Duration::from_secs(self.wrong_password_window_secs)
+    }
+    pub fn shutdown_deadline(&self) -> Duration {
+        Duration::from_secs(self.shutdown_deadline_secs)
+    }
+    pub fn game_record_directory(&self) -> Option<PathBuf> {
+        self.game_record_directory.as_ref().map(PathBuf::from)
+    }
+    pub fn snapshot_path(&self) -> &Path {
+        Path::new(&self.snapshot_path)
+    }
+    pub fn snapshot_interval(&self) -> Duration {
+        Duration::from_secs(self.snapshot_interval_secs)
+    }
+}
@@ -38,6 +38,9 @@ impl ControllerParameters{
         }
     }
     
+    pub fn available(&self)->&AvailableAbilitySelection{
+        &self.available
+    }
     pub fn validate_selection(&self, game: &Game, selection: &AbilitySelection)->bool{
         self.available.validate_selection(game, selection)
     }
@@ -402,6 +402,9 @@ impl SavedController{
     pub fn selection(&self)->&AbilitySelection{
         &self.selection
     }
+    pub fn parameters(&self)->&ControllerParameters{
+        &self.available_ability_data
+    }
     pub fn reset_on_phase_start(&mut self, phase: PhaseType){
         if let Some(reset_phase) = self.available_ability_data.reset_on_phase_start(){
             if phase == reset_phase{
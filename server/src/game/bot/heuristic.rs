@@ -0,0 +1,152 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    game::{
+        ability_input::{AbilitySelection, AvailableAbilitySelection, ControllerID, ControllerParameters, PlayerListSelection},
+        chat::ChatMessageVariant,
+        phase::PhaseState,
+        player::{PlayerIndex, PlayerReference},
+        verdict::Verdict
+    },
+    vec_map::VecMap, vec_set::VecSet
+};
+
+use super::{BotStrategy, BotView};
+
+/// Suspicion added when someone votes against this bot
+const VOTED_AGAINST_ME: i32 = 1;
+/// Suspicion added or removed by this bot's own investigations
+const INVESTIGATION_RESULT: i32 = 3;
+
+/// Plays from nothing but its own chat: who voted against it, what its investigations found,
+/// and whose roles it was shown, who it treats as teammates.
+/// Its random choices come from its own RNG, since bots are outside the game and the game's RNG has to replay the same way without them.
+pub struct HeuristicBot {
+    rng: StdRng,
+    /// How many of the player's chat messages have been read
+    read: usize,
+    suspicion: VecMap<PlayerIndex, i32>,
+    teammates: VecSet<PlayerIndex>,
+    on_trial: Option<PlayerIndex>,
+    /// Who this bot last used an ability on, so the result can be pinned on them
+    last_target: Option<PlayerIndex>,
+}
+
+impl HeuristicBot {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            read: 0,
+            suspicion: VecMap::new(),
+            teammates: VecSet::new(),
+            on_trial: None,
+            last_target: None,
+        }
+    }
+
+    pub fn suspicion(&self, player: PlayerIndex) -> i32 {
+        self.suspicion.get(&player).copied().unwrap_or(0)
+    }
+
+    fn suspect(&mut self, player: PlayerIndex, amount: i32) {
+        let suspicion = self.suspicion(player).saturating_add(amount);
+        self.suspicion.insert(player, suspicion);
+    }
+
+    fn read_chat(&mut self, view: &BotView) {
+        let me = view.player.index();
+
+        for message in view.chat_messages.iter().skip(self.read) {
+            match message.variant() {
+                ChatMessageVariant::PlayersRoleRevealed { player, .. } => {
+                    self.teammates.insert(*player);
+                }
+                ChatMessageVariant::PlayersRoleConcealed { player } => {
+                    self.teammates.remove(player);
+                }
+                ChatMessageVariant::PhaseChange { phase, .. } => {
+                    self.on_trial = match phase {
+                        PhaseState::Testimony { player_on_trial, .. } |
+                        PhaseState::Judgement { player_on_trial, .. } |
+                        PhaseState::FinalWords { player_on_trial } => Some(player_on_trial.index()),
+                        _ => None,
+                    };
+                }
+                ChatMessageVariant::Voted { voter, votee: Some(votee) } if *votee == me => {
+                    self.suspect(*voter, VOTED_AGAINST_ME);
+                }
+                ChatMessageVariant::JudgementVerdict { voter_player_index, verdict: Verdict::Guilty } if self.on_trial == Some(me) => {
+                    self.suspect(*voter_player_index, VOTED_AGAINST_ME);
+                }
+                ChatMessageVariant::AbilityUsed { player, selection: AbilitySelection::PlayerList { selection: PlayerListSelection(targets) }, .. } if *player == me => {
+                    self.last_target = targets.first().map(PlayerReference::index);
+                }
+                ChatMessageVariant::SheriffResult { suspicious } => {
+                    self.investigated(if *suspicious { INVESTIGATION_RESULT } else { INVESTIGATION_RESULT.saturating_neg() });
+                }
+                ChatMessageVariant::SnoopResult { townie } => {
+                    self.investigated(if *townie { INVESTIGATION_RESULT.saturating_neg() } else { INVESTIGATION_RESULT });
+                }
+                _ => {}
+            }
+        }
+
+        self.read = view.chat_messages.len();
+    }
+
+    fn investigated(&mut self, amount: i32) {
+        if let Some(target) = self.last_target {
+            self.suspect(target, amount);
+        }
+    }
+
+    /// Everyone in `players` who is neither this bot nor a teammate, most suspicious first
+    fn others_by_suspicion<'a>(&mut self, view: &BotView, players: impl Iterator<Item = &'a PlayerReference>) -> Vec<PlayerReference> {
+        let mut others: Vec<PlayerReference> = players
+            .filter(|player| **player != view.player && !self.teammates.contains(&player.index()))
+            .copied()
+            .collect();
+
+        // Shuffled first so ties are broken at random
+        others.shuffle(&mut self.rng);
+        others.sort_by_key(|player| std::cmp::Reverse(self.suspicion(player.index())));
+        others
+    }
+}
+
+impl BotStrategy for HeuristicBot {
+    fn nominate(&mut self, view: &BotView, candidates: &VecSet<PlayerReference>) -> Option<PlayerReference> {
+        self.read_chat(view);
+
+        self.others_by_suspicion(view, candidates.iter())
+            .first()
+            .copied()
+            .filter(|player| self.suspicion(player.index()) > 0)
+    }
+
+    fn verdict(&mut self, view: &BotView, player_on_trial: PlayerReference) -> Verdict {
+        self.read_chat(view);
+
+        if player_on_trial == view.player || self.teammates.contains(&player_on_trial.index()) {
+            return Verdict::Innocent;
+        }
+        match self.suspicion(player_on_trial.index()) {
+            suspicion if suspicion > 0 => Verdict::Guilty,
+            suspicion if suspicion < 0 => Verdict::Innocent,
+            _ => Verdict::Abstain,
+        }
+    }
+
+    /// Only player list abilities are used, on the most suspicious player they allow
+    fn select(&mut self, view: &BotView, _id: &ControllerID, parameters: &ControllerParameters) -> Option<AbilitySelection> {
+        self.read_chat(view);
+
+        let AvailableAbilitySelection::PlayerList { selection: available } = parameters.available() else {return None};
+        if available.max_players == Some(0) {
+            return None;
+        }
+
+        let target = self.others_by_suspicion(view, available.available_players.iter()).first().copied()?;
+        Some(AbilitySelection::new_player_list(vec![target]))
+    }
+}
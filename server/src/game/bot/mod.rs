@@ -0,0 +1,114 @@
+pub mod heuristic;
+
+use crate::{packet::ToServerPacket, vec_set::VecSet};
+
+use super::{
+    ability_input::{AbilityInput, AbilitySelection, AvailableAbilitySelection, ControllerID, ControllerParameters, ControllerParametersMap},
+    chat::ChatMessage,
+    phase::{PhaseState, PhaseType},
+    player::{PlayerIndex, PlayerReference},
+    verdict::Verdict,
+    Game
+};
+
+/// Everything a bot decides from, which is no more than its player's client is shown
+pub struct BotView<'a> {
+    pub player: PlayerReference,
+    pub phase: &'a PhaseState,
+    pub day_number: u8,
+    pub chat_messages: &'a [ChatMessage],
+    /// The controllers this player can use, the same ones sent to a human in `YourAllowedControllers`
+    pub controllers: &'a ControllerParametersMap,
+}
+
+/// How a bot plays. Bots act through the same packets a client sends,
+/// so their choices are recorded and replayed like anyone else's.
+pub trait BotStrategy: Send {
+    /// Who to nominate out of `candidates`, if anyone
+    fn nominate(&mut self, view: &BotView, candidates: &VecSet<PlayerReference>) -> Option<PlayerReference>;
+    fn verdict(&mut self, view: &BotView, player_on_trial: PlayerReference) -> Verdict;
+    /// What to select in a controller other than the nomination one. `None` leaves it as it is.
+    fn select(&mut self, view: &BotView, id: &ControllerID, parameters: &ControllerParameters) -> Option<AbilitySelection>;
+    /// Bots vote to skip ahead once they've acted, so they only hold a phase up as long as the humans do
+    fn fast_forward(&mut self, _view: &BotView) -> bool {
+        true
+    }
+}
+
+/// A seat in a game played by a [`BotStrategy`]
+pub struct Bot {
+    strategy: Box<dyn BotStrategy>,
+    phase: Option<(PhaseType, u8)>,
+    controllers: ControllerParametersMap,
+}
+
+impl Bot {
+    pub fn new(strategy: Box<dyn BotStrategy>) -> Self {
+        Self {
+            strategy,
+            phase: None,
+            controllers: ControllerParametersMap::default(),
+        }
+    }
+
+    /// Seeded from the game's seed, so a bot in a game with a fixed seed always plays the same way
+    pub fn heuristic(game_seed: u64, player: PlayerIndex) -> Self {
+        Self::new(Box::new(heuristic::HeuristicBot::new(game_seed.wrapping_add(u64::from(player)))))
+    }
+
+    /// Acts for `player` when a phase starts, and whenever its controllers change during one
+    pub fn tick(&mut self, game: &mut Game, player: PlayerReference) {
+        if game.game_is_over() || !player.alive(game) {
+            return;
+        }
+
+        let phase = (game.current_phase().phase(), game.day_number());
+        let new_phase = self.phase != Some(phase);
+        let controllers = game.saved_controllers.controller_parameters_allowed_to_player(player);
+        if !new_phase && controllers == self.controllers {
+            return;
+        }
+
+        let view = BotView {
+            player,
+            phase: game.current_phase(),
+            day_number: game.day_number(),
+            chat_messages: player.chat_messages(game),
+            controllers: &controllers,
+        };
+
+        let mut packets = Vec::new();
+        for (id, parameters) in controllers.controller_parameters().iter() {
+            if parameters.grayed_out() {continue}
+            if !new_phase && self.controllers.controller_parameters().get(id) == Some(parameters) {continue}
+
+            let selection = if let ControllerID::Nominate { .. } = id {
+                let AvailableAbilitySelection::PlayerList { selection: available } = parameters.available() else {continue};
+                self.strategy.nominate(&view, &available.available_players)
+                    .map(|nominee| AbilitySelection::new_player_list(vec![nominee]))
+            } else {
+                self.strategy.select(&view, id, parameters)
+            };
+
+            if let Some(selection) = selection {
+                packets.push(ToServerPacket::AbilityInput { ability_input: AbilityInput::new(id.clone(), selection) });
+            }
+        }
+
+        if new_phase {
+            if let PhaseState::Judgement { player_on_trial, .. } = view.phase {
+                packets.push(ToServerPacket::Judgement { verdict: self.strategy.verdict(&view, *player_on_trial) });
+            }
+            if self.strategy.fast_forward(&view) {
+                packets.push(ToServerPacket::VoteFastForwardPhase { fast_forward: true });
+            }
+        }
+
+        self.phase = Some(phase);
+        self.controllers = controllers;
+
+        for packet in packets {
+            game.on_client_message(player.index(), packet);
+        }
+    }
+}
@@ -57,6 +57,9 @@ pub enum ChatMessageVariant {
     GameOver { synopsis: Synopsis },
     #[serde(rename_all = "camelCase")]
     PlayerQuit{player_index: PlayerIndex, game_over: bool},
+    /// Someone else took over a player who quit
+    #[serde(rename_all = "camelCase")]
+    PlayerSubstituted{player_index: PlayerIndex, bot: bool},
 
 
     
@@ -0,0 +1,214 @@
+use std::{path::{Path, PathBuf}, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{game::{
+    ability_input::AbilityInput, chat::ChatMessage, components::{night_visits::NightVisits, synopsis::{Synopsis, SynopsisTracker}},
+    game_conclusion::GameConclusion, grave::{Grave, GraveReference}, phase::{PhaseState, PhaseType},
+    player::{PlayerIndex, PlayerReference}, role::Role, settings::Settings, visit::Visit, win_condition::WinCondition, Game
+}, packet::ToServerPacket};
+
+/// Bump this whenever [`GameRecord`] or [`JournalEvent`] changes shape, so tools reading old files can tell.
+pub const GAME_RECORD_VERSION: u32 = 5;
+/// Version 3 and 4 records only lack [`GameInput::Resume`] and [`GameInput::Substitute`], so they replay the same
+pub const OLDEST_REPLAYABLE_GAME_RECORD_VERSION: u32 = 3;
+
+/// Append-only record of everything that happened in a game.
+/// Written to disk as a [`GameRecord`] when the game ends.
+#[derive(Default)]
+pub struct EventJournal {
+    entries: Vec<JournalEntry>,
+    /// Everything from outside the game that changed it, in order. Feeding these into a new game
+    /// with the same seed and settings reproduces this game.
+    inputs: Vec<GameInput>,
+    /// Where the record is written when the game ends. If `None`, nothing is written.
+    export_directory: Option<PathBuf>,
+}
+
+/// The file format of an exported journal
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameRecord {
+    pub version: u32,
+    pub seed: u64,
+    pub settings: Settings,
+    pub players: Vec<String>,
+    pub entries: Vec<JournalEntry>,
+    pub inputs: Vec<GameInput>,
+    /// `None` if the game hadn't ended when the record was taken
+    pub outcome: Option<GameOutcome>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GameInput {
+    #[serde(rename_all = "camelCase")]
+    Tick { time_passed: Duration },
+    #[serde(rename_all = "camelCase")]
+    Packet { player: PlayerIndex, packet: ToServerPacket },
+    HostForceSkipPhase,
+    HostForceEndGame,
+    #[serde(rename_all = "camelCase")]
+    LoseConnection { player: PlayerIndex },
+    #[serde(rename_all = "camelCase")]
+    Reconnect { player: PlayerIndex },
+    /// Reconnected without being sent the whole game again
+    #[serde(rename_all = "camelCase")]
+    Resume { player: PlayerIndex },
+    #[serde(rename_all = "camelCase")]
+    Quit { player: PlayerIndex },
+    /// Someone else took over a player who quit
+    #[serde(rename_all = "camelCase")]
+    Substitute { player: PlayerIndex, bot: bool },
+    #[serde(rename_all = "camelCase")]
+    SetPlayerName { player: PlayerIndex, name: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameOutcome {
+    pub conclusion: GameConclusion,
+    pub graves: Vec<Grave>,
+    pub synopsis: Synopsis,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub day_number: u8,
+    pub phase: PhaseType,
+    pub event: JournalEvent,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum JournalEvent {
+    #[serde(rename_all = "camelCase")]
+    PhaseStart { phase: PhaseState },
+    #[serde(rename_all = "camelCase")]
+    AbilityInput { player: PlayerReference, input: AbilityInput },
+    #[serde(rename_all = "camelCase")]
+    NightVisits { visits: Vec<Visit> },
+    #[serde(rename_all = "camelCase")]
+    Grave { grave: Grave },
+    #[serde(rename_all = "camelCase")]
+    RoleSwitch { player: PlayerReference, old: Role, new: Role },
+    #[serde(rename_all = "camelCase")]
+    Convert { player: PlayerReference, old: WinCondition, new: WinCondition },
+    #[serde(rename_all = "camelCase")]
+    ChatMessage { player: PlayerReference, message: ChatMessage },
+    #[serde(rename_all = "camelCase")]
+    GameEnded { conclusion: GameConclusion },
+}
+
+impl EventJournal {
+    pub fn new(export_directory: Option<PathBuf>) -> Self {
+        Self {
+            entries: Vec::new(),
+            inputs: Vec::new(),
+            export_directory
+        }
+    }
+
+    pub fn set_export_directory(game: &mut Game, export_directory: Option<PathBuf>) {
+        game.event_journal.export_directory = export_directory;
+    }
+
+    pub fn entries(game: &Game) -> &Vec<JournalEntry> {
+        &game.event_journal.entries
+    }
+
+    pub fn record(game: &mut Game, event: JournalEvent) {
+        let entry = JournalEntry {
+            day_number: game.day_number(),
+            phase: game.current_phase().phase(),
+            event
+        };
+        game.event_journal.entries.push(entry);
+    }
+
+    pub fn record_input(game: &mut Game, input: GameInput) {
+        game.event_journal.inputs.push(input);
+    }
+
+    pub fn conclusion(game: &Game) -> Option<GameConclusion> {
+        game.event_journal.entries.iter().find_map(|entry| match &entry.event {
+            JournalEvent::GameEnded { conclusion } => Some(conclusion.clone()),
+            _ => None
+        })
+    }
+
+    pub fn game_record(game: &Game) -> GameRecord {
+        GameRecord {
+            version: GAME_RECORD_VERSION,
+            seed: game.seed,
+            settings: game.settings.clone(),
+            players: PlayerReference::all_players(game).map(|p| p.name(game).clone()).collect(),
+            entries: game.event_journal.entries.clone(),
+            inputs: game.event_journal.inputs.clone(),
+            outcome: Self::conclusion(game).map(|conclusion| GameOutcome {
+                graves: game.graves.clone(),
+                synopsis: SynopsisTracker::get(game, conclusion.clone()),
+                conclusion,
+            }),
+        }
+    }
+
+    //event listeners
+    pub fn on_phase_start(game: &mut Game, phase: PhaseState) {
+        // Night actions happen as the night ends, so this is the first point where the visits are final
+        if phase.phase() == PhaseType::Obituary {
+            let visits = NightVisits::all_visits_cloned(game);
+            Self::record(game, JournalEvent::NightVisits { visits });
+        }
+        Self::record(game, JournalEvent::PhaseStart { phase });
+    }
+    pub fn on_validated_ability_input_received(game: &mut Game, player: PlayerReference, input: AbilityInput) {
+        Self::record(game, JournalEvent::AbilityInput { player, input });
+    }
+    pub fn on_grave_added(game: &mut Game, grave: GraveReference) {
+        let grave = grave.deref(game).clone();
+        Self::record(game, JournalEvent::Grave { grave });
+    }
+    pub fn on_role_switch(game: &mut Game, player: PlayerReference, old: Role, new: Role) {
+        Self::record(game, JournalEvent::RoleSwitch { player, old, new });
+    }
+    pub fn on_convert(game: &mut Game, player: PlayerReference, old: WinCondition, new: WinCondition) {
+        Self::record(game, JournalEvent::Convert { player, old, new });
+    }
+    pub fn on_chat_message_added(game: &mut Game, player: PlayerReference, message: ChatMessage) {
+        Self::record(game, JournalEvent::ChatMessage { player, message });
+    }
+    /// Must go after everything else the game does when ending, so the final messages are in the record
+    pub fn on_game_ending(game: &mut Game, conclusion: GameConclusion) {
+        // The host can force the game to end after it already has
+        if Self::conclusion(game).is_some() {return}
+
+        Self::record(game, JournalEvent::GameEnded { conclusion });
+
+        if let Some(directory) = game.event_journal.export_directory.clone() {
+            let record = Self::game_record(game);
+            // Every lobby ticks on the same thread, so the disk is left to another one
+            std::thread::spawn(move || {
+                if let Err(err) = Self::write_to_directory(&record, &directory) {
+                    tracing::warn!(directory = %directory.display(), %err, "Failed to write game record");
+                }
+            });
+        }
+    }
+
+    fn write_to_directory(record: &GameRecord, directory: &Path) -> std::io::Result<PathBuf> {
+        let json = serde_json::to_string(record)?;
+
+        std::fs::create_dir_all(directory)?;
+        let path = directory.join(format!(
+            "{}_{}.json",
+            chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"),
+            record.seed
+        ));
+        std::fs::write(&path, json)?;
+
+        tracing::info!(path = %path.display(), "Wrote game record");
+        Ok(path)
+    }
+}
@@ -155,7 +155,7 @@ impl Mafia{
                 .filter(|p| InsiderGroupID::Mafia.is_player_in_revealed_group(game, *p))
                 .collect::<Vec<_>>();
 
-            let Some(insider) = insiders.choose(&mut rand::rng()) else {return};
+            let Some(insider) = insiders.choose(&mut game.rng) else {return};
 
             SyndicateGunItem::give_gun(game, *insider);
         }
@@ -212,7 +212,7 @@ impl Mafia{
         ) {return;}
         
         //choose random mafia to be mafia killing
-        let random_mafia = living_players_to_convert.choose(&mut rand::rng());
+        let random_mafia = living_players_to_convert.choose(&mut game.rng);
         
         if let Some(random_mafia) = random_mafia {
             random_mafia.set_role_and_win_condition_and_revealed_group(game, role);
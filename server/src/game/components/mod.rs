@@ -15,6 +15,7 @@ pub mod forfeit_vote;
 pub mod night_visits;
 pub mod syndicate_gun_item;
 pub mod synopsis;
+pub mod event_journal;
 pub mod dead_can_still_play_message;
 pub mod nomination_controller;
 pub mod forward_messages;
\ No newline at end of file
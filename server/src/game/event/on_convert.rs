@@ -1,5 +1,5 @@
 use crate::game::{
-    components::synopsis::SynopsisTracker, player::PlayerReference, win_condition::WinCondition, Game
+    components::{event_journal::EventJournal, synopsis::SynopsisTracker}, player::PlayerReference, win_condition::WinCondition, Game
 };
 
 #[must_use = "Event must be invoked"]
@@ -13,6 +13,7 @@ impl OnConvert{
         Self{ player, old, new }
     }
     pub fn invoke(self, game: &mut Game){
-        SynopsisTracker::on_convert(game, self.player, self.old, self.new);
+        SynopsisTracker::on_convert(game, self.player, self.old.clone(), self.new.clone());
+        EventJournal::on_convert(game, self.player, self.old, self.new);
     }
 }
\ No newline at end of file
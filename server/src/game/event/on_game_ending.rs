@@ -1,6 +1,6 @@
-use crate::game::{
-    game_conclusion::GameConclusion, player::PlayerReference, Game
-};
+use crate::{game::{
+    components::event_journal::EventJournal, game_conclusion::GameConclusion, player::PlayerReference, Game
+}, metrics};
 
 #[must_use = "Event must be invoked"]
 pub struct OnGameEnding {
@@ -14,10 +14,19 @@ impl OnGameEnding{
         }
     }
     pub fn invoke(&self, game: &mut Game){
+        // The host can force the game to end after it already has
+        let already_ended = EventJournal::conclusion(game).is_some();
+
         for player_ref in PlayerReference::all_players(game){
             player_ref.on_game_ending(game);
         }
 
         game.on_game_ending(self.conclusion.clone());
+
+        EventJournal::on_game_ending(game, self.conclusion.clone());
+
+        if !already_ended {
+            metrics::game_ended(&self.conclusion);
+        }
     }
 }
@@ -1,5 +1,5 @@
 use crate::game::{
-    grave::GraveReference, modifiers::Modifiers, player::PlayerReference, Game
+    components::event_journal::EventJournal, grave::GraveReference, modifiers::Modifiers, player::PlayerReference, Game
 };
 
 
@@ -17,6 +17,7 @@ impl OnGraveAdded{
         }
 
         Modifiers::on_grave_added(game, self.grave);
+        EventJournal::on_grave_added(game, self.grave);
 
         game.on_grave_added(self.grave);
     }
@@ -1,6 +1,6 @@
 use crate::game::{
     ability_input::saved_controllers_map::SavedControllersMap, components::{
-        cult::Cult, detained::Detained,
+        cult::Cult, detained::Detained, event_journal::EventJournal,
         mafia::Mafia, night_visits::NightVisits,
         verdicts_today::VerdictsToday
     }, modifiers::Modifiers, phase::PhaseState, player::PlayerReference, Game
@@ -26,6 +26,7 @@ impl OnPhaseStart{
         Cult::on_phase_start(game, self.phase.phase());
         SavedControllersMap::on_phase_start(game, self.phase.phase());
         Modifiers::on_phase_start(game, self.phase.clone());
+        EventJournal::on_phase_start(game, self.phase.clone());
 
         game.on_phase_start(self.phase.phase());
     }
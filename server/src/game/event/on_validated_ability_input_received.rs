@@ -1,7 +1,7 @@
 use crate::game::{
     ability_input::AbilityInput,
     components::{
-        forfeit_vote::ForfeitVote, forward_messages::ForwardMessages, nomination_controller::NominationController,
+        event_journal::EventJournal, forfeit_vote::ForfeitVote, forward_messages::ForwardMessages, nomination_controller::NominationController,
         syndicate_gun_item::SyndicateGunItem
     },
     player::PlayerReference,
@@ -25,5 +25,6 @@ impl OnValidatedAbilityInputReceived{
         ForfeitVote::on_validated_ability_input_received(game, self.actor_ref, self.input.clone());
         ForwardMessages::on_validated_ability_input_received(game, self.actor_ref, self.input.clone());
         NominationController::on_validated_ability_input_received(game, self.actor_ref, self.input.clone());
+        EventJournal::on_validated_ability_input_received(game, self.actor_ref, self.input);
     }
 }
\ No newline at end of file
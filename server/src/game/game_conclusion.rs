@@ -1,6 +1,16 @@
 use serde::{Deserialize, Serialize};
 
-use super::{player::PlayerReference, role::Role, role_list::RoleSet, win_condition::WinCondition, Game};
+use crate::vec_set::VecSet;
+
+use super::{components::insider_group::InsiderGroupID, player::PlayerReference, role::Role, role_list::RoleSet, win_condition::WinCondition, Game};
+
+/// Everything [`GameConclusion::game_is_over`] needs to know about a living player,
+/// so it can also be asked about players who aren't in a game yet
+pub struct LivingPlayer<'a> {
+    pub role: Role,
+    pub win_condition: &'a WinCondition,
+    pub keeps_game_running: bool,
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "camelCase")]
@@ -37,32 +47,36 @@ impl GameConclusion {
     }
     ///either return Some(EndGameCondition) or None (if the game is not over yet)
     pub fn game_is_over(game: &Game)->Option<GameConclusion> {
+        let living_players = PlayerReference::all_players(game)
+            .filter(|player| player.alive(game))
+            .map(|player| LivingPlayer {
+                role: player.role(game),
+                win_condition: player.win_condition(game),
+                keeps_game_running: player.keeps_game_running(game),
+            })
+            .collect::<Vec<_>>();
+
+        Self::game_is_over_among(&living_players)
+    }
+
+    pub fn game_is_over_among(living_players: &[LivingPlayer])->Option<GameConclusion> {
 
         //Special wildcard case
-        let living_roles = PlayerReference::all_players(game).filter_map(|player|{
-            if player.alive(game){
-                Some(player.role(game))
-            }else{
-                None
-            }
-        }).collect::<Vec<_>>();
-
-        if living_roles.iter().all(|role|matches!(role, Role::Wildcard|Role::TrueWildcard)) && living_roles.len() > 1 {
+        if living_players.iter().all(|player|matches!(player.role, Role::Wildcard|Role::TrueWildcard)) && living_players.len() > 1 {
             return None;
         }
         
         //if nobody is left to hold game hostage
-        if !PlayerReference::all_players(game).any(|player| player.alive(game) && player.keeps_game_running(game)){
+        if !living_players.iter().any(|player| player.keeps_game_running){
             return Some(GameConclusion::Draw);
         }
 
         //find one end game condition that everyone agrees on
         GameConclusion::all().into_iter().find(|resolution| 
-            PlayerReference::all_players(game)
-                .filter(|p|p.alive(game))
-                .filter(|p|p.keeps_game_running(game))
+            living_players.iter()
+                .filter(|p|p.keeps_game_running)
                 .all(|p|
-                    match p.win_condition(game){
+                    match p.win_condition{
                         WinCondition::GameConclusionReached{win_if_any} => win_if_any.contains(resolution),
                         WinCondition::RoleStateWon => true,
                     }
@@ -90,6 +104,17 @@ impl GameConclusion {
             matches!(role, Role::Apostle | Role::Zealot)
         }
     }
+    /// If they can consistently kill then they keep the game running
+    /// Town kills by voting
+    /// Mafia kills with MK or gun
+    /// Cult kills / converts
+    pub fn player_keeps_game_running(role: Role, win_condition: &WinCondition, insider_groups: &VecSet<InsiderGroupID>)->bool{
+        if insider_groups.contains(&InsiderGroupID::Mafia) {return true;}
+        if insider_groups.contains(&InsiderGroupID::Cult) {return true;}
+        if win_condition.is_loyalist_for(GameConclusion::Town) {return true;}
+
+        Self::keeps_game_running(role)
+    }
 }
 
 
@@ -8,6 +8,10 @@ pub mod role;
 pub mod visit;
 pub mod verdict;
 pub mod role_list;
+pub mod role_generation;
+pub mod role_list_notation;
+pub mod role_list_analysis;
+pub mod preset;
 pub mod settings;
 pub mod game_conclusion;
 pub mod components;
@@ -21,6 +25,8 @@ pub mod modifiers;
 pub mod win_condition;
 pub mod role_outline_reference;
 pub mod ability_input;
+pub mod replay;
+pub mod bot;
 
 use std::time::Duration;
 use ability_input::saved_controllers_map::SavedControllersMap;
@@ -38,26 +44,32 @@ use components::insider_group::InsiderGroupID;
 use components::insider_group::InsiderGroups;
 use components::syndicate_gun_item::SyndicateGunItem;
 use components::synopsis::SynopsisTracker;
+use components::event_journal::{EventJournal, GameInput};
 use components::verdicts_today::VerdictsToday;
 use event::on_tick::OnTick;
 use modifiers::ModifierType;
 use modifiers::Modifiers;
 use event::before_initial_role_creation::BeforeInitialRoleCreation;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use role_generation::RoleGenerator;
 use role_list::RoleAssignment;
 use role_list::RoleOutlineOptionInsiderGroups;
 use role_list::RoleOutlineOptionWinCondition;
 use role_outline_reference::RoleOutlineReference;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use win_condition::WinCondition;
 
 use crate::client_connection::ClientConnection;
+use crate::config::Config;
 use crate::game::event::on_game_start::OnGameStart;
 use crate::game::player::PlayerIndex;
 use crate::packet::RejectJoinReason;
 use crate::packet::ToClientPacket;
 use crate::vec_map::VecMap;
 use crate::vec_set::VecSet;
+use crate::websocket_connections::connection::ClientSender;
 use chat::{ChatMessageVariant, ChatGroup, ChatMessage};
 use player::PlayerReference;
 use player::Player;
@@ -103,6 +115,11 @@ pub struct Game {
     
     /// Whether the game is still updating phase times
     pub ticking: bool,
+
+    /// The seed this game was generated from. A game created with the same seed, settings and inputs plays out identically.
+    pub seed: u64,
+    /// Every random decision in the game must be drawn from this, never from `rand::rng()`
+    pub rng: StdRng,
     
     
     //components with data
@@ -123,10 +140,11 @@ pub struct Game {
     pub detained: Detained,
     pub confused: Confused,
     pub drunk_aura: DrunkAura,
-    pub synopsis_tracker: SynopsisTracker
+    pub synopsis_tracker: SynopsisTracker,
+    pub event_journal: EventJournal
 }
 
-#[derive(Serialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum RejectStartReason {
     TooManyClients,
@@ -134,10 +152,15 @@ pub enum RejectStartReason {
     RoleListTooSmall,
     RoleListCannotCreateRoles,
     ZeroTimeGame,
-    PlayerDisconnected
+    PlayerDisconnected,
+    /// Fewer players than the host's minimum
+    TooFewPlayers,
+    /// More players than the host's maximum
+    TooManyPlayers,
+    GameAlreadyStarted,
 }
 
-#[derive(Serialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum GameOverReason {
     ReachedMaxDay,
@@ -145,131 +168,76 @@ pub enum GameOverReason {
     Draw
 }
 
+/// Roles dealt by [`Game::deal_roles`], ready to start a game with
+pub struct DealtRoles {
+    seed: u64,
+    /// Left where dealing the roles left it, so the game plays out the same as if it had dealt them itself
+    rng: StdRng,
+    assignments: Vec<(PlayerReference, RoleOutlineReference, RoleAssignment)>,
+}
+
 
 
 impl Game {
     /// `players` must have length 255 or lower.
     pub fn new(settings: Settings, players: Vec<PlayerInitializeParameters>, spectators: Vec<SpectatorInitializeParameters>) -> Result<Self, RejectStartReason>{
+        let roles = Self::deal_roles(&settings, players.len())?;
+        Self::new_with_roles(settings, roles, players, spectators)
+    }
+
+    /// Picks everyone's roles, trying again while the game would be over before it starts.
+    /// Nothing is sent to anyone, so this can be done away from the lobby, since it's the slow part of starting a game.
+    /// `player_count` must be 255 or lower.
+    pub fn deal_roles(settings: &Settings, player_count: usize) -> Result<DealtRoles, RejectStartReason> {
         //check settings are not completly off the rails
         if settings.phase_times.game_ends_instantly() {
             return Err(RejectStartReason::ZeroTimeGame);
         }
         
 
-        let mut role_generation_tries = 0u8;
-        const MAX_ROLE_GENERATION_TRIES: u8 = 250;
-        let (mut game, assignments) = loop {
-
-            if role_generation_tries >= MAX_ROLE_GENERATION_TRIES {
-                return Err(RejectStartReason::RoleListCannotCreateRoles);
-            }
+        let seed = settings.seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
 
-            let settings = settings.clone();
-            let role_list = settings.role_list.clone();
+        // Stand-ins for the players, only used to check whether the game is over
+        let players: Vec<PlayerInitializeParameters> = (0..player_count)
+            .map(|_| PlayerInitializeParameters {
+                connection: ClientConnection::Connected(ClientSender::detached()),
+                name: String::new(),
+                host: false,
+            })
+            .collect();
 
-            let random_outline_assignments = match role_list.create_random_role_assignments(&settings.enabled_roles){
-                Some(roles) => {roles},
-                None => {
-                    role_generation_tries = role_generation_tries.saturating_add(1);
-                    continue;
-                }
-            };
-
-            let assignments = Self::assign_players_to_assignments(random_outline_assignments);            
-
-
-            // Create list of players
-            let mut new_players = Vec::new();
-            for (player_index, player) in players.iter().enumerate() {
-
-                let ClientConnection::Connected(ref sender) = player.connection else {
-                    return Err(RejectStartReason::PlayerDisconnected)
-                };
-                let Some((_, _, assignment)) = assignments.iter().find(|(p,_,_)|p.index() as usize == player_index) else {
-                    return Err(RejectStartReason::RoleListTooSmall)
-                };
-
-                // Set win condition & Insider group here so we can check if game ends
-                let win_condition = match &assignment.win_condition {
-                    RoleOutlineOptionWinCondition::RoleDefault => assignment.role.default_state().default_win_condition(),
-                    RoleOutlineOptionWinCondition::GameConclusionReached { win_if_any } => {
-                        WinCondition::GameConclusionReached { 
-                            win_if_any: win_if_any.iter().cloned().collect()
-                        }
-                    },
-                };
+        let mut role_generator = RoleGenerator::new(settings);
+        let mut role_generation_tries = 0u8;
+        loop {
 
-                let new_player = Player::new(
-                    player.name.clone(),
-                    sender.clone(),
-                    assignment.role,
-                    win_condition
-                );
-                
-                new_players.push(new_player);
+            if role_generation_tries >= Config::get().max_role_generation_tries {
+                return Err(RejectStartReason::RoleListCannotCreateRoles);
             }
 
-            #[expect(clippy::cast_possible_truncation, reason = "Explained in doc comment")]
-            let num_players = new_players.len() as u8;
-
-            let mut game = Self{
-                pitchfork: Pitchfork::new(num_players),
-
-                assignments: assignments.clone(),
-                ticking: true,
-                spectators: spectators.clone().into_iter().map(Spectator::new).collect(),
-                spectator_chat_messages: Vec::new(),
-                players: new_players.into_boxed_slice(),
-                graves: Vec::new(),
-                phase_machine: PhaseStateMachine::new(settings.phase_times.clone()),
-                modifiers: Modifiers::default_from_settings(settings.enabled_modifiers.clone()),
-                settings,
-
-                saved_controllers: SavedControllersMap::default(),
-                night_visits: NightVisits::default(),
-                syndicate_gun_item: SyndicateGunItem::default(),
-                cult: Cult::default(),
-                mafia: Mafia,
-                arsonist_doused: ArsonistDoused::default(),
-                puppeteer_marionette: PuppeteerMarionette::default(),
-                mafia_recruits: MafiaRecruits::default(),
-                love_linked: LoveLinked::default(),
-                verdicts_today: VerdictsToday::default(),
-                poison: Poison::default(),
-
-                revealed_groups: InsiderGroups::default(),
-                detained: Detained::default(),
-                confused: Confused::default(),
-                drunk_aura: DrunkAura::default(),
-                synopsis_tracker: SynopsisTracker::new(num_players)
+            // Generation only fails when no role list fits the settings, so trying again won't help.
+            // Tries are only spent on role lists where the game is over before it starts.
+            let Some(random_outline_assignments) = role_generator.generate(&mut rng) else {
+                return Err(RejectStartReason::RoleListCannotCreateRoles);
             };
 
-            // Just distribute insider groups, this is for game over checking (Keeps game running syndicate gun)
-            for player in PlayerReference::all_players(&game){
-                let Some((player, _, assignment)) = assignments
-                    .iter()
-                    .find(|(p,_,_)|*p == player) else {
-                        return Err(RejectStartReason::RoleListTooSmall)
-                    };
-                
-                let insider_groups = match &assignment.insider_groups {
-                    RoleOutlineOptionInsiderGroups::RoleDefault => assignment.role.default_state().default_revealed_groups(),
-                    RoleOutlineOptionInsiderGroups::Custom { insider_groups } => insider_groups.iter().copied().collect(),
-                };
-                
-                for group in insider_groups{
-                    unsafe {
-                        group.add_player_to_revealed_group_unchecked(&mut game, *player);
-                    }
-                }
-            }
+            let assignments = Self::assign_players_to_assignments(random_outline_assignments, &mut rng);            
 
+            let game = Self::create(settings.clone(), DealtRoles { seed, rng, assignments }, &players, Vec::new())?;
 
             if !game.game_is_over() {
-                break (game, assignments);
+                return Ok(DealtRoles { seed, rng: game.rng, assignments: game.assignments });
             }
+            rng = game.rng;
             role_generation_tries = role_generation_tries.saturating_add(1);
-        };
+        }
+    }
+
+    /// Starts a game with roles from [`Game::deal_roles`], which must have been dealt for the same settings and number of players.
+    /// `players` must have length 255 or lower.
+    pub fn new_with_roles(settings: Settings, roles: DealtRoles, players: Vec<PlayerInitializeParameters>, spectators: Vec<SpectatorInitializeParameters>) -> Result<Self, RejectStartReason>{
+        let mut game = Self::create(settings, roles, &players, spectators)?;
+        let assignments = game.assignments.clone();
 
         if game.game_is_over() {
             return Err(RejectStartReason::RoleListCannotCreateRoles);
@@ -329,11 +297,108 @@ impl Game {
         Ok(game)
     }
     
+    /// Sets up the game with everyone's roles, without sending anything.
+    /// `players` must have length 255 or lower.
+    fn create(settings: Settings, roles: DealtRoles, players: &[PlayerInitializeParameters], spectators: Vec<SpectatorInitializeParameters>) -> Result<Self, RejectStartReason>{
+        let DealtRoles { seed, rng, assignments } = roles;
+
+        // Create list of players
+        let mut new_players = Vec::new();
+        for (player_index, player) in players.iter().enumerate() {
+
+            let ClientConnection::Connected(ref sender) = player.connection else {
+                return Err(RejectStartReason::PlayerDisconnected)
+            };
+            let Some((_, _, assignment)) = assignments.iter().find(|(p,_,_)|p.index() as usize == player_index) else {
+                return Err(RejectStartReason::RoleListTooSmall)
+            };
+
+            // Set win condition & Insider group here so we can check if game ends
+            let win_condition = match &assignment.win_condition {
+                RoleOutlineOptionWinCondition::RoleDefault => assignment.role.default_state().default_win_condition(),
+                RoleOutlineOptionWinCondition::GameConclusionReached { win_if_any } => {
+                    WinCondition::GameConclusionReached { 
+                        win_if_any: win_if_any.iter().cloned().collect()
+                    }
+                },
+            };
+
+            let new_player = Player::new(
+                player.name.clone(),
+                sender.clone(),
+                assignment.role,
+                win_condition
+            );
+            
+            new_players.push(new_player);
+        }
+
+        #[expect(clippy::cast_possible_truncation, reason = "Explained in doc comment")]
+        let num_players = new_players.len() as u8;
+
+        let mut game = Self{
+            pitchfork: Pitchfork::new(num_players),
+
+            assignments: assignments.clone(),
+            ticking: true,
+            seed,
+            rng,
+            spectators: spectators.into_iter().map(Spectator::new).collect(),
+            spectator_chat_messages: Vec::new(),
+            players: new_players.into_boxed_slice(),
+            graves: Vec::new(),
+            phase_machine: PhaseStateMachine::new(settings.phase_times.clone()),
+            modifiers: Modifiers::default_from_settings(settings.enabled_modifiers.clone()),
+            settings,
+
+            saved_controllers: SavedControllersMap::default(),
+            night_visits: NightVisits::default(),
+            syndicate_gun_item: SyndicateGunItem::default(),
+            cult: Cult::default(),
+            mafia: Mafia,
+            arsonist_doused: ArsonistDoused::default(),
+            puppeteer_marionette: PuppeteerMarionette::default(),
+            mafia_recruits: MafiaRecruits::default(),
+            love_linked: LoveLinked::default(),
+            verdicts_today: VerdictsToday::default(),
+            poison: Poison::default(),
+
+            revealed_groups: InsiderGroups::default(),
+            detained: Detained::default(),
+            confused: Confused::default(),
+            drunk_aura: DrunkAura::default(),
+            synopsis_tracker: SynopsisTracker::new(num_players),
+            event_journal: EventJournal::new(Config::get().game_record_directory())
+        };
+
+        // Just distribute insider groups, this is for game over checking (Keeps game running syndicate gun)
+        for player in PlayerReference::all_players(&game){
+            let Some((player, _, assignment)) = assignments
+                .iter()
+                .find(|(p,_,_)|*p == player) else {
+                    return Err(RejectStartReason::RoleListTooSmall)
+                };
+            
+            let insider_groups = match &assignment.insider_groups {
+                RoleOutlineOptionInsiderGroups::RoleDefault => assignment.role.default_state().default_revealed_groups(),
+                RoleOutlineOptionInsiderGroups::Custom { insider_groups } => insider_groups.iter().copied().collect(),
+            };
+            
+            for group in insider_groups{
+                unsafe {
+                    group.add_player_to_revealed_group_unchecked(&mut game, *player);
+                }
+            }
+        }
+
+        Ok(game)
+    }
+
     /// `initialization_data` must have length 255 or lower
     #[expect(clippy::cast_possible_truncation, reason = "See doc comment")]
-    fn assign_players_to_assignments(initialization_data: Vec<RoleAssignment>)->Vec<(PlayerReference, RoleOutlineReference, RoleAssignment)>{
+    fn assign_players_to_assignments(initialization_data: Vec<RoleAssignment>, rng: &mut StdRng)->Vec<(PlayerReference, RoleOutlineReference, RoleAssignment)>{
         let mut player_indices: Vec<PlayerIndex> = (0..initialization_data.len() as PlayerIndex).collect();
-        player_indices.shuffle(&mut rand::rng());
+        player_indices.shuffle(rng);
 
         initialization_data
             .into_iter()
@@ -506,6 +571,8 @@ impl Game {
 
         if !self.ticking { return }
 
+        EventJournal::record_input(self, GameInput::Tick { time_passed });
+
         if let Some(conclusion) = GameConclusion::game_is_over(self) {
             OnGameEnding::new(conclusion).invoke(self);
         }
@@ -587,11 +654,6 @@ impl Game {
             spectator.send_packet(packet.clone());
         }
     }
-    
-    pub(crate) fn is_any_client_connected(&self) -> bool {
-        PlayerReference::all_players(self).any(|p| p.is_connected(self))
-        || SpectatorPointer::all_spectators(self).any(|s| s.is_connected(self))
-    }
 }
 
 pub mod test {
@@ -604,14 +666,17 @@ pub mod test {
             mafia_recruits::MafiaRecruits, night_visits::NightVisits,
             pitchfork::Pitchfork, poison::Poison,
             puppeteer_marionette::PuppeteerMarionette, syndicate_gun_item::SyndicateGunItem,
-            synopsis::SynopsisTracker, verdicts_today::VerdictsToday
+            synopsis::SynopsisTracker, verdicts_today::VerdictsToday,
+            event_journal::EventJournal
         }, 
         event::{before_initial_role_creation::BeforeInitialRoleCreation, on_game_start::OnGameStart},
         phase::PhaseStateMachine, player::{test::mock_player, PlayerReference},
         role::Role, settings::Settings, Game, RejectStartReason
     };
+    use rand::{rngs::StdRng, SeedableRng};
     
-    pub fn mock_game(settings: Settings, number_of_players: u8) -> Result<Game, RejectStartReason> {
+    /// `seed` drives every random decision in the game, including role generation
+    pub fn mock_game(settings: Settings, number_of_players: u8, seed: u64) -> Result<Game, RejectStartReason> {
 
         //check settings are not completly off the rails
         if settings.phase_times.game_ends_instantly() {
@@ -619,14 +684,14 @@ pub mod test {
         }
 
         let settings = settings.clone();
-        let role_list = settings.role_list.clone();
+        let mut rng = StdRng::seed_from_u64(seed);
         
-        let random_outline_assignments = match role_list.create_random_role_assignments(&settings.enabled_roles){
+        let random_outline_assignments = match settings.create_random_role_assignments(&mut rng){
             Some(roles) => {roles},
             None => {return Err(RejectStartReason::RoleListCannotCreateRoles);}
         };
 
-        let assignments = Game::assign_players_to_assignments(random_outline_assignments);
+        let assignments = Game::assign_players_to_assignments(random_outline_assignments, &mut rng);
         
         let shuffled_roles = assignments.iter().map(|(_,_,r)|r.role).collect::<Vec<Role>>();
 
@@ -648,6 +713,8 @@ pub mod test {
             
             assignments,
             ticking: true,
+            seed,
+            rng,
             spectators: Vec::new(),
             spectator_chat_messages: Vec::new(),
             players: players.into_boxed_slice(),
@@ -671,7 +738,8 @@ pub mod test {
             detained: Default::default(),
             confused: Default::default(),
             drunk_aura: Default::default(),
-            synopsis_tracker: SynopsisTracker::new(number_of_players)
+            synopsis_tracker: SynopsisTracker::new(number_of_players),
+            event_journal: EventJournal::new(None)
         };
 
         //set wincons and revealed groups
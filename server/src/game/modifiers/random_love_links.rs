@@ -1,4 +1,4 @@
-use rand::seq::IteratorRandom;
+use rand::seq::IndexedRandom;
 
 use crate::game::{components::love_linked::LoveLinked, player::PlayerReference, Game};
 
@@ -21,14 +21,18 @@ impl ModifierTrait for RandomLoveLinks{
             let random_unlinked_player = PlayerReference::all_players(game)
                 .filter(|p| *p != player)
                 .filter(|p| LoveLinked::get_links(game, *p).is_empty())
-                .choose(&mut rand::rng());
+                .collect::<Vec<_>>()
+                .choose(&mut game.rng)
+                .copied();
 
             if let Some(other_player) = random_unlinked_player {
                 LoveLinked::add_love_link(game, player, other_player);
             }else{
                 let random_player = PlayerReference::all_players(game)
                     .filter(|p| *p != player)
-                    .choose(&mut rand::rng());
+                    .collect::<Vec<_>>()
+                    .choose(&mut game.rng)
+                    .copied();
 
                 if let Some(other_player) = random_player {
                     LoveLinked::add_love_link(game, player, other_player);
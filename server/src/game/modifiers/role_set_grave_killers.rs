@@ -21,6 +21,10 @@ impl ModifierTrait for RoleSetGraveKillers{
                     for killer in killers {
                         new_killers.push(
                             if let GraveKiller::Role(killer_role) = killer {
+                                // The host's own sets are more specific than the built-in ones, so they come first.
+                                // A set of one role would give the killer away, so those fall back to the built-in ones.
+                                let killer_custom_role_set = game.settings.custom_role_sets.iter()
+                                    .find(|set| set.roles.len() >= 2 && set.roles.contains(&killer_role));
                                 let killer_role_set = [
                                     RoleSet::Town,
                                     RoleSet::Mafia,
@@ -30,7 +34,9 @@ impl ModifierTrait for RoleSetGraveKillers{
                                     RoleSet::Neutral,
                                 ].iter().find(|set| set.get_roles().contains(&killer_role));
     
-                                if let Some(role_set) = killer_role_set {
+                                if let Some(custom_role_set) = killer_custom_role_set {
+                                    GraveKiller::CustomRoleSet(custom_role_set.name.clone())
+                                } else if let Some(role_set) = killer_role_set {
                                     GraveKiller::RoleSet(role_set.clone())
                                 } else {
                                     killer
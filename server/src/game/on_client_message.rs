@@ -1,7 +1,8 @@
-use crate::{log, packet::ToServerPacket, strings::TidyableString};
+use crate::{packet::ToServerPacket, strings::TidyableString};
 
 use super::{
     chat::{ChatGroup, ChatMessageVariant, MessageSender},
+    components::event_journal::{EventJournal, GameInput},
     event::on_fast_forward::OnFastForward, modifiers::{ModifierType, Modifiers},
     phase::PhaseType,
     player::{PlayerIndex, PlayerReference},
@@ -35,11 +36,13 @@ impl Game {
         let sender_player_ref = match PlayerReference::new(self, sender_player_index){
             Ok(sender_player_ref) => sender_player_ref,
             Err(_) => {
-                log!(error "Game"; "Received message from invalid player index: {}", sender_player_index);
+                tracing::warn!(player_index = sender_player_index, "Received message from invalid player index");
                 return;
             }
         };
 
+        EventJournal::record_input(self, GameInput::Packet { player: sender_player_index, packet: incoming_packet.clone() });
+
         'packet_match: {match incoming_packet {
             ToServerPacket::Judgement { verdict } => {
                 if self.current_phase().phase() != PhaseType::Judgement {break 'packet_match;}
@@ -200,7 +203,7 @@ impl Game {
                 sender_player_ref.set_fast_forward_vote(self, fast_forward);
             },
             _ => {
-                log!(fatal "Game"; "Unimplemented ToServerPacket: {incoming_packet:?}");
+                tracing::error!(player_index = sender_player_index, packet = ?incoming_packet, "Unimplemented ToServerPacket");
                 unreachable!();
             }
         }}
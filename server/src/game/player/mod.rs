@@ -7,9 +7,11 @@ mod player_event_listeners;
 
 pub use player_reference::PlayerIndex;
 pub use player_reference::PlayerReference;
+use std::cell::RefCell;
+
 use vec1::Vec1;
 
-use crate::client_connection::ClientConnection;
+use crate::client_connection::{ClientConnection, SentPackets};
 use crate::vec_map::VecMap;
 use crate::vec_set::VecSet;
 use crate::{
@@ -35,6 +37,8 @@ pub struct PlayerInitializeParameters {
 }
 pub struct Player {
     connection: ClientConnection,
+    /// Packets are sent through a shared reference to the game, so numbering them needs interior mutability
+    sent_packets: RefCell<SentPackets>,
 
     name: String,
     role_state: RoleState,
@@ -86,6 +90,7 @@ impl Player {
     pub fn new(name: String, sender: ClientSender, role: Role, win_condition: WinCondition) -> Self {
         Self {
             connection: ClientConnection::Connected(sender),
+            sent_packets: RefCell::default(),
 
             name,
             role_state: role.default_state(),
@@ -133,7 +138,7 @@ impl Player {
 }
 
 pub mod test {
-    use std::time::Duration;
+    use std::{cell::RefCell, time::Duration};
 
     use crate::{client_connection::ClientConnection, game::{role::Role, verdict::Verdict}, vec_map::VecMap, vec_set::VecSet};
 
@@ -143,6 +148,7 @@ pub mod test {
         Player {
             // Since `tick` is never called in tests, this will never decrement.
             connection: ClientConnection::CouldReconnect { disconnect_timer: Duration::from_secs(1) },
+            sent_packets: RefCell::default(),
 
             name,
             role_state: role.default_state(),
@@ -6,6 +6,7 @@ use crate::{
         chat::{
             ChatMessage, ChatMessageVariant
         },
+        components::event_journal::EventJournal,
         event::{
             on_convert::OnConvert, on_fast_forward::OnFastForward,
             on_remove_role_label::OnRemoveRoleLabel
@@ -193,6 +194,7 @@ impl PlayerReference{
         }
     }
     pub fn add_chat_message(&self, game: &mut Game, message: ChatMessage) {
+        EventJournal::on_chat_message_added(game, *self, message.clone());
         self.deref_mut(game).chat_messages.push(message.clone());
         self.deref_mut(game).queued_chat_messages.push(message);
     }
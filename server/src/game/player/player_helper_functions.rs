@@ -294,7 +294,7 @@ impl PlayerReference{
 
     pub fn push_night_messages_to_player(&self, game: &mut Game){
         let mut messages = self.night_messages(game).to_vec();
-        messages.shuffle(&mut rand::rng());
+        messages.shuffle(&mut game.rng);
         messages.sort();
         self.send_packet(game, ToClientPacket::NightMessages { chat_messages: 
             messages.iter().map(|msg|ChatMessage::new_private(msg.clone())).collect()
@@ -379,16 +379,13 @@ impl PlayerReference{
             },
         }
     }
-    /// If they can consistently kill then they keep the game running
-    /// Town kills by voting
-    /// Mafia kills with MK or gun
-    /// Cult kills / converts
+    /// See [`GameConclusion::player_keeps_game_running`]
     pub fn keeps_game_running(&self, game: &Game) -> bool {
-        if InsiderGroupID::Mafia.is_player_in_revealed_group(game, *self) {return true;}
-        if InsiderGroupID::Cult.is_player_in_revealed_group(game, *self) {return true;}
-        if self.win_condition(game).is_loyalist_for(GameConclusion::Town) {return true;}
-        
-        GameConclusion::keeps_game_running(self.role(game))
+        GameConclusion::player_keeps_game_running(
+            self.role(game),
+            self.win_condition(game),
+            &InsiderGroupID::all_insider_groups_with_player(game, *self)
+        )
     }
 
     /*
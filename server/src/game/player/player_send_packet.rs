@@ -1,12 +1,10 @@
-use std::time::Duration;
-
 use crate::{
-    client_connection::ClientConnection, 
+    client_connection::{ClientConnection, PacketSequence}, 
+    config::Config,
     game::{
         chat::ChatMessageVariant, components::insider_group::InsiderGroupID,
         Game, GameOverReason
     },
-    lobby::GAME_DISCONNECT_TIMER_SECS,
     packet::ToClientPacket, websocket_connections::connection::ClientSender
 };
 
@@ -17,8 +15,12 @@ impl PlayerReference{
         self.deref_mut(game).connection = ClientConnection::Connected(sender);
         self.send_join_game_data(game);
     }
+    /// Reconnects without sending the whole game again. The caller sends the packets the client missed.
+    pub fn resume(&self, game: &mut Game, sender: ClientSender){
+        self.deref_mut(game).connection = ClientConnection::Connected(sender);
+    }
     pub fn lose_connection(&self, game: &mut Game){
-        self.deref_mut(game).connection = ClientConnection::CouldReconnect { disconnect_timer: Duration::from_secs(GAME_DISCONNECT_TIMER_SECS) };
+        self.deref_mut(game).connection = ClientConnection::CouldReconnect { disconnect_timer: Config::get().game_disconnect_timer() };
     }
     pub fn quit(&self, game: &mut Game) {
         self.deref_mut(game).connection = ClientConnection::Disconnected;
@@ -30,6 +32,16 @@ impl PlayerReference{
         }
     }
 
+    /// Hands a player who quit to someone else, who is sent everything the player had
+    pub fn substitute(&self, game: &mut Game, sender: ClientSender, bot: bool){
+        self.deref_mut(game).connection = ClientConnection::Connected(sender);
+        game.add_message_to_chat_group(
+            crate::game::chat::ChatGroup::All,
+            ChatMessageVariant::PlayerSubstituted{player_index: self.index(), bot}
+        );
+        self.send_join_game_data(game);
+    }
+
     pub fn connection<'a>(&self, game: &'a Game) -> &'a ClientConnection {
         &self.deref(game).connection
     }
@@ -42,9 +54,26 @@ impl PlayerReference{
     pub fn is_disconnected(&self, game: &Game) -> bool {
         matches!(self.deref(game).connection, ClientConnection::Disconnected)
     }
+    /// Quit while still in the game, so someone else can take over
+    pub fn is_abandoned(&self, game: &Game) -> bool {
+        self.is_disconnected(game) && self.alive(game) && !game.game_is_over()
+    }
 
     pub fn send_packet(&self, game: &Game, packet: ToClientPacket){
-        self.deref(game).connection.send_packet(packet);
+        let player = self.deref(game);
+        if let ClientConnection::Disconnected = player.connection {
+            return;
+        }
+        // Packets sent while the player could reconnect are kept too, so they can be resent
+        let packet = player.sent_packets.borrow_mut().push(packet);
+        player.connection.send_packet(packet);
+    }
+    /// Every packet sent after `last_sequence`, or `None` if the player has to be sent the whole game instead
+    pub fn missed_packets(&self, game: &Game, last_sequence: PacketSequence) -> Option<Vec<ToClientPacket>> {
+        self.deref(game).sent_packets.borrow().since(last_sequence)
+    }
+    pub fn forget_sent_packets(&self, game: &mut Game){
+        self.deref_mut(game).sent_packets.get_mut().clear();
     }
     pub fn send_packets(&self, game: &Game, packets: Vec<ToClientPacket>){
         for packet in packets{
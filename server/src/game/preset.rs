@@ -0,0 +1,340 @@
+//! Named settings presets that a host can load in one go.
+//!
+//! A preset is a JSON file meant to be edited by hand. Outlines and enabled roles are written
+//! in the notation from [`super::role_list_notation`]:
+//!
+//! ```text
+//! {
+//!     "format": 1,
+//!     "name": "Classic",
+//!     "description": "Optional",
+//!     "roleList": ["Jailor", "Town Investigative | Town Support", "Mafia Killing"],
+//!     "phaseTimes": { "briefing": 45, "obituary": 60, ... },
+//!     "enabledRoles": ["Town", "Mafia", "Jester"],
+//!     "enabledModifiers": ["deadCanChat"],
+//!     "roleWeights": { "Goon": 3, "Mafia Support": 2 },
+//!     "roleConstraints": ["At most 1: Doctor, Armorsmith", "Jester requires Town Investigative"],
+//!     "customRoleSets": { "Chaos Neutrals": ["Jester", "Politician", "Doomsayer"] }
+//! }
+//! ```
+//!
+//! Everything but `format`, `name` and `roleList` can be left out, and defaults to nothing,
+//! the default phase times, every role, no modifiers, every role weighing 1, no constraints,
+//! and no custom role sets.
+//! A role weight set on a role overrides one set on a role set it's in.
+//! Custom role sets are made of roles and built-in role sets, and can be used anywhere else in the file.
+
+use std::{collections::BTreeMap, fmt::Display, path::Path, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{config::Config, vec_map::VecMap, vec_set::VecSet};
+
+use super::{
+    modifiers::ModifierType, role::Role,
+    role_generation::RoleConstraint,
+    role_list::{CustomRoleSet, RoleList, RoleOutline, RoleOutlineOptionRoles, RoleSet},
+    role_list_notation::{validate_custom_role_sets, ConstraintParseError, CustomRoleSetError, OptionParseError, OutlineParseError},
+    settings::{PhaseTimeSettings, Settings}
+};
+
+/// Bump this whenever the file layout changes, and keep reading the older formats
+pub const PRESET_FORMAT: u32 = 1;
+
+const BUILT_IN_PRESETS: [(&str, &str); 4] = [
+    ("classic.json", include_str!("../../resources/presets/classic.json")),
+    ("beginner.json", include_str!("../../resources/presets/beginner.json")),
+    ("cult.json", include_str!("../../resources/presets/cult.json")),
+    ("fiends.json", include_str!("../../resources/presets/fiends.json")),
+];
+
+static LIBRARY: OnceLock<PresetLibrary> = OnceLock::new();
+
+#[derive(Deserialize)]
+struct PresetFormat {
+    format: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PresetFile {
+    format: u32,
+    name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    description: String,
+    role_list: Vec<String>,
+    #[serde(default)]
+    phase_times: PhaseTimeSettings,
+    #[serde(default = "every_role")]
+    enabled_roles: Vec<String>,
+    #[serde(default)]
+    enabled_modifiers: Vec<ModifierType>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    role_weights: BTreeMap<String, u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    role_constraints: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    custom_role_sets: BTreeMap<String, Vec<String>>,
+}
+
+fn every_role() -> Vec<String> {
+    vec![RoleOutlineOptionRoles::RoleSet { role_set: RoleSet::Any }.to_string()]
+}
+
+#[derive(Debug)]
+pub enum PresetError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    UnsupportedFormat(u32),
+    EmptyName,
+    EmptyRoleList,
+    /// `entry` counts from 1
+    RoleList { entry: usize, text: String, error: OutlineParseError },
+    /// `entry` counts from 1
+    EnabledRoles { entry: usize, text: String, error: OptionParseError },
+    RoleWeights { text: String, error: OptionParseError },
+    /// `entry` counts from 1
+    RoleConstraints { entry: usize, text: String, error: ConstraintParseError },
+    CustomRoleSetRoles { name: String, text: String, error: OptionParseError },
+    CustomRoleSet(CustomRoleSetError),
+}
+impl Display for PresetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresetError::Io(err) => write!(f, "Failed to read the preset: {err}"),
+            PresetError::Json(err) => write!(f, "Failed to parse the preset: {err}"),
+            PresetError::UnsupportedFormat(format) => write!(f, "Unsupported preset format {format}, the newest is {PRESET_FORMAT}"),
+            PresetError::EmptyName => write!(f, "The preset has no name"),
+            PresetError::EmptyRoleList => write!(f, "The preset's role list is empty"),
+            PresetError::RoleList { entry, text, error } => write!(f, "roleList entry {entry} \"{text}\", {error}"),
+            PresetError::EnabledRoles { entry, text, error } => write!(f, "enabledRoles entry {entry} \"{text}\": {error}"),
+            PresetError::RoleWeights { text, error } => write!(f, "roleWeights \"{text}\": {error}"),
+            PresetError::RoleConstraints { entry, text, error } => write!(f, "roleConstraints entry {entry} \"{text}\": {error}"),
+            PresetError::CustomRoleSetRoles { name, text, error } => write!(f, "customRoleSets \"{name}\" entry \"{text}\": {error}"),
+            PresetError::CustomRoleSet(error) => write!(f, "customRoleSets: {error}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Preset {
+    pub name: String,
+    pub description: String,
+    pub settings: Settings,
+}
+
+/// What clients are told about a preset, so the host can pick one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetSummary {
+    pub name: String,
+    pub description: String,
+    pub players: usize,
+}
+
+impl Preset {
+    pub fn new(name: String, settings: Settings) -> Self {
+        Self { name, description: String::new(), settings }
+    }
+
+    pub fn summary(&self) -> PresetSummary {
+        PresetSummary {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            players: self.settings.role_list.0.len(),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, PresetError> {
+        let PresetFormat { format } = serde_json::from_str(json).map_err(PresetError::Json)?;
+        if format != PRESET_FORMAT {
+            return Err(PresetError::UnsupportedFormat(format));
+        }
+
+        let file: PresetFile = serde_json::from_str(json).map_err(PresetError::Json)?;
+
+        let name = file.name.trim().to_string();
+        if name.is_empty() {
+            return Err(PresetError::EmptyName);
+        }
+        if file.role_list.is_empty() {
+            return Err(PresetError::EmptyRoleList);
+        }
+
+        let mut custom_role_sets = Vec::new();
+        for (name, entries) in file.custom_role_sets {
+            let mut roles = Vec::new();
+            for text in entries {
+                match text.parse::<RoleOutlineOptionRoles>() {
+                    Ok(entry) => roles.extend(entry.get_roles()),
+                    Err(error) => return Err(PresetError::CustomRoleSetRoles { name, text, error }),
+                }
+            }
+            custom_role_sets.push(CustomRoleSet::new(name, roles));
+        }
+        validate_custom_role_sets(&custom_role_sets).map_err(PresetError::CustomRoleSet)?;
+
+        let role_list = file.role_list.into_iter()
+            .enumerate()
+            .map(|(i, text)| match RoleOutline::parse_with(&text, &custom_role_sets) {
+                Ok(outline) => Ok(outline),
+                Err(error) => Err(PresetError::RoleList { entry: i.saturating_add(1), text, error }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut enabled_roles = VecSet::new();
+        for (i, text) in file.enabled_roles.into_iter().enumerate() {
+            match RoleOutlineOptionRoles::parse_with(&text, &custom_role_sets) {
+                Ok(roles) => enabled_roles.extend(roles.get_roles()),
+                Err(error) => return Err(PresetError::EnabledRoles { entry: i.saturating_add(1), text, error }),
+            }
+        }
+
+        let mut weights = Vec::new();
+        for (text, weight) in file.role_weights {
+            match RoleOutlineOptionRoles::parse_with(&text, &custom_role_sets) {
+                Ok(roles) => weights.push((roles, weight)),
+                Err(error) => return Err(PresetError::RoleWeights { text, error }),
+            }
+        }
+        // Sets first, so roles named on their own win
+        weights.sort_by_key(|(roles, _)| matches!(roles, RoleOutlineOptionRoles::Role { .. }));
+        let mut role_weights = VecMap::new();
+        for (roles, weight) in weights {
+            for role in roles.get_roles() {
+                role_weights.insert(role, weight);
+            }
+        }
+
+        let role_constraints = file.role_constraints.into_iter()
+            .enumerate()
+            .map(|(i, text)| match RoleConstraint::parse_with(&text, &custom_role_sets) {
+                Ok(constraint) => Ok(constraint),
+                Err(error) => Err(PresetError::RoleConstraints { entry: i.saturating_add(1), text, error }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            name,
+            description: file.description,
+            settings: Settings {
+                role_list: RoleList(role_list),
+                phase_times: file.phase_times,
+                enabled_roles,
+                enabled_modifiers: file.enabled_modifiers.into_iter().collect(),
+                role_weights,
+                role_constraints,
+                custom_role_sets,
+                seed: None,
+            }
+        })
+    }
+
+    /// The seed isn't saved, since presets are for playing many games
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        let enabled_roles = if Role::values().iter().all(|role| self.settings.enabled_roles.contains(role)) {
+            every_role()
+        } else {
+            self.settings.enabled_roles.iter()
+                .map(|role| RoleOutlineOptionRoles::Role { role: *role }.to_string())
+                .collect()
+        };
+
+        serde_json::to_string_pretty(&PresetFile {
+            format: PRESET_FORMAT,
+            name: self.name.clone(),
+            description: self.description.clone(),
+            role_list: self.settings.role_list.0.iter().map(ToString::to_string).collect(),
+            phase_times: self.settings.phase_times.clone(),
+            enabled_roles,
+            enabled_modifiers: self.settings.enabled_modifiers.iter().cloned().collect(),
+            role_weights: self.settings.role_weights.iter()
+                .map(|(role, weight)| (RoleOutlineOptionRoles::Role { role: *role }.to_string(), *weight))
+                .collect(),
+            role_constraints: self.settings.role_constraints.iter().map(ToString::to_string).collect(),
+            custom_role_sets: self.settings.custom_role_sets.iter()
+                .map(|set| (
+                    set.name.clone(),
+                    set.roles.iter().map(|role| RoleOutlineOptionRoles::Role { role: *role }.to_string()).collect()
+                ))
+                .collect(),
+        })
+    }
+}
+
+/// Every preset this server offers, in the order they're listed to hosts
+#[derive(Debug, Default)]
+pub struct PresetLibrary {
+    presets: Vec<Preset>,
+}
+
+impl PresetLibrary {
+    /// The built-in presets, then any in the configured presets directory.
+    /// Presets that fail to load are logged and left out.
+    pub fn get() -> &'static PresetLibrary {
+        LIBRARY.get_or_init(|| {
+            let (library, errors) = Self::load(Config::get().presets_directory.as_deref().map(Path::new));
+            for (source, error) in errors {
+                warn!(source, %error, "Failed to load preset");
+            }
+            info!(presets = library.presets.len(), "Loaded presets");
+            library
+        })
+    }
+
+    /// Presets in `directory` replace built-in presets with the same name.
+    /// Also returns every preset that failed to load, next to the file it came from.
+    pub fn load(directory: Option<&Path>) -> (PresetLibrary, Vec<(String, PresetError)>) {
+        let mut library = PresetLibrary::default();
+        let mut errors = Vec::new();
+
+        for (file, json) in BUILT_IN_PRESETS {
+            match Preset::from_json(json) {
+                Ok(preset) => library.insert(preset),
+                Err(error) => errors.push((file.to_string(), error)),
+            }
+        }
+
+        let Some(directory) = directory else {return (library, errors)};
+
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(error) => {
+                errors.push((directory.display().to_string(), PresetError::Io(error)));
+                return (library, errors);
+            }
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match std::fs::read_to_string(&path).map_err(PresetError::Io).and_then(|json| Preset::from_json(&json)) {
+                Ok(preset) => library.insert(preset),
+                Err(error) => errors.push((path.display().to_string(), error)),
+            }
+        }
+
+        (library, errors)
+    }
+
+    pub fn insert(&mut self, preset: Preset) {
+        if let Some(existing) = self.presets.iter_mut().find(|existing| existing.name == preset.name) {
+            *existing = preset;
+        } else {
+            self.presets.push(preset);
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    pub fn summaries(&self) -> Vec<PresetSummary> {
+        self.presets.iter().map(Preset::summary).collect()
+    }
+}
@@ -0,0 +1,153 @@
+use std::net::SocketAddr;
+
+use tokio::sync::mpsc;
+
+use crate::{client_connection::ClientConnection, lobby::Lobby, websocket_connections::connection::{ClientSender, Connection}};
+use super::{
+    components::event_journal::{EventJournal, GameInput, GameOutcome, GameRecord, GAME_RECORD_VERSION, OLDEST_REPLAYABLE_GAME_RECORD_VERSION},
+    event::{on_fast_forward::OnFastForward, on_game_ending::OnGameEnding},
+    game_conclusion::GameConclusion, grave::Grave,
+    player::{PlayerInitializeParameters, PlayerReference},
+    Game, RejectStartReason
+};
+
+#[derive(Debug)]
+pub enum ReplayError {
+    UnsupportedVersion(u32),
+    CouldNotStart(RejectStartReason),
+}
+
+#[derive(Debug)]
+pub enum ReplayMismatch {
+    /// The recorded game had ended but the replay didn't, or the other way around
+    Ended { expected: bool, actual: bool },
+    Conclusion { expected: GameConclusion, actual: GameConclusion },
+    Graves { expected: Vec<Grave>, actual: Vec<Grave> },
+    Synopsis,
+}
+
+/// Plays a recorded game again without any clients connected.
+/// The new game starts from the record's seed and settings and is fed the record's inputs in order.
+pub fn replay(record: &GameRecord) -> Result<Game, ReplayError> {
+    if !(OLDEST_REPLAYABLE_GAME_RECORD_VERSION..=GAME_RECORD_VERSION).contains(&record.version) {
+        return Err(ReplayError::UnsupportedVersion(record.version));
+    }
+
+    let mut settings = record.settings.clone();
+    settings.seed = Some(record.seed);
+
+    let players = record.players.iter()
+        .map(|name| PlayerInitializeParameters {
+            connection: ClientConnection::Connected(headless_sender()),
+            name: name.clone(),
+            host: false,
+        })
+        .collect();
+
+    let mut game = Game::new(settings, players, Vec::new()).map_err(ReplayError::CouldNotStart)?;
+    EventJournal::set_export_directory(&mut game, None);
+
+    for input in record.inputs.iter().cloned() {
+        apply_input(&mut game, input);
+    }
+
+    Ok(game)
+}
+
+/// Replays the record and lists every way the replay's outcome differs from the recorded one
+pub fn verify(record: &GameRecord) -> Result<Vec<ReplayMismatch>, ReplayError> {
+    let game = replay(record)?;
+    let actual = EventJournal::game_record(&game).outcome;
+
+    let (expected, actual) = match (record.outcome.clone(), actual) {
+        (Some(expected), Some(actual)) => (expected, actual),
+        (None, None) => return Ok(Vec::new()),
+        (expected, actual) => return Ok(vec![ReplayMismatch::Ended {
+            expected: expected.is_some(), actual: actual.is_some()
+        }]),
+    };
+
+    Ok(compare_outcomes(expected, actual))
+}
+
+fn compare_outcomes(expected: GameOutcome, actual: GameOutcome) -> Vec<ReplayMismatch> {
+    let mut mismatches = Vec::new();
+
+    if expected.conclusion != actual.conclusion {
+        mismatches.push(ReplayMismatch::Conclusion { expected: expected.conclusion, actual: actual.conclusion });
+    }
+
+    // Graves added in the same event can come out in any order
+    let mut expected_graves = expected.graves;
+    let mut actual_graves = actual.graves;
+    expected_graves.sort();
+    actual_graves.sort();
+    if expected_graves != actual_graves {
+        mismatches.push(ReplayMismatch::Graves { expected: expected_graves, actual: actual_graves });
+    }
+
+    // Synopsis equality is always true, so compare what would be sent instead
+    if serde_json::to_value(&expected.synopsis).ok() != serde_json::to_value(&actual.synopsis).ok() {
+        mismatches.push(ReplayMismatch::Synopsis);
+    }
+
+    mismatches
+}
+
+fn apply_input(game: &mut Game, input: GameInput) {
+    match input {
+        GameInput::Tick { time_passed } => game.tick(time_passed),
+        GameInput::Packet { player, packet } => game.on_client_message(player, packet),
+        GameInput::HostForceSkipPhase => {
+            EventJournal::record_input(game, GameInput::HostForceSkipPhase);
+            OnFastForward::invoke(game);
+        },
+        GameInput::HostForceEndGame => {
+            EventJournal::record_input(game, GameInput::HostForceEndGame);
+            let conclusion = GameConclusion::get_premature_conclusion(game);
+            OnGameEnding::new(conclusion).invoke(game);
+        },
+        GameInput::LoseConnection { player } => {
+            EventJournal::record_input(game, GameInput::LoseConnection { player });
+            if let Ok(player) = PlayerReference::new(game, player) {
+                player.lose_connection(game);
+            }
+        },
+        GameInput::Reconnect { player } => {
+            EventJournal::record_input(game, GameInput::Reconnect { player });
+            if let Ok(player) = PlayerReference::new(game, player) {
+                player.connect(game, headless_sender());
+            }
+        },
+        GameInput::Resume { player } => {
+            EventJournal::record_input(game, GameInput::Resume { player });
+            if let Ok(player) = PlayerReference::new(game, player) {
+                player.resume(game, headless_sender());
+            }
+        },
+        GameInput::Quit { player } => {
+            EventJournal::record_input(game, GameInput::Quit { player });
+            if let Ok(player) = PlayerReference::new(game, player) {
+                player.quit(game);
+            }
+        },
+        GameInput::Substitute { player, bot } => {
+            EventJournal::record_input(game, GameInput::Substitute { player, bot });
+            if let Ok(player) = PlayerReference::new(game, player) {
+                player.substitute(game, headless_sender(), bot);
+            }
+        },
+        GameInput::SetPlayerName { player, name } => {
+            EventJournal::record_input(game, GameInput::SetPlayerName { player, name: name.clone() });
+            if let Ok(player) = PlayerReference::new(game, player) {
+                Lobby::set_player_name_game(game, player, name);
+            }
+        },
+    }
+}
+
+/// A sender whose packets go nowhere
+fn headless_sender() -> ClientSender {
+    let (sender, _) = mpsc::unbounded_channel();
+    Connection::new(sender, SocketAddr::from(([0, 0, 0, 0], 0))).get_sender()
+}
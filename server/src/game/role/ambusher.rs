@@ -1,6 +1,6 @@
 
 use rand::seq::IndexedRandom;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::attack_power::AttackPower;
 use crate::game::attack_power::DefensePower;
@@ -20,7 +20,7 @@ use super::{
 
 
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Ambusher;
 
 
@@ -45,21 +45,22 @@ impl RoleStateImpl for Ambusher {
                         visit.target == target_ref &&
                         visit.visitor.alive(game) &&
                         visit.visitor.win_condition(game).is_loyalist_for(GameConclusion::Town)
-                    ).collect::<Vec<&Visit>>()
-                    .choose(&mut rand::rng())
+                    ).map(|visit| visit.visitor)
+                    .collect::<Vec<PlayerReference>>()
+                    .choose(&mut game.rng)
                     .copied()
                 {
-                    Some(priority_visitor.visitor)
+                    Some(priority_visitor)
                 } else {
                     NightVisits::all_visits(game).into_iter()
                         .filter(|visit|
                             ambush_visit != *visit &&
                             visit.target == target_ref &&
                             visit.visitor.alive(game)
-                        ).collect::<Vec<&Visit>>()
-                        .choose(&mut rand::rng())
+                        ).map(|visit| visit.visitor)
+                        .collect::<Vec<PlayerReference>>()
+                        .choose(&mut game.rng)
                         .copied()
-                        .map(|v|v.visitor)
                 };
 
                 if let Some(player_to_attack) = player_to_attacks_visit{
@@ -1,5 +1,5 @@
 use rand::seq::IndexedRandom;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::{attack_power::DefensePower, chat::ChatMessageVariant};
 use crate::game::phase::PhaseType;
@@ -16,7 +16,7 @@ pub struct Armorsmith {
     players_armor: Vec<PlayerReference>
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ClientRoleState {
     open_shops_remaining: u8
@@ -62,7 +62,7 @@ impl RoleStateImpl for Armorsmith {
 
                 if visitors.contains(&target){
                     self.players_armor.push(target);
-                }else if let Some(random_visitor) = visitors.choose(&mut rand::rng()) {
+                }else if let Some(random_visitor) = visitors.choose(&mut game.rng) {
                     self.players_armor.push(*random_visitor);
                 }
 
@@ -20,7 +20,7 @@ use rand::prelude::SliceRandom;
 use super::{common_role, Priority, Role, RoleStateImpl};
 
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Auditor{
     pub previously_given_results: VecMap<RoleOutlineReference, AuditorResult>,
@@ -112,7 +112,7 @@ impl RoleStateImpl for Auditor {
 
 impl Auditor{
     //panics if chosen_outline is not found
-    pub fn get_result(game: &Game, chosen_outline: RoleOutlineReference) -> AuditorResult {
+    pub fn get_result(game: &mut Game, chosen_outline: RoleOutlineReference) -> AuditorResult {
         let (role, _) = chosen_outline.deref_as_role_and_player_originally_generated(game);
         
         let outline = chosen_outline.deref(game);
@@ -127,12 +127,12 @@ impl Auditor{
                 .filter(|x|game.settings.enabled_roles.contains(x))
                 .filter(|x|*x != role)
                 .collect::<Vec<Role>>()
-                .choose(&mut rand::rng())
+                .choose(&mut game.rng)
                 .copied();
 
             if let Some(fake_role) = fake_role{
                 let mut two = [role, fake_role];
-                two.shuffle(&mut rand::rng());
+                two.shuffle(&mut game.rng);
                 AuditorResult::Two{roles: [two[0], two[1]]}
             } else {
                 AuditorResult::One{role}
@@ -140,7 +140,7 @@ impl Auditor{
         }
     }
     //panics if chosen_outline is not found
-    pub fn get_confused_result(game: &Game, chosen_outline: RoleOutlineReference) -> AuditorResult {        
+    pub fn get_confused_result(game: &mut Game, chosen_outline: RoleOutlineReference) -> AuditorResult {        
         let outline = chosen_outline.deref(game);
 
         if outline.get_role_assignments().len() == 1 || outline.get_role_assignments().len() == 2 {
@@ -150,7 +150,7 @@ impl Auditor{
                 .map(|assignment| assignment.role)
                 .filter(|x|game.settings.enabled_roles.contains(x))
                 .collect::<Vec<Role>>()
-                .choose(&mut rand::rng())
+                .choose(&mut game.rng)
                 .copied();
 
             if let Some(fake_role) = fake_role{
@@ -166,9 +166,9 @@ impl Auditor{
                 .filter(|x|game.settings.enabled_roles.contains(x))
                 .collect::<Vec<Role>>();
             
-            fake_roles.shuffle(&mut rand::rng());
+            fake_roles.shuffle(&mut game.rng);
 
-            let fake_roles = fake_roles.choose_multiple(&mut rand::rng(), 2).copied().collect::<Vec<Role>>();
+            let fake_roles = fake_roles.choose_multiple(&mut game.rng, 2).copied().collect::<Vec<Role>>();
 
             match (fake_roles.get(0), fake_roles.get(1)){
                 (Some(role1), Some(role2)) => {
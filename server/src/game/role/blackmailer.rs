@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::{attack_power::DefensePower, player::PlayerReference};
 
@@ -8,7 +8,7 @@ use crate::game::Game;
 use super::{ControllerID, ControllerParametersMap, Priority, Role, RoleStateImpl};
 
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Blackmailer;
 
 
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::chat::ChatGroup;
 use crate::game::phase::PhaseType;
@@ -8,7 +8,7 @@ use crate::game::player::PlayerReference;
 
 use super::RoleStateImpl;
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Chronokaiser;
 
 pub(super) const MAXIMUM_COUNT: Option<u8> = None;
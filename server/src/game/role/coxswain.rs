@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::ability_input::*;
 use crate::game::attack_power::DefensePower;
@@ -17,7 +17,7 @@ use super::{
 };
 
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Coxswain{
     pub targets: VecSet<PlayerReference>,
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::components::detained::Detained;
 use crate::game::{attack_power::DefensePower, components::love_linked::LoveLinked};
@@ -11,7 +11,7 @@ use crate::vec_set;
 use super::{common_role, AvailableAbilitySelection, ControllerID, ControllerParametersMap, InsiderGroupID, Priority, Role, RoleStateImpl};
 
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Cupid;
 
 
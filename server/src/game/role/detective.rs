@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::ability_input::ControllerID;
 use crate::game::components::confused::Confused;
@@ -14,7 +14,7 @@ use super::{ControllerParametersMap, Priority, Role, RoleStateImpl};
 pub(super) const MAXIMUM_COUNT: Option<u8> = None;
 pub(super) const DEFENSE: DefensePower = DefensePower::None;
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Detective;
 
 impl RoleStateImpl for Detective {
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::ability_input::*;
 use crate::game::chat::ChatMessageVariant;
@@ -14,7 +14,7 @@ use crate::vec_set::{vec_set, VecSet};
 use super::{InsiderGroupID, Priority, Role, RoleStateImpl};
 
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Disguiser{
     pub current_target: Option<PlayerReference>
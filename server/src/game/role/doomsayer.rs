@@ -17,7 +17,7 @@ pub struct Doomsayer {
     pub won: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ClientRoleState {
     guesses: [(PlayerReference, DoomsayerGuess); 3],
 }
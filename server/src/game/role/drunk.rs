@@ -1,5 +1,5 @@
 use rand::seq::IndexedRandom;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::components::drunk_aura::DrunkAura;
 use crate::game::{attack_power::DefensePower, components::confused::Confused};
@@ -12,7 +12,7 @@ use super::{Role, RoleStateImpl};
 pub(super) const MAXIMUM_COUNT: Option<u8> = None;
 pub(super) const DEFENSE: DefensePower = DefensePower::None;
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Drunk;
 
 impl RoleStateImpl for Drunk {
@@ -26,7 +26,7 @@ impl RoleStateImpl for Drunk {
         //special case here. I don't want to use set_role because it alerts the player their role changed
         //NOTE: It will still send a packet to the player that their role state updated,
         //so it might be deducible that the player is a drunk
-        if let Some(random_town_role) = possible_roles.choose(&mut rand::rng()) {
+        if let Some(random_town_role) = possible_roles.choose(&mut game.rng) {
             actor_ref.set_role_state(game, random_town_role.new_state(game));
         }
 
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::components::detained::Detained;
 use crate::game::components::insider_group::InsiderGroupID;
@@ -13,7 +13,7 @@ use crate::vec_set::{vec_set, VecSet};
 use super::{AbilitySelection, ControllerID, ControllerParametersMap, PlayerListSelection, Priority, Role, RoleStateImpl};
 
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Framer{
     framed_targets: VecSet<PlayerReference>
 }
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::attack_power::{AttackPower, DefensePower};
 use crate::game::grave::GraveKiller;
@@ -11,7 +11,7 @@ use crate::game::Game;
 use super::{ControllerID, PlayerListSelection, Priority, Role, RoleState, RoleStateImpl};
 
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Godfather;
 
 
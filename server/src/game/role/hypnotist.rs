@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::{attack_power::DefensePower, chat::ChatMessageVariant};
 use crate::game::player::PlayerReference;
@@ -8,7 +8,7 @@ use crate::game::visit::Visit;
 use crate::game::Game;
 use super::{ControllerID, ControllerParametersMap, Priority, Role, RoleState, RoleStateImpl};
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Hypnotist{
     pub roleblock: bool,
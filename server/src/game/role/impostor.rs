@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::ability_input::*;
 use crate::game::attack_power::DefensePower;
@@ -13,7 +13,7 @@ use super::godfather::Godfather;
 use super::{Priority, Role, RoleStateImpl};
 
 
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Impostor{
     pub backup: Option<PlayerReference>
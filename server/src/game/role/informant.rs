@@ -1,5 +1,5 @@
 use rand::prelude::SliceRandom;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::{attack_power::DefensePower, chat::ChatMessageVariant};
 use crate::game::player::PlayerReference;
@@ -9,7 +9,7 @@ use crate::game::visit::Visit;
 use crate::game::Game;
 use super::{ControllerID, ControllerParametersMap, Priority, Role, RoleStateImpl};
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Informant;
 
 
@@ -27,10 +27,10 @@ impl RoleStateImpl for Informant {
             let target_ref = visit.target;
 
             let mut visited_by: Vec<PlayerReference> =  visit.target.all_appeared_visitors(game).into_iter().filter(|p|actor_ref!=*p).collect();
-            visited_by.shuffle(&mut rand::rng());
+            visited_by.shuffle(&mut game.rng);
 
             let mut visited: Vec<PlayerReference> = target_ref.tracker_seen_visits(game).iter().map(|v|v.target).collect();
-            visited.shuffle(&mut rand::rng());
+            visited.shuffle(&mut game.rng);
 
             let message = ChatMessageVariant::InformantResult{
                 role: target_ref.role(game), 
@@ -1,6 +1,6 @@
 
 use rand::seq::IndexedRandom;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::attack_power::{AttackPower, DefensePower};
 use crate::game::chat::{ChatGroup, ChatMessageVariant};
@@ -22,7 +22,7 @@ pub struct Jester {
     won: bool,
 }
 
-#[derive(Clone, Serialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ClientRoleState;
 
 
@@ -58,7 +58,7 @@ impl RoleStateImpl for Jester {
                 .collect();
 
             let Some(target_ref) = all_killable_players
-                .choose(&mut rand::rng()) else {return};
+                .choose(&mut game.rng) else {return};
             
             *target_ref
         };
@@ -12,7 +12,7 @@ use crate::game::ability_input::*;
 use crate::vec_set;
 use super::{Priority, Role, RoleStateImpl};
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Kira;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, PartialOrd, Ord)]
@@ -1,5 +1,5 @@
 use rand::prelude::SliceRandom;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::{attack_power::DefensePower, chat::ChatMessageVariant};
 use crate::game::player::PlayerReference;
@@ -9,7 +9,7 @@ use crate::game::Game;
 
 use super::{ControllerID, ControllerParametersMap, Priority, Role, RoleStateImpl};
 
-#[derive(Clone, Serialize, Debug, Default)]
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
 pub struct Lookout;
 
 
@@ -24,7 +24,7 @@ impl RoleStateImpl for Lookout {
         if let Some(visit) = actor_visits.first(){
             
             let mut seen_players: Vec<PlayerReference> = visit.target.all_appeared_visitors(game).into_iter().filter(|p|actor_ref!=*p).collect();
-            seen_players.shuffle(&mut rand::rng());
+            seen_players.shuffle(&mut game.rng);
 
             let message = ChatMessageVariant::LookoutResult { players:
                 PlayerReference::ref_vec_to_index(seen_players.as_slice())
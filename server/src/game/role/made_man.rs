@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::attack_power::DefensePower;
 
@@ -6,7 +6,7 @@ use crate::game::attack_power::DefensePower;
 use super::RoleStateImpl;
 
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Goon;
 
 pub type ClientRoleState = Goon;
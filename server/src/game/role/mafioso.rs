@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::attack_power::AttackPower;
 use crate::game::role_list::RoleSet;
@@ -11,7 +11,7 @@ use crate::game::Game;
 use super::{common_role, ControllerID, Priority, Role, RoleStateImpl};
 
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Mafioso;
 
 
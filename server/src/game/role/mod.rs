@@ -251,7 +251,7 @@ mod macros {
                 }
             }
 
-            #[derive(Clone, Debug, Serialize)]
+            #[derive(Clone, Debug, Serialize, Deserialize)]
             #[serde(tag = "type", rename_all = "camelCase")]
             pub enum ClientRoleStateEnum {
                 $($name(<$name as RoleStateImpl>::ClientRoleState)),*
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::components::detained::Detained;
 use crate::game::{attack_power::DefensePower, phase::PhaseType};
@@ -23,7 +23,7 @@ pub struct Necromancer {
     currently_used_player: Option<PlayerReference> 
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ClientRoleState;
 
 impl RoleStateImpl for Necromancer {
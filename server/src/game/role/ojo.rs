@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::ability_input::ControllerID;
 use crate::game::attack_power::DefensePower;
@@ -17,7 +17,7 @@ use super::auditor::AuditorResult;
 use super::{common_role, AbilitySelection, AvailableAbilitySelection, ControllerParametersMap, Priority, Role, RoleOptionSelection, RoleStateImpl, TwoRoleOutlineOptionSelection};
 
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Ojo{
     pub previously_given_results: VecMap<RoleOutlineReference, AuditorResult>,
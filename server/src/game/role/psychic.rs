@@ -1,5 +1,5 @@
 use rand::seq::SliceRandom;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::components::confused::Confused;
 use crate::game::visit::Visit;
@@ -11,7 +11,7 @@ use crate::game::player::PlayerReference;
 use crate::game::Game;
 use super::{ControllerID, ControllerParametersMap, Priority, Role, RoleStateImpl};
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Psychic;
 
 
@@ -27,13 +27,12 @@ impl RoleStateImpl for Psychic {
                 let actor_visits = actor_ref.untagged_night_visits_cloned(game);
                 let Some(visit) = actor_visits.first() else {return};
 
-        actor_ref.push_night_message(game, 
-            if game.day_number() % 2 == 1 {
-                Psychic::get_result_evil(game, actor_ref, visit.target, Confused::is_confused(game, actor_ref))
-            }else{
-                Psychic::get_result_good(game, actor_ref, visit.target, Confused::is_confused(game, actor_ref))
-            }
-        );
+        let result = if game.day_number() % 2 == 1 {
+            Psychic::get_result_evil(game, actor_ref, visit.target, Confused::is_confused(game, actor_ref))
+        }else{
+            Psychic::get_result_good(game, actor_ref, visit.target, Confused::is_confused(game, actor_ref))
+        };
+        actor_ref.push_night_message(game, result);
     }
     fn controller_parameters_map(self, game: &Game, actor_ref: PlayerReference) -> ControllerParametersMap {
         crate::game::role::common_role::controller_parameters_map_player_list_night_typical(
@@ -56,14 +55,14 @@ impl RoleStateImpl for Psychic {
 }
 
 impl Psychic {
-    fn get_result_evil(game: &Game, actor_ref: PlayerReference, target: PlayerReference, confused: bool)->ChatMessageVariant{
+    fn get_result_evil(game: &mut Game, actor_ref: PlayerReference, target: PlayerReference, confused: bool)->ChatMessageVariant{
         
         let mut valid_players: Vec<_> = Self::get_valid_players(game, actor_ref, target)
             .into_iter()
             .filter(|p|!p.has_innocent_aura(game))
             .collect();
 
-        valid_players.shuffle(&mut rand::rng());
+        valid_players.shuffle(&mut game.rng);
 
         #[expect(clippy::indexing_slicing, reason = "We're iterating over indexes, so it's safe")]
         for i in 0..valid_players.len(){
@@ -77,13 +76,13 @@ impl Psychic {
 
         ChatMessageVariant::PsychicFailed
     }
-    fn get_result_good(game: &Game, actor_ref: PlayerReference, target: PlayerReference, confused: bool)->ChatMessageVariant{
+    fn get_result_good(game: &mut Game, actor_ref: PlayerReference, target: PlayerReference, confused: bool)->ChatMessageVariant{
         let mut valid_players: Vec<_> = Self::get_valid_players(game, actor_ref, target)
             .into_iter()
             .filter(|p|!p.has_suspicious_aura(game))
             .collect();
 
-        valid_players.shuffle(&mut rand::rng());
+        valid_players.shuffle(&mut game.rng);
 
         for player in valid_players{
             if confused || Self::contains_good(game, target, player){
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::attack_power::AttackPower;
 use crate::game::components::detained::Detained;
@@ -14,7 +14,7 @@ use crate::vec_set;
 
 use super::{AbilitySelection, ControllerID, ControllerParametersMap, IntegerSelection, Priority, Role, RoleStateImpl};
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Puppeteer{
     pub marionettes_remaining: u8,
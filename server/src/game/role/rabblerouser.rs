@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::{
     attack_power::DefensePower,
@@ -14,7 +14,7 @@ use super::{RoleState, RoleStateImpl};
 pub(super) const MAXIMUM_COUNT: Option<u8> = None;
 pub(super) const DEFENSE: DefensePower = DefensePower::None;
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Rabblerouser;
 
 impl RoleStateImpl for Rabblerouser {
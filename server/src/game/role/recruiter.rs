@@ -1,6 +1,6 @@
 
-use rand::seq::IteratorRandom;
-use serde::Serialize;
+use rand::seq::IndexedRandom;
+use serde::{Deserialize, Serialize};
 
 use crate::game::attack_power::{AttackPower, DefensePower};
 use crate::game::components::mafia_recruits::MafiaRecruits;
@@ -21,7 +21,7 @@ use super::{
 
 use vec1::vec1;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Recruiter{
     pub recruits_remaining: u8,
@@ -121,17 +121,21 @@ impl RoleStateImpl for Recruiter {
         let random_mafia_player = PlayerReference::all_players(game)
             .filter(|p|RoleSet::Mafia.get_roles().contains(&p.role(game)))
             .filter(|p|*p!=actor_ref)
-            .choose(&mut rand::rng());
+            .collect::<Vec<_>>()
+            .choose(&mut game.rng)
+            .copied();
 
         if let Some(random_mafia_player) = random_mafia_player {
 
             let random_town_role = RoleOutline {options: vec1![RoleOutlineOption {
                 win_condition: Default::default(), 
                 insider_groups: Default::default(), 
-                roles: RoleOutlineOptionRoles::RoleSet{ role_set: RoleSet::TownCommon } 
+                roles: RoleOutlineOptionRoles::RoleSet{ role_set: RoleSet::TownCommon },
+                weight: RoleOutlineOption::DEFAULT_WEIGHT
             }]}.get_random_role_assignments(
                 &game.settings.enabled_roles,
-                PlayerReference::all_players(game).map(|p|p.role(game)).collect::<Vec<_>>().as_slice()
+                PlayerReference::all_players(game).map(|p|p.role(game)).collect::<Vec<_>>().as_slice(),
+                &mut game.rng
             ).map(|assignment| assignment.role);
 
             if let Some(random_town_role) = random_town_role {
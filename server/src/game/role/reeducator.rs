@@ -1,5 +1,7 @@
-use rand::seq::IteratorRandom;
-use serde::Serialize;
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, IteratorRandom};
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
 use vec1::vec1;
 
 use crate::game::ability_input::ControllerID;
@@ -24,7 +26,7 @@ use super::{
 };
 
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Reeducator{
     convert_charges_remaining: bool,
@@ -175,7 +177,9 @@ impl RoleStateImpl for Reeducator {
             .filter(|p|!RoleSet::MafiaKilling.get_roles().contains(&p.role(game)))
             .filter(|p|*p!=actor_ref)
             .filter(|p|p.role(game)!=Role::Reeducator)
-            .choose(&mut rand::rng());
+            .collect::<Vec<_>>()
+            .choose(&mut game.rng)
+            .copied();
 
         if let Some(random_mafia_player) = random_mafia_player {
 
@@ -183,12 +187,14 @@ impl RoleStateImpl for Reeducator {
                 options: vec1![RoleOutlineOption {
                     win_condition: Default::default(),
                     insider_groups: Default::default(),
-                    roles: RoleOutlineOptionRoles::RoleSet { role_set: RoleSet::TownCommon }
+                    roles: RoleOutlineOptionRoles::RoleSet { role_set: RoleSet::TownCommon },
+                    weight: RoleOutlineOption::DEFAULT_WEIGHT
                 }]
             }
                 .get_random_role_assignments(
                     &game.settings.enabled_roles,
-                    PlayerReference::all_players(game).map(|p|p.role(game)).collect::<Vec<_>>().as_slice()
+                    PlayerReference::all_players(game).map(|p|p.role(game)).collect::<Vec<_>>().as_slice(),
+                    &mut game.rng
                 ).map(|assignment| assignment.role);
 
             if let Some(random_town_role) = random_town_role {
@@ -213,10 +219,11 @@ impl RoleStateImpl for Reeducator {
 }
 
 impl Reeducator {
+    /// Picked from the game seed rather than `game.rng` so the default stays the same every time it's asked for
     pub fn default_role(game: &Game) -> Option<Role> {
         RoleSet::MafiaSupport.get_roles().into_iter()
             .filter(|p|game.settings.enabled_roles.contains(p))
             .filter(|p|*p!=Role::Reeducator)
-            .choose(&mut rand::rng())
+            .choose(&mut StdRng::seed_from_u64(game.seed))
     }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::components::detained::Detained;
 use crate::game::role_list::RoleSet;
@@ -24,7 +24,7 @@ pub struct Retributionist {
     currently_used_player: Option<PlayerReference> 
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ClientRoleState;
 
 impl RoleStateImpl for Retributionist {
@@ -1,6 +1,6 @@
 
 use rand::seq::IndexedRandom;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::attack_power::DefensePower;
 use crate::game::chat::{ChatGroup, ChatMessageVariant};
@@ -22,7 +22,7 @@ pub struct Revolutionary {
     target: RevolutionaryTarget,
 }
 
-#[derive(Clone, Serialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ClientRoleState;
 
 #[derive(Clone, Serialize, Debug, PartialEq, Eq)]
@@ -83,7 +83,7 @@ impl RoleStateImpl for Revolutionary {
                 p.role(game) != Role::Mayor &&
                 p.role(game) != Role::Reporter
             ).collect::<Vec<PlayerReference>>()
-            .choose(&mut rand::rng())
+            .choose(&mut game.rng)
         {
             actor_ref.push_player_tag(game, *target, Tag::RevolutionaryTarget);
             actor_ref.set_role_state(game, RoleState::Revolutionary(Revolutionary{target: RevolutionaryTarget::Target(*target)}));
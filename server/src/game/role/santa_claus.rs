@@ -11,7 +11,7 @@ use crate::game::Game;
 use crate::vec_set::{vec_set, VecSet};
 use super::{AbilitySelection, ControllerID, ControllerParametersMap, Priority, Role, RoleStateImpl};
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SantaClaus {
     pub ability_used_last_night: Option<SantaListKind>,
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::win_condition::WinCondition;
 use crate::game::{attack_power::DefensePower, chat::ChatMessageVariant};
@@ -12,7 +12,7 @@ use super::{ControllerID, ControllerParametersMap, Priority, Role, RoleStateImpl
 use rand::prelude::SliceRandom;
 
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Scarecrow;
 
@@ -33,7 +33,7 @@ impl RoleStateImpl for Scarecrow {
         let target_ref = visit.target;
 
         let mut blocked_players = target_ref.ward(game);
-        blocked_players.shuffle(&mut rand::rng());
+        blocked_players.shuffle(&mut game.rng);
 
         let message = ChatMessageVariant::ScarecrowResult { players:
             PlayerReference::ref_vec_to_index(blocked_players.as_slice())
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::attack_power::AttackPower;
 use crate::game::{attack_power::DefensePower, grave::GraveKiller};
@@ -10,7 +10,7 @@ use crate::game::Game;
 use super::{Priority, Role, RoleStateImpl};
 use crate::game::ability_input::*;
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SerialKiller;
 
 pub(super) const MAXIMUM_COUNT: Option<u8> = None;
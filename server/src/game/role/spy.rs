@@ -11,7 +11,7 @@ use crate::game::Game;
 
 use super::{ControllerID, ControllerParametersMap, Priority, Role, RoleStateImpl};
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Spy;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -45,7 +45,7 @@ impl RoleStateImpl for Spy {
                             .collect()
                     );
                 }
-                mafia_visits.shuffle(&mut rand::rng());
+                mafia_visits.shuffle(&mut game.rng);
                 
                 actor_ref.push_night_message(game, ChatMessageVariant::SpyMafiaVisit { players: mafia_visits });               
             },
@@ -1,4 +1,5 @@
-use serde::Serialize;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::game::attack_power::DefensePower;
 use crate::game::components::confused::Confused;
@@ -14,7 +15,7 @@ use super::{Priority, RoleStateImpl};
 pub(super) const MAXIMUM_COUNT: Option<u8> = None;
 pub(super) const DEFENSE: DefensePower = DefensePower::None;
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct TallyClerk;
 
 
@@ -38,7 +39,7 @@ impl RoleStateImpl for TallyClerk {
         if Confused::is_confused(game, actor_ref){
             let total_guilties = VerdictsToday::guilties(game).len();
             //add or subtract 1 randomly from the count
-            if rand::random::<bool>(){
+            if game.rng.random::<bool>(){
                 evil_count = (evil_count.saturating_add(1u8)).min(total_guilties.try_into().unwrap_or(u8::MAX));
             }else{
                 evil_count = evil_count.saturating_sub(1u8);
@@ -1,5 +1,5 @@
 use rand::prelude::SliceRandom;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::{attack_power::DefensePower, chat::ChatMessageVariant};
 use crate::game::player::PlayerReference;
@@ -9,7 +9,7 @@ use crate::game::Game;
 
 use super::{ControllerID, ControllerParametersMap, Priority, Role, RoleStateImpl};
 
-#[derive(Clone, Serialize, Debug, Default)]
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
 pub struct Tracker;
 
 
@@ -26,7 +26,7 @@ impl RoleStateImpl for Tracker {
         if let Some(visit) = actor_visits.first(){
             
             let mut seen_players: Vec<PlayerReference> = visit.target.tracker_seen_visits(game).into_iter().map(|v|v.target).collect();
-            seen_players.shuffle(&mut rand::rng());
+            seen_players.shuffle(&mut game.rng);
 
             let message = ChatMessageVariant::TrackerResult { players:
                 PlayerReference::ref_vec_to_index(seen_players.as_slice())
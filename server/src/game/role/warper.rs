@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::components::detained::Detained;
 use crate::game::grave::Grave;
@@ -12,7 +12,7 @@ use crate::vec_set;
 
 use super::{common_role, AbilitySelection, AvailableAbilitySelection, ControllerID, ControllerParametersMap, Priority, Role, RoleStateImpl};
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Warper;
 
 pub(super) const MAXIMUM_COUNT: Option<u8> = None;
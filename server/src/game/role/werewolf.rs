@@ -1,6 +1,6 @@
 use rand::seq::SliceRandom;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::ability_input::{AbilitySelection, AvailableAbilitySelection};
 use crate::game::attack_power::{AttackPower, DefensePower};
@@ -23,7 +23,7 @@ pub struct Werewolf{
     pub tracked_players: VecSet<PlayerReference>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ClientRoleState;
 
 
@@ -112,7 +112,7 @@ impl RoleStateImpl for Werewolf {
                     .for_each(|player_ref|{
 
                     let mut players: Vec<PlayerIndex> = player_ref.tracker_seen_visits(game).into_iter().map(|p|p.target.index()).collect();
-                    players.shuffle(&mut rand::rng());
+                    players.shuffle(&mut game.rng);
 
                     actor_ref.push_night_message(game, 
                         ChatMessageVariant::WerewolfTrackingResult{
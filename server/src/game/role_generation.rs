@@ -0,0 +1,520 @@
+//! Picks a role for every outline, honoring weights and [`RoleConstraint`]s.
+//!
+//! Outlines are filled in order, each picking among its roles by weight. A role is only kept if
+//! the outlines after it can still be filled, so generation never paints itself into a corner,
+//! and only fails when no role list at all fits the settings.
+//!
+//! Whether they can be filled is worked out as a maximum flow from outlines through roles and `AtMost` constraints.
+//! That's exact unless constraints overlap without one being inside the other, or a `Requires` constraint
+//! could still come into play. Only then is every way of filling them searched, for at most [`MAX_SEARCH_STEPS`].
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use rand::{seq::IndexedRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::vec_set::VecSet;
+
+use super::{
+    role::Role,
+    role_list::{RoleAssignment, RoleOutline, RoleOutlineOptionRoles},
+    settings::Settings
+};
+
+/// A rule about which roles can be in the same game, across every outline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RoleConstraint {
+    /// No more than `count` players have any of `roles` between them
+    #[serde(rename_all = "camelCase")]
+    AtMost { count: u8, roles: Vec<RoleOutlineOptionRoles> },
+    /// If anyone has one of `roles`, someone also has one of `requires`
+    #[serde(rename_all = "camelCase")]
+    Requires { roles: Vec<RoleOutlineOptionRoles>, requires: Vec<RoleOutlineOptionRoles> },
+}
+
+fn expand(roles: &[RoleOutlineOptionRoles]) -> VecSet<Role> {
+    roles.iter().flat_map(RoleOutlineOptionRoles::get_roles).collect()
+}
+
+impl RoleConstraint {
+    /// No role list needs more, and each one makes checking whether a role list can be filled slower
+    pub const MAX_CONSTRAINTS: usize = 32;
+    /// How many roles and role sets each side of a constraint can list
+    pub const MAX_ROLES: usize = 32;
+
+    pub fn within_limits(constraints: &[RoleConstraint]) -> bool {
+        constraints.len() <= Self::MAX_CONSTRAINTS &&
+        constraints.iter().all(|constraint| match constraint {
+            RoleConstraint::AtMost { roles, .. } => roles.len() <= Self::MAX_ROLES,
+            RoleConstraint::Requires { roles, requires } => roles.len() <= Self::MAX_ROLES && requires.len() <= Self::MAX_ROLES,
+        })
+    }
+
+    /// The roles and role sets on both sides of this constraint
+    pub fn options(&self) -> impl Iterator<Item = &RoleOutlineOptionRoles> {
+        let (roles, requires): (&[RoleOutlineOptionRoles], &[RoleOutlineOptionRoles]) = match self {
+            RoleConstraint::AtMost { roles, .. } => (roles, &[]),
+            RoleConstraint::Requires { roles, requires } => (roles, requires),
+        };
+        roles.iter().chain(requires)
+    }
+
+    /// Every role this constraint limits or requires
+    pub fn mentioned_roles(&self) -> VecSet<Role> {
+        match self {
+            RoleConstraint::AtMost { roles, .. } => expand(roles),
+            RoleConstraint::Requires { roles, requires } => {
+                let mut mentioned = expand(roles);
+                mentioned.extend(expand(requires));
+                mentioned
+            }
+        }
+    }
+}
+
+impl Settings {
+    /// How likely `role` is to be picked over other roles in the same outline
+    pub fn role_weight(&self, role: Role) -> u8 {
+        self.role_weights.get(&role).copied().unwrap_or(1)
+    }
+
+    /// Every role `outline` could generate as, ignoring other outlines
+    pub fn outline_candidate_roles(&self, outline: &RoleOutline) -> VecSet<Role> {
+        candidates(self, outline).into_iter().map(|(assignment, _)| assignment.role).collect()
+    }
+
+    /// Output is the same order as the rolelist.
+    /// Returns `None` only if no combination of roles meets every maximum count and constraint.
+    pub fn create_random_role_assignments<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Vec<RoleAssignment>> {
+        RoleGenerator::new(self).generate(rng)
+    }
+}
+
+/// Each role an outline can be, with how likely it is
+fn candidates(settings: &Settings, outline: &RoleOutline) -> Vec<(RoleAssignment, u32)> {
+    outline.options.iter()
+        .flat_map(|option| option.roles.get_roles().into_iter().map(move |role| (option, role)))
+        .filter(|(_, role)| settings.enabled_roles.contains(role))
+        .map(|(option, role)| (
+            RoleAssignment {
+                role,
+                insider_groups: option.insider_groups.clone(),
+                win_condition: option.win_condition.clone(),
+            },
+            u32::from(option.weight).saturating_mul(u32::from(settings.role_weight(role)))
+        ))
+        .filter(|(_, weight)| *weight > 0)
+        .collect()
+}
+
+/// Most states [`RoleGenerator::can_finish`] searches while generating one role list, when a maximum flow can't answer on its own.
+/// Past this it gives up, and the role list fails to generate rather than holding the server up.
+pub const MAX_SEARCH_STEPS: usize = 10_000;
+
+enum Rule {
+    AtMost { count: usize, roles: VecSet<Role> },
+    Requires { roles: VecSet<Role>, requires: VecSet<Role> },
+}
+
+/// An `AtMost` rule in the flow network. Buckets are each inside another or apart, so every role flows through a single chain of them.
+struct Bucket {
+    count: usize,
+    roles: VecSet<Role>,
+    /// The smallest bucket this one is inside
+    parent: Option<usize>,
+}
+
+/// How many of each role have been picked. Ordered so it can be remembered in [`RoleGenerator::can_finish`]
+type Picked = BTreeMap<Role, usize>;
+
+/// Generates role lists for one set of settings.
+/// Keep it around to generate many, since working out each outline's roles is most of the work.
+pub struct RoleGenerator {
+    outlines: Vec<Vec<(RoleAssignment, u32)>>,
+    /// The distinct roles of each outline
+    outline_roles: Vec<Vec<Role>>,
+    /// A role no rule or maximum count limits, for each outline that has one.
+    /// These outlines can always be filled last, whatever else was picked.
+    free_roles: Vec<Option<Role>>,
+    rules: Vec<Rule>,
+    buckets: Vec<Bucket>,
+    /// Every `AtMost` rule is a bucket, so the flow only leaves out requirements
+    every_rule_is_a_bucket: bool,
+    /// States already known to have no way to finish, during the current [`RoleGenerator::generate`]
+    dead_ends: HashSet<(Vec<usize>, Picked)>,
+    search_steps_left: usize,
+}
+
+impl RoleGenerator {
+    pub fn new(settings: &Settings) -> Self {
+        let rules: Vec<Rule> = settings.role_constraints.iter()
+            .map(|constraint| match constraint {
+                RoleConstraint::AtMost { count, roles } => Rule::AtMost { count: usize::from(*count), roles: expand(roles) },
+                RoleConstraint::Requires { roles, requires } => Rule::Requires { roles: expand(roles), requires: expand(requires) },
+            })
+            .collect();
+        let constrained: VecSet<Role> = settings.role_constraints.iter()
+            .flat_map(RoleConstraint::mentioned_roles)
+            .collect();
+
+        let outlines: Vec<_> = settings.role_list.0.iter().map(|outline| candidates(settings, outline)).collect();
+        let outline_roles: Vec<Vec<Role>> = outlines.iter()
+            .map(|candidates| {
+                let mut roles: Vec<Role> = candidates.iter().map(|(assignment, _)| assignment.role).collect();
+                roles.sort();
+                roles.dedup();
+                roles
+            })
+            .collect();
+        let free_roles = outline_roles.iter()
+            .map(|roles| roles.iter()
+                .find(|role| role.maximum_count().is_none() && !constrained.contains(role))
+                .copied()
+            )
+            .collect();
+
+        let (buckets, every_rule_is_a_bucket) = buckets(&rules);
+
+        Self {
+            outlines, outline_roles, free_roles, rules, buckets, every_rule_is_a_bucket,
+            dead_ends: HashSet::new(),
+            search_steps_left: MAX_SEARCH_STEPS
+        }
+    }
+
+    /// How many search steps the last [`RoleGenerator::generate`] took, at most [`MAX_SEARCH_STEPS`]
+    pub fn search_steps(&self) -> usize {
+        MAX_SEARCH_STEPS.saturating_sub(self.search_steps_left)
+    }
+
+    /// See [`Settings::create_random_role_assignments`]
+    pub fn generate<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<Vec<RoleAssignment>> {
+        self.search_steps_left = MAX_SEARCH_STEPS;
+        // Only kept for one role list, otherwise retrying a role list would keep growing it
+        self.dead_ends.clear();
+        let mut picked = Picked::new();
+        let mut remaining: Vec<usize> = (0..self.outlines.len()).collect();
+        if !self.can_finish(&mut picked, &mut remaining) {
+            return None;
+        }
+
+        let mut assignments = Vec::with_capacity(self.outlines.len());
+        for outline in 0..self.outlines.len() {
+            remaining.retain(|other| *other != outline);
+
+            let mut options: Vec<(RoleAssignment, u32)> = self.outlines.get(outline)?.iter()
+                .filter(|(assignment, _)| self.can_add(&picked, assignment.role))
+                .cloned()
+                .collect();
+
+            // Some option always works, since the outlines before this one were only picked if it would
+            let assignment = loop {
+                let (assignment, _) = options.choose_weighted(rng, |(_, weight)| *weight).ok()?.clone();
+
+                add(&mut picked, assignment.role);
+                if self.can_finish(&mut picked, &mut remaining) {
+                    break assignment;
+                }
+                remove(&mut picked, assignment.role);
+                options.retain(|(option, _)| option.role != assignment.role);
+            };
+            assignments.push(assignment);
+        }
+        Some(assignments)
+    }
+
+    fn can_add(&self, picked: &Picked, role: Role) -> bool {
+        let count = |role: &Role| picked.get(role).copied().unwrap_or(0);
+
+        role.maximum_count().is_none_or(|max| count(&role) < usize::from(max)) &&
+        self.rules.iter().all(|rule| match rule {
+            Rule::AtMost { count: max, roles } => !roles.contains(&role) || roles.iter().map(count).sum::<usize>() < *max,
+            Rule::Requires { .. } => true,
+        })
+    }
+
+    /// The roles someone still has to be, because of roles already picked
+    fn unmet_requirements<'a>(&'a self, picked: &'a Picked) -> impl Iterator<Item = &'a VecSet<Role>> {
+        self.rules.iter().filter_map(move |rule| match rule {
+            Rule::Requires { roles, requires } if
+                roles.iter().any(|role| picked.contains_key(role)) &&
+                !requires.iter().any(|role| picked.contains_key(role))
+                => Some(requires),
+            _ => None,
+        })
+    }
+
+    /// Whether a requirement could still stop the `remaining` outlines from being filled:
+    /// it isn't met yet, and one of the roles that need it has been picked or could be
+    fn has_open_requirements(&self, picked: &Picked, remaining: &[usize]) -> bool {
+        self.rules.iter().any(|rule| match rule {
+            Rule::Requires { roles, requires } =>
+                !requires.iter().any(|role| picked.contains_key(role)) &&
+                roles.iter().any(|role|
+                    picked.contains_key(role) ||
+                    remaining.iter().any(|outline| self.outline_roles.get(*outline).is_some_and(|roles| roles.contains(role)))
+                ),
+            Rule::AtMost { .. } => false,
+        })
+    }
+
+    /// Whether the `remaining` outlines can be filled after what's been `picked`.
+    /// Both are put back how they were before returning.
+    /// Gives up once the search has taken [`MAX_SEARCH_STEPS`], as though they can't.
+    fn can_finish(&mut self, picked: &mut Picked, remaining: &mut Vec<usize>) -> bool {
+        self.search(picked, remaining).unwrap_or(false)
+    }
+
+    /// `None` if it gave up
+    fn search(&mut self, picked: &mut Picked, remaining: &mut Vec<usize>) -> Option<bool> {
+        let unmet: Vec<VecSet<Role>> = self.unmet_requirements(picked).cloned().collect();
+        let is_free = |outline: &usize| self.free_roles.get(*outline).is_some_and(Option::is_some);
+
+        if unmet.is_empty() && remaining.iter().all(is_free) {
+            return Some(true);
+        }
+        if !self.fits_counts(picked, remaining) {
+            return Some(false);
+        }
+        if self.every_rule_is_a_bucket && !self.has_open_requirements(picked, remaining) {
+            return Some(true);
+        }
+        self.search_steps_left = self.search_steps_left.checked_sub(1)?;
+        let addable = |outline: &usize| -> Vec<Role> {
+            self.outline_roles.get(*outline).into_iter().flatten()
+                .filter(|role| self.can_add(picked, **role))
+                .copied()
+                .collect()
+        };
+        if !unmet.iter().all(|requires| remaining.iter().any(|outline| addable(outline).iter().any(|role| requires.contains(role)))) {
+            return Some(false);
+        }
+
+        let mut key = (remaining.clone(), picked.clone());
+        key.0.sort_unstable();
+        if self.dead_ends.contains(&key) {
+            return Some(false);
+        }
+
+        // Fill the most limited outline first, so dead ends show up early.
+        // Outlines with a free role only need filling to meet a requirement,
+        // and then only with a required role, or the free role to leave it to another outline.
+        let choice = remaining.iter()
+            .enumerate()
+            .filter(|(_, outline)| !is_free(outline))
+            .map(|(index, outline)| (index, addable(outline)))
+            .min_by_key(|(_, roles)| roles.len())
+            .or_else(|| remaining.iter().enumerate().find_map(|(index, outline)| {
+                let mut roles: Vec<Role> = addable(outline).into_iter()
+                    .filter(|role| unmet.iter().any(|requires| requires.contains(role)))
+                    .collect();
+                if roles.is_empty() {return None}
+                roles.extend(self.free_roles.get(*outline).copied().flatten());
+                Some((index, roles))
+            }));
+        let Some((index, roles)) = choice else {return Some(true)};
+
+        let outline = remaining.swap_remove(index);
+        let mut finished = Some(false);
+        let mut tried = Vec::new();
+        for role in roles {
+            let signature = self.signature(picked, remaining, role);
+            if tried.contains(&signature) {continue}
+            tried.push(signature);
+
+            add(picked, role);
+            finished = self.search(picked, remaining);
+            remove(picked, role);
+            if finished != Some(false) {break}
+        }
+        remaining.push(outline);
+        let last = remaining.len().saturating_sub(1);
+        remaining.swap(index, last);
+
+        if finished == Some(false) {
+            self.dead_ends.insert(key);
+        }
+        finished
+    }
+
+    /// Roles with the same signature can stand in for each other, so once one has failed the rest would too:
+    /// what each rule says about the role, how many more of it are allowed, and which `remaining` outlines could be it
+    fn signature(&self, picked: &Picked, remaining: &[usize], role: Role) -> (Vec<(bool, bool)>, Option<usize>, Vec<bool>) {
+        let rules = self.rules.iter()
+            .map(|rule| match rule {
+                Rule::AtMost { roles, .. } => (roles.contains(&role), false),
+                Rule::Requires { roles, requires } => (roles.contains(&role), requires.contains(&role)),
+            })
+            .collect();
+        let allowed = role.maximum_count().map(|max| usize::from(max).saturating_sub(picked.get(&role).copied().unwrap_or(0)));
+        let outlines = remaining.iter()
+            .map(|outline| self.outline_roles.get(*outline).is_some_and(|roles| roles.contains(&role)))
+            .collect();
+        (rules, allowed, outlines)
+    }
+
+    /// Whether the `remaining` outlines without a free role fit within every maximum count and bucket,
+    /// going by the most outlines that can flow from their roles through the buckets.
+    /// Requirements are left out, so this can only be sure they don't fit.
+    fn fits_counts(&self, picked: &Picked, remaining: &[usize]) -> bool {
+        let count = |role: &Role| picked.get(role).copied().unwrap_or(0);
+
+        // Outlines with the same roles are one node, so big role lists don't make big networks
+        let mut groups: HashMap<Vec<Role>, usize> = HashMap::new();
+        for outline in remaining {
+            if self.free_roles.get(*outline).is_some_and(Option::is_some) {continue}
+            let roles: Vec<Role> = self.outline_roles.get(*outline).into_iter().flatten()
+                .filter(|role| self.can_add(picked, **role))
+                .copied()
+                .collect();
+            if roles.is_empty() {return false}
+            let size = groups.entry(roles).or_insert(0);
+            *size = size.saturating_add(1);
+        }
+        let needed: usize = groups.values().sum();
+        if needed == 0 {return true}
+
+        let roles: Vec<Role> = groups.keys().flatten().copied().collect::<VecSet<Role>>().into_iter().collect();
+        let group_node = |group: usize| group.saturating_add(2);
+        let role_node = |role: &Role| roles.iter().position(|other| other == role).map(|index| index.saturating_add(groups.len()).saturating_add(2));
+        let bucket_node = |bucket: usize| bucket.saturating_add(roles.len()).saturating_add(groups.len()).saturating_add(2);
+        let (source, sink) = (0, 1);
+
+        let mut flow = Flow::new(bucket_node(self.buckets.len()));
+        for (group, (group_roles, size)) in groups.iter().enumerate() {
+            flow.add_edge(source, group_node(group), *size);
+            for role in group_roles.iter().filter_map(role_node) {
+                flow.add_edge(group_node(group), role, *size);
+            }
+        }
+        for role in &roles {
+            let Some(node) = role_node(role) else {continue};
+            let capacity = role.maximum_count().map_or(needed, |max| usize::from(max).saturating_sub(count(role)));
+            // The smallest bucket a role is in is the start of its chain
+            let bucket = self.buckets.iter().enumerate()
+                .filter(|(_, bucket)| bucket.roles.contains(role))
+                .min_by_key(|(_, bucket)| bucket.roles.len())
+                .map(|(index, _)| bucket_node(index));
+            flow.add_edge(node, bucket.unwrap_or(sink), capacity);
+        }
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            let capacity = bucket.count.saturating_sub(bucket.roles.iter().map(count).sum());
+            flow.add_edge(bucket_node(index), bucket.parent.map_or(sink, bucket_node), capacity);
+        }
+
+        flow.max_flow(source, sink, needed) >= needed
+    }
+}
+
+/// The `AtMost` rules that can be buckets, smallest first, and whether that's all of them.
+/// A rule that partly overlaps a smaller one is left out, which only lets more role lists through.
+fn buckets(rules: &[Rule]) -> (Vec<Bucket>, bool) {
+    let mut at_most: Vec<(usize, &VecSet<Role>)> = rules.iter()
+        .filter_map(|rule| match rule {
+            Rule::AtMost { count, roles } => Some((*count, roles)),
+            Rule::Requires { .. } => None,
+        })
+        .collect();
+    at_most.sort_by_key(|(_, roles)| roles.len());
+
+    let mut buckets: Vec<Bucket> = Vec::new();
+    let mut every_rule_is_a_bucket = true;
+    for (count, roles) in at_most {
+        let nests = buckets.iter().all(|bucket|
+            bucket.roles.iter().all(|role| roles.contains(role)) || !bucket.roles.iter().any(|role| roles.contains(role))
+        );
+        if !nests {
+            every_rule_is_a_bucket = false;
+            continue;
+        }
+        let index = buckets.len();
+        for bucket in buckets.iter_mut() {
+            if bucket.parent.is_none() && bucket.roles.iter().all(|role| roles.contains(role)) {
+                bucket.parent = Some(index);
+            }
+        }
+        buckets.push(Bucket { count, roles: roles.clone(), parent: None });
+    }
+    (buckets, every_rule_is_a_bucket)
+}
+
+/// A flow network, with each edge next to its reverse
+struct Flow {
+    /// Where each edge goes, and how much more it can take
+    edges: Vec<(usize, usize)>,
+    /// The edges out of each node
+    adjacent: Vec<Vec<usize>>,
+}
+
+impl Flow {
+    fn new(nodes: usize) -> Self {
+        Self { edges: Vec::new(), adjacent: vec![Vec::new(); nodes] }
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, capacity: usize) {
+        let edge = self.edges.len();
+        self.edges.push((to, capacity));
+        self.edges.push((from, 0));
+        if let Some(out) = self.adjacent.get_mut(from) {
+            out.push(edge);
+        }
+        if let Some(out) = self.adjacent.get_mut(to) {
+            out.push(edge ^ 1);
+        }
+    }
+
+    /// Stops early once `enough` flows
+    fn max_flow(&mut self, source: usize, sink: usize, enough: usize) -> usize {
+        let mut total = 0;
+        while total < enough {
+            // Breadth first search for a path with room left, remembering the edge into each node
+            let mut edge_into: Vec<Option<usize>> = vec![None; self.adjacent.len()];
+            let mut queue = VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                if node == sink {break}
+                for edge in self.adjacent.get(node).into_iter().flatten() {
+                    let Some((to, capacity)) = self.edges.get(*edge).copied() else {continue};
+                    if capacity == 0 || to == source {continue}
+                    if let Some(into @ None) = edge_into.get_mut(to) {
+                        *into = Some(*edge);
+                        queue.push_back(to);
+                    }
+                }
+            }
+
+            let mut path = Vec::new();
+            let mut node = sink;
+            while let Some(edge) = edge_into.get(node).copied().flatten() {
+                path.push(edge);
+                node = self.edges.get(edge ^ 1).map_or(source, |(from, _)| *from);
+            }
+            let Some(bottleneck) = path.iter().filter_map(|edge| self.edges.get(*edge)).map(|(_, capacity)| *capacity).min() else {break};
+
+            for edge in path {
+                if let Some((_, capacity)) = self.edges.get_mut(edge) {
+                    *capacity = capacity.saturating_sub(bottleneck);
+                }
+                if let Some((_, capacity)) = self.edges.get_mut(edge ^ 1) {
+                    *capacity = capacity.saturating_add(bottleneck);
+                }
+            }
+            total = total.saturating_add(bottleneck);
+        }
+        total
+    }
+}
+
+fn add(picked: &mut Picked, role: Role) {
+    let count = picked.entry(role).or_insert(0);
+    *count = count.saturating_add(1);
+}
+
+fn remove(picked: &mut Picked, role: Role) {
+    if let Some(count) = picked.get_mut(&role) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            picked.remove(&role);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use rand::seq::IndexedRandom;
+use rand::{seq::IndexedRandom, Rng};
 use serde::{Deserialize, Serialize};
 use vec1::{
     vec1,
@@ -12,20 +12,6 @@ use super::{components::insider_group::InsiderGroupID, game_conclusion::GameConc
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RoleList(pub Vec<RoleOutline>);
 impl RoleList {
-    /// Output is the same order as the rolelist
-    pub fn create_random_role_assignments(&self, enabled_roles: &VecSet<Role>) -> Option<Vec<RoleAssignment>> {
-        let mut generated_data = Vec::<RoleAssignment>::new();
-        for entry in self.0.iter(){
-            if let Some(player_initialization_data) = entry.get_random_role_assignments(
-                enabled_roles, &generated_data.iter().map(|datum| datum.role).collect::<Vec<Role>>()
-            ){
-                generated_data.push(player_initialization_data);
-            }else{
-                return None;
-            }
-        }
-        Some(generated_data)
-    }
     pub fn simplify(&mut self){
         for entry in self.0.iter_mut(){
             entry.simplify();
@@ -71,7 +57,8 @@ impl Default for RoleOutline {
         Self {options: vec1![RoleOutlineOption{
             win_condition: Default::default(),
             insider_groups: Default::default(),
-            roles: RoleOutlineOptionRoles::RoleSet { role_set: RoleSet::Any }
+            roles: RoleOutlineOptionRoles::RoleSet { role_set: RoleSet::Any },
+            weight: RoleOutlineOption::DEFAULT_WEIGHT
         }]}
     }
 }
@@ -80,9 +67,11 @@ impl RoleOutline{
         RoleOutline{options: vec1![RoleOutlineOption{
             win_condition: Default::default(),
             insider_groups: Default::default(),
-            roles: RoleOutlineOptionRoles::Role{role}
+            roles: RoleOutlineOptionRoles::Role{role},
+            weight: RoleOutlineOption::DEFAULT_WEIGHT
         }]}
     }
+    /// A role appears once for every option it's in
     pub fn get_role_assignments(&self) -> Vec<RoleAssignment> {
         self.options.iter()
             .flat_map(|r| 
@@ -94,19 +83,26 @@ impl RoleOutline{
                     })
             ).collect()
     }
-    pub fn get_random_role_assignments(&self, enabled_roles: &VecSet<Role>, taken_roles: &[Role]) -> Option<RoleAssignment> {
-        let options = self.get_role_assignments()
-            .into_iter()
-            .filter(|r|role_can_generate(r.role, enabled_roles, taken_roles))
+    /// Picks by option weight, but ignores role weights and constraints, which only apply when the game starts
+    pub fn get_random_role_assignments<R: Rng + ?Sized>(&self, enabled_roles: &VecSet<Role>, taken_roles: &[Role], rng: &mut R) -> Option<RoleAssignment> {
+        let options = self.options.iter()
+            .flat_map(|option| option.roles.get_roles().into_iter().map(move |role| (option, role)))
+            .filter(|(_, role)| role_can_generate(*role, enabled_roles, taken_roles))
             .collect::<Vec<_>>();
-        options.choose(&mut rand::rng()).cloned()
+        options.choose_weighted(rng, |(option, _)| option.weight).ok()
+            .map(|(option, role)| RoleAssignment{
+                role: *role,
+                insider_groups: option.insider_groups.clone(),
+                win_condition: option.win_condition.clone()
+            })
     }
     pub fn simplify(&mut self){
         let mut new_options = self.options.to_vec();
 
         new_options = new_options.into_iter().collect::<VecSet<_>>().into_iter().collect();
 
-        for option_a in self.options.iter(){
+        // Options with their own weight change the odds on purpose, so they're kept
+        for option_a in self.options.iter().filter(|option| option.weight == RoleOutlineOption::DEFAULT_WEIGHT){
             for option_b in self.options.iter(){
                 if option_a.roles.is_subset(&option_b.roles) && option_a != option_b {
                     new_options.retain(|r| r != option_a);
@@ -152,7 +148,7 @@ impl RoleOutlineOptionInsiderGroups {
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, PartialOrd, Ord)]
 #[serde(rename_all = "camelCase")]
 pub struct RoleOutlineOption {
     #[serde(flatten)]
@@ -161,6 +157,26 @@ pub struct RoleOutlineOption {
     pub win_condition: RoleOutlineOptionWinCondition,
     #[serde(flatten, skip_serializing_if = "RoleOutlineOptionInsiderGroups::is_default")]
     pub insider_groups: RoleOutlineOptionInsiderGroups,
+    /// Every role this option can be is this many times as likely to be picked. Zero means never.
+    #[serde(skip_serializing_if = "RoleOutlineOption::is_default_weight")]
+    pub weight: u8,
+}
+impl RoleOutlineOption {
+    pub const DEFAULT_WEIGHT: u8 = 1;
+
+    pub fn is_default_weight(weight: &u8) -> bool {
+        *weight == Self::DEFAULT_WEIGHT
+    }
+}
+impl Default for RoleOutlineOption {
+    fn default() -> Self {
+        Self {
+            roles: Default::default(),
+            win_condition: Default::default(),
+            insider_groups: Default::default(),
+            weight: Self::DEFAULT_WEIGHT,
+        }
+    }
 }
 
 /// Watch this!
@@ -200,6 +216,13 @@ impl<'de> Deserialize<'de> for RoleOutlineOption {
                         option.roles = RoleOutlineOptionRoles::Role { role }
                     }
                 }
+            } else if let Some(value) = map.get("customRoleSet") {
+                if let Ok(custom_role_set) = CustomRoleSet::deserialize(value) {
+                    option.roles = RoleOutlineOptionRoles::CustomRoleSet { custom_role_set }
+                }
+            }
+            if let Some(weight) = map.get("weight").and_then(serde_json::Value::as_u64) {
+                option.weight = u8::try_from(weight).unwrap_or(u8::MAX);
             }
         }
 
@@ -215,6 +238,8 @@ pub enum RoleOutlineOptionRoles {
     RoleSet{role_set: RoleSet},
     #[serde(rename_all = "camelCase")]
     Role{role: Role},
+    #[serde(rename_all = "camelCase")]
+    CustomRoleSet{custom_role_set: CustomRoleSet},
 }
 impl Default for RoleOutlineOptionRoles {
     fn default() -> Self {
@@ -228,7 +253,9 @@ impl RoleOutlineOptionRoles{
                 role_set.get_roles()
             }
             RoleOutlineOptionRoles::Role { role } => 
-                vec![*role]
+                vec![*role],
+            RoleOutlineOptionRoles::CustomRoleSet { custom_role_set } =>
+                custom_role_set.roles.clone()
         }
     }
     pub fn is_subset(&self, other: &RoleOutlineOptionRoles) -> bool {
@@ -247,6 +274,27 @@ impl Ord for RoleOutlineOptionRoles {
 }
 
 
+/// A role set the host named and filled in themselves.
+/// Outlines keep a copy of the roles, so they mean the same thing wherever they're sent.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomRoleSet {
+    pub name: String,
+    pub roles: Vec<Role>,
+}
+impl CustomRoleSet {
+    pub const MAX_NAME_LENGTH: usize = 40;
+
+    /// Roles are sorted and deduplicated, so two sets with the same roles compare equal
+    pub fn new(name: String, roles: impl IntoIterator<Item = Role>) -> Self {
+        let mut roles: Vec<Role> = roles.into_iter().collect();
+        roles.sort();
+        roles.dedup();
+        Self { name: name.trim().to_string(), roles }
+    }
+}
+
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
 #[serde(rename_all = "camelCase")]
 pub enum RoleSet {
@@ -270,6 +318,16 @@ pub enum RoleSet {
     Minions
 }
 impl RoleSet{
+    pub fn values() -> Vec<RoleSet> {
+        vec![
+            RoleSet::Any,
+            RoleSet::Town, RoleSet::TownCommon, RoleSet::TownInvestigative,
+            RoleSet::TownProtective, RoleSet::TownKilling, RoleSet::TownSupport,
+            RoleSet::Mafia, RoleSet::MafiaSupport, RoleSet::MafiaKilling,
+            RoleSet::Cult, RoleSet::Fiends,
+            RoleSet::Neutral, RoleSet::Minions
+        ]
+    }
     pub fn get_roles(&self) -> Vec<Role> {
         match self {
             RoleSet::Any => Role::values(),
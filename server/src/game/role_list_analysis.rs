@@ -0,0 +1,197 @@
+//! Checks a role list before a game starts, so the host can see why a game won't start,
+//! or how often it would be over before anyone plays.
+//!
+//! Without this, the host only learns a role list can't work when starting the game fails.
+
+use std::collections::{HashMap, VecDeque};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::vec_set::VecSet;
+
+use super::{
+    components::insider_group::InsiderGroupID,
+    game_conclusion::{GameConclusion, LivingPlayer},
+    role::Role,
+    role_generation::RoleGenerator,
+    role_list::{RoleAssignment, RoleOutlineOptionInsiderGroups, RoleOutlineOptionWinCondition},
+    settings::Settings,
+    win_condition::WinCondition
+};
+
+/// How many role lists are generated to estimate how often generation fails or the game starts over.
+/// They're generated from a fixed seed, so the same settings always get the same estimate.
+pub const SAMPLES: u32 = 200;
+const SAMPLE_SEED: u64 = 0;
+
+/// Outline indices match the role list
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleListAnalysis {
+    /// Outlines without a single enabled role to pick
+    pub unsatisfiable_outlines: Vec<u8>,
+    pub max_count_conflicts: Vec<MaxCountConflict>,
+    /// Zero if some outline can never be filled, since there'd be nothing to estimate
+    pub samples: u32,
+    /// Samples where no role list met every constraint.
+    /// Generation only fails when nothing fits, so this is either none or all of them.
+    pub failed_generations: u32,
+    /// Samples where the game was over before it started, like when nobody can kill
+    pub instant_game_overs: u32,
+}
+
+/// Outlines that can only be filled with `roles`, which allow fewer copies between them than there are outlines
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxCountConflict {
+    pub outlines: Vec<u8>,
+    pub roles: Vec<Role>,
+}
+
+impl RoleListAnalysis {
+    pub fn new(settings: &Settings) -> Self {
+        let outline_roles: Vec<Vec<Role>> = settings.role_list.0.iter()
+            .map(|outline| settings.outline_candidate_roles(outline).into_iter().collect())
+            .collect();
+
+        let mut analysis = Self {
+            unsatisfiable_outlines: outline_roles.iter()
+                .enumerate()
+                .filter(|(_, roles)| roles.is_empty())
+                .filter_map(|(index, _)| u8::try_from(index).ok())
+                .collect(),
+            max_count_conflicts: max_count_conflicts(&outline_roles),
+            ..Default::default()
+        };
+
+        if analysis.can_generate() {
+            analysis.sample(settings);
+        }
+
+        analysis
+    }
+
+    /// Whether some way of filling the outlines exists, however rare
+    pub fn can_generate(&self) -> bool {
+        self.unsatisfiable_outlines.is_empty() && self.max_count_conflicts.is_empty() && self.failed_generations == 0
+    }
+
+    fn sample(&mut self, settings: &Settings) {
+        let mut rng = StdRng::seed_from_u64(SAMPLE_SEED);
+        let mut generator = RoleGenerator::new(settings);
+
+        self.samples = SAMPLES;
+        for _ in 0..SAMPLES {
+            let Some(assignments) = generator.generate(&mut rng) else {
+                self.failed_generations = SAMPLES;
+                self.instant_game_overs = 0;
+                return;
+            };
+            if starts_over(&assignments) {
+                self.instant_game_overs = self.instant_game_overs.saturating_add(1);
+            }
+        }
+    }
+}
+
+/// Mirrors how [`super::Game::new`] checks the game isn't over before it starts
+fn starts_over(assignments: &[RoleAssignment]) -> bool {
+    let players: Vec<(Role, WinCondition, VecSet<InsiderGroupID>)> = assignments.iter()
+        .map(|assignment| {
+            let win_condition = match &assignment.win_condition {
+                RoleOutlineOptionWinCondition::RoleDefault => assignment.role.default_state().default_win_condition(),
+                RoleOutlineOptionWinCondition::GameConclusionReached { win_if_any } => WinCondition::GameConclusionReached {
+                    win_if_any: win_if_any.iter().cloned().collect()
+                },
+            };
+            let insider_groups = match &assignment.insider_groups {
+                RoleOutlineOptionInsiderGroups::RoleDefault => assignment.role.default_state().default_revealed_groups(),
+                RoleOutlineOptionInsiderGroups::Custom { insider_groups } => insider_groups.clone(),
+            };
+            (assignment.role, win_condition, insider_groups)
+        })
+        .collect();
+
+    let living_players: Vec<LivingPlayer> = players.iter()
+        .map(|(role, win_condition, insider_groups)| LivingPlayer {
+            role: *role,
+            win_condition,
+            keeps_game_running: GameConclusion::player_keeps_game_running(*role, win_condition, insider_groups),
+        })
+        .collect();
+
+    GameConclusion::game_is_over_among(&living_players).is_some()
+}
+
+/// Matches outlines to roles, each role taking at most its maximum count.
+/// Whenever an outline can't be matched, the outlines that were competing for its roles are a conflict.
+/// Outlines with no roles at all are left out, since they're reported on their own.
+fn max_count_conflicts(outline_roles: &[Vec<Role>]) -> Vec<MaxCountConflict> {
+    let unlimited = outline_roles.len();
+    let capacity = |role: &Role| role.maximum_count().map_or(unlimited, usize::from);
+
+    let mut assigned: Vec<Option<Role>> = vec![None; outline_roles.len()];
+    let mut used: HashMap<Role, usize> = HashMap::new();
+    let mut conflicts: Vec<(VecSet<usize>, VecSet<Role>)> = Vec::new();
+
+    for start in 0..outline_roles.len() {
+        if outline_roles.get(start).is_none_or(Vec::is_empty) {continue}
+
+        // Breadth first search for a role with room, moving other outlines out of the way
+        let mut visited = VecSet::new();
+        let mut roles_seen = VecSet::new();
+        // Which outline gives up which role, so this one can take it
+        let mut moved_from: HashMap<usize, (usize, Role)> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        visited.insert(start);
+
+        let mut found = None;
+        'search: while let Some(outline) = queue.pop_front() {
+            for role in outline_roles.get(outline).into_iter().flatten() {
+                if used.get(role).copied().unwrap_or(0) < capacity(role) {
+                    found = Some((outline, *role));
+                    break 'search;
+                }
+                roles_seen.insert(*role);
+                for (other, other_role) in assigned.iter().enumerate() {
+                    if *other_role == Some(*role) && visited.insert(other).is_none() {
+                        moved_from.insert(other, (outline, *role));
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+
+        let Some((mut outline, mut role)) = found else {
+            // Every role these outlines could be is already full of them
+            if let Some((outlines, roles)) = conflicts.iter_mut().find(|(outlines, _)| visited.iter().any(|o| outlines.contains(o))) {
+                outlines.extend(visited);
+                roles.extend(roles_seen);
+            } else {
+                conflicts.push((visited, roles_seen));
+            }
+            continue;
+        };
+
+        used.insert(role, used.get(&role).copied().unwrap_or(0).saturating_add(1));
+        loop {
+            if let Some(slot) = assigned.get_mut(outline) {
+                *slot = Some(role);
+            }
+            let Some((previous, previous_role)) = moved_from.get(&outline).copied() else {break};
+            outline = previous;
+            role = previous_role;
+        }
+    }
+
+    conflicts.into_iter()
+        .map(|(outlines, roles)| {
+            let mut outlines: Vec<u8> = outlines.into_iter().filter_map(|outline| u8::try_from(outline).ok()).collect();
+            outlines.sort();
+            let mut roles: Vec<Role> = roles.into_iter().collect();
+            roles.sort();
+            MaxCountConflict { outlines, roles }
+        })
+        .collect()
+}
@@ -0,0 +1,428 @@
+//! A compact text notation for role outlines, meant for people to read and write by hand.
+//!
+//! An outline is its options separated by `|`, e.g. `Town Investigative | Mafia Support`.
+//! Each option is a role or role set, optionally followed by tags that override its defaults:
+//! `Witch [wins: Mafia, Fiends] [insiders: Mafia] [weight: 3]`.
+//! Names ignore case, spaces, dashes and underscores, so `town investigative` and `TownInvestigative` also work.
+//!
+//! A role list is one outline per line. Blank lines and lines starting with `#` are skipped.
+//!
+//! Role constraints are written `At most 1: Doctor, Armorsmith` or `Jester requires Town Investigative`,
+//! with any number of roles or role sets on either side.
+//!
+//! Custom role sets are written by name, and can only be read with the `_with` functions,
+//! which are given the sets to look names up in.
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::Serialize;
+use vec1::Vec1;
+
+use crate::vec_set::VecSet;
+
+use super::{
+    components::insider_group::InsiderGroupID, game_conclusion::GameConclusion, role::Role,
+    role_generation::RoleConstraint,
+    role_list::{CustomRoleSet, RoleList, RoleOutline, RoleOutlineOption, RoleOutlineOptionInsiderGroups, RoleOutlineOptionRoles, RoleOutlineOptionWinCondition, RoleSet}
+};
+
+const WINS_TAG: &str = "wins";
+const INSIDERS_TAG: &str = "insiders";
+const WEIGHT_TAG: &str = "weight";
+const AT_MOST: &str = "at most";
+const REQUIRES: &str = "requires";
+
+/// What's wrong with one option of an outline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionParseError {
+    /// Nothing between two `|`s, or at either end of the outline
+    Empty,
+    UnknownRoles(String),
+    UnknownTag(String),
+    UnknownConclusion(String),
+    UnknownInsiderGroup(String),
+    /// Not a whole number from 0 to 255
+    InvalidWeight(String),
+    /// A `[` without a `]`, or text between tags
+    Malformed(String),
+}
+impl Display for OptionParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionParseError::Empty => write!(f, "no role or role set given"),
+            OptionParseError::UnknownRoles(name) => write!(f, "unknown role or role set \"{name}\""),
+            OptionParseError::UnknownTag(tag) => write!(f, "unknown tag \"{tag}\", expected \"{WINS_TAG}\", \"{INSIDERS_TAG}\" or \"{WEIGHT_TAG}\""),
+            OptionParseError::UnknownConclusion(name) => write!(f, "unknown game conclusion \"{name}\""),
+            OptionParseError::UnknownInsiderGroup(name) => write!(f, "unknown insider group \"{name}\""),
+            OptionParseError::InvalidWeight(weight) => write!(f, "weight \"{weight}\" isn't a whole number from 0 to 255"),
+            OptionParseError::Malformed(text) => write!(f, "can't read \"{text}\""),
+        }
+    }
+}
+
+/// `option` counts from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineParseError {
+    pub option: usize,
+    pub error: OptionParseError,
+}
+impl Display for OutlineParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "option {}: {}", self.option, self.error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintParseError {
+    /// Neither `At most N: ...` nor `... requires ...`
+    Malformed(String),
+    InvalidCount(String),
+    Roles(OptionParseError),
+}
+impl Display for ConstraintParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstraintParseError::Malformed(text) => write!(f, "can't read \"{text}\", expected \"At most 1: Doctor, Armorsmith\" or \"Jester requires Town Investigative\""),
+            ConstraintParseError::InvalidCount(count) => write!(f, "count \"{count}\" isn't a whole number from 0 to 255"),
+            ConstraintParseError::Roles(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// Why a custom role set can't be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CustomRoleSetError {
+    EmptyName,
+    NameTooLong(String),
+    /// The name has a character or word the notation uses, so it couldn't be read back
+    ReservedName(String),
+    /// Another role, role set or custom role set already goes by this name
+    NameTaken(String),
+    EmptyRoles(String),
+}
+impl Display for CustomRoleSetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CustomRoleSetError::EmptyName => write!(f, "a custom role set has no name"),
+            CustomRoleSetError::NameTooLong(name) => write!(f, "custom role set \"{name}\" has a name longer than {} characters", CustomRoleSet::MAX_NAME_LENGTH),
+            CustomRoleSetError::ReservedName(name) => write!(f, "custom role set \"{name}\" can't have |, [, ], :, # or commas in its name, or the word \"{REQUIRES}\""),
+            CustomRoleSetError::NameTaken(name) => write!(f, "\"{name}\" is already the name of a role or role set"),
+            CustomRoleSetError::EmptyRoles(name) => write!(f, "custom role set \"{name}\" has no roles"),
+        }
+    }
+}
+
+/// Checks every set can be written and read back in this notation, without being mistaken for anything else
+pub fn validate_custom_role_sets(custom_role_sets: &[CustomRoleSet]) -> Result<(), CustomRoleSetError> {
+    let mut names: Vec<String> = RoleSet::values().iter().map(display_name)
+        .chain(Role::values().iter().map(display_name))
+        .map(|name| normalize(&name))
+        .collect();
+
+    for set in custom_role_sets {
+        let name = set.name.trim();
+        if name.is_empty() {
+            return Err(CustomRoleSetError::EmptyName);
+        }
+        if name.chars().count() > CustomRoleSet::MAX_NAME_LENGTH {
+            return Err(CustomRoleSetError::NameTooLong(name.to_string()));
+        }
+        if name.contains(['|', '[', ']', ':', '#', ',']) || split_keyword(&format!(" {name} "), REQUIRES).is_some() {
+            return Err(CustomRoleSetError::ReservedName(name.to_string()));
+        }
+        if names.contains(&normalize(name)) {
+            return Err(CustomRoleSetError::NameTaken(name.to_string()));
+        }
+        if set.roles.is_empty() {
+            return Err(CustomRoleSetError::EmptyRoles(name.to_string()));
+        }
+        names.push(normalize(name));
+    }
+    Ok(())
+}
+
+/// Checks the copies of custom role sets that outlines and constraints carry, since they're sent by the client too.
+/// Each is checked on its own, because the same set can be used more than once.
+pub fn validate_embedded_custom_role_sets<'a>(roles: impl IntoIterator<Item = &'a RoleOutlineOptionRoles>) -> Result<(), CustomRoleSetError> {
+    for roles in roles {
+        if let RoleOutlineOptionRoles::CustomRoleSet { custom_role_set } = roles {
+            validate_custom_role_sets(std::slice::from_ref(custom_role_set))?;
+        }
+    }
+    Ok(())
+}
+
+/// `line` counts from 1, including skipped lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleListParseError {
+    pub line: usize,
+    pub error: OutlineParseError,
+}
+impl Display for RoleListParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, {}", self.line, self.error)
+    }
+}
+
+/// `townInvestigative` becomes `Town Investigative`
+fn display_name(value: &impl Serialize) -> String {
+    let Ok(serde_json::Value::String(name)) = serde_json::to_value(value) else {return String::new()};
+
+    let mut display = String::with_capacity(name.len().saturating_add(4));
+    for (i, c) in name.chars().enumerate() {
+        if i == 0 {
+            display.extend(c.to_uppercase());
+        } else {
+            if c.is_uppercase() {
+                display.push(' ');
+            }
+            display.push(c);
+        }
+    }
+    display
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn find_by_name<T: Serialize>(values: impl IntoIterator<Item = T>, name: &str) -> Option<T> {
+    let name = normalize(name);
+    values.into_iter().find(|value| normalize(&display_name(value)) == name)
+}
+
+fn write_list<T: Serialize>(f: &mut std::fmt::Formatter<'_>, tag: &str, values: impl Iterator<Item = T>) -> std::fmt::Result {
+    let names: Vec<String> = values.map(|value| display_name(&value)).collect();
+    write!(f, " [{tag}: {}]", names.join(", "))
+}
+
+fn parse_list<T: Serialize + Eq>(
+    values: impl Fn() -> Vec<T>,
+    list: &str,
+    unknown: fn(String) -> OptionParseError
+) -> Result<VecSet<T>, OptionParseError> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| find_by_name(values(), name).ok_or_else(|| unknown(name.to_string())))
+        .collect()
+}
+
+impl Display for RoleOutlineOptionRoles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoleOutlineOptionRoles::RoleSet { role_set } => write!(f, "{}", display_name(role_set)),
+            RoleOutlineOptionRoles::Role { role } => write!(f, "{}", display_name(role)),
+            RoleOutlineOptionRoles::CustomRoleSet { custom_role_set } => write!(f, "{}", custom_role_set.name),
+        }
+    }
+}
+impl RoleOutlineOptionRoles {
+    /// Role sets are checked first, though no role or valid custom role set shares a name with one
+    pub fn parse_with(s: &str, custom_role_sets: &[CustomRoleSet]) -> Result<Self, OptionParseError> {
+        let name = s.trim();
+        if name.is_empty() {
+            return Err(OptionParseError::Empty);
+        }
+
+        if let Some(role_set) = find_by_name(RoleSet::values(), name) {
+            Ok(RoleOutlineOptionRoles::RoleSet { role_set })
+        } else if let Some(role) = find_by_name(Role::values(), name) {
+            Ok(RoleOutlineOptionRoles::Role { role })
+        } else if let Some(custom_role_set) = custom_role_sets.iter().find(|set| normalize(&set.name) == normalize(name)) {
+            Ok(RoleOutlineOptionRoles::CustomRoleSet { custom_role_set: custom_role_set.clone() })
+        } else {
+            Err(OptionParseError::UnknownRoles(name.to_string()))
+        }
+    }
+}
+impl FromStr for RoleOutlineOptionRoles {
+    type Err = OptionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, &[])
+    }
+}
+
+impl Display for RoleOutlineOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.roles)?;
+        if let RoleOutlineOptionWinCondition::GameConclusionReached { win_if_any } = &self.win_condition {
+            write_list(f, WINS_TAG, win_if_any.iter())?;
+        }
+        if let RoleOutlineOptionInsiderGroups::Custom { insider_groups } = &self.insider_groups {
+            write_list(f, INSIDERS_TAG, insider_groups.iter())?;
+        }
+        if !RoleOutlineOption::is_default_weight(&self.weight) {
+            write!(f, " [{WEIGHT_TAG}: {}]", self.weight)?;
+        }
+        Ok(())
+    }
+}
+impl RoleOutlineOption {
+    pub fn parse_with(s: &str, custom_role_sets: &[CustomRoleSet]) -> Result<Self, OptionParseError> {
+        let (roles, mut tags) = s.split_once('[')
+            .map(|(roles, tags)| (roles, Some(tags)))
+            .unwrap_or((s, None));
+
+        let mut option = RoleOutlineOption {
+            roles: RoleOutlineOptionRoles::parse_with(roles, custom_role_sets)?,
+            ..Default::default()
+        };
+
+        while let Some(rest) = tags {
+            let Some((tag, rest)) = rest.split_once(']') else {
+                return Err(OptionParseError::Malformed(format!("[{rest}")));
+            };
+            let Some((key, list)) = tag.split_once(':') else {
+                return Err(OptionParseError::Malformed(format!("[{tag}]")));
+            };
+
+            match normalize(key).as_str() {
+                WINS_TAG => option.win_condition = RoleOutlineOptionWinCondition::GameConclusionReached {
+                    win_if_any: parse_list(GameConclusion::all, list, OptionParseError::UnknownConclusion)?
+                },
+                INSIDERS_TAG => option.insider_groups = RoleOutlineOptionInsiderGroups::Custom {
+                    insider_groups: parse_list(|| InsiderGroupID::all().into_iter().collect(), list, OptionParseError::UnknownInsiderGroup)?
+                },
+                WEIGHT_TAG => option.weight = list.trim().parse()
+                    .map_err(|_| OptionParseError::InvalidWeight(list.trim().to_string()))?,
+                _ => return Err(OptionParseError::UnknownTag(key.trim().to_string())),
+            }
+
+            let rest = rest.trim_start();
+            tags = if rest.is_empty() {
+                None
+            } else if let Some(rest) = rest.strip_prefix('[') {
+                Some(rest)
+            } else {
+                return Err(OptionParseError::Malformed(rest.to_string()));
+            };
+        }
+
+        Ok(option)
+    }
+}
+impl FromStr for RoleOutlineOption {
+    type Err = OptionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, &[])
+    }
+}
+
+impl Display for RoleOutline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, option) in self.options.iter().enumerate() {
+            if i != 0 {
+                write!(f, " | ")?;
+            }
+            write!(f, "{option}")?;
+        }
+        Ok(())
+    }
+}
+impl RoleOutline {
+    pub fn parse_with(s: &str, custom_role_sets: &[CustomRoleSet]) -> Result<Self, OutlineParseError> {
+        let options = s.split('|')
+            .enumerate()
+            .map(|(i, option)| RoleOutlineOption::parse_with(option, custom_role_sets)
+                .map_err(|error| OutlineParseError { option: i.saturating_add(1), error }))
+            .collect::<Result<Vec<RoleOutlineOption>, _>>()?;
+
+        Vec1::try_from_vec(options)
+            .map(|options| RoleOutline { options })
+            .map_err(|_| OutlineParseError { option: 1, error: OptionParseError::Empty })
+    }
+}
+impl FromStr for RoleOutline {
+    type Err = OutlineParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, &[])
+    }
+}
+
+fn write_roles(f: &mut std::fmt::Formatter<'_>, roles: &[RoleOutlineOptionRoles]) -> std::fmt::Result {
+    let names: Vec<String> = roles.iter().map(ToString::to_string).collect();
+    write!(f, "{}", names.join(", "))
+}
+
+fn parse_roles(list: &str, custom_role_sets: &[CustomRoleSet]) -> Result<Vec<RoleOutlineOptionRoles>, ConstraintParseError> {
+    list.split(',')
+        .map(|name| RoleOutlineOptionRoles::parse_with(name, custom_role_sets).map_err(ConstraintParseError::Roles))
+        .collect()
+}
+
+/// Splits around the first `keyword` between words, ignoring case
+fn split_keyword<'a>(text: &'a str, keyword: &str) -> Option<(&'a str, &'a str)> {
+    let index = text.to_ascii_lowercase().find(&format!(" {keyword} "))?;
+    Some((text.get(..index)?, text.get(index.saturating_add(keyword.len()).saturating_add(2)..)?))
+}
+
+impl Display for RoleConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoleConstraint::AtMost { count, roles } => {
+                write!(f, "At most {count}: ")?;
+                write_roles(f, roles)
+            }
+            RoleConstraint::Requires { roles, requires } => {
+                write_roles(f, roles)?;
+                write!(f, " {REQUIRES} ")?;
+                write_roles(f, requires)
+            }
+        }
+    }
+}
+impl RoleConstraint {
+    pub fn parse_with(s: &str, custom_role_sets: &[CustomRoleSet]) -> Result<Self, ConstraintParseError> {
+        let text = s.trim();
+
+        if let Some((head, roles)) = text.split_once(':') {
+            let Some(count) = head.trim().to_ascii_lowercase().strip_prefix(AT_MOST).map(|count| count.trim().to_string()) else {
+                return Err(ConstraintParseError::Malformed(text.to_string()));
+            };
+            Ok(RoleConstraint::AtMost {
+                count: count.parse().map_err(|_| ConstraintParseError::InvalidCount(count))?,
+                roles: parse_roles(roles, custom_role_sets)?,
+            })
+        } else if let Some((roles, requires)) = split_keyword(text, REQUIRES) {
+            Ok(RoleConstraint::Requires {
+                roles: parse_roles(roles, custom_role_sets)?,
+                requires: parse_roles(requires, custom_role_sets)?
+            })
+        } else {
+            Err(ConstraintParseError::Malformed(text.to_string()))
+        }
+    }
+}
+impl FromStr for RoleConstraint {
+    type Err = ConstraintParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, &[])
+    }
+}
+
+impl RoleList {
+    /// One outline per line
+    pub fn to_notation(&self) -> String {
+        self.0.iter().map(RoleOutline::to_string).collect::<Vec<_>>().join("\n")
+    }
+    pub fn from_notation(text: &str) -> Result<RoleList, RoleListParseError> {
+        Self::from_notation_with(text, &[])
+    }
+    pub fn from_notation_with(text: &str, custom_role_sets: &[CustomRoleSet]) -> Result<RoleList, RoleListParseError> {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .map(|(i, line)| RoleOutline::parse_with(line, custom_role_sets)
+                .map_err(|error| RoleListParseError { line: i.saturating_add(1), error }))
+            .collect::<Result<Vec<RoleOutline>, _>>()
+            .map(RoleList)
+    }
+}
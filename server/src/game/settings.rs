@@ -2,19 +2,56 @@ use std::time::Duration;
 
 use serde::{Serialize, Deserialize};
 
-use crate::vec_set::VecSet;
+use crate::{vec_map::VecMap, vec_set::VecSet};
 
-use super::{modifiers::ModifierType, phase::PhaseType, role::Role, role_list::RoleList};
+use super::{modifiers::ModifierType, phase::PhaseType, role::Role, role_generation::RoleConstraint, role_list::{CustomRoleSet, RoleList, RoleOutlineOptionRoles}};
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Settings{
     pub role_list: RoleList,
     pub phase_times: PhaseTimeSettings,
     pub enabled_roles: VecSet<Role>,
     pub enabled_modifiers: VecSet<ModifierType>,
+    /// How likely each role is to be picked over others in the same outline. Roles left out have a weight of 1
+    #[serde(default)]
+    pub role_weights: VecMap<Role, u8>,
+    #[serde(default)]
+    pub role_constraints: Vec<RoleConstraint>,
+    /// Role sets the host named, which outlines and constraints can use like the built-in ones
+    #[serde(default)]
+    pub custom_role_sets: Vec<CustomRoleSet>,
+    /// If set, the game is generated and played out from this seed instead of a random one
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Settings {
+    /// Outlines and constraints keep their own copy of each custom role set,
+    /// so this brings them up to date with the sets of the same name.
+    /// Ones whose set is gone keep the roles they had.
+    pub fn refresh_custom_role_sets(&mut self) {
+        let custom_role_sets = &self.custom_role_sets;
+        let refresh = |roles: &mut RoleOutlineOptionRoles| {
+            if let RoleOutlineOptionRoles::CustomRoleSet { custom_role_set } = roles {
+                if let Some(current) = custom_role_sets.iter().find(|set| set.name == custom_role_set.name) {
+                    custom_role_set.clone_from(current);
+                }
+            }
+        };
+
+        for option in self.role_list.0.iter_mut().flat_map(|outline| outline.options.iter_mut()) {
+            refresh(&mut option.roles);
+        }
+        for constraint in self.role_constraints.iter_mut() {
+            match constraint {
+                RoleConstraint::AtMost { roles, .. } => roles.iter_mut().for_each(refresh),
+                RoleConstraint::Requires { roles, requires } => roles.iter_mut().chain(requires.iter_mut()).for_each(refresh),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PhaseTimeSettings{
     pub briefing: u64,
@@ -42,6 +79,21 @@ impl PhaseTimeSettings {
             PhaseType::Recess => Duration::MAX
         }
     }
+    /// Recess has no time to set
+    pub fn set_time_for(&mut self, phase: PhaseType, time: u64) {
+        match phase {
+            PhaseType::Briefing => { self.briefing = time; }
+            PhaseType::Obituary => { self.obituary = time; }
+            PhaseType::Discussion => { self.discussion = time; }
+            PhaseType::FinalWords => { self.final_words = time; }
+            PhaseType::Dusk => { self.dusk = time; }
+            PhaseType::Judgement => { self.judgement = time; }
+            PhaseType::Night => { self.night = time; }
+            PhaseType::Testimony => { self.testimony = time; }
+            PhaseType::Nomination => { self.nomination = time; }
+            PhaseType::Recess => { }
+        }
+    }
     pub fn game_ends_instantly(&self)->bool{
         [self.obituary, self.discussion, self.nomination, self.night, self.dusk].iter().all(|t| *t == 0)
     }
@@ -1,6 +1,9 @@
+use serde::{Deserialize, Serialize};
+
 use super::player::PlayerReference;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Visit {
     pub visitor: PlayerReference,
     pub target: PlayerReference,
@@ -27,7 +30,8 @@ impl Visit {
     }
 }
 
-#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum VisitTag{
     #[default]
     Role,   //The order matters
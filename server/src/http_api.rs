@@ -0,0 +1,176 @@
+//! A read-only JSON API over plain HTTP, so bots and status pages can see open lobbies
+//! without speaking the websocket protocol.
+//!
+//! - `GET /api/status`: [`ServerStatus`]
+//! - `GET /api/lobbies`: every listed lobby, as [`LobbyResource`]s
+//! - `GET /api/lobbies/{room_code}`: one [`LobbyResource`]
+
+use std::{sync::{Arc, Mutex}, time::Duration};
+
+use serde::Serialize;
+use tokio::{io::{AsyncRead, AsyncReadExt, AsyncWriteExt}, net::TcpListener, task::JoinHandle};
+use tracing::{info, warn};
+
+use crate::{
+    listener::{Listener, RoomCode},
+    packet::LobbyPreviewData,
+    websocket_connections::{protocol::ProtocolVersion, ForceLock}
+};
+
+/// Requests are only ever a request line and a few headers, so anything bigger is turned away
+const MAX_REQUEST_HEAD_BYTES: usize = 8 * 1024;
+/// Clients that don't finish sending their request in time are dropped, so they can't hold a task open forever
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    pub version: &'static str,
+    pub protocol_version: ProtocolVersion,
+    /// The server is shutting down, and new lobbies can't be hosted or joined
+    pub draining: bool,
+    pub lobbies: usize,
+    pub games_in_progress: usize,
+    pub connected_clients: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LobbyResource {
+    pub room_code: RoomCode,
+    #[serde(flatten)]
+    pub preview: LobbyPreviewData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn json(value: &impl Serialize) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status: 200, body },
+            Err(_) => Self::error(500, "Failed to serialize response"),
+        }
+    }
+    fn error(status: u16, message: &str) -> Self {
+        Self { status, body: serde_json::json!({ "error": message }).to_string() }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+
+    fn to_http(&self) -> String {
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+            self.status, self.reason(), self.body.len(), self.body
+        )
+    }
+}
+
+/// Answers one request. `path` may have a query string, which is ignored.
+pub fn route(listener: &Listener, method: &str, path: &str) -> Response {
+    if method != "GET" {
+        return Response::error(405, "Only GET is supported");
+    }
+    let path = path.split('?').next().unwrap_or_default().trim_end_matches('/');
+
+    match path.strip_prefix("/api/") {
+        Some("status") => Response::json(&listener.status()),
+        Some("lobbies") => {
+            let mut lobbies: Vec<LobbyResource> = listener.lobby_previews()
+                .into_iter()
+                .map(|(room_code, preview)| LobbyResource { room_code, preview })
+                .collect();
+            lobbies.sort_by_key(|lobby| lobby.room_code);
+            Response::json(&lobbies)
+        }
+        Some(lobby) if lobby.starts_with("lobbies/") => {
+            let Ok(room_code) = lobby.trim_start_matches("lobbies/").parse::<RoomCode>() else {
+                return Response::error(400, "Room code should be a number");
+            };
+            match listener.lobby_preview(room_code) {
+                Some(preview) => Response::json(&LobbyResource { room_code, preview }),
+                None => Response::error(404, "No lobby with that room code"),
+            }
+        }
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+/// Reads up to the blank line ending the headers, and returns the method and path from the request line.
+/// The body, if any, is ignored.
+pub async fn read_request(stream: &mut (impl AsyncRead + Unpin)) -> Result<(String, String), Response> {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+
+    let read_head = async {
+        loop {
+            let length = stream.read(&mut chunk).await.map_err(|_| Response::error(400, "Malformed request"))?;
+            head.extend_from_slice(chunk.get(..length).unwrap_or_default());
+
+            if head.windows(4).any(|window| window == b"\r\n\r\n") {
+                return Ok(());
+            }
+            if head.len() > MAX_REQUEST_HEAD_BYTES {
+                return Err(Response::error(431, "Request headers are too large"));
+            }
+            if length == 0 {
+                return Err(Response::error(400, "Malformed request"));
+            }
+        }
+    };
+    tokio::time::timeout(REQUEST_TIMEOUT, read_head).await
+        .map_err(|_| Response::error(408, "Request took too long to send"))??;
+
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+    match (request_line.next(), request_line.next()) {
+        (Some(method), Some(path)) => Ok((method.to_string(), path.to_string())),
+        _ => Err(Response::error(400, "Malformed request")),
+    }
+}
+
+/// Serves the API until the returned task is aborted
+pub fn serve(address: String, listener: Arc<Mutex<Listener>>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let tcp_listener = match TcpListener::bind(&address).await {
+            Ok(tcp_listener) => tcp_listener,
+            Err(err) => {
+                warn!(address, %err, "Failed to bind HTTP API");
+                return;
+            }
+        };
+        info!(address, "Serving HTTP API");
+
+        loop {
+            let Ok((mut stream, _)) = tcp_listener.accept().await else {continue};
+            let listener = listener.clone();
+
+            tokio::spawn(async move {
+                let response = match read_request(&mut stream).await {
+                    Ok((method, path)) => {
+                        // Released before the response is written, so a slow client doesn't hold up every lobby
+                        let listener = listener.force_lock();
+                        route(&listener, &method, &path)
+                    }
+                    Err(response) => response,
+                };
+
+                let _ = stream.write_all(response.to_http().as_bytes()).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    })
+}
@@ -12,6 +12,10 @@
 #![warn(clippy::expl_impl_clone_on_copy)]
 #![allow(clippy::new_without_default, reason = "This lint is stupid")]
 
+pub mod config;
+pub mod logging;
+pub mod metrics;
+pub mod http_api;
 pub mod game;
 pub mod websocket_connections;
 pub mod listener;
@@ -21,55 +25,6 @@ pub mod client_connection;
 pub mod vec_map;
 pub mod vec_set;
 
-pub mod log {
-    #[macro_export]
-    /// Log a statement to the console. 
-    /// When logging using this macro, a timestamp and possibly a marker is added to the message.
-    /// 
-    /// # Examples
-    /// ```
-    /// use mafia_server::log;
-    /// log!(error "Error location"; "Error message");
-    /// log!(error "Game::new"; "Failed to generate role. rolelist wasnt big enough for number of players");
-    /// log!(info "Listener"; "{}: {}", "Received message", "message");
-    /// ```
-    /// 
-    /// # Markers
-    /// - `fatal`: Prints the word FATAL
-    /// - `error`: Prints red and writes "WARN"
-    /// - `important`: 
-    /// - `info`: 
-    /// 
-    /// if none are put then it defaults to info
-    /// 
-    macro_rules! log {
-        // Each case in this macro definition is for a different log marker.
-        // None
-        ($expr:expr) => {
-            println!("\x1b[0;90m{}\x1b[0m {}", chrono::Local::now().format("%m.%d %I:%M:%S"), $expr)
-        };
-        // Fatal error
-        (fatal $prefix:expr; $($expr:expr),*) => {
-            log!(&format!("\x1b[0;1;91m[{}] FATAL\x1b[0m \x1b[0;1;41m{}\x1b[0m", $prefix, &format!($($expr),*)))
-        };
-        // Warning error
-        (error $prefix:expr; $($expr:expr),*) => {
-            log!(&format!("\x1b[0;1;91m[{}] WARN\x1b[0m {}", $prefix, &format!($($expr),*)))
-        };
-        // Important
-        (important $prefix:expr; $($expr:expr),*) => {
-            log!(&format!("\x1b[0;1;93m[{}]\x1b[0m {}", $prefix, &format!($($expr),*)))
-        };
-        // Info
-        (info $prefix:expr; $($expr:expr),*) => {
-            log!(&format!("\x1b[0;1;32m[{}]\x1b[0m {}", $prefix, &format!($($expr),*)))
-        };
-        // Default (use info)
-        ($prefix:expr; $($expr:expr),*) => {
-            log!(info $prefix; $($expr),*)
-        };
-    }
-}
 pub mod strings{
     pub trait TidyableString {
         fn trim_whitespace(&self) -> Self;
@@ -1,40 +1,95 @@
-use std::{collections::HashMap, net::SocketAddr, ops::Mul, sync::{Arc, Mutex}, time::Duration};
+use std::{collections::{BTreeMap, HashMap, VecDeque}, io::Write, net::SocketAddr, ops::Mul, os::unix::fs::{OpenOptionsExt, PermissionsExt}, path::Path, sync::{Arc, Mutex, PoisonError}, time::Duration};
 
 use rand::random;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
 use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
 
 use crate::{
-    lobby::{lobby_client::LobbyClientID, Lobby}, 
-    log, 
+    client_connection::PacketSequence,
+    config::Config,
+    http_api::ServerStatus,
+    metrics,
+    lobby::{lobby_access::{new_invite_code, normalize_invite_code, JoinCredentials}, lobby_client::LobbyClientID, snapshot::LobbySnapshot, Lobby}, 
     packet::{LobbyPreviewData, RejectJoinReason, ToClientPacket, ToServerPacket}, 
-    websocket_connections::connection::Connection
+    websocket_connections::{connection::Connection, protocol::{DecodeError, Encoding, Protocol, PROTOCOL_VERSION}}
 };
 
 pub type RoomCode = usize;
 
+/// Every open lobby, saved so a restarted server can pick up where the last one left off
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListenerSnapshot {
+    pub lobbies: Vec<LobbySnapshot>,
+}
+impl ListenerSnapshot {
+    /// Blocks on disk, so call it without holding the listener lock
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        // The periodic save and the one on shutdown can overlap, and they share a temporary file
+        static WRITING: Mutex<()> = Mutex::new(());
+        let _writing = WRITING.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let json = serde_json::to_string(self)?;
+
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        // Write to a temporary file first so a crash mid-write doesn't destroy the last good snapshot.
+        // Lobby passwords are in it, so nobody else gets to read it.
+        let temporary_path = path.with_extension("tmp");
+        let mut file = std::fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(&temporary_path)?;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        file.write_all(json.as_bytes())?;
+        std::fs::rename(&temporary_path, path)
+    }
+}
+
 struct ListenerClient {
     connection: Connection,
     location: ListenerClientLocation,
     last_ping: tokio::time::Instant,
+    /// The client said hello with a protocol version this server can't speak
+    protocol_rejected: bool,
+    /// When the client last gave a wrong lobby password, within the wrong password window
+    wrong_passwords: VecDeque<tokio::time::Instant>,
 }
 impl ListenerClient{
-    const PONG_INTERVAL: Duration = Duration::from_secs(5);
-
     fn new(connection: Connection) -> Self {
         Self {
             connection,
             location: ListenerClientLocation::OutsideLobby,
             last_ping: tokio::time::Instant::now(),
+            protocol_rejected: false,
+            wrong_passwords: VecDeque::new(),
         }
     }
     fn on_ping(&mut self) {
         self.last_ping = tokio::time::Instant::now();
     }
+    /// Whether the client can try another password, so passwords can't be guessed quickly
+    fn can_try_password(&mut self) -> bool {
+        let now = tokio::time::Instant::now();
+        while let Some(time) = self.wrong_passwords.front() {
+            if now.saturating_duration_since(*time) > Config::get().wrong_password_window() {
+                self.wrong_passwords.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.wrong_passwords.len() < Config::get().max_wrong_passwords
+    }
+    fn on_join_rejected(&mut self, reason: RejectJoinReason) {
+        if let RejectJoinReason::WrongPassword = reason {
+            self.wrong_passwords.push_back(tokio::time::Instant::now());
+        }
+    }
     fn ping_timed_out(&self) -> bool {
-        self.last_ping.elapsed() > Self::PONG_INTERVAL.mul(2)
+        self.last_ping.elapsed() > Config::get().pong_interval().mul(2)
     }
     fn tick(&mut self) {
-        if Self::PONG_INTERVAL < tokio::time::Instant::now().saturating_duration_since(self.last_ping) {
+        if Config::get().pong_interval() < tokio::time::Instant::now().saturating_duration_since(self.last_ping) {
             self.connection.send(ToClientPacket::Pong);
         }
     }
@@ -52,18 +107,111 @@ enum ListenerClientLocation {
 pub struct Listener {
     lobbies: HashMap<RoomCode, Lobby>,
     clients: HashMap<SocketAddr, ListenerClient>,
+    time_since_snapshot: Duration,
+    /// Set once the server starts shutting down. New lobbies can't be hosted or joined after this.
+    draining_since: Option<tokio::time::Instant>,
 }
 impl Listener{
     pub fn new() -> Self {
         Self {
             lobbies: HashMap::new(),
             clients: HashMap::new(),
+            time_since_snapshot: Duration::ZERO,
+            draining_since: None,
+        }
+    }
+
+    /// Rejects every new host and join from now on, and tells every client how long until the server goes down.
+    /// Players already in a game can still rejoin it.
+    pub fn start_draining(&mut self) {
+        self.draining_since = Some(tokio::time::Instant::now());
+
+        if let Some(packet) = self.shutdown_packet() {
+            for client in self.clients.values() {
+                client.connection.send(packet.clone());
+            }
+        }
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining_since.is_some()
+    }
+
+    /// True once no game is still being played, or the shutdown deadline has passed
+    pub fn is_drained(&self) -> bool {
+        self.drain_time_left().is_some_and(|time_left| time_left.is_zero()) ||
+        !self.lobbies.values().any(Lobby::is_game_in_progress)
+    }
+
+    fn drain_time_left(&self) -> Option<Duration> {
+        self.draining_since.map(|since| Config::get().shutdown_deadline().saturating_sub(since.elapsed()))
+    }
+
+    fn shutdown_packet(&self) -> Option<ToClientPacket> {
+        self.drain_time_left().map(|time_left| ToClientPacket::ServerShuttingDown { seconds_left: time_left.as_secs() })
+    }
+
+    /// Unlisted lobbies are left out
+    pub fn lobby_previews(&self) -> HashMap<RoomCode, LobbyPreviewData> {
+        self.lobbies.iter()
+            .filter(|(_, lobby)| lobby.access.is_listed())
+            .map(|(room_code, lobby)| (*room_code, lobby.preview_data()))
+            .collect()
+    }
+    pub fn lobby_preview(&self, room_code: RoomCode) -> Option<LobbyPreviewData> {
+        self.lobbies.get(&room_code)
+            .filter(|lobby| lobby.access.is_listed())
+            .map(Lobby::preview_data)
+    }
+
+    pub fn status(&self) -> ServerStatus {
+        ServerStatus {
+            version: env!("CARGO_PKG_VERSION"),
+            protocol_version: PROTOCOL_VERSION,
+            draining: self.is_draining(),
+            lobbies: self.lobbies.len(),
+            games_in_progress: self.lobbies.values().filter(|lobby| lobby.is_game_in_progress()).count(),
+            connected_clients: self.clients.len(),
+        }
+    }
+
+    /// Lobbies that can't be rebuilt (including ones whose game panics while replaying) are dropped
+    pub fn restore(snapshot: ListenerSnapshot) -> Self {
+        let mut listener = Self::new();
+
+        for lobby in snapshot.lobbies {
+            let room_code = lobby.room_code;
+            match std::panic::catch_unwind(|| Lobby::restore(lobby)) {
+                Ok(Ok(lobby)) => {
+                    info!(room_code, "Restored lobby");
+                    listener.lobbies.insert(room_code, lobby);
+                }
+                Ok(Err(err)) => warn!(room_code, error = ?err, "Failed to restore lobby"),
+                Err(_) => warn!(room_code, "Failed to restore lobby, the game panicked while replaying"),
+            }
         }
+
+        listener
     }
 
-    pub fn start(listener: Arc<Mutex<Self>>) {
-        const DESIRED_FRAME_TIME: Duration = Duration::from_secs(1);
+    pub fn snapshot(&self) -> ListenerSnapshot {
+        ListenerSnapshot {
+            lobbies: self.lobbies.values().filter_map(Lobby::snapshot).collect()
+        }
+    }
 
+    /// Returns `Ok(None)` if there's no snapshot at the path
+    pub fn load_snapshot(path: &Path) -> std::io::Result<Option<ListenerSnapshot>> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        Ok(Some(serde_json::from_str(&json)?))
+    }
+
+    /// Ticks the listener until the returned task is aborted, or the lock is poisoned
+    pub fn start(listener: Arc<Mutex<Self>>) -> JoinHandle<()> {
         tokio::spawn(async move {
             let mut frame_start_time = tokio::time::Instant::now();
             loop {
@@ -75,24 +223,25 @@ impl Listener{
                 } else { 
                     return;
                 }
+                metrics::tick_finished(frame_start_time.elapsed());
 
-                tokio::time::sleep(DESIRED_FRAME_TIME.saturating_sub(tokio::time::Instant::now().saturating_duration_since(frame_start_time))).await;
+                tokio::time::sleep(Config::get().frame_time().saturating_sub(tokio::time::Instant::now().saturating_duration_since(frame_start_time))).await;
             }
-        });
+        })
     }
     fn tick(&mut self, delta_time: Duration){
         let mut closed_lobbies = Vec::new();
         let mut closed_clients = Vec::new();
                     
-        let Listener { ref mut lobbies, ref mut clients} = *self;
+        let Listener { ref mut lobbies, ref mut clients, .. } = *self;
 
-        // log!(info "Listener"; "lobbies: {:?} players: {:?}", lobbies.keys(), _players.len());
+        // debug!(lobbies = ?lobbies.keys(), players = clients.len());
 
         for (room_code, lobby) in lobbies.iter_mut() {
             if lobby.is_closed() {
                 closed_lobbies.push(*room_code);
             } else {
-                lobby.tick(delta_time);
+                lobby.run_isolated(|lobby| lobby.tick(delta_time));
             }
         }
 
@@ -104,24 +253,59 @@ impl Listener{
         }
 
         for key in closed_lobbies {
-            log!(important "Lobby"; "Closed {key} due to lobby closed");
+            info!(room_code = key, "Closed lobby");
             self.delete_lobby(key);
         }
+        // Both states are always reported, so an empty server shows zeros rather than nothing
+        let mut lobbies_by_state = BTreeMap::from([("lobby", 0), ("game", 0)]);
+        for lobby in self.lobbies.values() {
+            let Some(state) = lobby.state_name() else {continue};
+            let count: &mut u64 = lobbies_by_state.entry(state).or_default();
+            *count = count.saturating_add(1);
+        }
+        metrics::set_lobbies_by_state(lobbies_by_state);
+        metrics::set_connected_clients(self.clients.len());
+
         for key in closed_clients {
-            log!(important "Connection"; "Closed {key} due to ping timed out");
+            info!(address = %key, "Closed connection due to ping timed out");
+            metrics::ping_timed_out();
             let _ = self.delete_player(&key, true);
         }
+
+        self.time_since_snapshot = self.time_since_snapshot.saturating_add(delta_time);
+        if self.time_since_snapshot >= Config::get().snapshot_interval() {
+            self.time_since_snapshot = Duration::ZERO;
+            let snapshot = self.snapshot();
+            std::thread::spawn(move || {
+                if let Err(err) = snapshot.save(Config::get().snapshot_path()) {
+                    warn!(%err, "Failed to save snapshot");
+                }
+            });
+        }
     }
 
     fn create_lobby(&mut self) -> Option<RoomCode>{
+        if self.lobbies.len() >= Config::get().max_lobbies {
+            return None;
+        }
+
         let room_code = ((random::<u16>() as usize)..usize::MAX).find(
             |code| !self.lobbies.contains_key(code)
         )?;
 
-        let lobby = Lobby::new(room_code);
+        let mut lobby = Lobby::new(room_code);
+        while self.room_code_for_invite_code(&lobby.access.invite_code).is_some() {
+            lobby.access.invite_code = new_invite_code(&mut rand::rng());
+        }
         self.lobbies.insert(room_code, lobby);
         Some(room_code)
     }
+    fn room_code_for_invite_code(&self, invite_code: &str) -> Option<RoomCode> {
+        let invite_code = normalize_invite_code(invite_code);
+        self.lobbies.iter()
+            .find(|(_, lobby)| lobby.access.invite_code == invite_code)
+            .map(|(room_code, _)| *room_code)
+    }
     fn delete_lobby(&mut self, room_code: RoomCode){
         let clients_to_remove: Vec<_> = self.clients.iter()
             .filter(|p| 
@@ -140,59 +324,78 @@ impl Listener{
         self.lobbies.remove(&room_code);
     }
 
-    fn set_player_in_lobby_initial_connect(&mut self, connection: &Connection, room_code: RoomCode){
+    fn set_player_in_lobby_initial_connect(&mut self, connection: &Connection, room_code: RoomCode, credentials: &JoinCredentials){
+        if self.is_draining() {
+            connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerShuttingDown });
+            return;
+        }
+
         let Some(lobby) = self.lobbies.get_mut(&room_code) else {
             connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::RoomDoesntExist });
             return;
         };
 
-        let Some(sender_player_location) = self.clients
-            .get_mut(connection.get_address())
-            .map(|p|&mut p.location)
-        else{
-            log!(error "Listener"; "{} {}", "Received packet from unconnected player!", connection.get_address());
+        let Some(client) = self.clients.get_mut(connection.get_address()) else{
+            warn!(address = %connection.get_address(), "Received packet from unconnected player!");
             connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerBusy });
             return;
         };
 
-        match lobby.join_player(&connection.get_sender()) {
+        if lobby.access.is_password_protected() && !client.can_try_password() {
+            connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::TooManyPasswordAttempts });
+            return;
+        }
+
+        match lobby.join_player(&connection.get_sender(), credentials) {
             Ok(lobby_client_id) => {
-                *sender_player_location = ListenerClientLocation::InLobby { room_code, lobby_client_id };
+                client.location = ListenerClientLocation::InLobby { room_code, lobby_client_id };
         
                 connection.send(ToClientPacket::LobbyName { name: lobby.name.clone() })
             }
             Err(reason) => {
+                client.on_join_rejected(reason);
                 connection.get_sender().send(ToClientPacket::RejectJoin { reason });
             }
         }
     }
-    fn set_player_in_lobby_reconnect(&mut self, connection: &Connection, room_code: RoomCode, lobby_client_id: LobbyClientID){
+    fn set_player_in_lobby_reconnect(
+        &mut self, connection: &Connection, room_code: RoomCode, lobby_client_id: LobbyClientID,
+        credentials: &JoinCredentials, last_sequence: Option<PacketSequence>
+    ){
 
         let Some(lobby) = self.lobbies.get_mut(&room_code) else {
             connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::RoomDoesntExist });
             return;
         };
 
-        let Some(sender_player_location) = 
-            self.clients
-            .get_mut(connection.get_address())
-            .map(|p|&mut p.location)
-        else{
-            log!(error "Listener"; "{} {}", "Received packet from unconnected player!", connection.get_address());
+        let Some(client) = self.clients.get_mut(connection.get_address()) else{
+            warn!(address = %connection.get_address(), "Received packet from unconnected player!");
             connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerBusy });
             return;
         };
 
-        if lobby.rejoin_player(&connection.get_sender(), lobby_client_id).is_ok() {
-            *sender_player_location = ListenerClientLocation::InLobby { room_code, lobby_client_id };
+        if lobby.access.is_password_protected() && !client.can_try_password() {
+            connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::TooManyPasswordAttempts });
+            return;
         }
+
+        // The lobby tells the client why if it can't rejoin
+        if let Err(reason) = lobby.rejoin_player(&connection.get_sender(), lobby_client_id, credentials, last_sequence) {
+            client.on_join_rejected(reason);
+            return;
+        }
+        client.location = ListenerClientLocation::InLobby { room_code, lobby_client_id };
         
-        connection.send(ToClientPacket::LobbyName { name: lobby.name.clone() })
+        connection.send(ToClientPacket::LobbyName { name: lobby.name.clone() });
+
+        if let Some(packet) = self.shutdown_packet() {
+            connection.send(packet);
+        }
     }
     //returns if player was in the lobby
     fn set_player_outside_lobby(&mut self, address: &SocketAddr, rejoinable: bool) -> bool {
         let Some(listener_client) = self.clients.get_mut(address) else {
-            log!(error "Listener"; "{} {}", "Attempted set_player_outside_lobby with address that isn't in the map", address);
+            warn!(%address, "Attempted set_player_outside_lobby with address that isn't in the map");
             return false;
         };
         listener_client.connection.send(ToClientPacket::ForcedOutsideLobby);
@@ -258,47 +461,82 @@ impl Listener{
     pub fn on_message(&mut self, connection: &Connection, message: &Message) {
         if message.is_empty() { return }
 
-        log!(info "Listener"; "{}: {}", &connection.get_address().to_string(), message);
+        debug!(address = %connection.get_address(), %message, "Received message");
         if let Err(k) = self.handle_message(connection, message){
-            log!(error "Listener"; "Serde error when receiving message from {}: {}\n{}", &connection.get_address().to_string(), k, message);
+            warn!(address = %connection.get_address(), err = %k, %message, "Serde error when receiving message");
         }
     }
 
-    fn handle_message(&mut self, connection: &Connection, message: &Message) -> Result<(), serde_json::Error> {
-        let incoming_packet = serde_json::from_str::<ToServerPacket>(message.to_string().as_str())?;
+    fn handle_message(&mut self, connection: &Connection, message: &Message) -> Result<(), DecodeError> {
+        let incoming_packet = Encoding::decode(message)?;
+        metrics::packet_received(&incoming_packet);
+
+        if 
+            self.clients.get(connection.get_address()).is_some_and(|client| client.protocol_rejected) &&
+            !matches!(incoming_packet, ToServerPacket::Hello { .. } | ToServerPacket::Ping)
+        {
+            connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::IncompatibleVersion });
+            return Ok(());
+        }
 
         match incoming_packet {
+            ToServerPacket::Hello { protocol_version, capabilities } => {
+                let Some(client) = self.clients.get_mut(connection.get_address()) else {
+                    warn!(address = %connection.get_address(), "Received packet from unconnected player!");
+                    return Ok(());
+                };
+
+                match Protocol::negotiate(protocol_version, capabilities) {
+                    Ok(protocol) => {
+                        client.protocol_rejected = false;
+                        connection.set_protocol(protocol.clone());
+                        connection.send(ToClientPacket::HelloAccepted {
+                            protocol_version: protocol.version,
+                            capabilities: protocol.capabilities
+                        });
+                    }
+                    Err(reason) => {
+                        info!(address = %connection.get_address(), protocol_version, "Rejected client with an incompatible protocol version");
+                        client.protocol_rejected = true;
+                        connection.send(ToClientPacket::RejectJoin { reason });
+                    }
+                }
+            },
             ToServerPacket::Ping => {
                 if let Some(client) = self.clients.get_mut(connection.get_address()){
                     client.on_ping();
                 }
             },
             ToServerPacket::LobbyListRequest => {
-                connection.send(ToClientPacket::LobbyList{lobbies: self.lobbies.iter()
-                    .map(|(room_code, lobby)|
-                        (*room_code, LobbyPreviewData { 
-                            name: lobby.name.clone(),
-                            in_game: lobby.is_in_game(),
-                            players: lobby.get_player_list() 
-                        }
-                    ))
-                    .collect::<HashMap<RoomCode, LobbyPreviewData>>()});
+                connection.send(ToClientPacket::LobbyList{lobbies: self.lobby_previews()});
             },
-            ToServerPacket::ReJoin {room_code, player_id } => {
-                self.set_player_in_lobby_reconnect(connection, room_code, player_id);
+            ToServerPacket::ReJoin {room_code, player_id, last_sequence, password, invite_code } => {
+                let invited = invite_code.is_some_and(|invite_code| self.room_code_for_invite_code(&invite_code) == Some(room_code));
+                self.set_player_in_lobby_reconnect(connection, room_code, player_id, &JoinCredentials { invited, password }, last_sequence);
             }
-            ToServerPacket::Join{ room_code } => {
-                self.set_player_in_lobby_initial_connect(connection, room_code);
+            ToServerPacket::Join{ room_code, password } => {
+                self.set_player_in_lobby_initial_connect(connection, room_code, &JoinCredentials { invited: false, password });
+            },
+            ToServerPacket::JoinWithInviteCode{ invite_code, password } => {
+                let Some(room_code) = self.room_code_for_invite_code(&invite_code) else {
+                    connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::RoomDoesntExist });
+                    return Ok(());
+                };
+                self.set_player_in_lobby_initial_connect(connection, room_code, &JoinCredentials { invited: true, password });
             },
             ToServerPacket::Host => {
+                if self.is_draining() {
+                    connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerShuttingDown });
+                    return Ok(());
+                }
                 let Some(room_code) = self.create_lobby() else {
                     connection.send(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerBusy });
                     return Ok(());
                 };
                 
-                self.set_player_in_lobby_initial_connect(connection, room_code);
+                self.set_player_in_lobby_initial_connect(connection, room_code, &JoinCredentials::default());
 
-                log!(important "Lobby"; "Created {room_code}");
+                info!(room_code, "Created lobby");
             },
             ToServerPacket::Leave => {
                 self.set_player_outside_lobby(connection.get_address(), false);
@@ -308,12 +546,12 @@ impl Listener{
                     .get(connection.get_address())
                     .map(|p|&p.location)
                 else{
-                    log!(error "Listener"; "{} {}", "Received lobby/game packet from unconnected player!", connection.get_address());
+                    warn!(address = %connection.get_address(), "Received lobby/game packet from unconnected player!");
                     return Ok(());
                 };
 
                 let ListenerClientLocation::InLobby{room_code, lobby_client_id: host_id} = host_location else {
-                    log!(error "Listener"; "{} {}", "Received lobby/game packet from player not in a lobby!", connection.get_address());
+                    warn!(address = %connection.get_address(), "Received lobby/game packet from player not in a lobby!");
                     return Ok(());
                 };
 
@@ -341,15 +579,15 @@ impl Listener{
                     .get_mut(connection.get_address())
                     .map(|p|&mut p.location)
                 else{
-                    log!(error "Listener"; "{} {}", "Received lobby/game packet from unconnected player!", connection.get_address());
+                    warn!(address = %connection.get_address(), "Received lobby/game packet from unconnected player!");
                     return Ok(());
                 };
 
                 if let ListenerClientLocation::InLobby { room_code, lobby_client_id } = sender_player_location {
                     if let Some(lobby) = self.lobbies.get_mut(room_code){
-                        lobby.on_client_message(&connection.get_sender(), *lobby_client_id, incoming_packet);
+                        lobby.run_isolated(|lobby| lobby.on_client_message(&connection.get_sender(), *lobby_client_id, incoming_packet));
                     } else {
-                        log!(error "listener.rs"; "Received a message from a player in a lobby that doesnt exist");
+                        warn!(room_code = *room_code, lobby_client_id = *lobby_client_id, "Received a message from a player in a lobby that doesnt exist");
                     }
                 }
             }
@@ -0,0 +1,81 @@
+//! Role list analyses generate hundreds of role lists, so they're worked out on a blocking thread,
+//! and kept until the settings they depend on change.
+
+use tokio::sync::oneshot;
+
+use crate::game::{role_list_analysis::RoleListAnalysis, settings::{PhaseTimeSettings, Settings}};
+
+use super::blocking;
+
+/// The latest role list analysis for a lobby, and the one being worked out
+#[derive(Default)]
+pub struct AnalysisCache {
+    /// The last analysis to finish, and the settings it was for
+    finished: Option<(Settings, RoleListAnalysis)>,
+    running: Option<(Settings, oneshot::Receiver<RoleListAnalysis>)>,
+    /// Settings that changed while another analysis was running, analyzed once it's done
+    queued: Option<Settings>,
+}
+
+impl AnalysisCache {
+    /// The analysis for `settings`, if it's already been worked out
+    pub fn get(&self, settings: &Settings) -> Option<&RoleListAnalysis> {
+        let inputs = Self::inputs(settings);
+        self.finished.as_ref()
+            .filter(|(analyzed, _)| *analyzed == inputs)
+            .map(|(_, analysis)| analysis)
+    }
+
+    /// Starts analyzing `settings` if that hasn't been done already, and returns the analysis if it's ready.
+    /// Only one analysis runs at a time, so when settings change quicker than that, only the latest are analyzed.
+    pub fn request(&mut self, settings: &Settings) -> Option<&RoleListAnalysis> {
+        let inputs = Self::inputs(settings);
+        if self.finished.as_ref().is_some_and(|(analyzed, _)| *analyzed == inputs) {
+            self.queued = None;
+        } else if self.running.as_ref().is_some_and(|(analyzing, _)| *analyzing == inputs) {
+            self.queued = None;
+            return None;
+        } else if self.running.is_some() {
+            self.queued = Some(inputs);
+            return None;
+        } else {
+            self.start(inputs);
+            // Already done if there's no runtime to do it on
+            return self.poll();
+        }
+        self.get(settings)
+    }
+
+    /// Returns an analysis that just finished, if it's for the latest settings that were requested
+    pub fn poll(&mut self) -> Option<&RoleListAnalysis> {
+        let (_, receiver) = self.running.as_mut()?;
+        let result = match receiver.try_recv() {
+            Err(oneshot::error::TryRecvError::Empty) => return None,
+            result => result.ok(),
+        };
+        let (inputs, _) = self.running.take()?;
+
+        if let Some(queued) = self.queued.take() {
+            self.start(queued);
+            return None;
+        }
+        // Nothing was sent if the analysis panicked
+        let analysis = result?;
+        self.finished = Some((inputs, analysis));
+        self.finished.as_ref().map(|(_, analysis)| analysis)
+    }
+
+    fn start(&mut self, inputs: Settings) {
+        let settings = inputs.clone();
+        self.running = Some((inputs, blocking::spawn(move || RoleListAnalysis::new(&settings))));
+    }
+
+    /// Phase times and the seed don't change the analysis, so they're left out when comparing settings
+    fn inputs(settings: &Settings) -> Settings {
+        Settings {
+            phase_times: PhaseTimeSettings::default(),
+            seed: None,
+            ..settings.clone()
+        }
+    }
+}
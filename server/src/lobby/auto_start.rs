@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    client_connection::ClientConnection, config::Config, game::{settings::Settings, RejectStartReason},
+    packet::ToClientPacket, vec_map::VecMap
+};
+
+use super::{lobby_client::{LobbyClient, LobbyClientID, Ready}, Lobby, LobbyState};
+
+/// Hosts can't be swapped out quicker than this, so a host reading the settings isn't taken for idle
+const MIN_IDLE_HOST_TIMEOUT_SECS: u64 = 60;
+
+/// How the host wants the game to be started. Player counts include bots, but not spectators.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct StartSettings {
+    /// Counts down to the start once every player is ready and there's an outline for each of them
+    pub auto_start: bool,
+    pub min_players: Option<u8>,
+    /// Clients joining past this are made spectators
+    pub max_players: Option<u8>,
+    /// A host who sends nothing for this long hands hosting to another client
+    pub idle_host_timeout_secs: Option<u64>,
+}
+
+impl StartSettings {
+    /// A maximum of 0 means no maximum, and the minimum can't be above the maximum
+    pub fn sanitize(self) -> Self {
+        let max_players = self.max_players.filter(|max| *max != 0);
+        let min_players = match (self.min_players, max_players) {
+            (Some(min), Some(max)) => Some(min.min(max)),
+            (min, _) => min,
+        };
+        Self {
+            auto_start: self.auto_start,
+            min_players,
+            max_players,
+            idle_host_timeout_secs: self.idle_host_timeout_secs.map(|secs| secs.max(MIN_IDLE_HOST_TIMEOUT_SECS)),
+        }
+    }
+
+    pub fn check_player_count(&self, players: usize) -> Result<(), RejectStartReason> {
+        if self.min_players.is_some_and(|min| players < min as usize) {
+            Err(RejectStartReason::TooFewPlayers)
+        } else if self.max_players.is_some_and(|max| players > max as usize) {
+            Err(RejectStartReason::TooManyPlayers)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// No one else can take a seat
+    pub fn is_full(&self, players: usize) -> bool {
+        self.max_players.is_some_and(|max| players >= max as usize)
+    }
+
+    pub fn idle_host_timeout(&self) -> Option<Duration> {
+        self.idle_host_timeout_secs.map(Duration::from_secs)
+    }
+}
+
+/// A lobby's start settings, and where its countdown is at
+#[derive(Clone, Debug, Default)]
+pub struct AutoStart {
+    pub settings: StartSettings,
+    /// Time left until the game starts, if counting down
+    countdown: Option<Duration>,
+    /// How long since the host last sent a packet
+    host_idle: Duration,
+}
+
+impl AutoStart {
+    pub fn new(settings: StartSettings) -> Self {
+        Self { settings, ..Self::default() }
+    }
+}
+
+impl Lobby {
+    /// Every player is ready and connected, and the role list and start settings fit how many there are
+    fn is_ready_to_start(settings: &Settings, clients: &VecMap<LobbyClientID, LobbyClient>, start_settings: &StartSettings) -> bool {
+        let players = Self::player_count(clients);
+
+        players != 0 &&
+        players == settings.role_list.0.len() &&
+        start_settings.check_player_count(players).is_ok() &&
+        clients.values()
+            .filter(|client| !client.is_spectator())
+            .all(|client| client.ready != Ready::NotReady && matches!(client.connection, ClientConnection::Connected(_)))
+    }
+
+    /// Starts the countdown if the lobby just became ready to start, or cancels it if it stopped being ready
+    pub(super) fn update_start_countdown(&mut self) {
+        let LobbyState::Lobby { settings, clients } = &self.lobby_state else {return};
+
+        let ready = self.auto_start.settings.auto_start && Self::is_ready_to_start(settings, clients, &self.auto_start.settings);
+
+        match (ready, self.auto_start.countdown) {
+            (true, None) => {
+                let countdown = Config::get().auto_start_countdown();
+                self.auto_start.countdown = Some(countdown);
+                self.send_to_all(ToClientPacket::StartCountdown { seconds_left: Some(countdown.as_secs()) });
+            }
+            (false, Some(_)) => {
+                self.auto_start.countdown = None;
+                self.send_to_all(ToClientPacket::StartCountdown { seconds_left: None });
+            }
+            _ => {}
+        }
+    }
+
+    /// A packet from the host shows they're still around
+    pub(super) fn reset_host_idle(&mut self, lobby_client_id: LobbyClientID) {
+        if self.is_host(lobby_client_id) {
+            self.auto_start.host_idle = Duration::ZERO;
+        }
+    }
+
+    pub(super) fn tick_auto_start(&mut self, time_passed: Duration) {
+        self.tick_idle_host(time_passed);
+        self.update_start_countdown();
+
+        let Some(countdown) = self.auto_start.countdown else {return};
+
+        match countdown.checked_sub(time_passed).filter(|left| !left.is_zero()) {
+            Some(left) => {
+                self.auto_start.countdown = Some(left);
+                if left.as_secs() != countdown.as_secs() {
+                    self.send_to_all(ToClientPacket::StartCountdown { seconds_left: Some(left.as_secs()) });
+                }
+            }
+            None => {
+                self.auto_start.countdown = None;
+                if let Err(reason) = self.start_game() {
+                    self.reject_start(reason);
+                }
+            }
+        }
+    }
+
+    /// For starts that failed after whoever asked for them was answered, like auto starts and roles that took a while to deal
+    pub(super) fn reject_start(&mut self, reason: RejectStartReason) {
+        info!(?reason, "Failed to start game");
+        // Otherwise it would count down and fail again for as long as nothing changes
+        if self.auto_start.settings.auto_start {
+            self.auto_start.settings.auto_start = false;
+            self.send_to_all(ToClientPacket::StartCountdown { seconds_left: None });
+            self.send_to_all(ToClientPacket::LobbyStartSettings { start_settings: self.auto_start.settings.clone() });
+        }
+        self.send_to_hosts(ToClientPacket::RejectStart { reason });
+    }
+
+    /// Hosting goes to the first connected client who isn't a bot
+    fn tick_idle_host(&mut self, time_passed: Duration) {
+        let Some(timeout) = self.auto_start.settings.idle_host_timeout() else {return};
+        let LobbyState::Lobby { clients, .. } = &mut self.lobby_state else {return};
+
+        self.auto_start.host_idle = self.auto_start.host_idle.saturating_add(time_passed);
+        if self.auto_start.host_idle < timeout {return}
+
+        let Some(new_host) = clients.iter()
+            .find(|(_, client)| !client.is_host() && !client.is_bot() && matches!(client.connection, ClientConnection::Connected(_)))
+            .map(|(id, _)| *id)
+        else {return};
+
+        for client in clients.values_mut().filter(|client| client.is_host()) {
+            client.ready = Ready::NotReady;
+        }
+        if let Some(client) = clients.get_mut(&new_host) {
+            client.set_host();
+        }
+        self.auto_start.host_idle = Duration::ZERO;
+
+        info!(new_host, "Idle host replaced");
+        Self::send_players_lobby(clients);
+        Self::send_visibility(clients, &self.access);
+    }
+
+    fn send_to_hosts(&self, packet: ToClientPacket) {
+        let LobbyState::Lobby { clients, .. } = &self.lobby_state else {return};
+        for client in clients.values().filter(|client| client.is_host()) {
+            client.send(packet.clone());
+        }
+    }
+}
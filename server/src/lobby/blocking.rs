@@ -0,0 +1,29 @@
+//! Some lobby work, like analyzing a role list, is too slow to do while every lobby waits on the listener.
+//! It's done on a blocking thread instead, and a later tick picks up the result.
+
+use tokio::{runtime::Handle, sync::oneshot};
+use tracing::{error, Span};
+
+use super::Lobby;
+
+/// Runs `work` on a blocking thread, and sends back what it returns.
+/// A panic is caught like a lobby's, so nothing is sent back rather than the server restarting.
+/// Without a runtime, like in tests, there's nothing else to hold up, so it's done straight away.
+pub fn spawn<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> oneshot::Receiver<T> {
+    let (sender, receiver) = oneshot::channel();
+    // Logged as part of the lobby that asked for it
+    let span = Span::current();
+    let run = move || {
+        let _span = span.entered();
+        match Lobby::catch_panic(work) {
+            Ok(result) => {let _ = sender.send(result);}
+            Err(payload) => error!(panic = Lobby::panic_message(&payload), "Lobby work panicked"),
+        }
+    };
+
+    match Handle::try_current() {
+        Ok(runtime) => {runtime.spawn_blocking(run);}
+        Err(_) => run(),
+    }
+    receiver
+}
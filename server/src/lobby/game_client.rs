@@ -1,10 +1,10 @@
 use std::{collections::VecDeque, time::Instant};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::{player::PlayerIndex, spectator::spectator_pointer::SpectatorIndex};
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GameClient{
     #[serde(rename = "clientType")]
@@ -14,7 +14,7 @@ pub struct GameClient{
     #[serde(skip)]
     pub last_message_times: VecDeque<Instant>,
 }
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(tag = "type", content = "index", rename_all="camelCase")]
 pub enum GameClientLocation {
     Player(PlayerIndex),
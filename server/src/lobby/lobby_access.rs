@@ -0,0 +1,106 @@
+use rand::{seq::IndexedRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{packet::RejectJoinReason, strings::TidyableString};
+
+const MAX_PASSWORD_LENGTH: usize = 64;
+const INVITE_CODE_LENGTH: usize = 6;
+/// No 0, O, 1, I or L, so codes read out loud or copied by hand still work
+const INVITE_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LobbyVisibility {
+    /// Listed, and anyone can join
+    #[default]
+    Public,
+    /// Not listed, and can only be joined with the invite code
+    Unlisted,
+    /// Listed, but joining needs the password
+    Password { password: String },
+}
+
+impl LobbyVisibility {
+    /// An empty password makes the lobby public
+    pub fn sanitize(self) -> Self {
+        match self {
+            LobbyVisibility::Password { password } => {
+                let password = password.remove_newline().truncate(MAX_PASSWORD_LENGTH);
+                if password.is_empty() {
+                    LobbyVisibility::Public
+                } else {
+                    LobbyVisibility::Password { password }
+                }
+            }
+            visibility => visibility,
+        }
+    }
+}
+
+/// What a client knew about the lobby when it asked to join
+#[derive(Clone, Debug, Default)]
+pub struct JoinCredentials {
+    /// The client found the lobby by its invite code rather than its room code
+    pub invited: bool,
+    pub password: Option<String>,
+}
+
+/// Who can find and join a lobby
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LobbyAccess {
+    pub visibility: LobbyVisibility,
+    /// A short code that can be shared instead of the room code. Room codes are easy to guess, these aren't.
+    pub invite_code: String,
+}
+
+impl LobbyAccess {
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self { visibility: LobbyVisibility::Public, invite_code: new_invite_code(rng) }
+    }
+
+    pub fn is_listed(&self) -> bool {
+        !matches!(self.visibility, LobbyVisibility::Unlisted)
+    }
+
+    pub fn is_password_protected(&self) -> bool {
+        matches!(self.visibility, LobbyVisibility::Password { .. })
+    }
+
+    /// Only hosts are told the password, everyone else just sees that there is one
+    pub fn visibility_for(&self, host: bool) -> LobbyVisibility {
+        match &self.visibility {
+            LobbyVisibility::Password { .. } if !host => LobbyVisibility::Password { password: String::new() },
+            visibility => visibility.clone(),
+        }
+    }
+
+    pub fn check(&self, credentials: &JoinCredentials) -> Result<(), RejectJoinReason> {
+        match &self.visibility {
+            LobbyVisibility::Public => Ok(()),
+            // Unlisted lobbies act like they don't exist, so they can't be found by trying room codes
+            LobbyVisibility::Unlisted if credentials.invited => Ok(()),
+            LobbyVisibility::Unlisted => Err(RejectJoinReason::RoomDoesntExist),
+            LobbyVisibility::Password { password } => match &credentials.password {
+                Some(given) if given == password => Ok(()),
+                Some(_) => Err(RejectJoinReason::WrongPassword),
+                None => Err(RejectJoinReason::PasswordRequired),
+            },
+        }
+    }
+}
+
+pub fn new_invite_code<R: Rng + ?Sized>(rng: &mut R) -> String {
+    (0..INVITE_CODE_LENGTH)
+        .filter_map(|_| INVITE_CODE_ALPHABET.choose(rng).map(|c| char::from(*c)))
+        .collect()
+}
+
+/// Invite codes are matched ignoring case, spaces and dashes
+pub fn normalize_invite_code(invite_code: &str) -> String {
+    invite_code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_uppercase)
+        .collect()
+}
@@ -1,7 +1,7 @@
 use std::collections::VecDeque;
 use std::time::Instant;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::game::player::PlayerReference;
 use crate::game::spectator::spectator_pointer::SpectatorPointer;
@@ -12,7 +12,7 @@ use super::game_client::GameClient;
 use super::GameClientLocation;
 
 pub type LobbyClientID = u32;
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LobbyClient{
     pub connection: ClientConnection,
@@ -23,7 +23,7 @@ pub struct LobbyClient{
     pub last_message_times: VecDeque<Instant>,
 }
 
-#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum Ready {
     Host,
@@ -31,13 +31,17 @@ pub enum Ready {
     NotReady,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
 #[serde(rename_all = "camelCase")]
 pub enum LobbyClientType{
     Spectator,
     Player{
         name: String,
+    },
+    /// Takes a seat like a player, but is played by the server
+    Bot{
+        name: String,
     }
 }
 
@@ -50,6 +54,15 @@ impl LobbyClient {
             last_message_times: VecDeque::new()
         }
     }
+    /// Bots have nowhere to send packets to, but count as connected so they're never dropped
+    pub fn new_bot(name: String)->Self{
+        LobbyClient{
+            connection: ClientConnection::Connected(ClientSender::detached()),
+            ready: Ready::Ready,
+            client_type: LobbyClientType::Bot{name},
+            last_message_times: VecDeque::new()
+        }
+    }
     pub fn new_from_game_client(game: &Game, game_client: GameClient)->Self{
 
         match game_client.client_location {
@@ -87,6 +100,10 @@ impl LobbyClient {
         matches!(self.client_type, LobbyClientType::Spectator)
     }
 
+    pub fn is_bot(&self) -> bool {
+        matches!(self.client_type, LobbyClientType::Bot { .. })
+    }
+
     pub fn send(&self, message: ToClientPacket) {
         if let ClientConnection::Connected(ref sender) = self.connection {
             sender.send(message);
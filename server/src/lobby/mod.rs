@@ -2,26 +2,39 @@
 pub mod lobby_client;
 pub mod game_client;
 pub mod on_client_message;
+pub mod snapshot;
+pub mod lobby_access;
+pub mod auto_start;
+mod analysis_cache;
+mod blocking;
+mod pending_start;
 mod name_validation;
 
-use std::time::Duration;
+use std::{any::Any, cell::Cell, collections::VecDeque, panic::AssertUnwindSafe, time::Duration};
 
 use lobby_client::Ready;
+use tracing::{error, info, info_span, Span};
 
 use crate::{
-    client_connection::ClientConnection, game::{
-        player::PlayerReference, role_list::RoleOutline, settings::Settings, spectator::{spectator_pointer::SpectatorPointer, SpectatorInitializeParameters}, Game
+    client_connection::{ClientConnection, PacketSequence}, config::Config, game::{
+        bot::Bot, components::event_journal::{EventJournal, GameInput}, player::{PlayerIndex, PlayerInitializeParameters, PlayerReference}, preset::PresetLibrary, role_list::RoleOutline, role_list_analysis::RoleListAnalysis, settings::Settings, spectator::{spectator_pointer::{SpectatorIndex, SpectatorPointer}, SpectatorInitializeParameters}, Game, RejectStartReason
     }, listener::RoomCode, lobby::game_client::GameClientLocation, packet::{
-        HostDataPacketGameClient, RejectJoinReason, ToClientPacket
+        HostDataPacketGameClient, LobbyPreviewData, RejectJoinReason, ToClientPacket
     }, vec_map::VecMap, websocket_connections::connection::ClientSender
 };
 
 
-use self::{game_client::GameClient, lobby_client::{LobbyClient, LobbyClientID, LobbyClientType}};
+use self::{analysis_cache::AnalysisCache, pending_start::PendingStart, auto_start::{AutoStart, StartSettings}, game_client::GameClient, lobby_access::{JoinCredentials, LobbyAccess}, lobby_client::{LobbyClient, LobbyClientID, LobbyClientType}};
 
 pub struct Lobby {
     room_code: RoomCode,
     pub name: String,
+    pub access: LobbyAccess,
+    auto_start: AutoStart,
+    role_list_analysis: AnalysisCache,
+    pending_start: Option<PendingStart>,
+    /// How long a restored game stays open while nobody has rejoined it yet
+    restore_grace: Duration,
     lobby_state: LobbyState,
 }
 
@@ -33,12 +46,15 @@ enum LobbyState {
     Game {
         game: Game,
         clients: VecMap<LobbyClientID, GameClient>,
+        bots: VecMap<LobbyClientID, Bot>,
     },
     Closed
 }
 
-pub const LOBBY_DISCONNECT_TIMER_SECS: u64 = 5;
-pub const GAME_DISCONNECT_TIMER_SECS: u64 = 60 * 2;
+thread_local! {
+    /// Set while this thread is inside [`Lobby::run_isolated`]
+    static PANIC_ISOLATED: Cell<bool> = const { Cell::new(false) };
+}
 
 
 impl Lobby {
@@ -46,6 +62,11 @@ impl Lobby {
         Self { 
             room_code,
             name: name_validation::DEFAULT_SERVER_NAME.to_string(),
+            access: LobbyAccess::new(&mut rand::rng()),
+            auto_start: AutoStart::default(),
+            role_list_analysis: AnalysisCache::default(),
+            pending_start: None,
+            restore_grace: Duration::ZERO,
             lobby_state: LobbyState::Lobby{
                 settings: Settings::default(),
                 clients: VecMap::new()
@@ -53,16 +74,89 @@ impl Lobby {
         }
     }
 
+    /// Runs `f` on this lobby, catching any panic so that only this lobby goes down.
+    /// A lobby that panics is closed, and its clients are told why.
+    pub fn run_isolated(&mut self, f: impl FnOnce(&mut Lobby)) {
+        let _span = self.span().entered();
+
+        if let Err(payload) = Self::catch_panic(|| f(self)) {
+            error!(phase = self.phase_description(), panic = Self::panic_message(&payload), "Lobby panicked");
+
+            self.send_to_all(ToClientPacket::LobbyCrashed);
+            self.lobby_state = LobbyState::Closed;
+        }
+    }
+
+    /// Whether a panic on this thread will be caught by [`Lobby::run_isolated`]
+    pub fn is_panic_isolated() -> bool {
+        PANIC_ISOLATED.with(|isolated| isolated.get())
+    }
+
+    /// Runs `f`, catching any panic without it being treated as a server crash
+    fn catch_panic<T>(f: impl FnOnce() -> T) -> std::thread::Result<T> {
+        let was_isolated = PANIC_ISOLATED.with(|isolated| isolated.replace(true));
+        let result = std::panic::catch_unwind(AssertUnwindSafe(f));
+        PANIC_ISOLATED.with(|isolated| isolated.set(was_isolated));
+        result
+    }
+
+    /// Everything logged while running this lobby is tagged with these fields
+    fn span(&self) -> Span {
+        match &self.lobby_state {
+            LobbyState::Game { game, .. } => info_span!("lobby",
+                room_code = self.room_code,
+                phase = ?game.current_phase().phase(),
+                day = game.day_number()
+            ),
+            _ => info_span!("lobby", room_code = self.room_code),
+        }
+    }
+
+    fn phase_description(&self) -> String {
+        match &self.lobby_state {
+            LobbyState::Lobby { .. } => "lobby".to_string(),
+            LobbyState::Game { game, .. } => format!("{:?} {}", game.current_phase().phase(), game.day_number()),
+            LobbyState::Closed => "closed".to_string(),
+        }
+    }
+
+    fn panic_message(payload: &Box<dyn Any + Send>) -> &str {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            message
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message
+        } else {
+            "unknown panic"
+        }
+    }
+
+    /// Label for the lobby's state in metrics. Closed lobbies are removed on the tick they close, so they aren't counted.
+    pub fn state_name(&self) -> Option<&'static str> {
+        match self.lobby_state {
+            LobbyState::Lobby { .. } => Some("lobby"),
+            LobbyState::Game { .. } => Some("game"),
+            LobbyState::Closed => None,
+        }
+    }
+
     pub fn is_in_game(&self) -> bool {
         matches!(self.lobby_state, LobbyState::Game { .. })
     }
 
+    /// In a game that hasn't reached its conclusion yet
+    pub fn is_game_in_progress(&self) -> bool {
+        matches!(&self.lobby_state, LobbyState::Game { game, .. } if !game.game_is_over())
+    }
+
     pub fn set_rolelist_length(settings: &mut Settings, clients: &VecMap<LobbyClientID, LobbyClient>) {
-        let length = clients.iter()
-            .filter(|p| matches!(p.1.client_type, LobbyClientType::Player{..}))
-            .count();
+        settings.role_list.0.resize(Self::player_count(clients), RoleOutline::default());
+    }
 
-        settings.role_list.0.resize(length, RoleOutline::default());
+    /// Clients who take a seat when the game starts
+    pub fn player_count(clients: &VecMap<LobbyClientID, LobbyClient>) -> usize {
+        clients.iter()
+            .filter(|p| matches!(p.1.client_type, LobbyClientType::Player{..} | LobbyClientType::Bot{..}))
+            .count()
     }
 
     pub fn send_to_client_by_id(&self, lobby_client_id: LobbyClientID, packet: ToClientPacket) {
@@ -92,7 +186,7 @@ impl Lobby {
 
     pub fn get_player_names(clients: &VecMap<LobbyClientID, LobbyClient>) -> Vec<String> {
         clients.values().filter_map(|p| {
-            if let LobbyClientType::Player { name } = p.client_type.clone() {
+            if let LobbyClientType::Player { name } | LobbyClientType::Bot { name } = p.client_type.clone() {
                 Some(name)
             } else {
                 None
@@ -100,11 +194,46 @@ impl Lobby {
         }).collect()
     }
 
-    pub fn join_player(&mut self, send: &ClientSender) -> Result<LobbyClientID, RejectJoinReason>{
+    pub fn add_bot(&mut self) -> Result<LobbyClientID, RejectJoinReason> {
+        let LobbyState::Lobby { clients, settings } = &mut self.lobby_state else {
+            return Err(RejectJoinReason::GameAlreadyStarted);
+        };
+        if clients.len() >= Config::get().max_clients_per_lobby || self.auto_start.settings.is_full(Self::player_count(clients)) {
+            return Err(RejectJoinReason::RoomFull);
+        }
+
+        let name = name_validation::sanitize_name("".to_string(), &Self::get_player_names(clients), &mut rand::rng());
+        let Some(lobby_client_id) =
+            (clients
+                .iter()
+                .map(|(i,_)|*i)
+                .fold(0u32, u32::max) as LobbyClientID).checked_add(1) else {
+                    return Err(RejectJoinReason::RoomFull)
+                };
+
+        clients.insert(lobby_client_id, LobbyClient::new_bot(name));
+
+        Lobby::set_rolelist_length(settings, clients);
+
+        Self::send_players_lobby(clients);
+        for player in clients.iter(){
+            Self::send_settings(player.1, settings, self.name.clone(), &self.access, &self.auto_start.settings)
+        }
+        Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
+
+        Ok(lobby_client_id)
+    }
+
+    pub fn join_player(&mut self, send: &ClientSender, credentials: &JoinCredentials) -> Result<LobbyClientID, RejectJoinReason>{
+        self.access.check(credentials)?;
+
         match &mut self.lobby_state {
             LobbyState::Lobby { clients, settings } => {
+                if clients.len() >= Config::get().max_clients_per_lobby {
+                    return Err(RejectJoinReason::RoomFull);
+                }
 
-                let name = name_validation::sanitize_name("".to_string(), &Self::get_player_names(clients));
+                let name = name_validation::sanitize_name("".to_string(), &Self::get_player_names(clients), &mut rand::rng());
                 
                 let mut new_player = LobbyClient::new(name.clone(), send.clone(), clients.is_empty());
                 let Some(lobby_client_id) =
@@ -120,21 +249,31 @@ impl Lobby {
                     new_player.set_host();
                 }
 
+                let spectator = self.auto_start.settings.is_full(Self::player_count(clients));
+                if spectator {
+                    new_player.client_type = LobbyClientType::Spectator;
+                }
+
                 clients.insert(lobby_client_id, new_player);
 
                 Lobby::set_rolelist_length(settings, clients);
 
-                send.send(ToClientPacket::AcceptJoin{room_code: self.room_code, in_game: false, player_id: lobby_client_id, spectator: false});
+                send.send(ToClientPacket::AcceptJoin{room_code: self.room_code, in_game: false, player_id: lobby_client_id, spectator});
 
                 Self::send_players_lobby(clients);
 
                 for player in clients.iter(){
-                    Self::send_settings(player.1, settings, self.name.clone())
+                    Self::send_settings(player.1, settings, self.name.clone(), &self.access, &self.auto_start.settings)
                 }
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
+                self.update_start_countdown();
                 
                 Ok(lobby_client_id)
             },
-            LobbyState::Game{ clients, game} => {
+            LobbyState::Game{ clients, game, .. } => {
+                if clients.len() >= Config::get().max_clients_per_lobby {
+                    return Err(RejectJoinReason::RoomFull);
+                }
 
                 let is_host = !clients.iter().any(|p|p.1.host);
                 
@@ -178,12 +317,12 @@ impl Lobby {
             LobbyState::Lobby { clients, settings } => {
                 let player = clients.remove(&lobby_client_id);
         
-                if clients.is_empty() {
+                if clients.values().all(LobbyClient::is_bot) {
                     self.lobby_state = LobbyState::Closed;
                     return;
                 }
                 if !clients.iter().any(|p|p.1.is_host()) {
-                    if let Some(new_host) = clients.values_mut().next(){
+                    if let Some(new_host) = clients.values_mut().find(|client| !client.is_bot()){
                         new_host.set_host();
                     }
                 }
@@ -194,27 +333,23 @@ impl Lobby {
 
                 Self::send_players_lobby(clients);
                 for player in clients.iter(){
-                    Self::send_settings(player.1, settings, self.name.clone());
+                    Self::send_settings(player.1, settings, self.name.clone(), &self.access, &self.auto_start.settings);
                 }
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
             },
-            LobbyState::Game { game, clients } => {
+            LobbyState::Game { game, clients, bots } => {
+                bots.remove(&lobby_client_id);
                 let Some(game_player) = clients.get_mut(&lobby_client_id) else {return};
                 match game_player.client_location {
                     GameClientLocation::Player(player_index) => {
                         if let Ok(player_ref) = PlayerReference::new(game, player_index) {
+                            EventJournal::record_input(game, GameInput::Quit { player: player_index });
                             player_ref.quit(game);
                         }
                     },
                     GameClientLocation::Spectator(idx) => {
                         clients.remove(&lobby_client_id);
-                        for client in clients.iter_mut() {
-                            if let GameClientLocation::Spectator(index) = &mut client.1.client_location {
-                                if *index > idx {
-                                    *index = index.saturating_sub(1);
-                                }
-                            }
-                        }
-                        game.remove_spectator(idx);
+                        Self::remove_spectator(game, clients, idx);
                     }
                 }
 
@@ -237,24 +372,26 @@ impl Lobby {
                 }
 
                 client.connection = ClientConnection::CouldReconnect { 
-                    disconnect_timer: Duration::from_secs(LOBBY_DISCONNECT_TIMER_SECS)
+                    disconnect_timer: Config::get().lobby_disconnect_timer()
                 };
 
                 if !clients.iter().any(|p|p.1.is_host()) {
-                    if let Some(new_host) = clients.values_mut().next(){
+                    if let Some(new_host) = clients.values_mut().find(|client| !client.is_bot()){
                         new_host.set_host();
                     }
+                    Self::send_visibility(clients, &self.access);
                 }
 
                 Self::send_players_lobby(clients);
                 
             },
-            LobbyState::Game {game, clients: players} => {
+            LobbyState::Game {game, clients: players, ..} => {
                 let Some(game_player) = players.get_mut(&id) else {return};
 
                 if let GameClientLocation::Player(player_index) = game_player.client_location {
                     if let Ok(player_ref) = PlayerReference::new(game, player_index) {
                         if !player_ref.is_disconnected(game) {
+                            EventJournal::record_input(game, GameInput::LoseConnection { player: player_index });
                             player_ref.lose_connection(game);
 
                             Self::resend_host_data_to_all_hosts(game, players);
@@ -266,7 +403,16 @@ impl Lobby {
             LobbyState::Closed => {}
         }
     }
-    pub fn rejoin_player(&mut self, send: &ClientSender, lobby_client_id: LobbyClientID) -> Result<(), RejectJoinReason>{
+    /// `credentials` are checked like they are for a new client, so a private lobby can't be got into by rejoining.
+    /// In a game, a player who gives `last_sequence` is only sent the packets they missed, if those are still buffered
+    pub fn rejoin_player(
+        &mut self, send: &ClientSender, lobby_client_id: LobbyClientID, credentials: &JoinCredentials, last_sequence: Option<PacketSequence>
+    ) -> Result<(), RejectJoinReason>{
+        if let Err(reason) = self.access.check(credentials) {
+            send.send(ToClientPacket::RejectJoin{reason});
+            return Err(reason)
+        }
+
         match &mut self.lobby_state {
             LobbyState::Lobby { clients: players, settings } => {
                 let Some(player) = players.get_mut(&lobby_client_id) else {
@@ -277,7 +423,11 @@ impl Lobby {
                     player.connection = ClientConnection::Connected(send.clone());
                     send.send(ToClientPacket::AcceptJoin{room_code: self.room_code, in_game: false, player_id: lobby_client_id, spectator: false});
 
-                    Self::send_settings(player, settings, self.name.clone());
+                    Self::send_settings(player, settings, self.name.clone(), &self.access, &self.auto_start.settings);
+                    // Otherwise it's sent once it's been worked out
+                    if let Some(analysis) = self.role_list_analysis.get(settings) {
+                        player.send(ToClientPacket::RoleListAnalysis { analysis: analysis.clone() });
+                    }
                     Self::send_players_lobby(players);
                     
                     Ok(())
@@ -286,7 +436,7 @@ impl Lobby {
                     Err(RejectJoinReason::PlayerDoesntExist)
                 }
             },
-            LobbyState::Game { game, clients: players } => {
+            LobbyState::Game { game, clients: players, .. } => {
                 let Some(game_player) = players.get_mut(&lobby_client_id) else {
                     send.send(ToClientPacket::RejectJoin{reason: RejectJoinReason::PlayerDoesntExist});
                     return Err(RejectJoinReason::PlayerDoesntExist)
@@ -302,8 +452,18 @@ impl Lobby {
                         return Err(RejectJoinReason::PlayerTaken)
                     };
     
-                    send.send(ToClientPacket::AcceptJoin{room_code: self.room_code, in_game: true, player_id: lobby_client_id, spectator: false});
-                    player_ref.connect(game, send.clone());
+                    if let Some(missed_packets) = last_sequence.and_then(|last_sequence| player_ref.missed_packets(game, last_sequence)) {
+                        send.send(ToClientPacket::ResumeAccepted);
+                        EventJournal::record_input(game, GameInput::Resume { player: player_ref.index() });
+                        player_ref.resume(game, send.clone());
+                        for packet in missed_packets {
+                            send.send(packet);
+                        }
+                    } else {
+                        send.send(ToClientPacket::AcceptJoin{room_code: self.room_code, in_game: true, player_id: lobby_client_id, spectator: false});
+                        EventJournal::record_input(game, GameInput::Reconnect { player: player_ref.index() });
+                        player_ref.connect(game, send.clone());
+                    }
 
                     send.send(ToClientPacket::PlayersHost{hosts:
                         players
@@ -332,10 +492,10 @@ impl Lobby {
         let mut other_players = clients.clone();
         other_players.remove(&lobby_client_id);
         
-        let new_name: String = name_validation::sanitize_name(name, &Self::get_player_names(&other_players));
+        let new_name: String = name_validation::sanitize_name(name, &Self::get_player_names(&other_players), &mut rand::rng());
 
         if let Some(player) = clients.get_mut(&lobby_client_id){
-            if let LobbyClientType::Player { name } = &mut player.client_type {
+            if let LobbyClientType::Player { name } | LobbyClientType::Bot { name } = &mut player.client_type {
                 *name = new_name;
             }
         }
@@ -351,7 +511,7 @@ impl Lobby {
 
         other_players.remove(player_ref.index() as usize);
         
-        let new_name: String = name_validation::sanitize_name(name, &other_players);
+        let new_name: String = name_validation::sanitize_name(name, &other_players, &mut game.rng);
 
         player_ref.set_name(game, new_name);
     }
@@ -360,16 +520,157 @@ impl Lobby {
         matches!(self.lobby_state, LobbyState::Closed)
     }
 
+    /// Starts dealing roles, and starts the game once they're dealt. See [`Lobby::finish_start`]
+    pub fn start_game(&mut self) -> Result<(), RejectStartReason> {
+        let LobbyState::Lobby { settings, clients } = &mut self.lobby_state else {
+            return Err(RejectStartReason::GameAlreadyStarted);
+        };
+
+        let player_count = Self::player_count(clients);
+        self.auto_start.settings.check_player_count(player_count)?;
+
+        settings.role_list.simplify();
+        if self.pending_start.as_ref().is_some_and(|pending| pending.is_for(settings, player_count)) {
+            return Ok(());
+        }
+        self.pending_start = Some(PendingStart::deal(settings, player_count));
+
+        let role_list = settings.role_list.clone();
+        self.send_to_all(ToClientPacket::RoleList { role_list });
+
+        self.finish_start()
+    }
+
+    /// Seats every player and bot, and leaves spectators spectating, once the roles are dealt.
+    /// If the settings or players changed while they were dealt, they're dealt again.
+    fn finish_start(&mut self) -> Result<(), RejectStartReason> {
+        let Some(result) = self.pending_start.as_mut().and_then(PendingStart::poll) else {
+            return Ok(());
+        };
+        let Some(pending) = self.pending_start.take() else {return Ok(())};
+        let LobbyState::Lobby { settings, clients } = &self.lobby_state else {return Ok(())};
+        if !pending.is_for(settings, Self::player_count(clients)) {
+            return self.start_game();
+        }
+        let roles = match result {
+            Ok(roles) => roles,
+            Err(err) => {
+                info!(reason = ?err, "Failed to start game");
+                return Err(err)
+            }
+        };
+
+        let mut game_clients: VecMap<LobbyClientID, GameClient> = VecMap::new();
+        let mut bot_seats: Vec<(LobbyClientID, PlayerIndex)> = Vec::new();
+        let mut game_player_params = Vec::new();
+        let mut game_spectator_params = Vec::new();
+
+
+        let LobbyState::Lobby { settings, clients} = &mut self.lobby_state else {
+            unreachable!("LobbyState::Lobby was checked to be to LobbyState::Lobby in the previous line")
+        };
+
+        let mut next_player_index: PlayerIndex = 0;
+        let mut next_spectator_index: SpectatorIndex = 0;
+
+        for (lobby_client_id, lobby_client) in clients.clone() {
+            
+            game_clients.insert(lobby_client_id, 
+                if let LobbyClientType::Spectator = lobby_client.client_type {
+                    GameClient {
+                        client_location: GameClientLocation::Spectator(next_spectator_index),
+                        host: lobby_client.is_host(),
+                        last_message_times: VecDeque::new(),
+                    }
+                } else {
+                    GameClient {
+                        client_location: GameClientLocation::Player(next_player_index),
+                        host: lobby_client.is_host(),
+                        last_message_times: VecDeque::new(),
+                    }
+                }
+            );
+            
+            if lobby_client.is_bot() {
+                bot_seats.push((lobby_client_id, next_player_index));
+            }
+            
+            match lobby_client.client_type {
+                LobbyClientType::Player { ref name } | LobbyClientType::Bot { ref name } => {
+                    game_player_params.push(PlayerInitializeParameters{
+                        host: lobby_client.is_host(),
+                        connection: lobby_client.connection,
+                        name: name.clone(),
+                    });
+                    next_player_index = next_player_index.checked_add(1).ok_or(RejectStartReason::TooManyClients)?;
+                },
+                LobbyClientType::Spectator => {
+                    game_spectator_params.push(SpectatorInitializeParameters{
+                        host: lobby_client.is_host(),
+                        connection: lobby_client.connection,
+                    });
+                    next_spectator_index = next_spectator_index.checked_add(1).ok_or(RejectStartReason::TooManyClients)?;
+                }
+            }
+        }
+
+        let game = match Game::new_with_roles(settings.clone(), roles, game_player_params, game_spectator_params){
+            Ok(game) => game,
+            Err(err) => {
+                info!(reason = ?err, "Failed to start game");
+                return Err(err)
+            }
+        };
+        
+        info!(room_code = self.room_code, "Game started");
+
+        let bots = bot_seats.into_iter()
+            .map(|(id, player)| (id, Bot::heuristic(game.seed, player)))
+            .collect();
+
+        self.auto_start = AutoStart::new(self.auto_start.settings.clone());
+        self.lobby_state = LobbyState::Game{
+            game,
+            clients: game_clients,
+            bots,
+        };
+        let LobbyState::Game { game, .. } = &mut self.lobby_state else {
+            unreachable!("LobbyState::Game was set to be to LobbyState::Game in the previous line");
+        };
+
+        Lobby::send_players_game(game);
+        
+        self.send_to_all(ToClientPacket::LobbyName { name: self.name.clone() });
+        Ok(())
+    }
+
     pub fn tick(&mut self, time_passed: Duration){
         match &mut self.lobby_state {
-            LobbyState::Game { game, .. } => {
+            LobbyState::Game { game, clients, bots } => {
                 game.tick(time_passed);
+
+                for (id, bot) in bots.iter_mut() {
+                    if let Some(GameClient { client_location: GameClientLocation::Player(index), .. }) = clients.get(id) {
+                        if let Ok(player) = PlayerReference::new(game, *index) {
+                            bot.tick(game, player);
+                        }
+                    }
+                }
                 
-                if !game.is_any_client_connected() {
+                self.restore_grace = self.restore_grace.saturating_sub(time_passed);
+                if !Self::is_any_human_connected(game, clients, bots) && self.restore_grace.is_zero() {
                     self.lobby_state = LobbyState::Closed;
                 }
             }
             LobbyState::Lobby { settings: _settings, clients: players } => {
+                if let Some(analysis) = self.role_list_analysis.poll() {
+                    Self::send_analysis_to_all(players, analysis);
+                }
+                if let Err(reason) = self.finish_start() {
+                    self.reject_start(reason);
+                }
+                let LobbyState::Lobby { clients: players, .. } = &mut self.lobby_state else {return};
+
                 let mut to_remove = vec![];
 
                 for player in players.iter_mut() {
@@ -388,23 +689,34 @@ impl Lobby {
                 for player in to_remove {
                     self.remove_player(player);
                 }
+
+                self.tick_auto_start(time_passed);
             },
             LobbyState::Closed => {}
         }
     }
 
+    pub fn preview_data(&self) -> LobbyPreviewData {
+        LobbyPreviewData {
+            name: self.name.clone(),
+            in_game: self.is_in_game(),
+            password_protected: self.access.is_password_protected(),
+            players: self.get_player_list()
+        }
+    }
+
     pub fn get_player_list(&self)->Vec<(LobbyClientID, String)>{
         match &self.lobby_state {
             LobbyState::Lobby { settings:_, clients: players } => {
                 players.iter().filter_map(|p|
-                    if let LobbyClientType::Player { name } = &p.1.client_type {
+                    if let LobbyClientType::Player { name } | LobbyClientType::Bot { name } = &p.1.client_type {
                         Some((*p.0, name.clone()))
                     }else{
                         None
                     }
                 ).collect()
             },
-            LobbyState::Game { game, clients: players } => {
+            LobbyState::Game { game, clients: players, .. } => {
                 players.iter()
                     .filter_map(|(id, player)|
                         if let GameClientLocation::Player(player_index) = player.client_location {
@@ -443,12 +755,53 @@ impl Lobby {
     }
 
     /// Catches the sender up with the current lobby settings
-    pub fn send_settings(client: &LobbyClient, settings: &Settings, name: String) {
+    pub fn send_settings(client: &LobbyClient, settings: &Settings, name: String, access: &LobbyAccess, start_settings: &StartSettings) {
         client.send(ToClientPacket::LobbyName { name });
+        client.send(ToClientPacket::LobbyVisibility { visibility: access.visibility_for(client.is_host()) });
+        client.send(ToClientPacket::LobbyInviteCode { invite_code: access.invite_code.clone() });
+        client.send(ToClientPacket::LobbyStartSettings { start_settings: start_settings.clone() });
         client.send(ToClientPacket::PhaseTimes { phase_time_settings: settings.phase_times.clone() });
         client.send(ToClientPacket::RoleList { role_list: settings.role_list.clone() });
         client.send(ToClientPacket::EnabledRoles { roles: settings.enabled_roles.clone().into_iter().collect() });
         client.send(ToClientPacket::EnabledModifiers { modifiers: settings.enabled_modifiers.clone().into_iter().collect() });
+        client.send(ToClientPacket::RoleWeights { role_weights: settings.role_weights.clone() });
+        client.send(ToClientPacket::RoleConstraints { constraints: settings.role_constraints.clone() });
+        client.send(ToClientPacket::CustomRoleSets { custom_role_sets: settings.custom_role_sets.clone() });
+        client.send(ToClientPacket::Presets { presets: PresetLibrary::get().summaries() });
+    }
+
+    /// Sent again whenever the host changes, so only the current hosts know the password
+    fn send_visibility(clients: &VecMap<LobbyClientID, LobbyClient>, access: &LobbyAccess) {
+        for client in clients.values() {
+            client.send(ToClientPacket::LobbyVisibility { visibility: access.visibility_for(client.is_host()) });
+        }
+    }
+
+    fn send_visibility_to_all(&self) {
+        match &self.lobby_state {
+            LobbyState::Lobby { clients, .. } => Self::send_visibility(clients, &self.access),
+            LobbyState::Game { game, clients, .. } => {
+                game.send_packet_to_all(ToClientPacket::LobbyVisibility { visibility: self.access.visibility_for(false) });
+                for host_sender in Self::host_senders(game, clients) {
+                    host_sender.send(ToClientPacket::LobbyVisibility { visibility: self.access.visibility_for(true) });
+                }
+            }
+            LobbyState::Closed => {}
+        }
+    }
+
+    /// Sends the analysis right away if the settings it depends on haven't changed, otherwise once it's been worked out
+    fn send_role_list_analysis(cache: &mut AnalysisCache, clients: &VecMap<LobbyClientID, LobbyClient>, settings: &Settings) {
+        if let Some(analysis) = cache.request(settings) {
+            Self::send_analysis_to_all(clients, analysis);
+        }
+    }
+
+    fn send_analysis_to_all(clients: &VecMap<LobbyClientID, LobbyClient>, analysis: &RoleListAnalysis) {
+        let packet = ToClientPacket::RoleListAnalysis { analysis: analysis.clone() };
+        for client in clients.values() {
+            client.send(packet.clone());
+        }
     }
 
     //send the list of players to all players while in the lobby
@@ -503,17 +856,121 @@ impl Lobby {
         }
     }
 
+    /// Removes a spectator from the game, and moves the clients of the spectators after it down
+    fn remove_spectator(game: &mut Game, clients: &mut VecMap<LobbyClientID, GameClient>, idx: SpectatorIndex) {
+        for client in clients.values_mut() {
+            if let GameClientLocation::Spectator(index) = &mut client.client_location {
+                if *index > idx {
+                    *index = index.saturating_sub(1);
+                }
+            }
+        }
+        game.remove_spectator(idx);
+    }
+
+    /// Hands a player who quit to `lobby_client_id`, who has to be spectating
+    pub fn claim_player(&mut self, send: &ClientSender, lobby_client_id: LobbyClientID, player_index: PlayerIndex) -> Result<(), RejectJoinReason> {
+        let LobbyState::Game { game, clients, bots } = &mut self.lobby_state else {
+            return Err(RejectJoinReason::PlayerDoesntExist);
+        };
+        let Some(GameClient { client_location: GameClientLocation::Spectator(spectator_index), .. }) = clients.get(&lobby_client_id) else {
+            return Err(RejectJoinReason::PlayerTaken);
+        };
+        let spectator_index = *spectator_index;
+        let Ok(player_ref) = PlayerReference::new(game, player_index) else {
+            return Err(RejectJoinReason::PlayerDoesntExist);
+        };
+        if !player_ref.is_abandoned(game) {
+            return Err(RejectJoinReason::PlayerTaken);
+        }
+
+        // Whoever quit can't rejoin as this player anymore
+        if let Some(old_id) = Self::player_client_id(clients, player_index) {
+            clients.remove(&old_id);
+            bots.remove(&old_id);
+        }
+        Self::remove_spectator(game, clients, spectator_index);
+        let Some(client) = clients.get_mut(&lobby_client_id) else {
+            return Err(RejectJoinReason::PlayerDoesntExist);
+        };
+        client.client_location = GameClientLocation::Player(player_index);
+
+        send.send(ToClientPacket::AcceptJoin{room_code: self.room_code, in_game: true, player_id: lobby_client_id, spectator: false});
+        EventJournal::record_input(game, GameInput::Substitute { player: player_index, bot: false });
+        player_ref.substitute(game, send.clone(), false);
+
+        send.send(ToClientPacket::PlayersHost{hosts:
+            clients
+                .iter()
+                .filter(|p|p.1.host)
+                .map(|p|*p.0)
+                .collect()
+        });
+        Self::resend_host_data_to_all_hosts(game, clients);
+
+        Ok(())
+    }
+
+    /// Hands a player who quit to a bot, which takes the seat of the client who quit
+    pub fn substitute_bot(&mut self, player_index: PlayerIndex) -> Result<(), RejectJoinReason> {
+        let LobbyState::Game { game, clients, bots } = &mut self.lobby_state else {
+            return Err(RejectJoinReason::PlayerDoesntExist);
+        };
+        let Ok(player_ref) = PlayerReference::new(game, player_index) else {
+            return Err(RejectJoinReason::PlayerDoesntExist);
+        };
+        if !player_ref.is_abandoned(game) {
+            return Err(RejectJoinReason::PlayerTaken);
+        }
+        let Some(id) = Self::player_client_id(clients, player_index) else {
+            return Err(RejectJoinReason::PlayerDoesntExist);
+        };
+
+        bots.insert(id, Bot::heuristic(game.seed, player_index));
+        EventJournal::record_input(game, GameInput::Substitute { player: player_index, bot: true });
+        player_ref.substitute(game, ClientSender::detached(), true);
+
+        Self::resend_host_data_to_all_hosts(game, clients);
+
+        Ok(())
+    }
+
+    fn player_client_id(clients: &VecMap<LobbyClientID, GameClient>, player_index: PlayerIndex) -> Option<LobbyClientID> {
+        clients.iter()
+            .find(|(_, client)| matches!(client.client_location, GameClientLocation::Player(index) if index == player_index))
+            .map(|(id, _)| *id)
+    }
+
+    /// Bots are always connected, so they can't keep a game open by themselves
+    fn is_any_human_connected(game: &Game, clients: &VecMap<LobbyClientID, GameClient>, bots: &VecMap<LobbyClientID, Bot>) -> bool {
+        clients.iter()
+            .filter(|(id, _)| !bots.contains(id))
+            .any(|(_, client)| match client.client_location {
+                GameClientLocation::Player(index) => PlayerReference::new(game, index)
+                    .is_ok_and(|player| player.is_connected(game)),
+                GameClientLocation::Spectator(index) => SpectatorPointer::new(index).is_connected(game),
+            })
+    }
+
     fn resend_host_data_to_all_hosts(game: &Game, clients: &VecMap<LobbyClientID, GameClient>) {
-        for client in clients.values().filter(|client| client.host) {
-            let client_connection = match client.client_location {
+        for host_sender in Self::host_senders(game, clients) {
+            Self::resend_host_data(game, clients, &host_sender)
+        }
+    }
+
+    /// Hosts who are connected right now
+    fn host_senders(game: &Game, clients: &VecMap<LobbyClientID, GameClient>) -> Vec<ClientSender> {
+        clients.values()
+            .filter(|client| client.host)
+            .filter_map(|client| match client.client_location {
                 GameClientLocation::Player(index) => PlayerReference::new(game, index).map(|p| p.connection(game).clone()).ok(),
                 GameClientLocation::Spectator(index) => Some(SpectatorPointer::new(index).connection(game))
-            };
-
-            if let Some(ClientConnection::Connected(host_sender)) = client_connection {
-                Self::resend_host_data(game, clients, &host_sender)
-            }
-        }
+            })
+            .filter_map(|connection| match connection {
+                ClientConnection::Connected(host_sender) => Some(host_sender),
+                _ => None,
+            })
+            .collect()
     }
     
     fn resend_host_data(game: &Game, clients: &VecMap<LobbyClientID, GameClient>, send: &ClientSender) {
@@ -1,15 +1,16 @@
-use std::{collections::VecDeque, time::{Duration, Instant}};
+use std::time::Instant;
 
-use crate::{game::{chat::{ChatMessage, ChatMessageVariant}, event::{on_fast_forward::OnFastForward, on_game_ending::OnGameEnding}, game_conclusion::GameConclusion, phase::PhaseType, player::{PlayerIndex, PlayerInitializeParameters, PlayerReference}, spectator::{spectator_pointer::SpectatorIndex, SpectatorInitializeParameters}, Game, RejectStartReason}, lobby::game_client::{GameClient, GameClientLocation}, log, packet::{ToClientPacket, ToServerPacket}, strings::TidyableString, vec_map::VecMap, websocket_connections::connection::ClientSender};
+use tracing::warn;
 
-use super::{lobby_client::{LobbyClient, LobbyClientID, LobbyClientType, Ready}, name_validation::{self, sanitize_server_name}, Lobby, LobbyState};
+use crate::{config::Config, metrics, game::{chat::{ChatMessage, ChatMessageVariant}, components::event_journal::{EventJournal, GameInput}, event::{on_fast_forward::OnFastForward, on_game_ending::OnGameEnding}, game_conclusion::GameConclusion, player::PlayerReference, preset::PresetLibrary, role_generation::RoleConstraint, role_list::CustomRoleSet, role_list_notation::{validate_custom_role_sets, validate_embedded_custom_role_sets}}, lobby::game_client::GameClientLocation, packet::{ToClientPacket, ToServerPacket}, strings::TidyableString, vec_map::VecMap, websocket_connections::connection::ClientSender};
 
-pub const MESSAGE_PER_SECOND_LIMIT: u64 = 1;
-pub const MESSAGE_PER_SECOND_LIMIT_TIME: Duration = Duration::from_secs(10);
+use super::{lobby_client::{LobbyClient, LobbyClientID, LobbyClientType, Ready}, name_validation::{self, sanitize_server_name}, Lobby, LobbyState};
 
 impl Lobby {
     pub fn on_client_message(&mut self, send: &ClientSender, lobby_client_id: LobbyClientID, incoming_packet: ToServerPacket){
 
+        self.reset_host_idle(lobby_client_id);
+
         //RATE LIMITER
         match incoming_packet {
             ToServerPacket::Judgement { .. } |
@@ -22,7 +23,7 @@ impl Lobby {
                         if let Some(game_player) = clients.get_mut(&lobby_client_id) {
                             &mut game_player.last_message_times
                         } else {
-                            log!(error "LobbyState::Game"; "{} {:?}", "Message recieved from player not in game", incoming_packet);
+                            warn!(lobby_client_id, packet = ?incoming_packet, "Message received from player not in game");
                             return;
                         }
                     },
@@ -30,26 +31,27 @@ impl Lobby {
                         if let Some(lobby_client) = clients.get_mut(&lobby_client_id) {
                             &mut lobby_client.last_message_times
                         } else {
-                            log!(error "LobbyState::Lobby"; "{} {:?}", "Message recieved from player not in lobby", incoming_packet);
+                            warn!(lobby_client_id, packet = ?incoming_packet, "Message received from player not in lobby");
                             return;
                         }
                     }
                     LobbyState::Closed => {
-                        log!(error "LobbyState::Closed"; "{} {:?}", "Message recieved from player in closed lobby", incoming_packet);
+                        warn!(lobby_client_id, packet = ?incoming_packet, "Message received from player in closed lobby");
                         return;
                     }
                 };
 
                 let now = Instant::now();
                 while let Some(time) = last_message_times.front() {
-                    if now.duration_since(*time) > MESSAGE_PER_SECOND_LIMIT_TIME {
+                    if now.duration_since(*time) > Config::get().message_rate_limit_window() {
                         last_message_times.pop_front();
                     } else {
                         break;
                     }
                 }
-                if last_message_times.len() as u64 >= MESSAGE_PER_SECOND_LIMIT_TIME.as_secs() * MESSAGE_PER_SECOND_LIMIT {
+                if last_message_times.len() as u64 >= Config::get().messages_per_rate_limit_window() {
                     send.send(ToClientPacket::RateLimitExceeded);
+                    metrics::rate_limit_rejected();
                     return;
                 }
                 last_message_times.push_back(now);
@@ -63,7 +65,7 @@ impl Lobby {
         match incoming_packet {
             ToServerPacket::SendLobbyMessage { text } => {
                 let LobbyState::Lobby { clients, .. } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "ToServerPacket::SendLobbyMessage can not be used outside of LobbyState::Lobby", lobby_client_id);
+                    warn!(lobby_client_id, "ToServerPacket::SendLobbyMessage can not be used outside of LobbyState::Lobby");
                     return
                 };
 
@@ -87,15 +89,16 @@ impl Lobby {
             }
             ToServerPacket::SetSpectator { spectator } => {
                 let LobbyState::Lobby { clients, settings } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "ToServerPacket::SetName can not be used outside of LobbyState::Lobby", lobby_client_id);
+                    warn!(lobby_client_id, "ToServerPacket::SetName can not be used outside of LobbyState::Lobby");
                     return
                 };
                 
-                let new_name = name_validation::sanitize_name("".to_string(), &Self::get_player_names(clients));
+                let new_name = name_validation::sanitize_name("".to_string(), &Self::get_player_names(clients), &mut rand::rng());
+                let full = self.auto_start.settings.is_full(Self::player_count(clients));
                 if let Some(player) = clients.get_mut(&lobby_client_id){
                     match &player.client_type {
                         LobbyClientType::Spectator => {
-                            if !spectator {
+                            if !spectator && !full {
                                 player.client_type = LobbyClientType::Player { name: new_name}
                             }
                         },
@@ -104,17 +107,19 @@ impl Lobby {
                                 player.client_type = LobbyClientType::Spectator;
                             }
                         },
+                        LobbyClientType::Bot { .. } => {}
                     }
                 }
 
                 Lobby::set_rolelist_length(settings, clients);
                 Self::send_players_lobby(clients);
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
                 let role_list = settings.role_list.clone();
                 self.send_to_all(ToClientPacket::RoleList { role_list } );
             }
             ToServerPacket::SetName{ name } => {
                 let LobbyState::Lobby { clients, .. } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "ToServerPacket::SetName can not be used outside of LobbyState::Lobby", lobby_client_id);
+                    warn!(lobby_client_id, "ToServerPacket::SetName can not be used outside of LobbyState::Lobby");
                     return
                 };
 
@@ -122,7 +127,7 @@ impl Lobby {
             },
             ToServerPacket::ReadyUp{ ready } => {
                 let LobbyState::Lobby { clients, .. } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "ToServerPacket::ReadyUp can not be used outside of LobbyState::Lobby", lobby_client_id);
+                    warn!(lobby_client_id, "ToServerPacket::ReadyUp can not be used outside of LobbyState::Lobby");
                     return
                 };
 
@@ -140,10 +145,11 @@ impl Lobby {
                     }
                 }
                 Self::send_to_all(self, ToClientPacket::PlayersReady { ready });
+                self.update_start_countdown();
             },
             ToServerPacket::SetLobbyName{ name } => {
                 let LobbyState::Lobby { .. } = self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "ToServerPacket::SetLobbyName can not be used outside of LobbyState::Lobby", lobby_client_id);
+                    warn!(lobby_client_id, "ToServerPacket::SetLobbyName can not be used outside of LobbyState::Lobby");
                     return
                 };
 
@@ -160,129 +166,62 @@ impl Lobby {
                 
                 self.send_to_all(ToClientPacket::LobbyName { name })
             },
-            ToServerPacket::StartGame => {
-                let LobbyState::Lobby { settings, clients } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "ToServerPacket::StartGame can not be used outside of LobbyState::Lobby", lobby_client_id);
-                    return
-                };
-                if let Some(player) = clients.get(&lobby_client_id){
-                    if !player.is_host() {return}
-                }
-
-                settings.role_list.simplify();
-                let role_list = settings.role_list.clone();
-                
-                self.send_to_all(ToClientPacket::RoleList { role_list });
-
-                let mut game_clients: VecMap<LobbyClientID, GameClient> = VecMap::new();
-                let mut game_player_params = Vec::new();
-                let mut game_spectator_params = Vec::new();
+            ToServerPacket::SetLobbyVisibility{ visibility } => {
+                if !self.is_host(lobby_client_id) {return};
 
+                self.access.visibility = visibility.sanitize();
 
-                let LobbyState::Lobby { settings, clients} = &mut self.lobby_state else {
-                    unreachable!("LobbyState::Lobby was checked to be to LobbyState::Lobby in the previous line")
+                self.send_visibility_to_all()
+            },
+            ToServerPacket::SetLobbyStartSettings{ start_settings } => {
+                let LobbyState::Lobby { .. } = self.lobby_state else {
+                    warn!(lobby_client_id, "ToServerPacket::SetLobbyStartSettings can not be used outside of LobbyState::Lobby");
+                    return
                 };
 
-                let mut next_player_index: PlayerIndex = 0;
-                let mut next_spectator_index: SpectatorIndex = 0;
-
-                for (lobby_client_id, lobby_client) in clients.clone() {
-                    
-                    game_clients.insert(lobby_client_id, 
-                        if let LobbyClientType::Spectator = lobby_client.client_type {
-                            GameClient {
-                                client_location: GameClientLocation::Spectator(next_spectator_index),
-                                host: lobby_client.is_host(),
-                                last_message_times: VecDeque::new(),
-                            }
-                        } else {
-                            GameClient {
-                                client_location: GameClientLocation::Player(next_player_index),
-                                host: lobby_client.is_host(),
-                                last_message_times: VecDeque::new(),
-                            }
-                        }
-                    );
-                    
-                    match lobby_client.client_type {
-                        LobbyClientType::Player { ref name } => {
-                            game_player_params.push(PlayerInitializeParameters{
-                                host: lobby_client.is_host(),
-                                connection: lobby_client.connection,
-                                name: name.clone(),
-                            });
-                            if let Some(new_player_index) = next_player_index.checked_add(1) {
-                                next_player_index = new_player_index;
-                            } else {
-                                send.send(ToClientPacket::RejectStart { reason: RejectStartReason::TooManyClients });
-                                return;
-                            }
-                        },
-                        LobbyClientType::Spectator => {
-                            game_spectator_params.push(SpectatorInitializeParameters{
-                                host: lobby_client.is_host(),
-                                connection: lobby_client.connection,
-                            });
-                            if let Some(new_spectator_index) = next_spectator_index.checked_add(1) {
-                                next_spectator_index = new_spectator_index;
-                            } else {
-                                send.send(ToClientPacket::RejectStart { reason: RejectStartReason::TooManyClients });
-                                return;
-                            }
-                        }
-                    }
-                }
+                if !self.is_host(lobby_client_id) {return};
 
-                let game = match Game::new(settings.clone(), game_player_params, game_spectator_params){
-                    Ok(game) => game,
-                    Err(err) => {
-                        send.send(ToClientPacket::RejectStart { reason: err });
-                        log!(info "Lobby"; "Failed to start game: {:?}", err);
-                        return
-                    }
-                };
-                
-                log!(info "Lobby"; "Game started with room code {}", self.room_code);
+                self.auto_start.settings = start_settings.sanitize();
 
-                self.lobby_state = LobbyState::Game{
-                    game,
-                    clients: game_clients,
-                };
-                let LobbyState::Game { game, clients: _player } = &mut self.lobby_state else {
-                    unreachable!("LobbyState::Game was set to be to LobbyState::Game in the previous line");
+                self.send_to_all(ToClientPacket::LobbyStartSettings { start_settings: self.auto_start.settings.clone() });
+                self.update_start_countdown();
+            },
+            ToServerPacket::StartGame => {
+                let LobbyState::Lobby { clients, .. } = &self.lobby_state else {
+                    warn!(lobby_client_id, "ToServerPacket::StartGame can not be used outside of LobbyState::Lobby");
+                    return
                 };
+                if let Some(player) = clients.get(&lobby_client_id){
+                    if !player.is_host() {return}
+                }
 
-                Lobby::send_players_game(game);
-                
-                self.send_to_all(ToClientPacket::LobbyName { name: self.name.clone() })
+                if let Err(reason) = self.start_game() {
+                    send.send(ToClientPacket::RejectStart { reason });
+                }
             },
+            ToServerPacket::AddBot => {
+                if !self.is_host(lobby_client_id) {return};
+
+                if let Err(reason) = self.add_bot() {
+                    warn!(lobby_client_id, ?reason, "Couldn't add a bot");
+                }
+            }
             ToServerPacket::SetPhaseTime{phase, time} => {
                 let LobbyState::Lobby{ settings, clients  } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Attempted to change phase time outside of the lobby menu!", lobby_client_id);
+                    warn!(lobby_client_id, "Attempted to change phase time outside of the lobby menu!");
                     return;
                 };
                 if let Some(player) = clients.get(&lobby_client_id){
                     if !player.is_host() {return}
                 }
 
-                match phase {
-                    PhaseType::Briefing => { settings.phase_times.briefing = time; }
-                    PhaseType::Obituary => { settings.phase_times.obituary = time; }
-                    PhaseType::Discussion => { settings.phase_times.discussion = time; }
-                    PhaseType::FinalWords => { settings.phase_times.final_words = time; }
-                    PhaseType::Dusk => { settings.phase_times.dusk = time; }
-                    PhaseType::Judgement => { settings.phase_times.judgement = time; }
-                    PhaseType::Night => { settings.phase_times.night = time; }
-                    PhaseType::Testimony => { settings.phase_times.testimony = time; }
-                    PhaseType::Nomination => { settings.phase_times.nomination = time; }
-                    PhaseType::Recess => { }
-                };
+                settings.phase_times.set_time_for(phase, time);
                 
                 self.send_to_all(ToClientPacket::PhaseTime { phase, time });
             },
             ToServerPacket::SetPhaseTimes { phase_time_settings } => {
                 let LobbyState::Lobby{ settings, clients } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Attempted to change phase time outside of the lobby menu!", lobby_client_id);
+                    warn!(lobby_client_id, "Attempted to change phase time outside of the lobby menu!");
                     return;
                 };
                 if let Some(player) = clients.get(&lobby_client_id){
@@ -295,15 +234,23 @@ impl Lobby {
             }
             ToServerPacket::SetRoleList { role_list } => {
                 let LobbyState::Lobby{ settings, clients } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Can't modify game settings outside of the lobby menu", lobby_client_id);
+                    warn!(lobby_client_id, "Can't modify game settings outside of the lobby menu");
                     return;
                 };
                 if let Some(player) = clients.get(&lobby_client_id){
                     if !player.is_host() {return}
                 }
 
+                let embedded = role_list.0.iter().flat_map(|outline| outline.options.iter()).map(|option| &option.roles);
+                if let Err(error) = validate_embedded_custom_role_sets(embedded) {
+                    warn!(lobby_client_id, %error, "Attempted to set a role list with invalid custom role sets");
+                    return;
+                }
+
                 settings.role_list = role_list;
+                settings.refresh_custom_role_sets();
                 Lobby::set_rolelist_length(settings, clients);
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
                 
                 let role_list = settings.role_list.clone();
 
@@ -311,7 +258,7 @@ impl Lobby {
             }
             ToServerPacket::SetRoleOutline { index, role_outline } => {
                 let LobbyState::Lobby{ settings, clients } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Can't modify game settings outside of the lobby menu", lobby_client_id);
+                    warn!(lobby_client_id, "Can't modify game settings outside of the lobby menu");
                     return;
                 };
                 if let Some(player) = clients.get(&lobby_client_id){
@@ -319,14 +266,21 @@ impl Lobby {
                 }
 
                 if settings.role_list.0.len() <= index as usize {return}
+                if let Err(error) = validate_embedded_custom_role_sets(role_outline.options.iter().map(|option| &option.roles)) {
+                    warn!(lobby_client_id, %error, "Attempted to set a role outline with invalid custom role sets");
+                    return;
+                }
                 let Some(unset_outline) = settings.role_list.0.get_mut(index as usize) else {return};
-                *unset_outline = role_outline.clone();
+                *unset_outline = role_outline;
+                settings.refresh_custom_role_sets();
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
                 
+                let Some(role_outline) = settings.role_list.0.get(index as usize).cloned() else {return};
                 self.send_to_all(ToClientPacket::RoleOutline { index, role_outline });
             }
             ToServerPacket::SimplifyRoleList => {
                 let LobbyState::Lobby{ settings, clients } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Can't modify game settings outside of the lobby menu", lobby_client_id);
+                    warn!(lobby_client_id, "Can't modify game settings outside of the lobby menu");
                     return;
                 };
                 if let Some(player) = clients.get(&lobby_client_id){
@@ -334,24 +288,26 @@ impl Lobby {
                 }
 
                 settings.role_list.simplify();
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
                 let role_list = settings.role_list.clone();
                 
                 self.send_to_all(ToClientPacket::RoleList { role_list });
             }
             ToServerPacket::SetEnabledRoles {roles } => {
-                let LobbyState::Lobby{ settings, .. } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Can't modify game settings outside of the lobby menu", lobby_client_id);
+                let LobbyState::Lobby{ settings, clients } = &mut self.lobby_state else {
+                    warn!(lobby_client_id, "Can't modify game settings outside of the lobby menu");
                     return;
                 };
 
 
                 settings.enabled_roles = roles.into_iter().collect();
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
                 let roles = settings.enabled_roles.clone().into_iter().collect();
                 self.send_to_all(ToClientPacket::EnabledRoles { roles });
             }
             ToServerPacket::SetEnabledModifiers {modifiers } => {
                 let LobbyState::Lobby{ settings, .. } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Can't modify game settings outside of the lobby menu", lobby_client_id);
+                    warn!(lobby_client_id, "Can't modify game settings outside of the lobby menu");
                     return;
                 };
 
@@ -359,12 +315,107 @@ impl Lobby {
                 let modifiers = settings.enabled_modifiers.clone().into_iter().collect();
                 self.send_to_all(ToClientPacket::EnabledModifiers { modifiers });
             }
+            ToServerPacket::SetRoleWeights { role_weights } => {
+                let LobbyState::Lobby{ settings, clients } = &mut self.lobby_state else {
+                    warn!(lobby_client_id, "Can't modify game settings outside of the lobby menu");
+                    return;
+                };
+                if let Some(player) = clients.get(&lobby_client_id){
+                    if !player.is_host() {return}
+                }
+
+                settings.role_weights = role_weights.into_iter()
+                    .filter(|(_, weight)| *weight != 1)
+                    .collect();
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
+                let role_weights = settings.role_weights.clone();
+                self.send_to_all(ToClientPacket::RoleWeights { role_weights });
+            }
+            ToServerPacket::SetRoleConstraints { constraints } => {
+                let LobbyState::Lobby{ settings, clients } = &mut self.lobby_state else {
+                    warn!(lobby_client_id, "Can't modify game settings outside of the lobby menu");
+                    return;
+                };
+                if let Some(player) = clients.get(&lobby_client_id){
+                    if !player.is_host() {return}
+                }
+
+                if !RoleConstraint::within_limits(&constraints) {
+                    warn!(lobby_client_id, count = constraints.len(), "Attempted to set too many role constraints");
+                    return;
+                }
+                if let Err(error) = validate_embedded_custom_role_sets(constraints.iter().flat_map(RoleConstraint::options)) {
+                    warn!(lobby_client_id, %error, "Attempted to set role constraints with invalid custom role sets");
+                    return;
+                }
+
+                settings.role_constraints = constraints;
+                settings.refresh_custom_role_sets();
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
+                let constraints = settings.role_constraints.clone();
+                self.send_to_all(ToClientPacket::RoleConstraints { constraints });
+            }
+            ToServerPacket::SetCustomRoleSets { custom_role_sets } => {
+                let LobbyState::Lobby{ settings, clients } = &mut self.lobby_state else {
+                    warn!(lobby_client_id, "Can't modify game settings outside of the lobby menu");
+                    return;
+                };
+                if let Some(player) = clients.get(&lobby_client_id){
+                    if !player.is_host() {return}
+                }
+
+                let custom_role_sets: Vec<CustomRoleSet> = custom_role_sets.into_iter()
+                    .map(|set| CustomRoleSet::new(set.name, set.roles))
+                    .collect();
+                if let Err(error) = validate_custom_role_sets(&custom_role_sets) {
+                    warn!(lobby_client_id, %error, "Attempted to set invalid custom role sets");
+                    return;
+                }
+
+                settings.custom_role_sets = custom_role_sets;
+                settings.refresh_custom_role_sets();
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
+
+                let settings = settings.clone();
+                self.send_to_all(ToClientPacket::CustomRoleSets { custom_role_sets: settings.custom_role_sets });
+                self.send_to_all(ToClientPacket::RoleList { role_list: settings.role_list });
+                self.send_to_all(ToClientPacket::RoleConstraints { constraints: settings.role_constraints });
+            }
+            ToServerPacket::LoadPreset { name } => {
+                let LobbyState::Lobby{ settings, clients } = &mut self.lobby_state else {
+                    warn!(lobby_client_id, "Can't modify game settings outside of the lobby menu");
+                    return;
+                };
+                if let Some(player) = clients.get(&lobby_client_id){
+                    if !player.is_host() {return}
+                }
+
+                let Some(preset) = PresetLibrary::get().find(&name) else {
+                    warn!(lobby_client_id, name, "Attempted to load a preset that doesn't exist");
+                    return;
+                };
+
+                let seed = settings.seed;
+                *settings = preset.settings.clone();
+                settings.seed = seed;
+                Lobby::set_rolelist_length(settings, clients);
+                Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
+
+                let settings = settings.clone();
+                self.send_to_all(ToClientPacket::PhaseTimes { phase_time_settings: settings.phase_times });
+                self.send_to_all(ToClientPacket::RoleList { role_list: settings.role_list });
+                self.send_to_all(ToClientPacket::EnabledRoles { roles: settings.enabled_roles.into_iter().collect() });
+                self.send_to_all(ToClientPacket::EnabledModifiers { modifiers: settings.enabled_modifiers.into_iter().collect() });
+                self.send_to_all(ToClientPacket::RoleWeights { role_weights: settings.role_weights });
+                self.send_to_all(ToClientPacket::RoleConstraints { constraints: settings.role_constraints });
+                self.send_to_all(ToClientPacket::CustomRoleSets { custom_role_sets: settings.custom_role_sets });
+            }
             ToServerPacket::Leave => {
                 self.remove_player(lobby_client_id);
             }
             ToServerPacket::HostForceBackToLobby => {
-                let LobbyState::Game { game, clients } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Can't go back to lobby from while in lobby", lobby_client_id);
+                let LobbyState::Game { game, clients, bots } = &mut self.lobby_state else {
+                    warn!(lobby_client_id, "Can't go back to lobby from while in lobby");
                     return;
                 };
                 if let Some(player) = clients.get(&lobby_client_id){
@@ -373,7 +424,13 @@ impl Lobby {
 
                 let mut new_clients = VecMap::new();
                 for (lobby_client_id, game_client) in clients.clone() {
-                    new_clients.insert(lobby_client_id, LobbyClient::new_from_game_client(game, game_client));
+                    let mut lobby_client = LobbyClient::new_from_game_client(game, game_client);
+                    if bots.contains(&lobby_client_id) {
+                        if let LobbyClientType::Player { name } = &lobby_client.client_type {
+                            lobby_client = LobbyClient::new_bot(name.clone());
+                        }
+                    }
+                    new_clients.insert(lobby_client_id, lobby_client);
                 }
 
 
@@ -388,40 +445,43 @@ impl Lobby {
                     LobbyState::Lobby { clients, settings } => {
                         for (id, client) in clients.iter() {
                             client.send(ToClientPacket::YourId { player_id: *id });
-                            Self::send_settings(client, settings, self.name.clone());
+                            Self::send_settings(client, settings, self.name.clone(), &self.access, &self.auto_start.settings);
                         }
+                        Self::send_role_list_analysis(&mut self.role_list_analysis, clients, settings);
                         Self::send_players_lobby(clients);
                     }
                     _ => unreachable!("LobbyState::Lobby was set to be to LobbyState::Lobby in the previous line")
                 }
             }
             ToServerPacket::HostForceEndGame => {
-                let LobbyState::Game { game, clients } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Can't end game while in lobby", lobby_client_id);
+                let LobbyState::Game { game, clients, .. } = &mut self.lobby_state else {
+                    warn!(lobby_client_id, "Can't end game while in lobby");
                     return;
                 };
                 if let Some(player) = clients.get(&lobby_client_id){
                     if !player.host {return;}
                 }
 
+                EventJournal::record_input(game, GameInput::HostForceEndGame);
                 let conclusion = GameConclusion::get_premature_conclusion(game);
 
                 OnGameEnding::new(conclusion).invoke(game);
             }
             ToServerPacket::HostForceSkipPhase => {
-                let LobbyState::Game { game, clients } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Can't skip phase while in lobby", lobby_client_id);
+                let LobbyState::Game { game, clients, .. } = &mut self.lobby_state else {
+                    warn!(lobby_client_id, "Can't skip phase while in lobby");
                     return;
                 };
                 if let Some(player) = clients.get(&lobby_client_id){
                     if !player.host {return;}
                 }
                 
+                EventJournal::record_input(game, GameInput::HostForceSkipPhase);
                 OnFastForward::invoke(game);
             }
             ToServerPacket::HostDataRequest => {
-                let LobbyState::Game { clients, game } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {}", "Can't request game host data while in lobby", lobby_client_id);
+                let LobbyState::Game { clients, game, .. } = &mut self.lobby_state else {
+                    warn!(lobby_client_id, "Can't request game host data while in lobby");
                     return;
                 };
                 if let Some(player) = clients.get(&lobby_client_id){
@@ -431,13 +491,14 @@ impl Lobby {
                 Self::resend_host_data(game, clients, send);
             }
             ToServerPacket::HostForceSetPlayerName { id, name } => {
-                if let LobbyState::Game { game, clients } = &mut self.lobby_state {
+                if let LobbyState::Game { game, clients, .. } = &mut self.lobby_state {
                     if let Some(player) = clients.get(&lobby_client_id){
                         if !player.host {return;}
                     }
                     if let Some(player) = clients.get(&id) {
                         if let GameClientLocation::Player(index) = player.client_location {
                             if let Ok(player_ref) = PlayerReference::new(game, index) {
+                                EventJournal::record_input(game, GameInput::SetPlayerName { player: index, name: name.clone() });
                                 Self::set_player_name_game(game, player_ref, name);
                             }
                         }
@@ -449,9 +510,20 @@ impl Lobby {
                     Self::set_player_name(id, name, clients);
                 };
             }
+            ToServerPacket::ClaimPlayer { player_index } => {
+                if let Err(reason) = self.claim_player(send, lobby_client_id, player_index) {
+                    warn!(lobby_client_id, player_index, ?reason, "Couldn't take over player");
+                }
+            }
+            ToServerPacket::HostSubstituteBot { player_index } => {
+                if !self.is_host(lobby_client_id) {return}
+                if let Err(reason) = self.substitute_bot(player_index) {
+                    warn!(lobby_client_id, player_index, ?reason, "Couldn't give player to a bot");
+                }
+            }
             _ => {
-                let LobbyState::Game { game, clients } = &mut self.lobby_state else {
-                    log!(error "Lobby"; "{} {:?}", "ToServerPacket not implemented for lobby was sent during lobby: ", incoming_packet);
+                let LobbyState::Game { game, clients, .. } = &mut self.lobby_state else {
+                    warn!(lobby_client_id, packet = ?incoming_packet, "ToServerPacket not implemented for lobby was sent during lobby");
                     return;
                 };
 
@@ -0,0 +1,42 @@
+//! Starting a game deals everyone's roles first, which can take a while for tightly constrained role lists.
+//! The game is only created, and anyone told about it, once they're dealt.
+
+use tokio::sync::oneshot;
+
+use crate::game::{settings::Settings, DealtRoles, Game, RejectStartReason};
+
+use super::blocking;
+
+/// Roles being dealt for a game that's about to start
+pub struct PendingStart {
+    /// What the roles are being dealt for, since they can't be used if the lobby changed in the meantime
+    settings: Settings,
+    player_count: usize,
+    receiver: oneshot::Receiver<Result<DealtRoles, RejectStartReason>>,
+}
+
+impl PendingStart {
+    pub fn deal(settings: &Settings, player_count: usize) -> Self {
+        let dealt_settings = settings.clone();
+        Self {
+            settings: settings.clone(),
+            player_count,
+            receiver: blocking::spawn(move || Game::deal_roles(&dealt_settings, player_count)),
+        }
+    }
+
+    /// Whether these roles can start a game with `settings` and `player_count` players
+    pub fn is_for(&self, settings: &Settings, player_count: usize) -> bool {
+        self.settings == *settings && self.player_count == player_count
+    }
+
+    /// Returns the dealt roles once they're ready
+    pub fn poll(&mut self) -> Option<Result<DealtRoles, RejectStartReason>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(oneshot::error::TryRecvError::Empty) => None,
+            // Dealing them panicked
+            Err(oneshot::error::TryRecvError::Closed) => Some(Err(RejectStartReason::RoleListCannotCreateRoles)),
+        }
+    }
+}
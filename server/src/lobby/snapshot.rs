@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client_connection::ClientConnection,
+    config::Config,
+    game::{
+        components::event_journal::{EventJournal, GameInput, GameRecord},
+        bot::Bot, player::PlayerReference, replay::{self, ReplayError}, settings::Settings
+    },
+    listener::RoomCode, vec_map::VecMap
+};
+
+use super::{
+    analysis_cache::AnalysisCache,
+    auto_start::{AutoStart, StartSettings},
+    game_client::{GameClient, GameClientLocation},
+    lobby_access::LobbyAccess,
+    lobby_client::{LobbyClient, LobbyClientID, LobbyClientType, Ready},
+    Lobby, LobbyState
+};
+
+/// Everything needed to bring a lobby back after the server restarts.
+/// Games aren't stored directly, they're rebuilt by replaying their [`GameRecord`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LobbySnapshot {
+    pub room_code: RoomCode,
+    pub name: String,
+    /// Missing from snapshots taken before lobbies could be private.
+    /// Holds the lobby's password, which is why snapshots are only readable by the server.
+    #[serde(default)]
+    pub access: Option<LobbyAccess>,
+    /// Missing from snapshots taken before lobbies could auto start
+    #[serde(default)]
+    pub start_settings: StartSettings,
+    pub state: LobbyStateSnapshot,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LobbyStateSnapshot {
+    #[serde(rename_all = "camelCase")]
+    Lobby {
+        settings: Settings,
+        clients: Vec<(LobbyClientID, LobbyClientSnapshot)>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Game {
+        record: GameRecord,
+        clients: Vec<(LobbyClientID, GameClient)>,
+        /// Missing from snapshots taken before bots existed
+        #[serde(default)]
+        bots: Vec<LobbyClientID>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LobbyClientSnapshot {
+    pub ready: Ready,
+    pub client_type: LobbyClientType,
+}
+
+impl Lobby {
+    /// Returns `None` if the lobby is closed, since there's nothing to bring back
+    pub fn snapshot(&self) -> Option<LobbySnapshot> {
+        let state = match &self.lobby_state {
+            LobbyState::Lobby { settings, clients } => LobbyStateSnapshot::Lobby {
+                settings: settings.clone(),
+                clients: clients.iter()
+                    .map(|(id, client)| (*id, LobbyClientSnapshot {
+                        ready: client.ready.clone(),
+                        client_type: client.client_type.clone(),
+                    }))
+                    .collect(),
+            },
+            LobbyState::Game { game, clients, bots } => LobbyStateSnapshot::Game {
+                record: EventJournal::game_record(game),
+                clients: clients.iter().map(|(id, client)| (*id, client.clone())).collect(),
+                bots: bots.keys().copied().collect(),
+            },
+            LobbyState::Closed => return None,
+        };
+
+        Some(LobbySnapshot {
+            room_code: self.room_code,
+            name: self.name.clone(),
+            access: Some(self.access.clone()),
+            start_settings: self.auto_start.settings.clone(),
+            state,
+        })
+    }
+
+    /// Every client comes back disconnected, and has the game disconnect timer to rejoin their seat.
+    /// Spectators of a game aren't part of its record, so they have to join again as new spectators.
+    /// Bots come back connected, but remember nothing from before the restart.
+    pub fn restore(snapshot: LobbySnapshot) -> Result<Lobby, ReplayError> {
+        let lobby_state = match snapshot.state {
+            LobbyStateSnapshot::Lobby { settings, clients } => {
+                let mut restored_clients = VecMap::new();
+                for (id, client) in clients {
+                    if let LobbyClientType::Bot { name } = client.client_type {
+                        restored_clients.insert(id, LobbyClient::new_bot(name));
+                        continue;
+                    }
+                    restored_clients.insert(id, LobbyClient {
+                        connection: ClientConnection::CouldReconnect {
+                            disconnect_timer: Config::get().game_disconnect_timer()
+                        },
+                        ready: client.ready,
+                        client_type: client.client_type,
+                        last_message_times: VecDeque::new(),
+                    });
+                }
+                LobbyState::Lobby { settings, clients: restored_clients }
+            },
+            LobbyStateSnapshot::Game { record, clients, bots: bot_ids } => {
+                let mut game = replay::replay(&record)?;
+                EventJournal::set_export_directory(&mut game, Config::get().game_record_directory());
+
+                let mut bots = VecMap::new();
+                let mut bot_players = Vec::new();
+                for (id, client) in clients.iter() {
+                    if !bot_ids.contains(id) {continue}
+                    if let GameClientLocation::Player(index) = client.client_location {
+                        bots.insert(*id, Bot::heuristic(game.seed, index));
+                        bot_players.push(index);
+                    }
+                }
+
+                for player in PlayerReference::all_players(&game).collect::<Vec<_>>() {
+                    if !player.is_disconnected(&game) && !bot_players.contains(&player.index()) {
+                        EventJournal::record_input(&mut game, GameInput::LoseConnection { player: player.index() });
+                        player.lose_connection(&mut game);
+                    }
+                    // Clients kept counting packets from the old server, which the replay can't vouch for
+                    player.forget_sent_packets(&mut game);
+                }
+
+                let mut restored_clients = VecMap::new();
+                for (id, client) in clients {
+                    if let GameClientLocation::Player(_) = client.client_location {
+                        restored_clients.insert(id, client);
+                    }
+                }
+                if !restored_clients.values().any(|client| client.host) {
+                    if let Some((_, new_host)) = restored_clients.iter_mut().find(|(id, _)| !bots.contains(id)) {
+                        new_host.set_host();
+                    }
+                }
+                LobbyState::Game { game, clients: restored_clients, bots }
+            },
+        };
+
+        Ok(Lobby {
+            room_code: snapshot.room_code,
+            name: snapshot.name,
+            access: snapshot.access.unwrap_or_else(|| LobbyAccess::new(&mut rand::rng())),
+            auto_start: AutoStart::new(snapshot.start_settings),
+            role_list_analysis: AnalysisCache::default(),
+            pending_start: None,
+            restore_grace: Config::get().game_disconnect_timer(),
+            lobby_state,
+        })
+    }
+}
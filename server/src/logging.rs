@@ -0,0 +1,132 @@
+use std::{fmt::Display, path::Path, str::FromStr, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tokio::signal::unix::{signal, SignalKind};
+use tracing::Subscriber;
+use tracing_appender::{non_blocking::WorkerGuard, rolling};
+use tracing_subscriber::{
+    fmt::MakeWriter, layer::SubscriberExt, registry::LookupSpan, reload,
+    util::SubscriberInitExt, EnvFilter, Layer, Registry
+};
+
+use crate::config::{Config, CONFIG_PATH};
+
+const LOG_FILE_PREFIX: &str = "server.log";
+
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogFormat {
+    /// One line per event, colored when written to a terminal
+    #[default]
+    Human,
+    /// One JSON object per line, with every field and span as its own key
+    Json,
+}
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(LogFormat::Human),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LoggingError {
+    InvalidFilter(String),
+    AlreadyInitialized,
+}
+impl Display for LoggingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoggingError::InvalidFilter(err) => write!(f, "Invalid log filter: {err}"),
+            LoggingError::AlreadyInitialized => write!(f, "Logging was already initialized"),
+        }
+    }
+}
+
+/// Keeps the log file writer running. Logs written after this is dropped may be lost.
+pub struct LoggingGuard {
+    _file_guard: Option<WorkerGuard>,
+}
+
+/// Sets up logging to stdout and, if the config has a log directory, to a file there that rotates daily
+pub fn init(config: &Config) -> Result<LoggingGuard, LoggingError> {
+    let filter = EnvFilter::try_new(&config.log_filter)
+        .map_err(|err| LoggingError::InvalidFilter(err.to_string()))?;
+    let (filter, filter_handle) = reload::Layer::new(filter);
+
+    let stdout_layer = format_layer(config.log_format, std::io::stdout, true);
+
+    let (file_layer, file_guard) = match &config.log_directory {
+        Some(directory) => {
+            let (writer, guard) = tracing_appender::non_blocking(rolling::daily(directory, LOG_FILE_PREFIX));
+            (Some(format_layer(config.log_format, writer, false)), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(stdout_layer)
+        .with(file_layer)
+        .try_init()
+        .map_err(|_| LoggingError::AlreadyInitialized)?;
+
+    let _ = FILTER_HANDLE.set(filter_handle);
+
+    Ok(LoggingGuard { _file_guard: file_guard })
+}
+
+/// Changes which logs are kept while the server is running.
+/// Uses the same syntax as `RUST_LOG`, e.g. `info,mafia_server::listener=debug`
+pub fn set_filter(directives: &str) -> Result<(), LoggingError> {
+    let filter = EnvFilter::try_new(directives)
+        .map_err(|err| LoggingError::InvalidFilter(err.to_string()))?;
+
+    if let Some(handle) = FILTER_HANDLE.get() {
+        handle.reload(filter).map_err(|err| LoggingError::InvalidFilter(err.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Re-reads the log filter from the config file and environment whenever the process receives SIGHUP.
+/// `args` are the command line flags the server was started with, which still take precedence.
+pub fn reload_filter_on_sighup(args: Vec<String>) {
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(err) => {
+            tracing::warn!(%err, "Failed to listen for SIGHUP, the log filter can't be changed while running");
+            return;
+        }
+    };
+
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            let result = Config::load(Path::new(CONFIG_PATH), std::env::vars(), args.clone())
+                .map_err(|err| err.to_string())
+                .and_then(|(config, _)| set_filter(&config.log_filter).map(|()| config.log_filter).map_err(|err| err.to_string()));
+
+            match result {
+                Ok(filter) => tracing::info!(filter, "Reloaded log filter"),
+                Err(err) => tracing::warn!(err, "Failed to reload log filter, keeping the old one"),
+            }
+        }
+    });
+}
+
+fn format_layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Human => tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(ansi).boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer().json().with_writer(writer).boxed(),
+    }
+}
@@ -1,12 +1,6 @@
 
-use mafia_server::{log, websocket_connections::websocket_listener::create_ws_server};
-use serde::Deserialize;
-use std::{fs, thread, time::Duration};
-
-#[derive(Deserialize)]
-struct Config{
-    address: String,
-}
+use mafia_server::{config::{Config, CONFIG_PATH}, logging, metrics, websocket_connections::websocket_listener::{create_ws_server, ServerExit}};
+use std::{env, path::Path, process, thread, time::Duration};
 
 ///
 /// The Main function
@@ -16,14 +10,45 @@ struct Config{
 /// 
 #[tokio::main]
 async fn main() -> ! {
-    let config = serde_json::from_str::<Config>(
-        &fs::read_to_string("./resources/config.json").expect("Failed to read the config file")
-    ).unwrap();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (config, ignored_env_vars) = match Config::load(Path::new(CONFIG_PATH), env::vars(), args.clone()) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(1);
+        }
+    };
+    Config::set(config);
+
+    let logging_guard = match logging::init(Config::get()) {
+        Ok(guard) => guard,
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(1);
+        }
+    };
+    logging::reload_filter_on_sighup(args);
+    for name in ignored_env_vars {
+        tracing::warn!(name, "Ignoring unknown environment variable");
+    }
+
+    if let Some(metrics_address) = &Config::get().metrics_address {
+        metrics::serve(metrics_address.clone());
+    }
+
+    let address = Config::get().address.clone();
 
     loop {
-        create_ws_server(&config.address).await;
+        let exit = create_ws_server(&address).await;
         // This delay is only to make sure disconnect messages are sent before the server restarts
         thread::sleep(Duration::from_secs(1));
-        log!(important "Main"; "Restarting server...");
+
+        if exit == ServerExit::ShutDown {
+            break;
+        }
+        tracing::info!("Restarting server...");
     }
+
+    drop(logging_guard);
+    process::exit(0);
 }
@@ -0,0 +1,176 @@
+use std::{collections::BTreeMap, fmt::Write, sync::{atomic::{AtomicU64, Ordering}, LazyLock, Mutex, PoisonError}, time::Duration};
+
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpListener};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+
+use crate::{game::game_conclusion::GameConclusion, packet::ToServerPacket, websocket_connections::protocol::Encoding};
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::default);
+
+/// Upper bounds of the tick duration histogram buckets, in seconds
+const TICK_DURATION_BUCKETS: [f64; 8] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+/// Counters and gauges describing server load, rendered in the Prometheus text format
+#[derive(Default)]
+struct Metrics {
+    lobbies_by_state: Mutex<BTreeMap<&'static str, u64>>,
+    connected_clients: AtomicU64,
+    packets_received: Mutex<BTreeMap<&'static str, u64>>,
+    packets_sent: Mutex<BTreeMap<&'static str, u64>>,
+    bytes_sent: Mutex<BTreeMap<&'static str, u64>>,
+    rate_limit_rejections: AtomicU64,
+    ping_timeouts: AtomicU64,
+    tick_durations: Mutex<Histogram>,
+    games_ended: Mutex<BTreeMap<String, u64>>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    bucket_counts: [u64; TICK_DURATION_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+pub fn set_lobbies_by_state(lobbies_by_state: BTreeMap<&'static str, u64>) {
+    *METRICS.lobbies_by_state.lock().unwrap_or_else(PoisonError::into_inner) = lobbies_by_state;
+}
+pub fn set_connected_clients(count: usize) {
+    METRICS.connected_clients.store(count as u64, Ordering::Relaxed);
+}
+/// Only packets that decoded are counted, so clients can't invent new labels
+pub fn packet_received(packet: &ToServerPacket) {
+    increment(&METRICS.packets_received, packet.type_name(), 1);
+}
+/// `packet_type` is [`ToClientPacket::type_name`] of the packet that was encoded into `message`
+pub fn packet_sent(packet_type: &'static str, message: &Message) {
+    increment(&METRICS.packets_sent, packet_type, 1);
+    if let Some(encoding) = Encoding::of(message) {
+        increment(&METRICS.bytes_sent, encoding.name(), message.len() as u64);
+    }
+}
+pub fn rate_limit_rejected() {
+    METRICS.rate_limit_rejections.fetch_add(1, Ordering::Relaxed);
+}
+pub fn ping_timed_out() {
+    METRICS.ping_timeouts.fetch_add(1, Ordering::Relaxed);
+}
+pub fn tick_finished(duration: Duration) {
+    let seconds = duration.as_secs_f64();
+    let mut histogram = METRICS.tick_durations.lock().unwrap_or_else(PoisonError::into_inner);
+
+    for (bound, count) in TICK_DURATION_BUCKETS.iter().zip(histogram.bucket_counts.iter_mut()) {
+        if seconds <= *bound {
+            *count = count.saturating_add(1);
+        }
+    }
+    histogram.count = histogram.count.saturating_add(1);
+    histogram.sum += seconds;
+}
+pub fn game_ended(conclusion: &GameConclusion) {
+    let conclusion = serde_json::to_value(conclusion).ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{conclusion:?}"));
+    let mut games_ended = METRICS.games_ended.lock().unwrap_or_else(PoisonError::into_inner);
+    let count = games_ended.entry(conclusion).or_default();
+    *count = count.saturating_add(1);
+}
+
+fn increment(counters: &Mutex<BTreeMap<&'static str, u64>>, label: &'static str, amount: u64) {
+    let mut counters = counters.lock().unwrap_or_else(PoisonError::into_inner);
+    let count = counters.entry(label).or_default();
+    *count = count.saturating_add(amount);
+}
+
+/// Renders every metric in the Prometheus text exposition format
+pub fn render() -> String {
+    let mut out = String::new();
+
+    write_labeled(&mut out, "mafia_lobbies", "gauge", "Open lobbies by state", "state",
+        METRICS.lobbies_by_state.lock().unwrap_or_else(PoisonError::into_inner).iter().map(|(k, v)| (*k, *v)));
+    write_single(&mut out, "mafia_connected_clients", "gauge", "Websocket connections",
+        METRICS.connected_clients.load(Ordering::Relaxed));
+    write_labeled(&mut out, "mafia_packets_received_total", "counter", "Packets received by type", "type",
+        METRICS.packets_received.lock().unwrap_or_else(PoisonError::into_inner).iter().map(|(k, v)| (*k, *v)));
+    write_labeled(&mut out, "mafia_packets_sent_total", "counter", "Packets sent by type", "type",
+        METRICS.packets_sent.lock().unwrap_or_else(PoisonError::into_inner).iter().map(|(k, v)| (*k, *v)));
+    write_labeled(&mut out, "mafia_bytes_sent_total", "counter", "Bytes of packets sent by encoding", "encoding",
+        METRICS.bytes_sent.lock().unwrap_or_else(PoisonError::into_inner).iter().map(|(k, v)| (*k, *v)));
+    write_single(&mut out, "mafia_rate_limit_rejections_total", "counter", "Chat messages rejected by the rate limiter",
+        METRICS.rate_limit_rejections.load(Ordering::Relaxed));
+    write_single(&mut out, "mafia_ping_timeouts_total", "counter", "Connections closed because they stopped pinging",
+        METRICS.ping_timeouts.load(Ordering::Relaxed));
+    write_labeled(&mut out, "mafia_games_ended_total", "counter", "Finished games by conclusion", "conclusion",
+        METRICS.games_ended.lock().unwrap_or_else(PoisonError::into_inner).iter().map(|(k, v)| (k.as_str(), *v)));
+
+    let histogram = METRICS.tick_durations.lock().unwrap_or_else(PoisonError::into_inner);
+    let _ = writeln!(out, "# HELP mafia_tick_duration_seconds Time spent ticking every lobby once");
+    let _ = writeln!(out, "# TYPE mafia_tick_duration_seconds histogram");
+    for (bound, count) in TICK_DURATION_BUCKETS.iter().zip(histogram.bucket_counts.iter()) {
+        let _ = writeln!(out, "mafia_tick_duration_seconds_bucket{{le=\"{bound}\"}} {count}");
+    }
+    let _ = writeln!(out, "mafia_tick_duration_seconds_bucket{{le=\"+Inf\"}} {}", histogram.count);
+    let _ = writeln!(out, "mafia_tick_duration_seconds_sum {}", histogram.sum);
+    let _ = writeln!(out, "mafia_tick_duration_seconds_count {}", histogram.count);
+
+    out
+}
+
+fn write_single(out: &mut String, name: &str, metric_type: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {metric_type}");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+fn write_labeled<'a>(
+    out: &mut String, name: &str, metric_type: &str, help: &str, label: &str,
+    values: impl Iterator<Item = (&'a str, u64)>
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {metric_type}");
+    for (label_value, value) in values {
+        let _ = writeln!(out, "{name}{{{label}=\"{}\"}} {value}", escape_label_value(label_value));
+    }
+}
+
+/// Backslashes, double quotes and newlines have to be escaped inside a label value
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Serves the metrics over plain HTTP at `GET /metrics` until the process exits
+pub fn serve(address: String) {
+    tokio::spawn(async move {
+        let listener = match TcpListener::bind(&address).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                warn!(address, %err, "Failed to bind metrics endpoint");
+                return;
+            }
+        };
+        info!(address, "Serving metrics");
+
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {continue};
+
+            tokio::spawn(async move {
+                let mut request = [0u8; 1024];
+                let Ok(length) = stream.read(&mut request).await else {return};
+                let request = String::from_utf8_lossy(request.get(..length).unwrap_or_default());
+
+                let response = if request.starts_with("GET /metrics ") {
+                    let body = render();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                };
+
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+}
@@ -21,17 +21,18 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use vec1::Vec1;
 
-use crate::{client_connection::ClientConnection, game::{ability_input::{AbilityInput, ControllerID, SavedController}, chat::{ChatGroup, ChatMessage}, components::insider_group::InsiderGroupID, grave::Grave, modifiers::ModifierType, phase::{PhaseState, PhaseType}, player::{PlayerIndex, PlayerReference}, role::{doomsayer::DoomsayerGuess, ClientRoleStateEnum, Role}, role_list::{RoleList, RoleOutline}, settings::PhaseTimeSettings, tag::Tag, verdict::Verdict, Game, GameOverReason, RejectStartReason}, listener::RoomCode, lobby::{game_client::GameClientLocation, lobby_client::{LobbyClient, LobbyClientID}}, log, vec_map::VecMap, vec_set::VecSet};
+use crate::{client_connection::{ClientConnection, PacketSequence}, game::{ability_input::{AbilityInput, ControllerID, SavedController}, chat::{ChatGroup, ChatMessage}, components::insider_group::InsiderGroupID, grave::Grave, modifiers::ModifierType, phase::{PhaseState, PhaseType}, player::{PlayerIndex, PlayerReference}, role::{doomsayer::DoomsayerGuess, ClientRoleStateEnum, Role}, preset::PresetSummary, role_generation::RoleConstraint, role_list::{CustomRoleSet, RoleList, RoleOutline}, role_list_analysis::RoleListAnalysis, settings::PhaseTimeSettings, tag::Tag, verdict::Verdict, Game, GameOverReason, RejectStartReason}, listener::RoomCode, lobby::{auto_start::StartSettings, game_client::GameClientLocation, lobby_access::LobbyVisibility, lobby_client::{LobbyClient, LobbyClientID}}, vec_map::{VecMap, VecMapPatch}, vec_set::VecSet, websocket_connections::protocol::{Capability, ProtocolVersion}};
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct LobbyPreviewData {
     pub name: String,
     pub in_game: bool,
+    pub password_protected: bool,
     pub players: Vec<(LobbyClientID, String)>
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct HostDataPacketGameClient {
     pub client_type: GameClientLocation,
@@ -39,36 +40,62 @@ pub struct HostDataPacketGameClient {
     pub host: bool,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "type")]
 pub enum ToClientPacket{
     Pong,
+    /// A packet sent to a player in a game, numbered so they can resume from it after reconnecting.
+    /// Only sent to clients with the `resume` capability; everyone else gets the packet inside.
+    Sequenced{sequence: PacketSequence, packet: Box<ToClientPacket>},
+    /// Answers `Hello` with the version and capabilities the server will use for this connection
+    #[serde(rename_all = "camelCase")]
+    HelloAccepted{protocol_version: ProtocolVersion, capabilities: VecSet<Capability>},
     
     #[serde(rename_all = "camelCase")]
     RateLimitExceeded,
     
     ForcedDisconnect,
     ForcedOutsideLobby,
+    /// The lobby hit a bug and was closed. Sent just before `ForcedOutsideLobby`.
+    LobbyCrashed,
+    /// The server is going down for a restart. Games still running are saved when it does, and can be rejoined after.
+    #[serde(rename_all = "camelCase")]
+    ServerShuttingDown{seconds_left: u64},
 
     // Pre lobby
     #[serde(rename_all = "camelCase")]
     LobbyList{lobbies: HashMap<RoomCode, LobbyPreviewData>},
     #[serde(rename_all = "camelCase")]
     AcceptJoin{room_code: RoomCode, in_game: bool, player_id: LobbyClientID, spectator: bool},
+    /// Answers `ReJoin` when only the packets after `last_sequence` are resent, instead of the whole game.
+    /// The client keeps the game state it had.
+    ResumeAccepted,
     RejectJoin{reason: RejectJoinReason},
     
     // Lobby
     LobbyName{name: String},
+    LobbyVisibility{visibility: LobbyVisibility},
+    #[serde(rename_all = "camelCase")]
+    LobbyInviteCode{invite_code: String},
+    #[serde(rename_all = "camelCase")]
+    LobbyStartSettings{start_settings: StartSettings},
     #[serde(rename_all = "camelCase")]
     YourId{player_id: LobbyClientID},
     #[serde(rename_all = "camelCase")]
     LobbyClients{clients: VecMap<LobbyClientID, LobbyClient>},
+    /// Only what changed since the last `LobbyClients`. The patch packets below all work this way,
+    /// and are only sent to clients with [`Capability::StatePatches`].
+    #[serde(rename_all = "camelCase")]
+    LobbyClientsPatch{clients: VecMapPatch<LobbyClientID, LobbyClient>},
     PlayersHost{hosts: Vec<LobbyClientID>},
     PlayersReady{ready: Vec<LobbyClientID>},
     #[serde(rename_all = "camelCase")]
     PlayersLostConnection{lost_connection: Vec<LobbyClientID>},
     StartGame,
+    /// Seconds until an auto start, sent as it counts down. `None` means it was cancelled.
+    #[serde(rename_all = "camelCase")]
+    StartCountdown{seconds_left: Option<u64>},
     #[serde(rename_all = "camelCase")]
     RejectStart{reason: RejectStartReason},
 
@@ -85,6 +112,15 @@ pub enum ToClientPacket{
     EnabledRoles{roles: Vec<Role>},
     #[serde(rename_all = "camelCase")]
     EnabledModifiers{modifiers: Vec<ModifierType>},
+    /// The presets a host can load with `LoadPreset`
+    Presets{presets: Vec<PresetSummary>},
+    #[serde(rename_all = "camelCase")]
+    RoleWeights{role_weights: VecMap<Role, u8>},
+    RoleConstraints{constraints: Vec<RoleConstraint>},
+    #[serde(rename_all = "camelCase")]
+    CustomRoleSets{custom_role_sets: Vec<CustomRoleSet>},
+    /// Sent whenever the role list, enabled roles, role weights, role constraints, or number of players changes
+    RoleListAnalysis{analysis: RoleListAnalysis},
 
     // Host
     HostData { clients: VecMap<LobbyClientID, HostDataPacketGameClient> },
@@ -105,6 +141,8 @@ pub enum ToClientPacket{
     PlayerAlive{alive: Vec<bool>},
     #[serde(rename_all = "camelCase")]
     PlayerVotes{votes_for_player: VecMap<PlayerIndex, u8>},
+    #[serde(rename_all = "camelCase")]
+    PlayerVotesPatch{votes_for_player: VecMapPatch<PlayerIndex, u8>},
 
     #[serde(rename_all = "camelCase")]
     YourSendChatGroups{send_chat_groups: Vec<ChatGroup>},
@@ -115,11 +153,19 @@ pub enum ToClientPacket{
     YourAllowedControllers{
         save: VecMap<ControllerID, SavedController>
     },
+    #[serde(rename_all = "camelCase")]
+    YourAllowedControllersPatch{
+        save: VecMapPatch<ControllerID, SavedController>
+    },
 
     #[serde(rename_all = "camelCase")]
     YourRoleLabels{role_labels: VecMap<PlayerIndex, Role>},
     #[serde(rename_all = "camelCase")]
+    YourRoleLabelsPatch{role_labels: VecMapPatch<PlayerIndex, Role>},
+    #[serde(rename_all = "camelCase")]
     YourPlayerTags{player_tags: VecMap<PlayerIndex, Vec1<Tag>>},
+    #[serde(rename_all = "camelCase")]
+    YourPlayerTagsPatch{player_tags: VecMapPatch<PlayerIndex, Vec1<Tag>>},
     YourWill{will: String},
     YourNotes{notes: Vec<String>},
     #[serde(rename_all = "camelCase")]
@@ -143,9 +189,85 @@ pub enum ToClientPacket{
     GameOver{reason: GameOverReason},
 }
 impl ToClientPacket {
+    /// The `type` tag this packet is sent with. Sequenced packets are named after the packet inside them.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Sequenced { packet, .. } => packet.type_name(),
+            Self::Pong { .. } => "pong",
+            Self::HelloAccepted { .. } => "helloAccepted",
+            Self::RateLimitExceeded { .. } => "rateLimitExceeded",
+            Self::ForcedDisconnect { .. } => "forcedDisconnect",
+            Self::ForcedOutsideLobby { .. } => "forcedOutsideLobby",
+            Self::LobbyCrashed { .. } => "lobbyCrashed",
+            Self::ServerShuttingDown { .. } => "serverShuttingDown",
+            Self::LobbyList { .. } => "lobbyList",
+            Self::AcceptJoin { .. } => "acceptJoin",
+            Self::ResumeAccepted { .. } => "resumeAccepted",
+            Self::RejectJoin { .. } => "rejectJoin",
+            Self::LobbyName { .. } => "lobbyName",
+            Self::LobbyVisibility { .. } => "lobbyVisibility",
+            Self::LobbyInviteCode { .. } => "lobbyInviteCode",
+            Self::LobbyStartSettings { .. } => "lobbyStartSettings",
+            Self::YourId { .. } => "yourId",
+            Self::LobbyClients { .. } => "lobbyClients",
+            Self::LobbyClientsPatch { .. } => "lobbyClientsPatch",
+            Self::PlayersHost { .. } => "playersHost",
+            Self::PlayersReady { .. } => "playersReady",
+            Self::PlayersLostConnection { .. } => "playersLostConnection",
+            Self::StartGame { .. } => "startGame",
+            Self::StartCountdown { .. } => "startCountdown",
+            Self::RejectStart { .. } => "rejectStart",
+            Self::RoleList { .. } => "roleList",
+            Self::RoleOutline { .. } => "roleOutline",
+            Self::PhaseTime { .. } => "phaseTime",
+            Self::PhaseTimes { .. } => "phaseTimes",
+            Self::EnabledRoles { .. } => "enabledRoles",
+            Self::EnabledModifiers { .. } => "enabledModifiers",
+            Self::Presets { .. } => "presets",
+            Self::RoleWeights { .. } => "roleWeights",
+            Self::RoleConstraints { .. } => "roleConstraints",
+            Self::CustomRoleSets { .. } => "customRoleSets",
+            Self::RoleListAnalysis { .. } => "roleListAnalysis",
+            Self::HostData { .. } => "hostData",
+            Self::GamePlayers { .. } => "gamePlayers",
+            Self::GameInitializationComplete { .. } => "gameInitializationComplete",
+            Self::BackToLobby { .. } => "backToLobby",
+            Self::YourPlayerIndex { .. } => "yourPlayerIndex",
+            Self::YourFellowInsiders { .. } => "yourFellowInsiders",
+            Self::Phase { .. } => "phase",
+            Self::PhaseTimeLeft { .. } => "phaseTimeLeft",
+            Self::PlayerAlive { .. } => "playerAlive",
+            Self::PlayerVotes { .. } => "playerVotes",
+            Self::PlayerVotesPatch { .. } => "playerVotesPatch",
+            Self::YourSendChatGroups { .. } => "yourSendChatGroups",
+            Self::YourInsiderGroups { .. } => "yourInsiderGroups",
+            Self::YourAllowedControllers { .. } => "yourAllowedControllers",
+            Self::YourAllowedControllersPatch { .. } => "yourAllowedControllersPatch",
+            Self::YourRoleLabels { .. } => "yourRoleLabels",
+            Self::YourRoleLabelsPatch { .. } => "yourRoleLabelsPatch",
+            Self::YourPlayerTags { .. } => "yourPlayerTags",
+            Self::YourPlayerTagsPatch { .. } => "yourPlayerTagsPatch",
+            Self::YourWill { .. } => "yourWill",
+            Self::YourNotes { .. } => "yourNotes",
+            Self::YourCrossedOutOutlines { .. } => "yourCrossedOutOutlines",
+            Self::YourDeathNote { .. } => "yourDeathNote",
+            Self::YourRoleState { .. } => "yourRoleState",
+            Self::YourJudgement { .. } => "yourJudgement",
+            Self::YourVoteFastForwardPhase { .. } => "yourVoteFastForwardPhase",
+            Self::AddChatMessages { .. } => "addChatMessages",
+            Self::AddGrave { .. } => "addGrave",
+            Self::NightMessages { .. } => "nightMessages",
+            Self::GameOver { .. } => "gameOver",
+        }
+    }
     pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self).inspect_err(|_|{
-            log!(error "Serde error"; "Parsing JSON string: {:?}", self);
+            tracing::error!(packet = ?self, "Failed to serialize packet");
+        })
+    }
+    pub fn to_message_pack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        rmp_serde::to_vec_named(self).inspect_err(|_|{
+            tracing::error!(packet = ?self, "Failed to serialize packet");
         })
     }
     pub fn new_player_votes(game: &mut Game)->ToClientPacket{
@@ -169,28 +291,60 @@ impl ToClientPacket {
     }
 }
 
-#[derive(Serialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum RejectJoinReason {
     GameAlreadyStarted,
     RoomFull,
     RoomDoesntExist,
     ServerBusy,
+    ServerShuttingDown,
+    /// The client's protocol version is too old or too new for this server
+    IncompatibleVersion,
+    PasswordRequired,
+    WrongPassword,
+    /// The client gave too many wrong passwords lately
+    TooManyPasswordAttempts,
 
     PlayerTaken,
     PlayerDoesntExist,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum ToServerPacket{
+    /// Sent once, right after connecting. Clients that never send it are treated as the legacy protocol version.
+    #[serde(rename_all = "camelCase")]
+    Hello{protocol_version: ProtocolVersion, capabilities: VecSet<Capability>},
     Ping,
     // Pre Lobby
     LobbyListRequest,
     #[serde(rename_all = "camelCase")]
-    ReJoin{room_code: RoomCode, player_id: LobbyClientID},
+    ReJoin{
+        room_code: RoomCode, 
+        player_id: LobbyClientID,
+        /// The last sequence number the client saw, if it wants to resume instead of being sent the whole game
+        #[serde(default)]
+        last_sequence: Option<PacketSequence>,
+        /// Needed to rejoin a password protected lobby, like it is to join one
+        #[serde(default)]
+        password: Option<String>,
+        /// Needed to rejoin an unlisted lobby, like it is to join one
+        #[serde(default)]
+        invite_code: Option<String>
+    },
+    #[serde(rename_all = "camelCase")]
+    Join{
+        room_code: RoomCode,
+        #[serde(default)]
+        password: Option<String>
+    },
     #[serde(rename_all = "camelCase")]
-    Join{room_code: RoomCode},
+    JoinWithInviteCode{
+        invite_code: String,
+        #[serde(default)]
+        password: Option<String>
+    },
     Host,
     Leave,
     #[serde(rename_all = "camelCase")]
@@ -202,7 +356,12 @@ pub enum ToServerPacket{
     SetName{name: String},
     ReadyUp{ready: bool},
     SetLobbyName{name: String},
+    SetLobbyVisibility{visibility: LobbyVisibility},
+    #[serde(rename_all = "camelCase")]
+    SetLobbyStartSettings{start_settings: StartSettings},
     StartGame,
+    /// Seats a bot. Bots are removed by kicking them.
+    AddBot,
 
     // Settings
     #[serde(rename_all = "camelCase")]
@@ -219,6 +378,15 @@ pub enum ToServerPacket{
     SetEnabledRoles{roles: Vec<Role>},
     #[serde(rename_all = "camelCase")]
     SetEnabledModifiers{modifiers: Vec<ModifierType>},
+    /// Roles left out have a weight of 1
+    #[serde(rename_all = "camelCase")]
+    SetRoleWeights{role_weights: VecMap<Role, u8>},
+    SetRoleConstraints{constraints: Vec<RoleConstraint>},
+    /// Outlines and constraints using a set by name are updated to its new roles
+    #[serde(rename_all = "camelCase")]
+    SetCustomRoleSets{custom_role_sets: Vec<CustomRoleSet>},
+    /// Replaces the role list, phase times, enabled roles, enabled modifiers, role weights, role constraints and custom role sets with a preset's
+    LoadPreset{name: String},
 
     // Host
     HostDataRequest,
@@ -226,8 +394,14 @@ pub enum ToServerPacket{
     HostForceEndGame,
     HostForceSkipPhase,
     HostForceSetPlayerName { id: LobbyClientID, name: String },
+    /// Gives a player who quit to a bot
+    #[serde(rename_all = "camelCase")]
+    HostSubstituteBot { player_index: PlayerIndex },
 
     // Game
+    /// A spectator takes over a player who quit
+    #[serde(rename_all = "camelCase")]
+    ClaimPlayer{player_index: PlayerIndex},
     #[serde(rename_all = "camelCase")]
     Judgement{verdict: Verdict},
 
@@ -261,4 +435,59 @@ pub enum ToServerPacket{
 
     #[serde(rename_all = "camelCase")]
     VoteFastForwardPhase{fast_forward: bool},
-}
\ No newline at end of file
+}
+
+impl ToServerPacket {
+    /// The `type` tag this packet is sent with
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Hello { .. } => "hello",
+            Self::Ping { .. } => "ping",
+            Self::LobbyListRequest { .. } => "lobbyListRequest",
+            Self::ReJoin { .. } => "reJoin",
+            Self::Join { .. } => "join",
+            Self::JoinWithInviteCode { .. } => "joinWithInviteCode",
+            Self::Host { .. } => "host",
+            Self::Leave { .. } => "leave",
+            Self::Kick { .. } => "kick",
+            Self::SendLobbyMessage { .. } => "sendLobbyMessage",
+            Self::SetSpectator { .. } => "setSpectator",
+            Self::SetName { .. } => "setName",
+            Self::ReadyUp { .. } => "readyUp",
+            Self::SetLobbyName { .. } => "setLobbyName",
+            Self::SetLobbyVisibility { .. } => "setLobbyVisibility",
+            Self::SetLobbyStartSettings { .. } => "setLobbyStartSettings",
+            Self::StartGame { .. } => "startGame",
+            Self::AddBot { .. } => "addBot",
+            Self::SetRoleList { .. } => "setRoleList",
+            Self::SetRoleOutline { .. } => "setRoleOutline",
+            Self::SimplifyRoleList { .. } => "simplifyRoleList",
+            Self::SetPhaseTime { .. } => "setPhaseTime",
+            Self::SetPhaseTimes { .. } => "setPhaseTimes",
+            Self::SetEnabledRoles { .. } => "setEnabledRoles",
+            Self::SetEnabledModifiers { .. } => "setEnabledModifiers",
+            Self::SetRoleWeights { .. } => "setRoleWeights",
+            Self::SetRoleConstraints { .. } => "setRoleConstraints",
+            Self::SetCustomRoleSets { .. } => "setCustomRoleSets",
+            Self::LoadPreset { .. } => "loadPreset",
+            Self::HostDataRequest { .. } => "hostDataRequest",
+            Self::HostForceBackToLobby { .. } => "hostForceBackToLobby",
+            Self::HostForceEndGame { .. } => "hostForceEndGame",
+            Self::HostForceSkipPhase { .. } => "hostForceSkipPhase",
+            Self::HostForceSetPlayerName { .. } => "hostForceSetPlayerName",
+            Self::HostSubstituteBot { .. } => "hostSubstituteBot",
+            Self::ClaimPlayer { .. } => "claimPlayer",
+            Self::Judgement { .. } => "judgement",
+            Self::SendChatMessage { .. } => "sendChatMessage",
+            Self::SendWhisper { .. } => "sendWhisper",
+            Self::SaveWill { .. } => "saveWill",
+            Self::SaveNotes { .. } => "saveNotes",
+            Self::SaveCrossedOutOutlines { .. } => "saveCrossedOutOutlines",
+            Self::SaveDeathNote { .. } => "saveDeathNote",
+            Self::AbilityInput { .. } => "abilityInput",
+            Self::SetDoomsayerGuess { .. } => "setDoomsayerGuess",
+            Self::SetConsortOptions { .. } => "setConsortOptions",
+            Self::VoteFastForwardPhase { .. } => "voteFastForwardPhase",
+        }
+    }
+}
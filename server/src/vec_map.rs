@@ -147,6 +147,46 @@ impl<K,  V> FromIterator<(K, V)> for VecMap<K, V> where K: Eq {
     }
 }
 
+impl<K, V> VecMap<K, V> where K: Eq + Clone, V: Clone {
+    /// The entries that were added, changed, or removed since `old`.
+    /// `same` decides whether a value changed.
+    pub fn patch_from_by(&self, old: &Self, same: impl Fn(&V, &V) -> bool) -> VecMapPatch<K, V> {
+        VecMapPatch {
+            set: self.iter()
+                .filter(|(k, v)| !old.get(k).is_some_and(|old| same(old, v)))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            removed: old.keys()
+                .filter(|k| !self.contains_key(k))
+                .cloned()
+                .collect(),
+        }
+    }
+    pub fn patch_from(&self, old: &Self) -> VecMapPatch<K, V> where V: PartialEq {
+        self.patch_from_by(old, V::eq)
+    }
+}
+
+/// The changes that turn one [`VecMap`] into another
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VecMapPatch<K, V> where K: Eq {
+    pub set: VecMap<K, V>,
+    pub removed: Vec<K>,
+}
+impl<K, V> VecMapPatch<K, V> where K: Eq {
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.removed.is_empty()
+    }
+    pub fn apply(self, map: &mut VecMap<K, V>) {
+        for key in self.removed {
+            map.remove(&key);
+        }
+        for (key, value) in self.set {
+            map.insert(key, value);
+        }
+    }
+}
+
 impl<K, V> Serialize for VecMap<K, V> where K: Eq, K: Serialize, V: Serialize {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
         self.vec.serialize(serializer)
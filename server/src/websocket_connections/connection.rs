@@ -1,18 +1,29 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::{Arc, PoisonError, RwLock}};
 
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::packet::ToClientPacket;
 
+use super::protocol::Protocol;
+
 #[derive(Debug, Clone)]
 pub struct Connection {
     tx: ClientSender,
-    address: SocketAddr
+    address: SocketAddr,
+    protocol: Arc<RwLock<Protocol>>,
 }
 
 impl Connection {
     pub fn new(tx: UnboundedSender<ToClientPacket>, address: SocketAddr) -> Self {
-        Self { tx: ClientSender { tx }, address }
+        Self { tx: ClientSender { tx }, address, protocol: Arc::new(RwLock::new(Protocol::legacy())) }
+    }
+
+    /// What the client agreed to speak in its hello packet
+    pub fn protocol(&self) -> Protocol {
+        self.protocol.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+    pub fn set_protocol(&self, protocol: Protocol) {
+        *self.protocol.write().unwrap_or_else(PoisonError::into_inner) = protocol;
     }
 
     pub fn get_address(&self) -> &SocketAddr {
@@ -39,6 +50,11 @@ pub struct ClientSender {
 }
 
 impl ClientSender {
+    /// A sender with nobody listening, for connections only known from what the server reported about them
+    pub fn detached() -> Self {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        Self { tx }
+    }
     pub fn send(&self, message: ToClientPacket) {
         let _ = self.tx.send(message);
     }
@@ -1,6 +1,9 @@
 use std::sync::{Mutex, Arc, MutexGuard};
 
 pub mod connection;
+pub mod protocol;
+pub mod sent_state;
+pub mod tls;
 pub mod websocket_listener;
 
 pub trait ForceLock {
@@ -0,0 +1,336 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::Message;
+
+use vec1::Vec1;
+
+use crate::{game::{chat::{ChatMessage, ChatMessageVariant}, grave::{Grave, GraveDeathCause, GraveInformation, GraveKiller}, role_generation::RoleConstraint, role_list::{RoleList, RoleOutline, RoleOutlineOption, RoleOutlineOptionRoles, RoleSet}}, lobby::lobby_client::LobbyClientType, packet::{RejectJoinReason, ToClientPacket, ToServerPacket}, vec_set::VecSet};
+
+pub type ProtocolVersion = u32;
+
+/// Bump this whenever a packet changes in a way that older clients can't read,
+/// and teach [`Protocol::downgrade`] how to rewrite the new packets for the old version
+pub const PROTOCOL_VERSION: ProtocolVersion = 11;
+/// Clients from before the hello packet existed never send one, so they're assumed to speak this version
+pub const LEGACY_PROTOCOL_VERSION: ProtocolVersion = 1;
+/// The first version with the hello packet. [`Protocol::downgrade`] handles every version since,
+/// so clients that haven't reloaded in a while keep working.
+pub const OLDEST_SUPPORTED_PROTOCOL_VERSION: ProtocolVersion = 2;
+
+/// Optional features a client can ask for in its hello packet
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum Capability {
+    /// Packets are sent as MessagePack in binary frames instead of JSON in text frames
+    MessagePack,
+    /// Packets to players in a game are wrapped in [`ToClientPacket::Sequenced`],
+    /// and the client can resume from the last one it saw when it rejoins
+    Resume,
+    /// Collections the client already has are sent as patches instead of in full.
+    /// See [`super::sent_state::SentState`]
+    StatePatches,
+    /// Anything this server doesn't know about. Never accepted.
+    #[serde(other)]
+    Unknown,
+}
+impl Capability {
+    pub fn is_supported(&self) -> bool {
+        !matches!(self, Capability::Unknown)
+    }
+}
+
+/// What a connection agreed to speak
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Protocol {
+    pub version: ProtocolVersion,
+    pub capabilities: VecSet<Capability>,
+}
+
+impl Default for Protocol {
+    fn default() -> Self {
+        Self::legacy()
+    }
+}
+
+impl Protocol {
+    /// Used until the client says hello
+    pub fn legacy() -> Self {
+        Self { version: LEGACY_PROTOCOL_VERSION, capabilities: VecSet::new() }
+    }
+
+    /// Keeps the capabilities both sides support. Clients too old or too new to talk to are rejected.
+    pub fn negotiate(version: ProtocolVersion, capabilities: VecSet<Capability>) -> Result<Self, RejectJoinReason> {
+        if !(OLDEST_SUPPORTED_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) {
+            return Err(RejectJoinReason::IncompatibleVersion);
+        }
+
+        Ok(Self {
+            version,
+            capabilities: capabilities.into_iter().filter(Capability::is_supported).collect(),
+        })
+    }
+
+    pub fn has(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        if self.has(Capability::MessagePack) {
+            Encoding::MessagePack
+        } else {
+            Encoding::Json
+        }
+    }
+
+    /// Rewrites a packet so a client on an older version, or without some capability, can read it.
+    /// Returns `None` if that client has no way to understand it.
+    pub fn downgrade(&self, packet: ToClientPacket) -> Option<ToClientPacket> {
+        if let ToClientPacket::Sequenced { sequence, packet } = packet {
+            let packet = self.downgrade(*packet)?;
+            return if self.has(Capability::Resume) {
+                Some(ToClientPacket::Sequenced { sequence, packet: Box::new(packet) })
+            } else {
+                Some(packet)
+            };
+        }
+
+        if self.version >= PROTOCOL_VERSION {
+            return Some(packet);
+        }
+
+        // Version 11 limited how often passwords can be tried, which older clients are told is a wrong password
+        let packet = match packet {
+            ToClientPacket::RejectJoin { reason: RejectJoinReason::TooManyPasswordAttempts } => {
+                ToClientPacket::RejectJoin { reason: RejectJoinReason::WrongPassword }
+            }
+            packet => packet,
+        };
+        if self.version >= 10 {
+            return Some(packet);
+        }
+
+        // Version 10 added auto start. Older clients can still start games by hand.
+        let packet = match packet {
+            ToClientPacket::LobbyStartSettings { .. } | ToClientPacket::StartCountdown { .. } => return None,
+            packet => packet,
+        };
+        if self.version >= 9 {
+            return Some(packet);
+        }
+
+        // Version 9 added custom role sets, which older clients are sent as the roles in them
+        let packet = match packet {
+            ToClientPacket::CustomRoleSets { .. } => return None,
+            ToClientPacket::RoleList { role_list } => ToClientPacket::RoleList {
+                role_list: RoleList(role_list.0.into_iter().map(expand_custom_role_sets).collect())
+            },
+            ToClientPacket::RoleOutline { index, role_outline } => ToClientPacket::RoleOutline {
+                index, role_outline: expand_custom_role_sets(role_outline)
+            },
+            ToClientPacket::RoleConstraints { constraints } => ToClientPacket::RoleConstraints {
+                constraints: constraints.into_iter()
+                    .map(|constraint| match constraint {
+                        RoleConstraint::AtMost { count, roles } => RoleConstraint::AtMost { count, roles: expand_roles(roles) },
+                        RoleConstraint::Requires { roles, requires } => RoleConstraint::Requires { roles: expand_roles(roles), requires: expand_roles(requires) },
+                    })
+                    .collect()
+            },
+            ToClientPacket::AddGrave { grave } => ToClientPacket::AddGrave { grave: without_custom_grave_killers(grave) },
+            ToClientPacket::AddChatMessages { chat_messages } => ToClientPacket::AddChatMessages {
+                chat_messages: chat_messages.into_iter()
+                    .map(|message| match message.variant {
+                        ChatMessageVariant::PlayerDied { grave } => ChatMessage::new(
+                            ChatMessageVariant::PlayerDied { grave: without_custom_grave_killers(grave) },
+                            message.chat_group
+                        ),
+                        _ => message,
+                    })
+                    .collect()
+            },
+            packet => packet,
+        };
+        if self.version >= 8 {
+            return Some(packet);
+        }
+
+        // Version 8 added role weights and constraints
+        let packet = match packet {
+            ToClientPacket::RoleWeights { .. } | ToClientPacket::RoleConstraints { .. } => return None,
+            packet => packet,
+        };
+        if self.version >= 7 {
+            return Some(packet);
+        }
+
+        // Version 7 added role list analysis
+        let packet = match packet {
+            ToClientPacket::RoleListAnalysis { .. } => return None,
+            packet => packet,
+        };
+        if self.version >= 6 {
+            return Some(packet);
+        }
+
+        // Version 6 added settings presets
+        let packet = match packet {
+            ToClientPacket::Presets { .. } => return None,
+            packet => packet,
+        };
+        if self.version >= 5 {
+            return Some(packet);
+        }
+
+        // Version 5 added substitutes for players who quit, which older clients aren't told about
+        let packet = match packet {
+            ToClientPacket::AddChatMessages { chat_messages } => ToClientPacket::AddChatMessages {
+                chat_messages: chat_messages.into_iter()
+                    .filter(|message| !matches!(message.variant(), ChatMessageVariant::PlayerSubstituted { .. }))
+                    .collect()
+            },
+            packet => packet,
+        };
+        if self.version >= 4 {
+            return Some(packet);
+        }
+
+        // Version 4 added bots, which older clients are shown as players
+        let packet = match packet {
+            ToClientPacket::LobbyClients { clients } => ToClientPacket::LobbyClients {
+                clients: clients.into_iter()
+                    .map(|(id, mut client)| {
+                        if let LobbyClientType::Bot { name } = client.client_type {
+                            client.client_type = LobbyClientType::Player { name };
+                        }
+                        (id, client)
+                    })
+                    .collect()
+            },
+            packet => packet,
+        };
+        if self.version >= 3 {
+            return Some(packet);
+        }
+
+        // Version 3 added private lobbies
+        let packet = match packet {
+            ToClientPacket::LobbyVisibility { .. } |
+            ToClientPacket::LobbyInviteCode { .. } => return None,
+            ToClientPacket::RejectJoin { reason: RejectJoinReason::PasswordRequired | RejectJoinReason::WrongPassword } => {
+                ToClientPacket::RejectJoin { reason: RejectJoinReason::RoomDoesntExist }
+            }
+            packet => packet,
+        };
+        if self.version >= 2 {
+            return Some(packet);
+        }
+
+        // Version 1 is everything from before the hello packet
+        match packet {
+            ToClientPacket::HelloAccepted { .. } |
+            ToClientPacket::ResumeAccepted |
+            ToClientPacket::LobbyCrashed |
+            ToClientPacket::ServerShuttingDown { .. } => None,
+            ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerShuttingDown } => {
+                Some(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerBusy })
+            }
+            packet => Some(packet),
+        }
+    }
+}
+
+/// One option for each role in a custom role set, keeping the set's win condition, insiders and weight
+fn expand_custom_role_sets(outline: RoleOutline) -> RoleOutline {
+    let options: Vec<RoleOutlineOption> = outline.options.iter()
+        .flat_map(|option| match &option.roles {
+            RoleOutlineOptionRoles::CustomRoleSet { custom_role_set } => custom_role_set.roles.iter()
+                .map(|role| RoleOutlineOption { roles: RoleOutlineOptionRoles::Role { role: *role }, ..option.clone() })
+                .collect(),
+            _ => vec![option.clone()],
+        })
+        .collect();
+
+    Vec1::try_from_vec(options).map_or(outline, |options| RoleOutline { options })
+}
+
+fn expand_roles(roles: Vec<RoleOutlineOptionRoles>) -> Vec<RoleOutlineOptionRoles> {
+    roles.into_iter()
+        .flat_map(|roles| match roles {
+            RoleOutlineOptionRoles::CustomRoleSet { custom_role_set } => custom_role_set.roles.into_iter()
+                .map(|role| RoleOutlineOptionRoles::Role { role })
+                .collect(),
+            roles => vec![roles],
+        })
+        .collect()
+}
+
+/// Says the killer could have been anyone, rather than giving away which role it was
+fn without_custom_grave_killers(mut grave: Grave) -> Grave {
+    if let GraveInformation::Normal { death_cause: GraveDeathCause::Killers(killers), .. } = &mut grave.information {
+        for killer in killers.iter_mut() {
+            if let GraveKiller::CustomRoleSet(_) = killer {
+                *killer = GraveKiller::RoleSet(RoleSet::Any);
+            }
+        }
+    }
+    grave
+}
+
+/// How packets are written to the websocket. Both use the same serde derives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Text frames
+    Json,
+    /// Binary frames, with struct fields written as maps so tagged enums still work
+    MessagePack,
+}
+
+impl Encoding {
+    /// Label for the encoding in metrics
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Json => "json",
+            Encoding::MessagePack => "messagePack",
+        }
+    }
+
+    /// Returns `None` if the packet couldn't be serialized
+    pub fn encode(&self, packet: &ToClientPacket) -> Option<Message> {
+        match self {
+            Encoding::Json => packet.to_json_string().ok().map(Message::text),
+            Encoding::MessagePack => packet.to_message_pack().ok().map(Message::binary),
+        }
+    }
+
+    /// Text frames are JSON and binary frames are MessagePack, whatever the connection negotiated
+    pub fn of(message: &Message) -> Option<Self> {
+        match message {
+            Message::Text(_) => Some(Encoding::Json),
+            Message::Binary(_) => Some(Encoding::MessagePack),
+            _ => None,
+        }
+    }
+
+    pub fn decode(message: &Message) -> Result<ToServerPacket, DecodeError> {
+        match message {
+            Message::Binary(bytes) => rmp_serde::from_slice(bytes).map_err(DecodeError::MessagePack),
+            message => serde_json::from_str(message.to_text().map_err(|_| DecodeError::NotText)?).map_err(DecodeError::Json),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Json(serde_json::Error),
+    MessagePack(rmp_serde::decode::Error),
+    NotText,
+}
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Json(err) => write!(f, "{err}"),
+            DecodeError::MessagePack(err) => write!(f, "{err}"),
+            DecodeError::NotText => write!(f, "Message isn't valid UTF-8"),
+        }
+    }
+}
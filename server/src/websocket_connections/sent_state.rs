@@ -0,0 +1,99 @@
+use std::mem::discriminant;
+
+use vec1::Vec1;
+
+use crate::{
+    game::{ability_input::{ControllerID, SavedController}, player::PlayerIndex, role::Role, tag::Tag},
+    lobby::lobby_client::{LobbyClient, LobbyClientID},
+    packet::ToClientPacket,
+    vec_map::{VecMap, VecMapPatch}
+};
+
+/// The collections last sent to one connection, so later sends only carry what changed.
+/// Only used for clients with [`super::protocol::Capability::StatePatches`].
+#[derive(Debug, Default)]
+pub struct SentState {
+    lobby_clients: Option<VecMap<LobbyClientID, LobbyClient>>,
+    player_votes: Option<VecMap<PlayerIndex, u8>>,
+    allowed_controllers: Option<VecMap<ControllerID, SavedController>>,
+    role_labels: Option<VecMap<PlayerIndex, Role>>,
+    player_tags: Option<VecMap<PlayerIndex, Vec1<Tag>>>,
+}
+
+enum Update<K: Eq, V> {
+    Full(VecMap<K, V>),
+    Patch(VecMapPatch<K, V>),
+}
+
+impl SentState {
+    /// Turns a full-state packet into a patch against what this client was sent last.
+    /// The first one of each kind goes out in full. Returns `None` if nothing changed.
+    pub fn patch(&mut self, packet: ToClientPacket) -> Option<ToClientPacket> {
+        Some(match packet {
+            ToClientPacket::Sequenced { sequence, packet } => {
+                ToClientPacket::Sequenced { sequence, packet: Box::new(self.patch(*packet)?) }
+            }
+            ToClientPacket::LobbyClients { clients } => {
+                match update(&mut self.lobby_clients, clients, same_lobby_client)? {
+                    Update::Full(clients) => ToClientPacket::LobbyClients { clients },
+                    Update::Patch(clients) => ToClientPacket::LobbyClientsPatch { clients },
+                }
+            }
+            ToClientPacket::PlayerVotes { votes_for_player } => {
+                match update(&mut self.player_votes, votes_for_player, PartialEq::eq)? {
+                    Update::Full(votes_for_player) => ToClientPacket::PlayerVotes { votes_for_player },
+                    Update::Patch(votes_for_player) => ToClientPacket::PlayerVotesPatch { votes_for_player },
+                }
+            }
+            ToClientPacket::YourAllowedControllers { save } => {
+                match update(&mut self.allowed_controllers, save, PartialEq::eq)? {
+                    Update::Full(save) => ToClientPacket::YourAllowedControllers { save },
+                    Update::Patch(save) => ToClientPacket::YourAllowedControllersPatch { save },
+                }
+            }
+            ToClientPacket::YourRoleLabels { role_labels } => {
+                match update(&mut self.role_labels, role_labels, PartialEq::eq)? {
+                    Update::Full(role_labels) => ToClientPacket::YourRoleLabels { role_labels },
+                    Update::Patch(role_labels) => ToClientPacket::YourRoleLabelsPatch { role_labels },
+                }
+            }
+            ToClientPacket::YourPlayerTags { player_tags } => {
+                match update(&mut self.player_tags, player_tags, PartialEq::eq)? {
+                    Update::Full(player_tags) => ToClientPacket::YourPlayerTags { player_tags },
+                    Update::Patch(player_tags) => ToClientPacket::YourPlayerTagsPatch { player_tags },
+                }
+            }
+            // The client throws its state away after these, so there's nothing left to patch
+            packet @ (
+                ToClientPacket::AcceptJoin { .. } |
+                ToClientPacket::StartGame |
+                ToClientPacket::BackToLobby |
+                ToClientPacket::ForcedOutsideLobby
+            ) => {
+                *self = Self::default();
+                packet
+            }
+            packet => packet,
+        })
+    }
+}
+
+fn update<K, V>(last_sent: &mut Option<VecMap<K, V>>, map: VecMap<K, V>, same: impl Fn(&V, &V) -> bool) -> Option<Update<K, V>>
+where K: Eq + Clone, V: Clone {
+    let Some(old) = last_sent.replace(map.clone()) else {
+        return Some(Update::Full(map));
+    };
+    let patch = map.patch_from_by(&old, same);
+    if patch.is_empty() {
+        None
+    } else {
+        Some(Update::Patch(patch))
+    }
+}
+
+/// Compares only what gets serialized
+fn same_lobby_client(old: &LobbyClient, new: &LobbyClient) -> bool {
+    old.ready == new.ready &&
+    old.client_type == new.client_type &&
+    discriminant(&old.connection) == discriminant(&new.connection)
+}
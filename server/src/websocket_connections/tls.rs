@@ -0,0 +1,89 @@
+use std::{fmt::Display, fs::File, io::BufReader, path::{Path, PathBuf}, sync::{Arc, PoisonError, RwLock}};
+
+use tokio::{signal::unix::{signal, SignalKind}, sync::broadcast};
+use tokio_rustls::{rustls::{self, crypto::ring, pki_types::{CertificateDer, PrivateKeyDer}}, TlsAcceptor};
+use tracing::{info, warn};
+
+#[derive(Debug)]
+pub enum TlsError {
+    Io(std::io::Error),
+    NoPrivateKey,
+    Rustls(rustls::Error),
+}
+impl Display for TlsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsError::Io(err) => write!(f, "{err}"),
+            TlsError::NoPrivateKey => write!(f, "No private key found in the key file"),
+            TlsError::Rustls(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+/// Terminates TLS for incoming connections.
+/// The certificate can be swapped out while the server is running; connections that already finished
+/// their handshake keep the certificate they started with.
+pub struct Tls {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    acceptor: RwLock<TlsAcceptor>,
+}
+
+impl Tls {
+    pub fn load(cert_path: PathBuf, key_path: PathBuf) -> Result<Arc<Self>, TlsError> {
+        let acceptor = Self::create_acceptor(&cert_path, &key_path)?;
+        Ok(Arc::new(Self { cert_path, key_path, acceptor: RwLock::new(acceptor) }))
+    }
+
+    pub fn acceptor(&self) -> TlsAcceptor {
+        self.acceptor.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Reads the certificate and key again. If they're invalid, the old ones are kept.
+    pub fn reload(&self) -> Result<(), TlsError> {
+        let acceptor = Self::create_acceptor(&self.cert_path, &self.key_path)?;
+        *self.acceptor.write().unwrap_or_else(PoisonError::into_inner) = acceptor;
+        Ok(())
+    }
+
+    /// Reloads the certificate whenever the process receives SIGHUP, until `stop` fires
+    pub fn reload_on_sighup(self: Arc<Self>, mut stop: broadcast::Receiver<()>) {
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(err) => {
+                warn!(%err, "Failed to listen for SIGHUP, certificates won't be reloaded");
+                return;
+            }
+        };
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(()) = hangups.recv() => match self.reload() {
+                        Ok(()) => info!(path = %self.cert_path.display(), "Reloaded certificate"),
+                        Err(err) => warn!(%err, "Failed to reload certificate, keeping the old one"),
+                    },
+                    _ = stop.recv() => break,
+                }
+            }
+        });
+    }
+
+    fn create_acceptor(cert_path: &Path, key_path: &Path) -> Result<TlsAcceptor, TlsError> {
+        let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(cert_path).map_err(TlsError::Io)?))
+            .collect::<Result<Vec<CertificateDer<'static>>, _>>()
+            .map_err(TlsError::Io)?;
+        let key: PrivateKeyDer<'static> = rustls_pemfile::private_key(&mut BufReader::new(File::open(key_path).map_err(TlsError::Io)?))
+            .map_err(TlsError::Io)?
+            .ok_or(TlsError::NoPrivateKey)?;
+
+        let config = rustls::ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(TlsError::Rustls)?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(TlsError::Rustls)?;
+
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+}
@@ -1,62 +1,187 @@
-use crate::{websocket_connections::{connection::Connection, ForceLock}, listener::Listener, log};
-use tokio_tungstenite::tungstenite::Message;
-use std::{net::SocketAddr, sync::{Arc, Mutex}, pin::pin};
+use crate::{config::Config, http_api, metrics, websocket_connections::{connection::Connection, protocol::Capability, sent_state::SentState, tls::Tls, ForceLock}, listener::Listener, lobby::Lobby};
+use tracing::{debug, error, info, warn};
+use std::{net::SocketAddr, sync::{Arc, Mutex}, pin::pin, time::Duration};
 
 use futures_util::{future::{self, Either}, StreamExt, SinkExt};
 
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::signal::unix::{signal, SignalKind};
 use tokio::sync::{mpsc, broadcast};
-use tokio::net::{TcpListener, TcpStream};
+use tokio::net::TcpListener;
 
-pub async fn create_ws_server(server_address: &str) {
+/// How often a draining server checks whether every game has finished
+const DRAIN_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Why [`create_ws_server`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerExit {
+    /// Something panicked outside of a lobby. The server should be started again.
+    Crashed,
+    /// The process was asked to stop, and every game either finished or was saved
+    ShutDown,
+}
+
+pub async fn create_ws_server(server_address: &str) -> ServerExit {
     #[expect(clippy::panic, reason = "Server cannot start without TCP listener")]
     let tcp_listener = TcpListener::bind(&server_address).await.unwrap_or_else(|err| {
         panic!("Failed to bind websocket server to address {server_address}: {err}")
     });
     
+    let tls = match (&Config::get().tls_cert_path, &Config::get().tls_key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            #[expect(clippy::panic, reason = "Server shouldn't silently fall back to plain websockets when TLS is configured")]
+            let tls = Tls::load(cert_path.into(), key_path.into()).unwrap_or_else(|err| {
+                panic!("Failed to load TLS certificate {cert_path} and key {key_path}: {err}")
+            });
+            Some(tls)
+        }
+        _ => None
+    };
+
     let mut crash_signal = broadcast::channel(1);
 
+    if let Some(tls) = &tls {
+        tls.clone().reload_on_sighup(crash_signal.0.subscribe());
+    }
+
+    // Remove the hook from the previous server instance, if any.
+    let _ = std::panic::take_hook();
+
+    // Restored before the new hook is set, so a game that panics while replaying doesn't restart the server again
+    let listener = match Listener::load_snapshot(Config::get().snapshot_path()) {
+        Ok(Some(snapshot)) => Listener::restore(snapshot),
+        Ok(None) => Listener::new(),
+        Err(err) => {
+            warn!(path = Config::get().snapshot_path, %err, "Failed to load snapshot");
+            Listener::new()
+        }
+    };
+
     {
-        // Remove the hook from the previous server instance, if any.
-        let _ = std::panic::take_hook();
         // Set the new hook
         let panic_crash_signal_sender = crash_signal.0.clone();
         let original_hook = std::panic::take_hook();
         std::panic::set_hook(Box::new(move |info| {
-            let _ = panic_crash_signal_sender.send(());
+            // Lobbies catch their own panics, so those don't need to take down the whole server
+            if !Lobby::is_panic_isolated() {
+                let _ = panic_crash_signal_sender.send(());
+            }
             original_hook(info)
         }))
     }
 
-    let event_listener = Arc::new(Mutex::new(Listener::new()));
-    Listener::start(event_listener.clone());
+    let event_listener = Arc::new(Mutex::new(listener));
+    let ticker = Listener::start(event_listener.clone());
+
+    let http_api = Config::get().http_api_address.clone()
+        .map(|address| http_api::serve(address, event_listener.clone()));
 
-    log!(important "Server"; "Started listening on {server_address}");
+    info!(address = server_address, tls = tls.is_some(), "Started listening");
 
-    loop {
-        let (stream, client_address) = match future::select(
-            pin!(tcp_listener.accept()), 
-            pin!(crash_signal.1.recv())
-        ).await {
-            Either::Left((Ok((stream, client_address)), _)) => (stream, client_address),
-            Either::Left((Err(_), _)) => continue, // TCP connection failed
-            Either::Right(_) => break // Received crash signal
+    let mut shutdown_requests = shutdown_requests();
+    let mut draining = false;
+    let mut drain_check = tokio::time::interval(DRAIN_CHECK_INTERVAL);
+
+    let exit = loop {
+        // Connections are still accepted while draining so players in running games can rejoin.
+        // Hosting and joining are rejected by the listener.
+        let (stream, client_address) = tokio::select! {
+            accepted = tcp_listener.accept() => match accepted {
+                Ok((stream, client_address)) => (stream, client_address),
+                Err(_) => continue, // TCP connection failed
+            },
+            _ = crash_signal.1.recv() => break ServerExit::Crashed,
+            Some(()) = shutdown_requests.recv() => {
+                // A second request skips waiting for games to finish
+                if draining {
+                    break ServerExit::ShutDown;
+                }
+                info!(deadline_secs = Config::get().shutdown_deadline_secs, "Draining before shutting down");
+                event_listener.force_lock().start_draining();
+                draining = true;
+                continue;
+            },
+            _ = drain_check.tick(), if draining => {
+                if event_listener.force_lock().is_drained() {
+                    break ServerExit::ShutDown;
+                }
+                continue;
+            },
         };
         
         let event_listener = event_listener.clone();
         let crash_signal = (crash_signal.0.clone(), crash_signal.1.resubscribe());
+        let tls_acceptor = tls.as_ref().map(|tls| tls.acceptor());
 
         tokio::spawn(async move {
-            if let Ok(connection) = handle_connection(stream, client_address, event_listener.clone(), crash_signal).await {
+            let result = match tls_acceptor {
+                Some(tls_acceptor) => match tls_acceptor.accept(stream).await {
+                    Ok(stream) => handle_connection(stream, client_address, event_listener.clone(), crash_signal).await,
+                    Err(error) => {
+                        debug!(address = %client_address, %error, "Failed TLS handshake");
+                        Err(ConnectionError)
+                    }
+                },
+                None => handle_connection(stream, client_address, event_listener.clone(), crash_signal).await,
+            };
+
+            if let Ok(connection) = result {
                 match event_listener.force_lock().on_disconnect(connection) {
-                    Ok(()) => log!(important "Connection"; "Disconnected {}", client_address),
-                    Err(reason) => log!(error "Connection"; "Failed to disconnect {}: {}", client_address, reason)
+                    Ok(()) => info!(address = %client_address, "Disconnected"),
+                    Err(reason) => warn!(address = %client_address, reason, "Failed to disconnect")
                 };
             } 
         });
+    };
+
+    if exit == ServerExit::Crashed {
+        error!("The server panicked!");
     }
 
-    log!(fatal "Server"; "The server panicked!");
-    log!(important "Server"; "Shutting down...");
+    // Otherwise an old listener whose lock wasn't poisoned keeps ticking, and overwrites the next server's snapshots
+    ticker.abort();
+    if let Some(http_api) = http_api {
+        http_api.abort();
+    }
+
+    // The lobby that panicked is saved too, but it's dropped on restore when its game panics again while replaying
+    let snapshot = event_listener.force_lock().snapshot();
+    if let Err(err) = snapshot.save(Config::get().snapshot_path()) {
+        warn!(path = Config::get().snapshot_path, %err, "Failed to save snapshot");
+    }
+    // Closes every connection
+    let _ = crash_signal.0.send(());
+
+    info!("Shutting down...");
+    exit
+}
+
+/// Receives a message whenever the process gets SIGTERM or SIGINT
+fn shutdown_requests() -> mpsc::UnboundedReceiver<()> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    let (mut terminates, mut interrupts) = match (signal(SignalKind::terminate()), signal(SignalKind::interrupt())) {
+        (Ok(terminates), Ok(interrupts)) => (terminates, interrupts),
+        (Err(err), _) | (_, Err(err)) => {
+            warn!(%err, "Failed to listen for SIGTERM and SIGINT, the server can't shut down gracefully");
+            return receiver;
+        }
+    };
+
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                Some(()) = terminates.recv() => {},
+                Some(()) = interrupts.recv() => {},
+                else => break,
+            }
+            if sender.send(()).is_err() {
+                break;
+            }
+        }
+    });
+
+    receiver
 }
 
 struct ConnectionError;
@@ -64,8 +189,8 @@ struct ConnectionError;
 // Code within this function __SHOULD NOT PANIC__ except for listener methods.
 // There is a panic hook that restarts the server. The server doesn't need to restart if a connection fails, so don't panic -- just disconnect.
 /// This runs until the connection is closed. It does not remove the connection from the listener.
-async fn handle_connection(
-    raw_stream: TcpStream, 
+async fn handle_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+    raw_stream: S, 
     client_address: SocketAddr, 
     listener: Arc<Mutex<Listener>>,
     mut crash_signal: (broadcast::Sender<()>, broadcast::Receiver<()>)
@@ -73,7 +198,7 @@ async fn handle_connection(
     let ws_stream = match tokio_tungstenite::accept_async(raw_stream).await {
         Ok(ws_stream) => ws_stream,
         Err(error) => {
-            log!(info "Connection"; "Failed to accept websocket handshake with {}: {}", client_address, error);
+            debug!(address = %client_address, %error, "Failed to accept websocket handshake");
             return Err(ConnectionError);
         }
     };
@@ -90,33 +215,52 @@ async fn handle_connection(
             return Err(ConnectionError)
         };
         let connection = Connection::new(mpsc_sender, client_address);
-        log!(important "Connection"; "Connected: {}", client_address);
+        info!(address = %client_address, "Connected");
         listener.on_connect(&connection);
         connection
     };
     
     // Route MPSC packets to client via TCP
-    let send_over_tcp = tokio::spawn(async move {
-        loop {
-            let message = match future::select(pin!(mpsc_receiver.recv()), pin!(crash_signal.1.recv())).await {
-                Either::Left((Some(message), _)) => message,
-                Either::Left((None, _)) => break, // Channel has been closed
-                Either::Right(_) => break // Server has been closed
-            };
+    let send_over_tcp = {
+        let connection = connection.clone();
+
+        tokio::spawn(async move {
+            let mut sent_state = SentState::default();
+            loop {
+                let message = match future::select(pin!(mpsc_receiver.recv()), pin!(crash_signal.1.recv())).await {
+                    Either::Left((Some(message), _)) => message,
+                    Either::Left((None, _)) => break, // Channel has been closed
+                    Either::Right(_) => break // Server has been closed
+                };
+
+                let protocol = connection.protocol();
+
+                // Older clients can't read some packets, or need them rewritten
+                let Some(message) = protocol.downgrade(message) else {continue};
+
+                let message = if protocol.has(Capability::StatePatches) {
+                    let Some(message) = sent_state.patch(message) else {continue};
+                    message
+                } else {
+                    message
+                };
             
-            let Ok(json_message) = message.to_json_string() else {break};
-
-            match tcp_sender.send(Message::text(json_message)).await {
-                Ok(_) => {},
-                Err(tokio_tungstenite::tungstenite::Error::ConnectionClosed) => break,
-                Err(err) => {
-                    log!(error "Connection"; "Failed to send packet. {}", err);
-                    break
-                },
+                let packet_type = message.type_name();
+                let Some(message) = protocol.encoding().encode(&message) else {break};
+                metrics::packet_sent(packet_type, &message);
+
+                match tcp_sender.send(message).await {
+                    Ok(_) => {},
+                    Err(tokio_tungstenite::tungstenite::Error::ConnectionClosed) => break,
+                    Err(err) => {
+                        warn!(%err, "Failed to send packet");
+                        break
+                    },
+                }
             }
-        }
-        let _ = tcp_sender.close().await;
-    });
+            let _ = tcp_sender.close().await;
+        })
+    };
 
     let receive_over_tcp = {
         let listener = listener.clone();
@@ -0,0 +1,188 @@
+mod kit;
+
+use std::time::Duration;
+
+use mafia_server::{
+    config::Config,
+    game::RejectStartReason,
+    lobby::{
+        auto_start::StartSettings, lobby_access::JoinCredentials, lobby_client::{LobbyClientID, LobbyClientType},
+        snapshot::LobbyStateSnapshot, Lobby
+    },
+    packet::{RejectJoinReason, ToClientPacket, ToServerPacket},
+    websocket_connections::{connection::ClientSender, protocol::Protocol}
+};
+use tokio::sync::mpsc::UnboundedReceiver;
+use kit::{client, sender};
+
+/// A host listening for packets, and five players who haven't readied up
+fn host_and_players(start_settings: StartSettings) -> (Lobby, ClientSender, UnboundedReceiver<ToClientPacket>, Vec<LobbyClientID>) {
+    let (mut lobby, host, host_packets, ids) = kit::lobby_with_players();
+    lobby.on_client_message(&host, ids[0], ToServerPacket::SetLobbyStartSettings { start_settings });
+    (lobby, host, host_packets, ids)
+}
+
+fn auto_start() -> StartSettings {
+    StartSettings { auto_start: true, ..StartSettings::default() }
+}
+
+fn ready_up(lobby: &mut Lobby, ids: &[LobbyClientID], ready: bool) {
+    for id in ids {
+        lobby.on_client_message(&sender(), *id, ToServerPacket::ReadyUp { ready });
+    }
+}
+
+fn countdowns(packets: &mut UnboundedReceiver<ToClientPacket>) -> Vec<Option<u64>> {
+    let mut countdowns = Vec::new();
+    while let Ok(packet) = packets.try_recv() {
+        if let ToClientPacket::StartCountdown { seconds_left } = packet {
+            countdowns.push(seconds_left);
+        }
+    }
+    countdowns
+}
+
+fn start_settings(lobby: &Lobby) -> StartSettings {
+    lobby.snapshot().expect("Lobby should be open").start_settings
+}
+
+#[test]
+fn countdown_starts_game_once_everyone_is_ready() {
+    let countdown = Config::get().auto_start_countdown_secs;
+    let (mut lobby, _, mut host_packets, ids) = host_and_players(auto_start());
+
+    ready_up(&mut lobby, &ids[1..ids.len().saturating_sub(1)], true);
+    lobby.tick(Duration::from_secs(1));
+    assert!(countdowns(&mut host_packets).is_empty());
+
+    ready_up(&mut lobby, &ids[ids.len().saturating_sub(1)..], true);
+    assert_eq!(countdowns(&mut host_packets), vec![Some(countdown)]);
+
+    for _ in 1..countdown {
+        lobby.tick(Duration::from_secs(1));
+    }
+    assert!(!lobby.is_in_game());
+    assert_eq!(countdowns(&mut host_packets), (1..countdown).rev().map(Some).collect::<Vec<_>>());
+
+    lobby.tick(Duration::from_secs(1));
+    assert!(lobby.is_in_game());
+}
+
+#[test]
+fn unreadying_cancels_countdown() {
+    let countdown = Config::get().auto_start_countdown_secs;
+    let (mut lobby, _, mut host_packets, ids) = host_and_players(auto_start());
+
+    ready_up(&mut lobby, &ids[1..], true);
+    lobby.tick(Duration::from_secs(1));
+    ready_up(&mut lobby, &ids[1..2], false);
+    assert_eq!(countdowns(&mut host_packets).last(), Some(&None));
+
+    lobby.tick(Duration::from_secs(countdown));
+    assert!(!lobby.is_in_game());
+    assert!(countdowns(&mut host_packets).is_empty());
+}
+
+#[test]
+fn no_countdown_without_auto_start() {
+    let countdown = Config::get().auto_start_countdown_secs;
+    let (mut lobby, _, mut host_packets, ids) = host_and_players(StartSettings::default());
+
+    ready_up(&mut lobby, &ids[1..], true);
+    lobby.tick(Duration::from_secs(countdown));
+    assert!(!lobby.is_in_game());
+    assert!(countdowns(&mut host_packets).is_empty());
+}
+
+#[test]
+fn minimum_players_is_enforced_on_start() {
+    let (mut lobby, host, mut host_packets, ids) = host_and_players(StartSettings { min_players: Some(7), ..auto_start() });
+
+    ready_up(&mut lobby, &ids[1..], true);
+    assert!(countdowns(&mut host_packets).is_empty());
+
+    lobby.on_client_message(&host, ids[0], ToServerPacket::StartGame);
+    assert!(!lobby.is_in_game());
+    let mut rejected = false;
+    while let Ok(packet) = host_packets.try_recv() {
+        rejected |= matches!(packet, ToClientPacket::RejectStart { reason: RejectStartReason::TooFewPlayers });
+    }
+    assert!(rejected);
+}
+
+#[test]
+fn players_past_the_maximum_spectate() {
+    let (mut lobby, _, _, _) = host_and_players(StartSettings { max_players: Some(6), ..StartSettings::default() });
+
+    let (late, mut late_packets) = client();
+    let late_id = lobby.join_player(&late, &JoinCredentials::default()).expect("Lobby should have room");
+    assert!(matches!(late_packets.try_recv(), Ok(ToClientPacket::AcceptJoin { spectator: true, .. })));
+    assert!(matches!(lobby.add_bot(), Err(RejectJoinReason::RoomFull)));
+
+    lobby.on_client_message(&late, late_id, ToServerPacket::SetSpectator { spectator: false });
+    let Some(LobbyStateSnapshot::Lobby { clients, .. }) = lobby.snapshot().map(|snapshot| snapshot.state) else {
+        panic!("Lobby should still be in the lobby");
+    };
+    assert!(clients.iter().any(|(id, client)| *id == late_id && client.client_type == LobbyClientType::Spectator));
+}
+
+#[test]
+fn idle_host_is_replaced() {
+    let (mut lobby, host, _, ids) = host_and_players(StartSettings { idle_host_timeout_secs: Some(60), ..StartSettings::default() });
+
+    lobby.tick(Duration::from_secs(50));
+    lobby.on_client_message(&host, ids[0], ToServerPacket::SetName { name: "Host".to_string() });
+    lobby.tick(Duration::from_secs(50));
+    assert!(lobby.is_host(ids[0]));
+
+    lobby.tick(Duration::from_secs(10));
+    assert!(!lobby.is_host(ids[0]));
+    assert!(lobby.is_host(ids[1]));
+}
+
+#[test]
+fn only_host_changes_start_settings() {
+    let (mut lobby, _, _, ids) = host_and_players(StartSettings::default());
+
+    lobby.on_client_message(&sender(), ids[1], ToServerPacket::SetLobbyStartSettings { start_settings: auto_start() });
+    assert_eq!(start_settings(&lobby), StartSettings::default());
+}
+
+#[test]
+fn start_settings_are_sanitized() {
+    let (lobby, _, _, _) = host_and_players(StartSettings {
+        auto_start: true,
+        min_players: Some(10),
+        max_players: Some(8),
+        idle_host_timeout_secs: Some(1),
+    });
+    assert_eq!(start_settings(&lobby), StartSettings {
+        auto_start: true,
+        min_players: Some(8),
+        max_players: Some(8),
+        idle_host_timeout_secs: Some(60),
+    });
+
+    let (lobby, _, _, _) = host_and_players(StartSettings { min_players: Some(3), max_players: Some(0), ..StartSettings::default() });
+    assert_eq!(start_settings(&lobby), StartSettings { min_players: Some(3), ..StartSettings::default() });
+}
+
+#[test]
+fn start_settings_survive_restore() {
+    let (lobby, _, _, _) = host_and_players(StartSettings { max_players: Some(6), ..auto_start() });
+    let snapshot = lobby.snapshot().expect("Lobby should be open");
+
+    let restored = Lobby::restore(snapshot).expect("Lobby should restore");
+    assert_eq!(start_settings(&restored), StartSettings { max_players: Some(6), ..auto_start() });
+}
+
+#[test]
+fn old_clients_are_not_sent_auto_start() {
+    let old = Protocol { version: 9, capabilities: Default::default() };
+    assert!(old.downgrade(ToClientPacket::LobbyStartSettings { start_settings: auto_start() }).is_none());
+    assert!(old.downgrade(ToClientPacket::StartCountdown { seconds_left: Some(3) }).is_none());
+    assert!(old.downgrade(ToClientPacket::RejectStart { reason: RejectStartReason::TooFewPlayers }).is_some());
+
+    let current = Protocol { version: 10, capabilities: Default::default() };
+    assert!(current.downgrade(ToClientPacket::StartCountdown { seconds_left: None }).is_some());
+}
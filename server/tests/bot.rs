@@ -0,0 +1,184 @@
+mod kit;
+
+use std::time::Duration;
+
+use mafia_server::{
+    game::{
+        ability_input::{ControllerID, ControllerParametersMap, PlayerListSelection},
+        bot::{heuristic::HeuristicBot, Bot, BotStrategy, BotView},
+        components::{event_journal::GameInput, insider_group::InsiderGroupID},
+        phase::PhaseType,
+        player::PlayerReference,
+        replay,
+        role::{godfather::Godfather, mafioso::Mafioso, villager::Villager, Role, RoleState},
+        role_list::{RoleList, RoleOutline},
+        verdict::Verdict,
+        Game
+    },
+    lobby::{lobby_access::JoinCredentials, lobby_client::LobbyClientType, snapshot::LobbyStateSnapshot, Lobby},
+    packet::ToServerPacket
+};
+use kit::sender;
+
+fn bot() -> Bot {
+    Bot::new(Box::new(HeuristicBot::new(0)))
+}
+
+fn view<'a>(game: &'a Game, player: PlayerReference, controllers: &'a ControllerParametersMap) -> BotView<'a> {
+    BotView {
+        player,
+        phase: game.current_phase(),
+        day_number: game.day_number(),
+        chat_messages: player.chat_messages(game),
+        controllers,
+    }
+}
+
+#[test]
+fn heuristic_bot_nominates_whoever_voted_against_it() {
+    kit::scenario!(game in Nomination 2 where
+        me: Villager,
+        accuser: Villager,
+        bystander: Villager,
+        _mafioso: Mafioso
+    );
+    let mut bot = bot();
+
+    accuser.vote_for_player(me);
+    bot.tick(&mut game, me.player_ref());
+
+    assert_eq!(
+        game.saved_controllers.get_controller_current_selection_player_list(ControllerID::nominate(me.player_ref())),
+        Some(PlayerListSelection(vec![accuser.player_ref()]))
+    );
+
+    let mut strategy = HeuristicBot::new(0);
+    let controllers = game.saved_controllers.controller_parameters_allowed_to_player(me.player_ref());
+    let view = view(&game, me.player_ref(), &controllers);
+    assert_eq!(strategy.verdict(&view, accuser.player_ref()), Verdict::Guilty);
+    assert_eq!(strategy.verdict(&view, bystander.player_ref()), Verdict::Abstain);
+}
+
+#[test]
+fn heuristic_bot_protects_its_team() {
+    kit::scenario!(game in Nomination 2 where
+        me: Mafioso,
+        teammate: Godfather,
+        _villager: Villager,
+        _other_villager: Villager
+    );
+    // Test games don't show insider groups to each other on their own
+    InsiderGroupID::Mafia.reveal_group_players(&mut game);
+    let mut bot = bot();
+
+    teammate.vote_for_player(me);
+    bot.tick(&mut game, me.player_ref());
+
+    assert_eq!(
+        game.saved_controllers.get_controller_current_selection_player_list(ControllerID::nominate(me.player_ref())),
+        Some(PlayerListSelection(vec![]))
+    );
+
+    let mut strategy = HeuristicBot::new(0);
+    let controllers = game.saved_controllers.controller_parameters_allowed_to_player(me.player_ref());
+    let view = view(&game, me.player_ref(), &controllers);
+    assert_eq!(strategy.verdict(&view, teammate.player_ref()), Verdict::Innocent);
+}
+
+#[test]
+fn bots_fill_seats_and_play_a_game_that_replays() {
+    let mut lobby = Lobby::new(1);
+    let host = sender();
+    let host_id = lobby.join_player(&host, &JoinCredentials::default()).expect("Lobby should have room");
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetSpectator { spectator: true });
+
+    for _ in 0..7 {
+        lobby.on_client_message(&host, host_id, ToServerPacket::AddBot);
+    }
+    let players = lobby.get_player_list();
+    assert_eq!(players.len(), 7);
+
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetEnabledRoles { roles: Role::values().into_iter().collect() });
+    // Random roles can deal a game that never ends, like a doctor saving whoever the mafia attacks every night.
+    // With nobody to protect anyone, someone dies every night until the game is over.
+    let role_list = RoleList(
+        [Role::Mafioso, Role::Detective, Role::Villager, Role::Villager, Role::Villager, Role::Villager, Role::Villager]
+            .into_iter()
+            .map(RoleOutline::new_exact)
+            .collect()
+    );
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleList { role_list });
+    lobby.on_client_message(&host, host_id, ToServerPacket::StartGame);
+    assert!(lobby.is_in_game());
+
+    // Bots skip every phase once they've acted, so a game of only bots runs on its own
+    for _ in 0..1000 {
+        if !lobby.is_game_in_progress() {break}
+        lobby.tick(Duration::from_secs(1));
+    }
+    assert!(!lobby.is_game_in_progress());
+    assert!(!lobby.is_closed());
+
+    let Some(LobbyStateSnapshot::Game { record, bots, .. }) = lobby.snapshot().map(|snapshot| snapshot.state) else {
+        panic!("Lobby should be in a game");
+    };
+    assert_eq!(bots.len(), 7);
+    assert!(record.inputs.iter().any(|input| matches!(
+        input,
+        GameInput::Packet { packet: ToServerPacket::AbilityInput { .. }, .. }
+    )));
+    assert!(replay::verify(&record).expect("Game should replay").is_empty());
+
+    lobby.on_client_message(&host, host_id, ToServerPacket::HostForceBackToLobby);
+    assert!(!lobby.is_in_game());
+    assert_eq!(lobby.get_player_list(), players);
+}
+
+#[test]
+fn bots_are_kicked_like_players_and_never_host() {
+    let mut lobby = Lobby::new(1);
+    let host = sender();
+    let host_id = lobby.join_player(&host, &JoinCredentials::default()).expect("Lobby should have room");
+    let bot_id = lobby.add_bot().expect("Lobby should have room");
+
+    let player = sender();
+    let player_id = lobby.join_player(&player, &JoinCredentials::default()).expect("Lobby should have room");
+    lobby.on_client_message(&player, player_id, ToServerPacket::AddBot);
+    assert_eq!(lobby.get_player_list().len(), 3);
+
+    lobby.remove_player(bot_id);
+    assert_eq!(lobby.get_player_list().len(), 2);
+
+    lobby.remove_player(host_id);
+    assert!(lobby.is_host(player_id));
+
+    lobby.on_client_message(&player, player_id, ToServerPacket::AddBot);
+    lobby.remove_player(player_id);
+    assert!(lobby.is_closed());
+}
+
+#[test]
+fn older_clients_see_bots_as_players() {
+    use mafia_server::{
+        packet::ToClientPacket,
+        lobby::lobby_client::LobbyClient,
+        vec_map::VecMap,
+        websocket_connections::protocol::{Protocol, PROTOCOL_VERSION}
+    };
+
+    let mut clients = VecMap::new();
+    clients.insert(1, LobbyClient::new_bot("Robot".to_string()));
+    let packet = ToClientPacket::LobbyClients { clients };
+
+    let old = Protocol { version: 3, capabilities: Default::default() };
+    let Some(ToClientPacket::LobbyClients { clients }) = old.downgrade(packet.clone()) else {
+        panic!("Lobby clients should still be sent");
+    };
+    assert_eq!(clients.get(&1).map(|client| client.client_type.clone()), Some(LobbyClientType::Player { name: "Robot".to_string() }));
+
+    let current = Protocol::negotiate(PROTOCOL_VERSION, Default::default()).expect("Current version should be accepted");
+    let Some(ToClientPacket::LobbyClients { clients }) = current.downgrade(packet) else {
+        panic!("Lobby clients should still be sent");
+    };
+    assert!(clients.get(&1).is_some_and(|client| client.is_bot()));
+}
@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use mafia_server::{config::{Config, ConfigError}, logging::LogFormat};
+
+fn load(env_vars: &[(&str, &str)], args: &[&str]) -> Result<Config, ConfigError> {
+    Config::load(
+        Path::new("./does_not_exist.json"),
+        env_vars.iter().map(|(k, v)| (k.to_string(), v.to_string())),
+        args.iter().map(|a| a.to_string())
+    ).map(|(config, _)| config)
+}
+
+#[test]
+fn flags_override_env_vars() {
+    let config = load(
+        &[("MAFIA_MAX_LOBBIES", "3"), ("MAFIA_MAX_CLIENTS_PER_LOBBY", "7"), ("PATH", "/bin")],
+        &["--max-lobbies", "5", "--address=127.0.0.1:9000", "--log-format", "json"]
+    ).expect("Config should be valid");
+
+    assert_eq!(config.max_lobbies, 5);
+    assert_eq!(config.max_clients_per_lobby, 7);
+    assert_eq!(config.address, "127.0.0.1:9000");
+    assert_eq!(config.log_format, LogFormat::Json);
+    assert_eq!(config.max_name_length, Config::default().max_name_length);
+}
+
+#[test]
+fn game_record_directory_can_be_moved() {
+    assert_eq!(Config::default().game_record_directory.as_deref(), Some("./game_records"));
+
+    let config = load(&[("MAFIA_GAME_RECORD_DIRECTORY", "/var/lib/mafia/records")], &[]).expect("Config should be valid");
+    assert_eq!(config.game_record_directory.as_deref(), Some("/var/lib/mafia/records"));
+}
+
+#[test]
+fn snapshots_can_be_moved_and_slowed_down() {
+    assert_eq!(Config::default().snapshot_path, "./snapshots/listener.json");
+    assert_eq!(Config::default().snapshot_interval_secs, 30);
+
+    let config = load(&[("MAFIA_SNAPSHOT_PATH", "/var/lib/mafia/listener.json")], &["--snapshot-interval-secs", "120"]).expect("Config should be valid");
+    assert_eq!(config.snapshot_path, "/var/lib/mafia/listener.json");
+    assert_eq!(config.snapshot_interval_secs, 120);
+    assert!(load(&[], &["--snapshot-interval-secs", "0"]).is_err());
+}
+
+#[test]
+fn unknown_env_vars_are_ignored() {
+    let env_vars = [("MAFIA_NOT_AN_OPTION", "1"), ("MAFIA_MAX_LOBBIES", "3")].map(|(k, v)| (k.to_string(), v.to_string()));
+    let (config, ignored) = Config::load(Path::new("./does_not_exist.json"), env_vars, Vec::new()).expect("Config should be valid");
+    assert_eq!(config.max_lobbies, 3);
+    assert_eq!(ignored, vec!["MAFIA_NOT_AN_OPTION".to_string()]);
+}
+
+#[test]
+fn invalid_config_is_rejected() {
+    assert!(matches!(load(&[], &["--max-lobbies", "lots"]), Err(ConfigError::InvalidValue { .. })));
+    assert!(matches!(load(&[], &["--max-lobbies", "0"]), Err(ConfigError::Invalid(_))));
+    assert!(matches!(load(&[], &["--not-an-option", "1"]), Err(ConfigError::UnknownKey(_))));
+    assert!(matches!(load(&[("MAFIA_FRAME_TIME_MILLIS", "")], &[]), Err(ConfigError::InvalidValue { .. })));
+    assert!(matches!(load(&[("MAFIA_LOG_FILTER", "info,=")], &[]), Err(ConfigError::InvalidValue { .. })));
+}
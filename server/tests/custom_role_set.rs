@@ -0,0 +1,271 @@
+mod kit;
+
+use mafia_server::{
+    game::{
+        chat::{ChatMessage, ChatMessageVariant},
+        grave::{Grave, GraveDeathCause, GraveInformation, GraveKiller, GravePhase},
+        modifiers::{ModifierType, Modifiers},
+        phase::PhaseType,
+        player::PlayerReference,
+        preset::{Preset, PresetError},
+        role::{detective::Detective, mafioso::Mafioso, vigilante::Vigilante, Role, RoleState},
+        role_generation::RoleConstraint,
+        role_list::{CustomRoleSet, RoleList, RoleOutline, RoleOutlineOptionRoles, RoleSet},
+        role_list_notation::{validate_custom_role_sets, CustomRoleSetError, OptionParseError, OutlineParseError},
+        settings::Settings,
+        test::mock_game
+    },
+    lobby::{lobby_access::JoinCredentials, Lobby},
+    packet::{ToClientPacket, ToServerPacket},
+    websocket_connections::protocol::Protocol
+};
+use rand::{rngs::StdRng, SeedableRng};
+use kit::{lobby_settings, sender};
+
+fn chaos_neutrals() -> CustomRoleSet {
+    CustomRoleSet::new("Chaos Neutrals".to_string(), [Role::Politician, Role::Jester, Role::Doomsayer])
+}
+
+#[test]
+fn custom_role_sets_are_written_by_name() {
+    let sets = [chaos_neutrals()];
+    let outline = RoleOutline::parse_with("chaos neutrals [weight: 2] | Mafia Support", &sets).expect("Outline should parse");
+
+    assert_eq!(outline.options.first().roles, RoleOutlineOptionRoles::CustomRoleSet { custom_role_set: chaos_neutrals() });
+    assert_eq!(outline.to_string(), "Chaos Neutrals [weight: 2] | Mafia Support");
+    assert_eq!(RoleOutline::parse_with(&outline.to_string(), &sets), Ok(outline.clone()));
+
+    // Without the lobby's sets, the name means nothing
+    assert_eq!("Chaos Neutrals".parse::<RoleOutline>(), Err(OutlineParseError {
+        option: 1, error: OptionParseError::UnknownRoles("Chaos Neutrals".to_string())
+    }));
+
+    let constraint = RoleConstraint::parse_with("At most 1: Chaos Neutrals", &sets).expect("Constraint should parse");
+    assert_eq!(constraint.to_string(), "At most 1: Chaos Neutrals");
+
+    let role_list = RoleList::from_notation_with("Chaos Neutrals\nTown", &sets).expect("Role list should parse");
+    assert_eq!(role_list.to_notation(), "Chaos Neutrals\nTown");
+}
+
+#[test]
+fn outlines_keep_their_custom_role_sets_as_json() {
+    let outline = RoleOutline::parse_with("Chaos Neutrals [weight: 3]", &[chaos_neutrals()]).expect("Outline should parse");
+    let json = serde_json::to_value(&outline).expect("Outline should serialize");
+
+    assert_eq!(json, serde_json::json!([{
+        "customRoleSet": {"name": "Chaos Neutrals", "roles": ["jester", "politician", "doomsayer"]},
+        "weight": 3
+    }]));
+    assert_eq!(serde_json::from_value::<RoleOutline>(json).expect("Outline should deserialize"), outline);
+}
+
+#[test]
+fn custom_role_set_names_must_be_readable() {
+    let named = |name: &str| CustomRoleSet::new(name.to_string(), [Role::Jester]);
+
+    assert_eq!(validate_custom_role_sets(&[chaos_neutrals(), named("Killers")]), Ok(()));
+    assert_eq!(validate_custom_role_sets(&[named("  ")]), Err(CustomRoleSetError::EmptyName));
+    assert!(matches!(validate_custom_role_sets(&[named(&"a".repeat(41))]), Err(CustomRoleSetError::NameTooLong(_))));
+    assert!(matches!(validate_custom_role_sets(&[named("Town | Mafia")]), Err(CustomRoleSetError::ReservedName(_))));
+    assert!(matches!(validate_custom_role_sets(&[named("Jester requires Mayor")]), Err(CustomRoleSetError::ReservedName(_))));
+    assert!(matches!(validate_custom_role_sets(&[named("town investigative")]), Err(CustomRoleSetError::NameTaken(_))));
+    assert!(matches!(validate_custom_role_sets(&[named("Jester")]), Err(CustomRoleSetError::NameTaken(_))));
+    assert!(matches!(validate_custom_role_sets(&[named("Killers"), named("killers")]), Err(CustomRoleSetError::NameTaken(_))));
+    assert!(matches!(
+        validate_custom_role_sets(&[CustomRoleSet::new("Nobody".to_string(), [])]),
+        Err(CustomRoleSetError::EmptyRoles(_))
+    ));
+}
+
+#[test]
+fn simplifying_drops_options_inside_a_custom_role_set() {
+    let mut role_list = RoleList::from_notation_with("Jester | Chaos Neutrals\nChaos Neutrals | Neutral", &[chaos_neutrals()])
+        .expect("Role list should parse");
+    role_list.simplify();
+
+    assert_eq!(role_list.to_notation(), "Chaos Neutrals\nNeutral");
+}
+
+#[test]
+fn custom_role_sets_generate_only_their_roles() {
+    let settings = Settings {
+        role_list: RoleList::from_notation_with("Chaos Neutrals\nChaos Neutrals\nMafioso", &[chaos_neutrals()]).expect("Role list should parse"),
+        enabled_roles: Role::values().into_iter().collect(),
+        custom_role_sets: vec![chaos_neutrals()],
+        ..Default::default()
+    };
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..100 {
+        let assignments = settings.create_random_role_assignments(&mut rng).expect("Role list should generate");
+        for assignment in assignments.iter().take(2) {
+            assert!(chaos_neutrals().roles.contains(&assignment.role), "{:?}", assignment.role);
+        }
+    }
+}
+
+#[test]
+fn graves_name_the_killers_custom_role_set() {
+    kit::scenario!(game in Night 2 where
+        vigilante: Vigilante,
+        mafioso: Mafioso,
+        _townie_a: Detective,
+        _townie_b: Detective
+    );
+    game.settings.custom_role_sets = vec![CustomRoleSet::new("Vigilantes".to_string(), [Role::Vigilante, Role::Veteran])];
+    // Scenarios start without modifiers
+    Modifiers::set_modifier(&mut game, ModifierType::RoleSetGraveKillers.default_state());
+
+    assert!(vigilante.send_ability_input_player_list_typical(mafioso));
+    game.next_phase();
+
+    assert_eq!(game.graves.first().map(|grave| grave.information.clone()), Some(GraveInformation::Normal {
+        role: Role::Mafioso,
+        will: String::new(),
+        death_cause: GraveDeathCause::Killers(vec![GraveKiller::CustomRoleSet("Vigilantes".to_string())]),
+        death_notes: vec![],
+    }));
+}
+
+#[test]
+fn graves_dont_name_custom_role_sets_of_one_role() {
+    kit::scenario!(game in Night 2 where
+        vigilante: Vigilante,
+        mafioso: Mafioso,
+        _townie_a: Detective,
+        _townie_b: Detective
+    );
+    game.settings.custom_role_sets = vec![CustomRoleSet::new("Vigilantes".to_string(), [Role::Vigilante])];
+    Modifiers::set_modifier(&mut game, ModifierType::RoleSetGraveKillers.default_state());
+
+    assert!(vigilante.send_ability_input_player_list_typical(mafioso));
+    game.next_phase();
+
+    assert_eq!(game.graves.first().map(|grave| grave.information.clone()), Some(GraveInformation::Normal {
+        role: Role::Mafioso,
+        will: String::new(),
+        death_cause: GraveDeathCause::Killers(vec![GraveKiller::RoleSet(RoleSet::Town)]),
+        death_notes: vec![],
+    }));
+}
+
+#[test]
+fn presets_define_custom_role_sets() {
+    let preset = Preset::from_json(r#"{
+        "format": 1,
+        "name": "Chaos",
+        "roleList": ["Chaos Neutrals", "Town", "Mafia Killing"],
+        "customRoleSets": {"Chaos Neutrals": ["Jester", "Politician", "Doomsayer"], "Healers": ["Town Protective"]},
+        "roleWeights": {"Chaos Neutrals": 2},
+        "roleConstraints": ["At most 1: Healers"]
+    }"#).expect("Preset should load");
+
+    assert_eq!(preset.settings.custom_role_sets.len(), 2);
+    assert!(preset.settings.custom_role_sets.contains(&chaos_neutrals()));
+    assert_eq!(preset.settings.role_weight(Role::Politician), 2);
+    assert_eq!(preset.settings.role_list.to_notation(), "Chaos Neutrals\nTown\nMafia Killing");
+
+    let loaded = Preset::from_json(&preset.to_json().expect("Preset should serialize")).expect("Preset should load");
+    assert_eq!(loaded.settings.custom_role_sets, preset.settings.custom_role_sets);
+    assert_eq!(loaded.settings.role_list, preset.settings.role_list);
+    assert_eq!(loaded.settings.role_constraints, preset.settings.role_constraints);
+
+    let clashing = Preset::from_json(r#"{"format": 1, "name": "Bad", "roleList": ["Town"], "customRoleSets": {"Mafia": ["Jester"]}}"#);
+    assert!(matches!(clashing, Err(PresetError::CustomRoleSet(CustomRoleSetError::NameTaken(_)))));
+    let unknown = Preset::from_json(r#"{"format": 1, "name": "Bad", "roleList": ["Town"], "customRoleSets": {"Odd": ["Jestr"]}}"#);
+    assert!(matches!(unknown, Err(PresetError::CustomRoleSetRoles { .. })));
+}
+
+#[test]
+fn host_sets_custom_role_sets() {
+    let mut lobby = Lobby::new(1);
+    let host = sender();
+    let host_id = lobby.join_player(&host, &JoinCredentials::default()).expect("Lobby should have room");
+    let player = sender();
+    let player_id = lobby.join_player(&player, &JoinCredentials::default()).expect("Lobby should have room");
+
+
+    lobby.on_client_message(&player, player_id, ToServerPacket::SetCustomRoleSets { custom_role_sets: vec![chaos_neutrals()] });
+    assert!(lobby_settings(&lobby).custom_role_sets.is_empty());
+
+    let invalid = CustomRoleSet::new("Town".to_string(), [Role::Jester]);
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetCustomRoleSets { custom_role_sets: vec![invalid] });
+    assert!(lobby_settings(&lobby).custom_role_sets.is_empty());
+
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetCustomRoleSets { custom_role_sets: vec![chaos_neutrals()] });
+    assert_eq!(lobby_settings(&lobby).custom_role_sets, vec![chaos_neutrals()]);
+
+    let role_list = RoleList::from_notation_with("Chaos Neutrals\nTown", &[chaos_neutrals()]).expect("Role list should parse");
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleList { role_list });
+    let constraint = RoleConstraint::parse_with("At most 1: Chaos Neutrals", &[chaos_neutrals()]).expect("Constraint should parse");
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleConstraints { constraints: vec![constraint] });
+
+    // Changing the set's roles changes every outline and constraint using it
+    let smaller = CustomRoleSet::new("Chaos Neutrals".to_string(), [Role::Jester]);
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetCustomRoleSets { custom_role_sets: vec![smaller.clone()] });
+    let expected = RoleOutlineOptionRoles::CustomRoleSet { custom_role_set: smaller };
+    assert_eq!(lobby_settings(&lobby).role_list.0.first().map(|outline| outline.options.first().roles.clone()), Some(expected.clone()));
+    assert_eq!(lobby_settings(&lobby).role_constraints, vec![RoleConstraint::AtMost { count: 1, roles: vec![expected.clone()] }]);
+
+    // Outlines and constraints carry their own copies, which are checked the same way
+    let sneaky = RoleOutlineOptionRoles::CustomRoleSet { custom_role_set: CustomRoleSet::new("Mafia".to_string(), [Role::Jester]) };
+    let outline = RoleOutline::parse_with("Town", &[]).map(|mut outline| {
+        outline.options.first_mut().roles = sneaky.clone();
+        outline
+    }).expect("Outline should parse");
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleOutline { index: 0, role_outline: outline.clone() });
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleList { role_list: RoleList(vec![outline]) });
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleConstraints { constraints: vec![RoleConstraint::AtMost { count: 1, roles: vec![sneaky] }] });
+    let settings = lobby_settings(&lobby);
+    assert_eq!(settings.role_list.0.first().map(|outline| outline.options.first().roles.clone()), Some(expected.clone()));
+    assert_eq!(settings.role_constraints, vec![RoleConstraint::AtMost { count: 1, roles: vec![expected] }]);
+}
+
+#[test]
+fn older_clients_are_sent_the_roles_in_custom_role_sets() {
+    let old = Protocol { version: 8, capabilities: Default::default() };
+
+    assert!(old.downgrade(ToClientPacket::CustomRoleSets { custom_role_sets: vec![chaos_neutrals()] }).is_none());
+
+    let role_list = RoleList::from_notation_with("Chaos Neutrals [weight: 2] | Town", &[chaos_neutrals()]).expect("Role list should parse");
+    let Some(ToClientPacket::RoleList { role_list }) = old.downgrade(ToClientPacket::RoleList { role_list }) else {
+        panic!("Role list should still be sent");
+    };
+    assert_eq!(role_list.to_notation(), "Jester [weight: 2] | Politician [weight: 2] | Doomsayer [weight: 2] | Town");
+
+    let game = mock_game(Settings {
+        role_list: RoleList(vec![RoleOutline::new_exact(Role::Villager); 2]),
+        enabled_roles: Role::values().into_iter().collect(),
+        ..Default::default()
+    }, 2, kit::_init::DEFAULT_SEED).expect("Game should start");
+    let grave = Grave {
+        player: PlayerReference::all_players(&game).next().expect("Game should have players"),
+        died_phase: GravePhase::Night,
+        day_number: 2,
+        information: GraveInformation::Normal {
+            role: Role::Villager,
+            will: String::new(),
+            death_cause: GraveDeathCause::Killers(vec![GraveKiller::CustomRoleSet("Vigilantes".to_string())]),
+            death_notes: vec![],
+        },
+    };
+    let downgraded_killers = |packet| match packet {
+        Some(ToClientPacket::AddGrave { grave }) => Some(grave.information),
+        Some(ToClientPacket::AddChatMessages { chat_messages }) => chat_messages.into_iter().next().and_then(|message| match message.variant {
+            ChatMessageVariant::PlayerDied { grave } => Some(grave.information),
+            _ => None,
+        }),
+        _ => None,
+    };
+    let expected = Some(GraveInformation::Normal {
+        role: Role::Villager,
+        will: String::new(),
+        death_cause: GraveDeathCause::Killers(vec![GraveKiller::RoleSet(RoleSet::Any)]),
+        death_notes: vec![],
+    });
+
+    assert_eq!(downgraded_killers(old.downgrade(ToClientPacket::AddGrave { grave: grave.clone() })), expected);
+    assert_eq!(downgraded_killers(old.downgrade(ToClientPacket::AddChatMessages {
+        chat_messages: vec![ChatMessage::new_private(ChatMessageVariant::PlayerDied { grave })]
+    })), expected);
+}
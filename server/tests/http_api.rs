@@ -0,0 +1,70 @@
+mod kit;
+
+use mafia_server::{
+    http_api::{read_request, route},
+    listener::Listener,
+    packet::{ToClientPacket, ToServerPacket}
+};
+use tokio::io::AsyncWriteExt;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+async fn requests_are_read_up_to_the_end_of_the_headers() {
+    let mut request: &[u8] = b"GET /api/status HTTP/1.1\r\nHost: localhost\r\n\r\n";
+    assert_eq!(read_request(&mut request).await, Ok(("GET".to_string(), "/api/status".to_string())));
+
+    let mut unfinished: &[u8] = b"GET /api/status HTTP/1.1\r\nHost: localhost\r\n";
+    assert_eq!(read_request(&mut unfinished).await.map_err(|response| response.status), Err(400));
+
+    let huge = format!("GET /api/status HTTP/1.1\r\nCookie: {}\r\n\r\n", "a".repeat(10_000));
+    assert_eq!(read_request(&mut huge.as_bytes()).await.map_err(|response| response.status), Err(431));
+}
+
+#[tokio::test]
+async fn slow_requests_time_out() {
+    let (mut client, mut server) = tokio::io::duplex(64);
+    client.write_all(b"GET /api/status HTTP/1.1\r\n").await.expect("Pipe should be open");
+    assert_eq!(read_request(&mut server).await.map_err(|response| response.status), Err(408));
+}
+
+fn json(body: &str) -> serde_json::Value {
+    serde_json::from_str(body).expect("Response should be JSON")
+}
+
+#[test]
+fn api_lists_lobbies_and_status() {
+    let (connection, mut receiver) = kit::connection(1);
+    let mut listener = Listener::new();
+    listener.on_connect(&connection);
+
+    let host = serde_json::to_string(&ToServerPacket::Host).expect("Packet should serialize");
+    listener.on_message(&connection, &Message::text(host));
+    let room_code = std::iter::from_fn(|| receiver.try_recv().ok())
+        .find_map(|packet| match packet {
+            ToClientPacket::AcceptJoin { room_code, .. } => Some(room_code),
+            _ => None,
+        })
+        .expect("Host should be accepted");
+
+    let status = route(&listener, "GET", "/api/status");
+    assert_eq!(status.status, 200);
+    let status = json(&status.body);
+    assert_eq!(status["lobbies"], 1);
+    assert_eq!(status["connectedClients"], 1);
+    assert_eq!(status["draining"], false);
+
+    let lobbies = route(&listener, "GET", "/api/lobbies?format=json");
+    assert_eq!(lobbies.status, 200);
+    let lobbies = json(&lobbies.body);
+    assert_eq!(lobbies[0]["roomCode"], room_code);
+    assert_eq!(lobbies[0]["inGame"], false);
+
+    let lobby = route(&listener, "GET", &format!("/api/lobbies/{room_code}"));
+    assert_eq!(lobby.status, 200);
+    assert_eq!(json(&lobby.body)["roomCode"], room_code);
+
+    assert_eq!(route(&listener, "GET", &format!("/api/lobbies/{}", room_code + 1)).status, 404);
+    assert_eq!(route(&listener, "GET", "/api/lobbies/abc").status, 400);
+    assert_eq!(route(&listener, "POST", "/api/status").status, 405);
+    assert_eq!(route(&listener, "GET", "/metrics").status, 404);
+}
@@ -1,11 +1,22 @@
-use mafia_server::game::{
-    chat::ChatMessageVariant, 
-    player::PlayerReference, 
-    role::RoleState, 
-    settings::Settings, 
-    test::mock_game, 
-    Game
+// Each test crate only uses some of the kit
+#![allow(dead_code)]
+
+use std::net::SocketAddr;
+
+use mafia_server::{
+    game::{
+        chat::ChatMessageVariant, 
+        player::PlayerReference, 
+        role::{Role, RoleState}, 
+        settings::Settings, 
+        test::mock_game, 
+        Game
+    },
+    lobby::{lobby_access::JoinCredentials, lobby_client::LobbyClientID, snapshot::LobbyStateSnapshot, Lobby},
+    packet::{ToClientPacket, ToServerPacket},
+    websocket_connections::connection::{ClientSender, Connection}
 };
+use tokio::sync::mpsc::{self, UnboundedReceiver};
 
 pub mod player;
 pub mod game;
@@ -17,18 +28,25 @@ pub struct TestScenario {
 
 #[allow(unused)]
 macro_rules! scenario {
-    ($game:ident in Briefing 1 $($tok:tt)*) => {
-        kit::scenario!($game $($tok)*);
+    ($game:ident seeded $seed:tt in Briefing 1 $($tok:tt)*) => {
+        kit::scenario!(@seeded $seed; $game $($tok)*);
     };
-    ($game:ident in $phase:ident $day:literal $($tok:tt)*) => {
-        kit::scenario!($game $($tok)*);
+    ($game:ident seeded $seed:tt in $phase:ident $day:literal $($tok:tt)*) => {
+        kit::scenario!(@seeded $seed; $game $($tok)*);
         $game.skip_to(PhaseType::$phase, $day);
     };
-    ($game:ident where
+    ($game:ident in $($tok:tt)*) => {
+        kit::scenario!($game seeded (kit::_init::DEFAULT_SEED) in $($tok)*);
+    };
+    ($game:ident where $($tok:tt)*) => {
+        kit::scenario!(@seeded (kit::_init::DEFAULT_SEED); $game where $($tok)*);
+    };
+    (@seeded $seed:tt; $game:ident where
         $($name:ident: $role:ident),*
     ) => {
         let mut scenario = kit::_init::create_basic_scenario(
-            vec![$(RoleState::$role($role::default())),*]
+            vec![$(RoleState::$role($role::default())),*],
+            $seed
         );
 
         let game = &mut scenario.game;
@@ -61,6 +79,46 @@ macro_rules! assert_not_contains {
 #[allow(unused)]
 pub(crate) use {scenario, assert_contains, assert_not_contains};
 
+/// A connection from `port` on this machine, and the packets it's sent.
+/// The listener tells clients apart by address, so each one it sees needs its own port.
+pub fn connection(port: u16) -> (Connection, UnboundedReceiver<ToClientPacket>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (Connection::new(sender, SocketAddr::from(([127, 0, 0, 1], port))), receiver)
+}
+
+/// A client in a lobby, and the packets it's sent
+pub fn client() -> (ClientSender, UnboundedReceiver<ToClientPacket>) {
+    let (connection, receiver) = connection(0);
+    (connection.get_sender(), receiver)
+}
+
+/// A client whose packets go nowhere, for tests that only look at the server's side
+pub fn sender() -> ClientSender {
+    client().0
+}
+
+/// A lobby with a host, whose packets are kept, and five other players, with every role enabled
+pub fn lobby_with_players() -> (Lobby, ClientSender, UnboundedReceiver<ToClientPacket>, Vec<LobbyClientID>) {
+    let mut lobby = Lobby::new(1);
+    let (host, host_packets) = client();
+    let host_id = lobby.join_player(&host, &JoinCredentials::default()).expect("Lobby should have room");
+    let mut ids = vec![host_id];
+    for _ in 0..5 {
+        ids.push(lobby.join_player(&sender(), &JoinCredentials::default()).expect("Lobby should have room"));
+    }
+
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetEnabledRoles { roles: Role::values().into_iter().collect() });
+    (lobby, host, host_packets, ids)
+}
+
+/// The settings of a lobby that hasn't started its game
+pub fn lobby_settings(lobby: &Lobby) -> Settings {
+    let Some(LobbyStateSnapshot::Lobby { settings, .. }) = lobby.snapshot().map(|snapshot| snapshot.state) else {
+        panic!("Lobby should be in the lobby menu");
+    };
+    settings
+}
+
 //Formats messages in a way where it's clear which phase each message was sent in
 pub fn _format_messages_debug(messages: Vec<ChatMessageVariant>) -> String{
     let mut string = "[\n".to_string();
@@ -80,12 +138,15 @@ pub fn _format_messages_debug(messages: Vec<ChatMessageVariant>) -> String{
 /// Stuff that shouldn't be called directly - only in macro invocations.
 #[doc(hidden)]
 pub mod _init {
-    use mafia_server::game::{role::Role, role_list::{RoleList, RoleOutline, RoleOutlineOption, RoleOutlineOptionInsiderGroups, RoleOutlineOptionRoles, RoleOutlineOptionWinCondition}};
+    use mafia_server::game::{role_list::{RoleList, RoleOutline, RoleOutlineOption, RoleOutlineOptionInsiderGroups, RoleOutlineOptionRoles, RoleOutlineOptionWinCondition}};
     use vec1::vec1;
 
     use super::*;
 
-    pub fn create_basic_scenario(roles: Vec<RoleState>) -> TestScenario {
+    /// Seed used by scenarios that don't specify one, so every test run generates the same game
+    pub const DEFAULT_SEED: u64 = 0;
+
+    pub fn create_basic_scenario(roles: Vec<RoleState>, seed: u64) -> TestScenario {
         let mut role_list = Vec::new();
         for role in roles.iter() {
             role_list.push(RoleOutline { options: 
@@ -93,6 +154,7 @@ pub mod _init {
                     roles: RoleOutlineOptionRoles::Role { role: role.role() },
                     insider_groups: RoleOutlineOptionInsiderGroups::RoleDefault,
                     win_condition: RoleOutlineOptionWinCondition::RoleDefault,
+                    weight: RoleOutlineOption::DEFAULT_WEIGHT,
                 }]
             });
         }
@@ -101,7 +163,7 @@ pub mod _init {
             role_list: RoleList(role_list),
             enabled_roles: Role::values().into_iter().collect(),
             ..Default::default()
-        }, roles.len() as u8){
+        }, roles.len() as u8, seed){
             Ok(game) => game,
             Err(err) => panic!("Failed to create game: {:?}", err),
         };
@@ -0,0 +1,358 @@
+mod kit;
+
+use std::time::Duration;
+
+use mafia_server::{
+    game::{components::event_journal::GameInput, player::PlayerIndex, replay},
+    config::Config,
+    listener::{Listener, ListenerSnapshot},
+    lobby::{
+        game_client::GameClientLocation, lobby_access::{JoinCredentials, LobbyVisibility}, lobby_client::LobbyClientID,
+        snapshot::{LobbySnapshot, LobbyStateSnapshot}, Lobby
+    },
+    packet::{RejectJoinReason, ToClientPacket, ToServerPacket},
+    websocket_connections::connection::{ClientSender, Connection}
+};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio_tungstenite::tungstenite::Message;
+use kit::{client, sender};
+
+fn connect(listener: &mut Listener, port: u16) -> (Connection, UnboundedReceiver<ToClientPacket>) {
+    let (connection, receiver) = kit::connection(port);
+    listener.on_connect(&connection);
+    (connection, receiver)
+}
+
+fn send(listener: &mut Listener, connection: &Connection, packet: ToServerPacket) {
+    listener.on_message(connection, &Message::text(serde_json::to_string(&packet).expect("Packet should serialize")));
+}
+
+fn player_index(lobby: &Lobby, id: LobbyClientID) -> PlayerIndex {
+    let Some(LobbyStateSnapshot::Game { clients, .. }) = lobby.snapshot().map(|snapshot| snapshot.state) else {
+        panic!("Lobby should be in a game");
+    };
+    match clients.into_iter().find(|(client_id, _)| *client_id == id) {
+        Some((_, client)) => match client.client_location {
+            GameClientLocation::Player(index) => index,
+            GameClientLocation::Spectator(_) => panic!("Client should be a player"),
+        },
+        None => panic!("Client should be in the game"),
+    }
+}
+
+/// A host and five players, one second into a game
+fn started_game() -> (Lobby, ClientSender, Vec<LobbyClientID>) {
+    let (mut lobby, host, _, ids) = kit::lobby_with_players();
+    lobby.on_client_message(&host, ids[0], ToServerPacket::StartGame);
+    lobby.tick(Duration::from_secs(1));
+    (lobby, host, ids)
+}
+
+fn substitutions(lobby: &Lobby) -> Vec<(PlayerIndex, bool)> {
+    let Some(LobbyStateSnapshot::Game { record, .. }) = lobby.snapshot().map(|snapshot| snapshot.state) else {
+        panic!("Lobby should be in a game");
+    };
+    assert!(replay::verify(&record).expect("Game should replay").is_empty());
+    record.inputs.into_iter().filter_map(|input| match input {
+        GameInput::Substitute { player, bot } => Some((player, bot)),
+        _ => None
+    }).collect()
+}
+
+#[test]
+fn spectator_takes_over_player_who_quit() {
+    let (mut lobby, _, ids) = started_game();
+    let quitter = ids[1];
+    let index = player_index(&lobby, quitter);
+    let name = lobby.get_player_list().into_iter().find(|(id, _)| *id == quitter).map(|(_, name)| name);
+
+    let (spectator, mut spectator_packets) = client();
+    let spectator_id = lobby.join_player(&spectator, &JoinCredentials::default()).expect("Lobby should have room");
+
+    // Nobody can take a player who is still playing
+    assert!(matches!(lobby.claim_player(&spectator, spectator_id, index), Err(RejectJoinReason::PlayerTaken)));
+
+    lobby.remove_player(quitter);
+    while spectator_packets.try_recv().is_ok() {}
+    lobby.on_client_message(&spectator, spectator_id, ToServerPacket::ClaimPlayer { player_index: index });
+
+    assert!(matches!(spectator_packets.try_recv(), Ok(ToClientPacket::AcceptJoin { spectator: false, .. })));
+    assert_eq!(player_index(&lobby, spectator_id), index);
+    assert_eq!(lobby.get_player_list().into_iter().find(|(id, _)| *id == spectator_id).map(|(_, name)| name), name);
+    assert!(matches!(lobby.rejoin_player(&sender(), quitter, &JoinCredentials::default(), None), Err(RejectJoinReason::PlayerDoesntExist)));
+    assert_eq!(substitutions(&lobby), vec![(index, false)]);
+}
+
+#[test]
+fn host_gives_player_who_quit_to_a_bot() {
+    let (mut lobby, host, ids) = started_game();
+    let quitter = ids[1];
+    let index = player_index(&lobby, quitter);
+    lobby.remove_player(quitter);
+
+    let player = ids[2];
+    lobby.on_client_message(&sender(), player, ToServerPacket::HostSubstituteBot { player_index: index });
+    assert!(substitutions(&lobby).is_empty());
+
+    lobby.on_client_message(&host, ids[0], ToServerPacket::HostSubstituteBot { player_index: index });
+    lobby.on_client_message(&host, ids[0], ToServerPacket::HostSubstituteBot { player_index: index });
+    assert_eq!(substitutions(&lobby), vec![(index, true)]);
+
+    let Some(LobbyStateSnapshot::Game { bots, .. }) = lobby.snapshot().map(|snapshot| snapshot.state) else {
+        panic!("Lobby should be in a game");
+    };
+    assert_eq!(bots, vec![quitter]);
+}
+
+#[test]
+fn restored_game_lets_players_rejoin() {
+    let (mut lobby, host, _, ids) = kit::lobby_with_players();
+    lobby.on_client_message(&host, ids[0], ToServerPacket::StartGame);
+    assert!(lobby.is_in_game());
+    for _ in 0..60 {
+        lobby.tick(Duration::from_secs(1));
+    }
+
+    let snapshot = lobby.snapshot().expect("Lobby is open");
+    let json = serde_json::to_string(&snapshot).expect("Snapshot should serialize");
+    let snapshot: LobbySnapshot = serde_json::from_str(&json).expect("Snapshot should deserialize");
+    let mut restored = Lobby::restore(snapshot).expect("Game should replay");
+
+    assert!(restored.is_in_game());
+    assert_eq!(restored.get_player_list(), lobby.get_player_list());
+
+    // Nobody has rejoined yet, but the game waits for them
+    restored.tick(Duration::from_secs(1));
+    assert!(!restored.is_closed());
+
+    // ...but not forever
+    let mut abandoned = Lobby::restore(serde_json::from_str(&json).expect("Snapshot should deserialize")).expect("Game should replay");
+    abandoned.tick(Config::get().game_disconnect_timer());
+    assert!(abandoned.is_closed());
+
+    for id in ids {
+        assert!(restored.rejoin_player(&sender(), id, &JoinCredentials::default(), None).is_ok());
+        assert!(matches!(restored.rejoin_player(&sender(), id, &JoinCredentials::default(), None), Err(RejectJoinReason::PlayerTaken)));
+    }
+}
+
+#[test]
+fn panicking_lobby_is_closed() {
+    let mut lobby = Lobby::new(1);
+    lobby.join_player(&sender(), &JoinCredentials::default()).expect("Lobby should have room");
+
+    lobby.run_isolated(|_| panic!("Simulated bug"));
+
+    assert!(lobby.is_closed());
+    assert!(!Lobby::is_panic_isolated());
+}
+
+#[test]
+fn draining_listener_rejects_new_lobbies() {
+    let mut listener = Listener::new();
+    let (connection, mut receiver) = connect(&mut listener, 1);
+
+    listener.start_draining();
+    assert!(listener.is_draining());
+    assert!(listener.is_drained());
+    assert!(matches!(receiver.try_recv(), Ok(ToClientPacket::ServerShuttingDown { .. })));
+
+    let host = serde_json::to_string(&ToServerPacket::Host).expect("Packet should serialize");
+    listener.on_message(&connection, &Message::text(host));
+    assert!(matches!(
+        receiver.try_recv(),
+        Ok(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerShuttingDown })
+    ));
+}
+
+#[test]
+fn rejoining_player_is_sent_only_missed_packets() {
+    let (mut lobby, host, mut host_packets, ids) = kit::lobby_with_players();
+    let host_id = ids[0];
+    lobby.on_client_message(&host, host_id, ToServerPacket::StartGame);
+    lobby.tick(Duration::from_secs(1));
+
+    let mut last_sequence = None;
+    while let Ok(packet) = host_packets.try_recv() {
+        if let ToClientPacket::Sequenced { sequence, .. } = packet {
+            last_sequence = Some(sequence);
+        }
+    }
+    let last_sequence = last_sequence.expect("Game packets should be sequenced");
+
+    lobby.remove_player_rejoinable(host_id);
+    // Long enough for the phase to change, but not for the host's seat to be given up
+    for _ in 0..90 {
+        lobby.tick(Duration::from_secs(1));
+    }
+
+    let (resumed, mut resumed_packets) = client();
+    lobby.rejoin_player(&resumed, host_id, &JoinCredentials::default(), Some(last_sequence)).expect("Host should be able to rejoin");
+
+    assert!(matches!(resumed_packets.try_recv(), Ok(ToClientPacket::ResumeAccepted)));
+    let mut expected_sequence = last_sequence + 1;
+    while let Ok(packet) = resumed_packets.try_recv() {
+        if let ToClientPacket::Sequenced { sequence, .. } = packet {
+            assert_eq!(sequence, expected_sequence);
+            expected_sequence += 1;
+        }
+    }
+    assert!(expected_sequence > last_sequence + 1, "Packets sent while disconnected should be resent");
+
+    // A sequence this server never sent falls back to sending the whole game
+    lobby.remove_player_rejoinable(host_id);
+    let (full, mut full_packets) = client();
+    lobby.rejoin_player(&full, host_id, &JoinCredentials::default(), Some(u64::MAX - 1)).expect("Host should be able to rejoin");
+    assert!(matches!(full_packets.try_recv(), Ok(ToClientPacket::AcceptJoin { .. })));
+}
+
+#[test]
+fn password_lobby_needs_the_password() {
+    let mut lobby = Lobby::new(1);
+    lobby.access.visibility = LobbyVisibility::Password { password: "hunter2".to_string() };
+
+    let password = |password: &str| JoinCredentials { invited: false, password: Some(password.to_string()) };
+    assert!(matches!(lobby.join_player(&sender(), &JoinCredentials::default()), Err(RejectJoinReason::PasswordRequired)));
+    assert!(matches!(lobby.join_player(&sender(), &password("hunter3")), Err(RejectJoinReason::WrongPassword)));
+    assert!(lobby.join_player(&sender(), &password("hunter2")).is_ok());
+}
+
+#[test]
+fn unlisted_lobby_is_only_joined_by_invite_code() {
+    let mut listener = Listener::new();
+
+    let (host, mut host_packets) = connect(&mut listener, 1);
+    send(&mut listener, &host, ToServerPacket::Host);
+    send(&mut listener, &host, ToServerPacket::SetLobbyVisibility { visibility: LobbyVisibility::Unlisted });
+
+    let mut room_code = None;
+    let mut invite_code = None;
+    while let Ok(packet) = host_packets.try_recv() {
+        match packet {
+            ToClientPacket::AcceptJoin { room_code: code, .. } => room_code = Some(code),
+            ToClientPacket::LobbyInviteCode { invite_code: code } => invite_code = Some(code),
+            _ => {}
+        }
+    }
+    let room_code = room_code.expect("Host should be accepted");
+    let invite_code = invite_code.expect("Host should be sent the invite code");
+
+    assert!(listener.lobby_previews().is_empty());
+
+    let (guest, mut guest_packets) = connect(&mut listener, 2);
+    send(&mut listener, &guest, ToServerPacket::Join { room_code, password: None });
+    assert!(matches!(guest_packets.try_recv(), Ok(ToClientPacket::RejectJoin { reason: RejectJoinReason::RoomDoesntExist })));
+
+    send(&mut listener, &guest, ToServerPacket::JoinWithInviteCode { invite_code: invite_code.to_lowercase(), password: None });
+    assert!(matches!(guest_packets.try_recv(), Ok(ToClientPacket::AcceptJoin { room_code: code, .. }) if code == room_code));
+}
+
+#[test]
+fn rejoining_needs_the_password() {
+    let mut listener = Listener::new();
+    let (host, mut host_packets) = connect(&mut listener, 1);
+    send(&mut listener, &host, ToServerPacket::Host);
+    let Some((room_code, player_id)) = std::iter::from_fn(|| host_packets.try_recv().ok())
+        .find_map(|packet| match packet {
+            ToClientPacket::AcceptJoin { room_code, player_id, .. } => Some((room_code, player_id)),
+            _ => None,
+        })
+    else {
+        panic!("Host should be accepted");
+    };
+    let password = "hunter2".to_string();
+    send(&mut listener, &host, ToServerPacket::SetLobbyVisibility { visibility: LobbyVisibility::Password { password: password.clone() } });
+    listener.on_disconnect(host).expect("Host should disconnect");
+
+    let (stranger, mut stranger_packets) = connect(&mut listener, 2);
+    send(&mut listener, &stranger, ToServerPacket::ReJoin { room_code, player_id, last_sequence: None, password: None, invite_code: None });
+    assert!(matches!(stranger_packets.try_recv(), Ok(ToClientPacket::RejectJoin { reason: RejectJoinReason::PasswordRequired })));
+    assert!(stranger_packets.try_recv().is_err());
+
+    let (host, mut host_packets) = connect(&mut listener, 3);
+    send(&mut listener, &host, ToServerPacket::ReJoin { room_code, player_id, last_sequence: None, password: Some(password), invite_code: None });
+    let packets: Vec<ToClientPacket> = std::iter::from_fn(|| host_packets.try_recv().ok()).collect();
+    assert!(matches!(packets.first(), Some(ToClientPacket::AcceptJoin { player_id: id, .. }) if *id == player_id));
+    assert!(packets.iter().any(|packet| matches!(packet, ToClientPacket::LobbyName { .. })));
+}
+
+#[test]
+fn only_hosts_are_sent_the_password() {
+    let mut lobby = Lobby::new(1);
+    let (host, mut host_packets) = client();
+    let host_id = lobby.join_player(&host, &JoinCredentials::default()).expect("Lobby should have room");
+    let (guest, mut guest_packets) = client();
+    lobby.join_player(&guest, &JoinCredentials::default()).expect("Lobby should have room");
+
+    let visibility = LobbyVisibility::Password { password: "hunter2".to_string() };
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetLobbyVisibility { visibility: visibility.clone() });
+
+    let last_visibility = |packets: &mut UnboundedReceiver<ToClientPacket>| std::iter::from_fn(|| packets.try_recv().ok())
+        .filter_map(|packet| match packet {
+            ToClientPacket::LobbyVisibility { visibility } => Some(visibility),
+            _ => None,
+        })
+        .last();
+    assert_eq!(last_visibility(&mut host_packets), Some(visibility));
+    assert_eq!(last_visibility(&mut guest_packets), Some(LobbyVisibility::Password { password: String::new() }));
+}
+
+#[tokio::test]
+async fn games_start_once_roles_are_dealt() {
+    let (mut lobby, host, mut host_packets, ids) = kit::lobby_with_players();
+    lobby.on_client_message(&host, ids[0], ToServerPacket::StartGame);
+    // Asking again while they're dealt doesn't deal them twice
+    lobby.on_client_message(&host, ids[0], ToServerPacket::StartGame);
+    for _ in 0..100 {
+        if lobby.is_in_game() {break}
+        lobby.tick(Duration::ZERO);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    assert!(lobby.is_in_game());
+    assert_eq!(lobby.get_player_list().len(), 6);
+    let starts = std::iter::from_fn(|| host_packets.try_recv().ok())
+        .filter(|packet| matches!(packet, ToClientPacket::Sequenced { packet, .. } if matches!(**packet, ToClientPacket::StartGame)))
+        .count();
+    assert_eq!(starts, 1);
+}
+
+#[test]
+fn passwords_cant_be_guessed_quickly() {
+    let mut listener = Listener::new();
+    let (host, mut host_packets) = connect(&mut listener, 1);
+    send(&mut listener, &host, ToServerPacket::Host);
+    send(&mut listener, &host, ToServerPacket::SetLobbyVisibility { visibility: LobbyVisibility::Password { password: "hunter2".to_string() } });
+    let Some(room_code) = std::iter::from_fn(|| host_packets.try_recv().ok())
+        .find_map(|packet| match packet {
+            ToClientPacket::AcceptJoin { room_code, .. } => Some(room_code),
+            _ => None,
+        })
+    else {
+        panic!("Host should be accepted");
+    };
+
+    let (guesser, mut guesser_packets) = connect(&mut listener, 2);
+    for _ in 0..Config::get().max_wrong_passwords {
+        send(&mut listener, &guesser, ToServerPacket::Join { room_code, password: Some("hunter3".to_string()) });
+        assert!(matches!(guesser_packets.try_recv(), Ok(ToClientPacket::RejectJoin { reason: RejectJoinReason::WrongPassword })));
+    }
+    send(&mut listener, &guesser, ToServerPacket::Join { room_code, password: Some("hunter2".to_string()) });
+    assert!(matches!(guesser_packets.try_recv(), Ok(ToClientPacket::RejectJoin { reason: RejectJoinReason::TooManyPasswordAttempts })));
+
+    let (guest, mut guest_packets) = connect(&mut listener, 3);
+    send(&mut listener, &guest, ToServerPacket::Join { room_code, password: Some("hunter2".to_string()) });
+    assert!(matches!(guest_packets.try_recv(), Ok(ToClientPacket::AcceptJoin { .. })));
+}
+
+#[test]
+fn snapshots_are_only_readable_by_the_server() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::temp_dir().join(format!("mafia_snapshot_{}", std::process::id())).join("listener.json");
+    ListenerSnapshot::default().save(&path).expect("Snapshot should save");
+    let mode = std::fs::metadata(&path).expect("Snapshot should exist").permissions().mode();
+    let _ = std::fs::remove_dir_all(path.parent().expect("Snapshot should be in a directory"));
+
+    assert_eq!(mode & 0o777, 0o600);
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use mafia_server::{game::game_conclusion::GameConclusion, metrics, packet::ToClientPacket, websocket_connections::protocol::Encoding};
+use tokio_tungstenite::tungstenite::Message;
+
+#[test]
+fn metrics_render_in_prometheus_format() {
+    let rejoin = Encoding::decode(&Message::text(r#"{"playerId": 3, "type": "reJoin", "roomCode": 1}"#)).expect("Packet should decode");
+    metrics::packet_received(&rejoin);
+    let pong = Encoding::MessagePack.encode(&ToClientPacket::Pong).expect("Packet should serialize");
+    metrics::packet_sent(ToClientPacket::Pong.type_name(), &pong);
+    let sequenced = ToClientPacket::Sequenced { sequence: 0, packet: Box::new(ToClientPacket::RateLimitExceeded) };
+    metrics::packet_sent(sequenced.type_name(), &Message::text(""));
+    metrics::tick_finished(Duration::from_millis(20));
+    metrics::game_ended(&GameConclusion::Town);
+
+    let rendered = metrics::render();
+    assert!(rendered.contains("mafia_packets_received_total{type=\"reJoin\"} 1"));
+    assert!(rendered.contains("mafia_packets_sent_total{type=\"pong\"} 1"));
+    assert!(rendered.contains("mafia_packets_sent_total{type=\"rateLimitExceeded\"} 1"));
+    assert!(rendered.contains(&format!("mafia_bytes_sent_total{{encoding=\"messagePack\"}} {}", pong.len())));
+    assert!(rendered.contains("mafia_tick_duration_seconds_bucket{le=\"0.01\"} 0"));
+    assert!(rendered.contains("mafia_tick_duration_seconds_bucket{le=\"0.05\"} 1"));
+    assert!(rendered.contains("mafia_games_ended_total{conclusion=\"town\"} 1"));
+}
@@ -0,0 +1,200 @@
+mod kit;
+
+use mafia_server::{
+    game::{
+        components::insider_group::InsiderGroupID, game_conclusion::GameConclusion, modifiers::ModifierType,
+        preset::{Preset, PresetError, PresetLibrary, PRESET_FORMAT}, role::Role,
+        role_list::{RoleList, RoleOutline, RoleOutlineOption, RoleOutlineOptionInsiderGroups, RoleOutlineOptionRoles, RoleOutlineOptionWinCondition, RoleSet},
+        role_list_notation::{OptionParseError, OutlineParseError, RoleListParseError},
+        settings::Settings
+    },
+    lobby::{lobby_access::JoinCredentials, snapshot::LobbyStateSnapshot, Lobby},
+    packet::ToServerPacket
+};
+use vec1::vec1;
+use kit::sender;
+
+fn lobby_settings(lobby: &Lobby) -> Settings {
+    let Some(LobbyStateSnapshot::Lobby { settings, .. }) = lobby.snapshot().map(|snapshot| snapshot.state) else {
+        panic!("Lobby should be in the lobby menu");
+    };
+    settings
+}
+
+#[test]
+fn every_role_and_role_set_round_trips() {
+    let roles = Role::values().into_iter().map(|role| RoleOutlineOptionRoles::Role { role })
+        .chain(RoleSet::values().into_iter().map(|role_set| RoleOutlineOptionRoles::RoleSet { role_set }));
+
+    for roles in roles {
+        assert_eq!(roles.to_string().parse::<RoleOutlineOptionRoles>(), Ok(roles));
+    }
+}
+
+#[test]
+fn outline_notation() {
+    let outline: RoleOutline = "town investigative |MafiaSupport| Witch [wins: Mafia, Fiends] [Insiders: mafia]"
+        .parse().expect("Outline should parse");
+
+    assert_eq!(outline, RoleOutline { options: vec1![
+        RoleOutlineOption { roles: RoleOutlineOptionRoles::RoleSet { role_set: RoleSet::TownInvestigative }, ..Default::default() },
+        RoleOutlineOption { roles: RoleOutlineOptionRoles::RoleSet { role_set: RoleSet::MafiaSupport }, ..Default::default() },
+        RoleOutlineOption {
+            roles: RoleOutlineOptionRoles::Role { role: Role::Witch },
+            win_condition: RoleOutlineOptionWinCondition::GameConclusionReached {
+                win_if_any: vec![GameConclusion::Mafia, GameConclusion::Fiends].into_iter().collect()
+            },
+            insider_groups: RoleOutlineOptionInsiderGroups::Custom {
+                insider_groups: vec![InsiderGroupID::Mafia].into_iter().collect()
+            },
+            weight: RoleOutlineOption::DEFAULT_WEIGHT,
+        },
+    ]});
+    assert_eq!(outline.to_string(), "Town Investigative | Mafia Support | Witch [wins: Mafia, Fiends] [insiders: Mafia]");
+    assert_eq!(outline.to_string().parse(), Ok(outline));
+
+    let no_insiders: RoleOutline = "Jester [insiders: ]".parse().expect("Outline should parse");
+    assert_eq!(no_insiders.to_string().parse(), Ok(no_insiders));
+}
+
+#[test]
+fn outline_errors_point_at_the_option() {
+    let error = |text: &str| text.parse::<RoleOutline>().err();
+
+    assert_eq!(error("Town Investigative | Mafa Support"), Some(OutlineParseError {
+        option: 2, error: OptionParseError::UnknownRoles("Mafa Support".to_string())
+    }));
+    assert_eq!(error("Jester ||"), Some(OutlineParseError { option: 2, error: OptionParseError::Empty }));
+    assert_eq!(error("Jester [wins: Jesters]"), Some(OutlineParseError {
+        option: 1, error: OptionParseError::UnknownConclusion("Jesters".to_string())
+    }));
+    assert_eq!(error("Town | Jester [team: Town]"), Some(OutlineParseError {
+        option: 2, error: OptionParseError::UnknownTag("team".to_string())
+    }));
+    assert!(matches!(error("Jester [wins: Town"), Some(OutlineParseError { option: 1, error: OptionParseError::Malformed(_) })));
+}
+
+#[test]
+fn role_list_notation_skips_comments_and_blank_lines() {
+    let text = "# Mafia\nMafia Killing\n\nMafia Support\n# Town\nTown Common | Jester\n";
+    let role_list = RoleList::from_notation(text).expect("Role list should parse");
+
+    assert_eq!(role_list.0.len(), 3);
+    assert_eq!(role_list.to_notation(), "Mafia Killing\nMafia Support\nTown Common | Jester");
+    assert_eq!(RoleList::from_notation(&role_list.to_notation()), Ok(role_list));
+
+    assert_eq!(RoleList::from_notation("Town\n\nTwon").err(), Some(RoleListParseError {
+        line: 3,
+        error: OutlineParseError { option: 1, error: OptionParseError::UnknownRoles("Twon".to_string()) }
+    }));
+}
+
+#[test]
+fn built_in_presets_load() {
+    let (library, errors) = PresetLibrary::load(None);
+    assert!(errors.is_empty(), "{}", errors.iter().map(|(file, error)| format!("{file}: {error}")).collect::<Vec<_>>().join("\n"));
+
+    let summaries = library.summaries();
+    assert!(!summaries.is_empty());
+    for summary in summaries {
+        let preset = library.find(&summary.name).expect("Summarized preset should exist");
+        assert_eq!(preset.settings.role_list.0.len(), summary.players);
+        assert!(preset.settings.create_random_role_assignments(&mut rand::rng()).is_some());
+    }
+}
+
+#[test]
+fn preset_round_trips_through_json() {
+    let mut settings = Settings {
+        role_list: RoleList::from_notation("Jailor\nTown Investigative | Town Support\nMafia Killing").expect("Role list should parse"),
+        enabled_roles: vec![Role::Jailor, Role::Detective, Role::Medium, Role::Godfather].into_iter().collect(),
+        enabled_modifiers: vec![ModifierType::DeadCanChat].into_iter().collect(),
+        seed: Some(4),
+        ..Default::default()
+    };
+    settings.phase_times.discussion = 90;
+
+    let preset = Preset::new("Small".to_string(), settings.clone());
+    let loaded = Preset::from_json(&preset.to_json().expect("Preset should serialize")).expect("Preset should load");
+
+    assert_eq!(loaded.name, "Small");
+    assert_eq!(loaded.settings.role_list, settings.role_list);
+    assert_eq!(loaded.settings.phase_times.discussion, 90);
+    assert_eq!(loaded.settings.enabled_roles, settings.enabled_roles);
+    assert_eq!(loaded.settings.enabled_modifiers, settings.enabled_modifiers);
+    assert_eq!(loaded.settings.seed, None);
+}
+
+#[test]
+fn preset_errors_point_at_the_entry() {
+    let preset = |role_list: &str, enabled_roles: &str| Preset::from_json(&format!(
+        r#"{{"format": {PRESET_FORMAT}, "name": "Broken", "roleList": {role_list}, "enabledRoles": {enabled_roles}}}"#
+    ));
+
+    assert!(preset(r#"["Town", "Mafia"]"#, r#"["Any"]"#).is_ok());
+    assert!(matches!(
+        preset(r#"["Town", "Town", "Mafia | Jestr"]"#, r#"["Any"]"#),
+        Err(PresetError::RoleList { entry: 3, error: OutlineParseError { option: 2, .. }, .. })
+    ));
+    assert!(matches!(
+        preset(r#"["Town"]"#, r#"["Town", "Nobody"]"#),
+        Err(PresetError::EnabledRoles { entry: 2, error: OptionParseError::UnknownRoles(_), .. })
+    ));
+    assert!(matches!(preset("[]", r#"["Any"]"#), Err(PresetError::EmptyRoleList)));
+    assert!(matches!(
+        Preset::from_json(r#"{"format": 999, "name": "Future", "roleList": ["Any"]}"#),
+        Err(PresetError::UnsupportedFormat(999))
+    ));
+
+    let message = preset(r#"["Mafa"]"#, r#"["Any"]"#).err().map(|error| error.to_string());
+    assert_eq!(message.as_deref(), Some(r#"roleList entry 1 "Mafa", option 1: unknown role or role set "Mafa""#));
+}
+
+#[test]
+fn presets_directory_overrides_built_in_presets() {
+    let directory = std::env::temp_dir().join(format!("mafia_presets_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).expect("Should create the presets directory");
+    std::fs::write(directory.join("classic.json"), format!(
+        r#"{{"format": {PRESET_FORMAT}, "name": "Classic", "roleList": ["Mafioso", "Villager", "Villager"]}}"#
+    )).expect("Should write a preset");
+    std::fs::write(directory.join("broken.json"), "{").expect("Should write a preset");
+    std::fs::write(directory.join("notes.txt"), "Not a preset").expect("Should write a file");
+
+    let (library, errors) = PresetLibrary::load(Some(&directory));
+    std::fs::remove_dir_all(&directory).expect("Should remove the presets directory");
+
+    assert_eq!(errors.len(), 1);
+    assert!(errors.iter().all(|(file, error)| file.ends_with("broken.json") && matches!(error, PresetError::Json(_))));
+    assert_eq!(library.find("Classic").map(|preset| preset.settings.role_list.0.len()), Some(3));
+    assert!(library.find("Beginner").is_some());
+}
+
+#[test]
+fn host_loads_preset() {
+    let mut lobby = Lobby::new(1);
+    let host = sender();
+    let host_id = lobby.join_player(&host, &JoinCredentials::default()).expect("Lobby should have room");
+    let player = sender();
+    let player_id = lobby.join_player(&player, &JoinCredentials::default()).expect("Lobby should have room");
+
+    let Some(preset) = PresetLibrary::get().summaries().into_iter().next() else {
+        panic!("There should be a built-in preset");
+    };
+
+    lobby.on_client_message(&player, player_id, ToServerPacket::LoadPreset { name: preset.name.clone() });
+    assert_eq!(lobby_settings(&lobby).enabled_modifiers, Default::default());
+
+    lobby.on_client_message(&host, host_id, ToServerPacket::LoadPreset { name: "Nonexistent".to_string() });
+    assert_eq!(lobby_settings(&lobby).enabled_modifiers, Default::default());
+
+    lobby.on_client_message(&host, host_id, ToServerPacket::LoadPreset { name: preset.name.clone() });
+    let settings = lobby_settings(&lobby);
+    let preset = PresetLibrary::get().find(&preset.name).expect("Preset should exist");
+
+    // The role list always matches the number of players
+    assert_eq!(settings.role_list.0.len(), 2);
+    assert_eq!(settings.role_list.0.get(..2), preset.settings.role_list.0.get(..2));
+    assert_eq!(settings.enabled_roles, preset.settings.enabled_roles);
+    assert_eq!(settings.enabled_modifiers, preset.settings.enabled_modifiers);
+    assert_eq!(settings.phase_times.night, preset.settings.phase_times.night);
+}
@@ -0,0 +1,170 @@
+mod kit;
+
+use mafia_server::{
+    client_connection::SentPackets,
+    config::Config,
+    listener::Listener,
+    lobby::lobby_access::LobbyVisibility,
+    packet::{RejectJoinReason, ToClientPacket, ToServerPacket},
+    vec_set::VecSet,
+    websocket_connections::{
+        protocol::{Capability, Encoding, Protocol, LEGACY_PROTOCOL_VERSION, OLDEST_SUPPORTED_PROTOCOL_VERSION, PROTOCOL_VERSION},
+        sent_state::SentState
+    },
+    vec_map::{vec_map, VecMap}
+};
+use tokio_tungstenite::tungstenite::Message;
+
+fn message(packet: &ToServerPacket) -> Message {
+    Message::text(serde_json::to_string(packet).expect("Packet should serialize"))
+}
+
+#[test]
+fn negotiate_accepts_every_version_since_hello() {
+    let protocol = Protocol::negotiate(PROTOCOL_VERSION, vec![Capability::Unknown].into_iter().collect())
+        .expect("Current version should be accepted");
+    assert_eq!(protocol.version, PROTOCOL_VERSION);
+    assert!(!protocol.has(Capability::Unknown));
+
+    assert!(Protocol::negotiate(PROTOCOL_VERSION - 1, VecSet::new()).is_ok());
+    assert!(Protocol::negotiate(OLDEST_SUPPORTED_PROTOCOL_VERSION, VecSet::new()).is_ok());
+    assert!(matches!(Protocol::negotiate(LEGACY_PROTOCOL_VERSION, VecSet::new()), Err(RejectJoinReason::IncompatibleVersion)));
+    assert!(matches!(Protocol::negotiate(0, VecSet::new()), Err(RejectJoinReason::IncompatibleVersion)));
+    assert!(matches!(Protocol::negotiate(PROTOCOL_VERSION + 1, VecSet::new()), Err(RejectJoinReason::IncompatibleVersion)));
+
+    let capabilities: VecSet<Capability> = serde_json::from_str(r#"["someFutureCapability"]"#)
+        .expect("Unknown capabilities should still deserialize");
+    assert!(capabilities.contains(&Capability::Unknown));
+}
+
+#[test]
+fn legacy_clients_get_packets_they_understand() {
+    let legacy = Protocol::legacy();
+    assert!(legacy.downgrade(ToClientPacket::ServerShuttingDown { seconds_left: 10 }).is_none());
+    assert!(matches!(
+        legacy.downgrade(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerShuttingDown }),
+        Some(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerBusy })
+    ));
+    assert!(matches!(legacy.downgrade(ToClientPacket::Pong), Some(ToClientPacket::Pong)));
+
+    let current = Protocol::negotiate(PROTOCOL_VERSION, VecSet::new()).expect("Current version should be accepted");
+    assert!(current.downgrade(ToClientPacket::ServerShuttingDown { seconds_left: 10 }).is_some());
+
+    let previous = Protocol::negotiate(PROTOCOL_VERSION - 1, VecSet::new()).expect("Previous version should be accepted");
+    assert!(matches!(
+        previous.downgrade(ToClientPacket::RejectJoin { reason: RejectJoinReason::TooManyPasswordAttempts }),
+        Some(ToClientPacket::RejectJoin { reason: RejectJoinReason::WrongPassword })
+    ));
+}
+
+#[test]
+fn oldest_supported_version_gets_packets_it_understands() {
+    let oldest = Protocol::negotiate(OLDEST_SUPPORTED_PROTOCOL_VERSION, VecSet::new()).expect("Oldest version should be accepted");
+    assert!(oldest.downgrade(ToClientPacket::LobbyVisibility { visibility: LobbyVisibility::Public }).is_none());
+    assert!(matches!(
+        oldest.downgrade(ToClientPacket::RejectJoin { reason: RejectJoinReason::PasswordRequired }),
+        Some(ToClientPacket::RejectJoin { reason: RejectJoinReason::RoomDoesntExist })
+    ));
+    assert!(matches!(
+        oldest.downgrade(ToClientPacket::RejectJoin { reason: RejectJoinReason::TooManyPasswordAttempts }),
+        Some(ToClientPacket::RejectJoin { reason: RejectJoinReason::RoomDoesntExist })
+    ));
+    assert!(oldest.downgrade(ToClientPacket::StartCountdown { seconds_left: Some(3) }).is_none());
+    assert!(oldest.downgrade(ToClientPacket::HelloAccepted { protocol_version: OLDEST_SUPPORTED_PROTOCOL_VERSION, capabilities: VecSet::new() }).is_some());
+    assert!(matches!(
+        oldest.downgrade(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerShuttingDown }),
+        Some(ToClientPacket::RejectJoin { reason: RejectJoinReason::ServerShuttingDown })
+    ));
+}
+
+#[test]
+fn incompatible_client_cant_host() {
+    let (connection, mut receiver) = kit::connection(1);
+    let mut listener = Listener::new();
+    listener.on_connect(&connection);
+
+    listener.on_message(&connection, &message(&ToServerPacket::Hello { protocol_version: PROTOCOL_VERSION + 1, capabilities: VecSet::new() }));
+    assert!(matches!(receiver.try_recv(), Ok(ToClientPacket::RejectJoin { reason: RejectJoinReason::IncompatibleVersion })));
+
+    listener.on_message(&connection, &message(&ToServerPacket::Host));
+    assert!(matches!(receiver.try_recv(), Ok(ToClientPacket::RejectJoin { reason: RejectJoinReason::IncompatibleVersion })));
+
+    listener.on_message(&connection, &message(&ToServerPacket::Hello { protocol_version: PROTOCOL_VERSION, capabilities: VecSet::new() }));
+    assert!(matches!(receiver.try_recv(), Ok(ToClientPacket::HelloAccepted { protocol_version: PROTOCOL_VERSION, .. })));
+    assert_eq!(connection.protocol().version, PROTOCOL_VERSION);
+}
+
+#[test]
+fn message_pack_is_negotiated_per_connection() {
+    let json = Protocol::negotiate(PROTOCOL_VERSION, VecSet::new()).expect("Current version should be accepted");
+    assert_eq!(json.encoding(), Encoding::Json);
+    assert!(matches!(json.encoding().encode(&ToClientPacket::Pong), Some(Message::Text(_))));
+
+    let message_pack = Protocol::negotiate(PROTOCOL_VERSION, vec![Capability::MessagePack].into_iter().collect())
+        .expect("Current version should be accepted");
+    assert_eq!(message_pack.encoding(), Encoding::MessagePack);
+
+    let packet = ToClientPacket::LobbyName { name: "Mafia".to_string() };
+    let Some(Message::Binary(bytes)) = message_pack.encoding().encode(&packet) else {
+        panic!("MessagePack should be sent in binary frames");
+    };
+    let decoded: serde_json::Value = rmp_serde::from_slice(&bytes).expect("Frame should be MessagePack");
+    assert_eq!(decoded, serde_json::json!({"type": "lobbyName", "name": "Mafia"}));
+
+    let join = ToServerPacket::Join { room_code: 7, password: None };
+    let frame = Message::binary(rmp_serde::to_vec_named(&join).expect("Packet should serialize"));
+    assert!(matches!(Encoding::decode(&frame), Ok(ToServerPacket::Join { room_code: 7, .. })));
+    assert!(matches!(Encoding::decode(&message(&join)), Ok(ToServerPacket::Join { room_code: 7, .. })));
+}
+
+#[test]
+fn sent_packets_resume_until_evicted() {
+    let mut sent = SentPackets::default();
+    let total = Config::get().resume_buffer_size + 10;
+    for _ in 0..total {
+        sent.push(ToClientPacket::Pong);
+    }
+    let last = (total - 1) as u64;
+
+    assert!(sent.since(last).is_some_and(|missed| missed.is_empty()));
+    let missed = sent.since(last - 5).expect("Recent packets should still be buffered");
+    assert_eq!(missed.len(), 5);
+    assert!(matches!(missed.first(), Some(ToClientPacket::Sequenced { sequence, .. }) if *sequence == last - 4));
+
+    // Evicted, and never sent
+    assert!(sent.since(0).is_none());
+    assert!(sent.since(last + 1).is_none());
+
+    let resume = Protocol::negotiate(PROTOCOL_VERSION, vec![Capability::Resume].into_iter().collect())
+        .expect("Current version should be accepted");
+    let packet = sent.push(ToClientPacket::Pong);
+    assert!(matches!(resume.downgrade(packet.clone()), Some(ToClientPacket::Sequenced { .. })));
+    assert!(matches!(Protocol::legacy().downgrade(packet), Some(ToClientPacket::Pong)));
+}
+
+#[test]
+fn sent_state_patches_what_changed() {
+    let mut sent = SentState::default();
+    let votes = |votes_for_player: VecMap<u8, u8>| ToClientPacket::PlayerVotes { votes_for_player };
+
+    assert!(matches!(sent.patch(votes(vec_map![(0, 1), (1, 2)])), Some(ToClientPacket::PlayerVotes { .. })));
+    assert!(sent.patch(votes(vec_map![(1, 2), (0, 1)])).is_none());
+
+    let Some(ToClientPacket::PlayerVotesPatch { votes_for_player: patch }) = sent.patch(votes(vec_map![(1, 3), (2, 1)])) else {
+        panic!("A change should be sent as a patch");
+    };
+    assert_eq!(patch.set, vec_map![(1, 3), (2, 1)]);
+    assert_eq!(patch.removed, vec![0]);
+
+    let mut client_votes = vec_map![(0, 1), (1, 2)];
+    patch.apply(&mut client_votes);
+    assert_eq!(client_votes, vec_map![(1, 3), (2, 1)]);
+
+    // Sequenced packets are patched too, and dropped if nothing changed
+    let sequenced = ToClientPacket::Sequenced { sequence: 0, packet: Box::new(votes(vec_map![(1, 3), (2, 1)])) };
+    assert!(sent.patch(sequenced).is_none());
+
+    // A client that rejoins starts from nothing
+    assert!(sent.patch(ToClientPacket::AcceptJoin { room_code: 0, in_game: true, player_id: 0, spectator: false }).is_some());
+    assert!(matches!(sent.patch(votes(vec_map![(1, 3), (2, 1)])), Some(ToClientPacket::PlayerVotes { .. })));
+}
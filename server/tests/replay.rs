@@ -0,0 +1,65 @@
+mod kit;
+
+use std::time::Duration;
+
+use mafia_server::game::{
+    components::event_journal::{EventJournal, GameInput, GameRecord, JournalEvent, GAME_RECORD_VERSION},
+    phase::PhaseType,
+    replay,
+    role::{detective::Detective, mafioso::Mafioso, Role, RoleState},
+    role_list::{RoleList, RoleOutline},
+    settings::Settings
+};
+
+#[test]
+fn event_journal_records_night() {
+    kit::scenario!(game in Night 1 where
+        sher: Detective,
+        mafia: Mafioso,
+        _townie: Detective
+    );
+    sher.send_ability_input_player_list_typical(mafia);
+    game.next_phase();
+
+    let record = EventJournal::game_record(&game);
+    assert!(record.entries.iter().any(|entry| matches!(&entry.event,
+        JournalEvent::AbilityInput { player, .. } if *player == sher.player_ref()
+    )));
+    assert!(record.entries.iter().any(|entry| matches!(&entry.event,
+        JournalEvent::NightVisits { visits } if visits.iter().any(|v| v.visitor == sher.player_ref() && v.target == mafia.player_ref())
+    )));
+
+    let json = serde_json::to_string(&record).expect("Game record should serialize");
+    let parsed: GameRecord = serde_json::from_str(&json).expect("Game record should deserialize");
+    assert_eq!(parsed.entries.len(), record.entries.len());
+}
+
+#[test]
+fn replay_matches_recorded_outcome() {
+    let mut inputs = vec![GameInput::Tick { time_passed: Duration::from_secs(1) }; 900];
+    inputs.push(GameInput::Quit { player: 0 });
+    inputs.extend(vec![GameInput::Tick { time_passed: Duration::from_secs(1) }; 900]);
+    inputs.push(GameInput::HostForceEndGame);
+
+    let unplayed = GameRecord {
+        version: GAME_RECORD_VERSION,
+        seed: 7,
+        settings: Settings {
+            role_list: RoleList(vec![RoleOutline::default(); 10]),
+            enabled_roles: Role::values().into_iter().collect(),
+            ..Default::default()
+        },
+        players: (0..10).map(|i| i.to_string()).collect(),
+        entries: Vec::new(),
+        inputs,
+        outcome: None,
+    };
+    let game = replay::replay(&unplayed).expect("Any outlines should always generate");
+    let record = EventJournal::game_record(&game);
+    assert!(record.outcome.is_some());
+
+    let json = serde_json::to_string(&record).expect("Game record should serialize");
+    let parsed: GameRecord = serde_json::from_str(&json).expect("Game record should deserialize");
+    let mismatches = replay::verify(&parsed).expect("Replay should start");
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+}
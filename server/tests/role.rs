@@ -4,7 +4,7 @@ use std::{ops::Deref, vec};
 
 pub(crate) use kit::{assert_contains, assert_not_contains};
 
-use mafia_server::game::{ability_input::{ability_selection::AbilitySelection, ControllerID, RoleOptionSelection}, game_conclusion::GameConclusion, role::engineer::Trap};
+use mafia_server::game::{ability_input::{ability_selection::AbilitySelection, ControllerID, RoleOptionSelection}, game_conclusion::GameConclusion, role::engineer::Trap, role_list::{RoleList, RoleOutline}, settings::Settings, test::mock_game};
 pub use mafia_server::game::{
     chat::{ChatMessageVariant, MessageSender, ChatGroup}, 
     grave::*,
@@ -108,6 +108,23 @@ pub use mafia_server::game::{
 // Pub use so that submodules don't have to reimport everything.
 pub use mafia_server::packet::ToServerPacket;
 
+#[test]
+fn same_seed_generates_same_roles() {
+    let settings = Settings {
+        role_list: RoleList(vec![RoleOutline::default(); 10]),
+        enabled_roles: Role::values().into_iter().collect(),
+        ..Default::default()
+    };
+    let generate_roles = |seed| {
+        let game = mock_game(settings.clone(), 10, seed).expect("Any outlines should always generate");
+        PlayerReference::all_players(&game).map(|p| p.role(&game)).collect::<Vec<Role>>()
+    };
+
+    for seed in 0..20 {
+        assert_eq!(generate_roles(seed), generate_roles(seed));
+    }
+}
+
 #[test]
 fn no_unwanted_tags() {
     kit::scenario!(game in Dusk 1 where
@@ -463,8 +480,8 @@ fn rabble_rouser_dies(){
 
 #[test]
 fn psychic_auras(){
-    for _ in 0..20 {
-        kit::scenario!(game in Night 1 where
+    for seed in 0..20 {
+        kit::scenario!(game seeded seed in Night 1 where
             psy: Psychic,
             god: Godfather,
             maf: Framer,
@@ -2840,8 +2857,8 @@ fn only_santa_and_krampus_ends_instantly() {
 
 #[test]
 fn santa_always_gets_their_naughty_selection() {
-    for _ in 0..20 {
-        kit::scenario!(game in Night 1 where
+    for seed in 0..20 {
+        kit::scenario!(game seeded seed in Night 1 where
             santa: SantaClaus,
             nice: Villager,
             naughty: Villager,
@@ -0,0 +1,218 @@
+mod kit;
+
+use mafia_server::{
+    game::{
+        preset::Preset,
+        role::Role,
+        role_generation::{RoleConstraint, RoleGenerator, MAX_SEARCH_STEPS},
+        role_list::{RoleList, RoleOutline, RoleOutlineOptionRoles, RoleSet},
+        role_list_notation::{ConstraintParseError, OptionParseError, OutlineParseError},
+        settings::Settings
+    },
+    lobby::{lobby_access::JoinCredentials, Lobby},
+    packet::{ToClientPacket, ToServerPacket},
+    vec_map::VecMap,
+    websocket_connections::protocol::Protocol
+};
+use rand::{rngs::StdRng, SeedableRng};
+use kit::{lobby_settings, sender};
+
+fn settings(role_list: &str, constraints: &[&str]) -> Settings {
+    Settings {
+        role_list: RoleList::from_notation(role_list).expect("Role list should parse"),
+        enabled_roles: Role::values().into_iter().collect(),
+        role_constraints: constraints.iter().map(|constraint| constraint.parse().expect("Constraint should parse")).collect(),
+        ..Default::default()
+    }
+}
+
+/// The roles of many generated role lists
+fn generate(settings: &Settings) -> Vec<Vec<Role>> {
+    let mut rng = StdRng::seed_from_u64(0);
+    (0..500)
+        .map(|_| settings.create_random_role_assignments(&mut rng)
+            .expect("Role list should generate")
+            .into_iter()
+            .map(|assignment| assignment.role)
+            .collect()
+        )
+        .collect()
+}
+
+#[test]
+fn option_weights_make_roles_more_likely() {
+    let games = generate(&settings("Villager [weight: 9] | Detective", &[]));
+    let villagers = games.iter().filter(|roles| roles.first() == Some(&Role::Villager)).count();
+
+    assert!((400..490).contains(&villagers), "{villagers} of 500 were villagers");
+}
+
+#[test]
+fn role_weights_apply_to_every_outline() {
+    let mut settings = settings("Villager | Detective\nTown Investigative | Villager", &[]);
+    settings.role_weights.insert(Role::Detective, 0);
+    settings.role_weights.insert(Role::Villager, 50);
+
+    let games = generate(&settings);
+    assert!(games.iter().all(|roles| !roles.contains(&Role::Detective)));
+    assert!(games.iter().filter(|roles| roles.get(1) == Some(&Role::Villager)).count() > 350);
+}
+
+#[test]
+fn at_most_constraints_are_never_broken() {
+    let settings = settings("Town Protective\nTown Protective\nTown Protective\nMafioso", &["At most 1: Doctor, Armorsmith"]);
+
+    for roles in generate(&settings) {
+        assert!(roles.iter().filter(|role| matches!(role, Role::Doctor | Role::Armorsmith)).count() <= 1, "{roles:?}");
+    }
+}
+
+#[test]
+fn requirements_are_always_met() {
+    let settings = settings("Jester | Villager\nTown Investigative | Villager\nMafioso", &["Jester requires Town Investigative"]);
+    let town_investigative = RoleOutlineOptionRoles::RoleSet { role_set: RoleSet::TownInvestigative }.get_roles();
+
+    let games = generate(&settings);
+    assert!(games.iter().any(|roles| roles.contains(&Role::Jester)));
+    for roles in games {
+        if roles.contains(&Role::Jester) {
+            assert!(roles.iter().any(|role| town_investigative.contains(role)), "{roles:?}");
+        }
+    }
+}
+
+#[test]
+fn generation_only_fails_when_nothing_fits() {
+    // Picking Jailor first would leave nothing for the second outline
+    let games = generate(&settings("Jailor | Villager\nJailor\nMafioso", &[]));
+    assert!(games.iter().all(|roles| roles.first() == Some(&Role::Villager)));
+
+    let mut rng = StdRng::seed_from_u64(0);
+    assert!(settings("Jester\nVillager", &["Jester requires Town Investigative"]).create_random_role_assignments(&mut rng).is_none());
+    assert!(settings("Doctor\nArmorsmith", &["At most 1: Doctor, Armorsmith"]).create_random_role_assignments(&mut rng).is_none());
+    assert!(settings("Jailor\nJailor", &[]).create_random_role_assignments(&mut rng).is_none());
+}
+
+#[test]
+fn large_constrained_role_lists_generate_quickly() {
+    let settings = settings(
+        &[vec!["Town Protective | Town Investigative"; 10], vec!["Any"; 20]].concat().join("\n"),
+        &["At most 1: Doctor, Armorsmith", "Jester requires Town Investigative", "At most 2: Town Investigative"]
+    );
+    let mut generator = RoleGenerator::new(&settings);
+    assert!(generator.generate(&mut StdRng::seed_from_u64(0)).is_some());
+    assert!(generator.search_steps() < MAX_SEARCH_STEPS, "Took {} steps", generator.search_steps());
+}
+
+#[test]
+fn impossible_counts_are_found_without_searching() {
+    // Searching every way to fill these would never finish
+    let settings = settings(&vec!["Town"; 40].join("\n"), &["At most 39: Town", "Jester requires Mafioso"]);
+    let mut generator = RoleGenerator::new(&settings);
+    assert!(generator.generate(&mut StdRng::seed_from_u64(0)).is_none());
+    assert_eq!(generator.search_steps(), 0);
+}
+
+#[test]
+fn overlapping_constraints_are_still_met() {
+    // Neither constraint is inside the other, so these need searching
+    let settings = settings(
+        &["Town Investigative | Town Protective | Town Support"; 4].join("\n"),
+        &["At most 2: Town Investigative, Town Protective", "At most 2: Town Protective, Town Support"]
+    );
+    for roles in generate(&settings) {
+        let count = |sets: &[RoleSet]| roles.iter().filter(|role| sets.iter().any(|set| set.get_roles().contains(role))).count();
+        assert!(count(&[RoleSet::TownInvestigative, RoleSet::TownProtective]) <= 2);
+        assert!(count(&[RoleSet::TownProtective, RoleSet::TownSupport]) <= 2);
+    }
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let settings = self::settings(
+        &["Town Investigative | Town Protective | Town Support"; 5].join("\n"),
+        &["At most 2: Town Investigative, Town Protective", "At most 2: Town Protective, Town Support"]
+    );
+    assert!(settings.create_random_role_assignments(&mut rng).is_none());
+}
+
+#[test]
+fn constraint_notation() {
+    let constraint: RoleConstraint = "at most 1: doctor,Armorsmith".parse().expect("Constraint should parse");
+    assert_eq!(constraint, RoleConstraint::AtMost {
+        count: 1,
+        roles: vec![RoleOutlineOptionRoles::Role { role: Role::Doctor }, RoleOutlineOptionRoles::Role { role: Role::Armorsmith }]
+    });
+    assert_eq!(constraint.to_string(), "At most 1: Doctor, Armorsmith");
+
+    let constraint: RoleConstraint = "Jester REQUIRES Town Investigative, Mayor".parse().expect("Constraint should parse");
+    assert_eq!(constraint.to_string(), "Jester requires Town Investigative, Mayor");
+    assert_eq!(constraint.to_string().parse(), Ok(constraint));
+
+    assert!(matches!("Jester needs Mayor".parse::<RoleConstraint>(), Err(ConstraintParseError::Malformed(_))));
+    assert!(matches!("At most one: Mayor".parse::<RoleConstraint>(), Err(ConstraintParseError::InvalidCount(_))));
+    assert_eq!("At most 1: Mayor, Mayr".parse::<RoleConstraint>(), Err(ConstraintParseError::Roles(OptionParseError::UnknownRoles("Mayr".to_string()))));
+}
+
+#[test]
+fn weight_notation() {
+    let outline: RoleOutline = "Goon [weight: 3] | Mafia Support".parse().expect("Outline should parse");
+    assert_eq!(outline.options.first().weight, 3);
+    assert_eq!(outline.to_string(), "Goon [weight: 3] | Mafia Support");
+
+    assert_eq!("Goon [weight: lots]".parse::<RoleOutline>(), Err(OutlineParseError {
+        option: 1, error: OptionParseError::InvalidWeight("lots".to_string())
+    }));
+}
+
+#[test]
+fn presets_set_role_weights_and_constraints() {
+    let preset = Preset::from_json(r#"{
+        "format": 1,
+        "name": "Weighted",
+        "roleList": ["Mafia Killing", "Town", "Town"],
+        "roleWeights": {"Goon": 3, "Mafia Killing": 2},
+        "roleConstraints": ["Jester requires Town Investigative"]
+    }"#).expect("Preset should load");
+
+    assert_eq!(preset.settings.role_weight(Role::Goon), 3);
+    assert_eq!(preset.settings.role_weight(Role::Mafioso), 2);
+    assert_eq!(preset.settings.role_weight(Role::Villager), 1);
+    assert_eq!(preset.settings.role_constraints.len(), 1);
+
+    let loaded = Preset::from_json(&preset.to_json().expect("Preset should serialize")).expect("Preset should load");
+    assert_eq!(loaded.settings.role_weights, preset.settings.role_weights);
+    assert_eq!(loaded.settings.role_constraints, preset.settings.role_constraints);
+}
+
+#[test]
+fn host_sets_role_weights_and_constraints() {
+    let mut lobby = Lobby::new(1);
+    let host = sender();
+    let host_id = lobby.join_player(&host, &JoinCredentials::default()).expect("Lobby should have room");
+    let player = sender();
+    let player_id = lobby.join_player(&player, &JoinCredentials::default()).expect("Lobby should have room");
+
+    let constraint: RoleConstraint = "At most 1: Doctor, Armorsmith".parse().expect("Constraint should parse");
+    let weights: VecMap<Role, u8> = vec![(Role::Goon, 4), (Role::Villager, 1)].into_iter().collect();
+
+    lobby.on_client_message(&player, player_id, ToServerPacket::SetRoleWeights { role_weights: weights.clone() });
+    lobby.on_client_message(&player, player_id, ToServerPacket::SetRoleConstraints { constraints: vec![constraint.clone()] });
+    assert!(lobby_settings(&lobby).role_weights.is_empty());
+    assert!(lobby_settings(&lobby).role_constraints.is_empty());
+
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleWeights { role_weights: weights });
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleConstraints { constraints: vec![constraint.clone()] });
+    // A weight of 1 is the default, so it isn't kept
+    assert_eq!(lobby_settings(&lobby).role_weights, vec![(Role::Goon, 4)].into_iter().collect());
+    assert_eq!(lobby_settings(&lobby).role_constraints, vec![constraint.clone()]);
+
+    let too_many = vec![constraint.clone(); RoleConstraint::MAX_CONSTRAINTS + 1];
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleConstraints { constraints: too_many });
+    assert_eq!(lobby_settings(&lobby).role_constraints, vec![constraint]);
+}
+
+#[test]
+fn older_clients_are_not_sent_weights_or_constraints() {
+    let old = Protocol { version: 7, capabilities: Default::default() };
+    assert!(old.downgrade(ToClientPacket::RoleWeights { role_weights: VecMap::new() }).is_none());
+    assert!(old.downgrade(ToClientPacket::RoleConstraints { constraints: Vec::new() }).is_none());
+}
@@ -0,0 +1,172 @@
+mod kit;
+
+use std::time::Duration;
+
+use mafia_server::{
+    game::{
+        role::Role,
+        role_list::RoleList,
+        role_list_analysis::{MaxCountConflict, RoleListAnalysis, SAMPLES},
+        settings::Settings
+    },
+    lobby::{lobby_access::JoinCredentials, Lobby},
+    packet::{ToClientPacket, ToServerPacket},
+    websocket_connections::protocol::Protocol
+};
+use kit::client;
+
+fn analyze(role_list: &str, enabled_roles: Vec<Role>) -> RoleListAnalysis {
+    RoleListAnalysis::new(&Settings {
+        role_list: RoleList::from_notation(role_list).expect("Role list should parse"),
+        enabled_roles: enabled_roles.into_iter().collect(),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn playable_role_list_has_nothing_to_report() {
+    let analysis = analyze("Mafioso\nVillager\nVillager\nVillager\nTown Investigative", Role::values());
+
+    assert!(analysis.can_generate());
+    assert_eq!(analysis.samples, SAMPLES);
+    assert_eq!(analysis.failed_generations, 0);
+    assert_eq!(analysis.instant_game_overs, 0);
+}
+
+#[test]
+fn outlines_without_enabled_roles_are_unsatisfiable() {
+    let analysis = analyze("Mafioso\nJailor\nTown Killing | Mayor\nVillager", vec![Role::Mafioso, Role::Villager, Role::Mayor]);
+
+    assert_eq!(analysis.unsatisfiable_outlines, vec![1]);
+    assert!(analysis.max_count_conflicts.is_empty());
+    assert!(!analysis.can_generate());
+    assert_eq!(analysis.samples, 0);
+}
+
+#[test]
+fn outlines_that_need_too_many_copies_conflict() {
+    let analysis = analyze("Mafioso\nVillager\nJailor\nJailor", Role::values());
+    assert_eq!(analysis.max_count_conflicts, vec![MaxCountConflict { outlines: vec![2, 3], roles: vec![Role::Jailor] }]);
+
+    // Neither outline is a problem alone, and the earlier outline could have taken either role
+    let analysis = analyze(
+        "Jailor | Mayor\nMafioso\nMayor | Jailor\nJailor | Mayor\nVillager",
+        Role::values()
+    );
+    assert_eq!(analysis.max_count_conflicts, vec![MaxCountConflict { outlines: vec![0, 2, 3], roles: vec![Role::Jailor, Role::Mayor] }]);
+    assert!(analysis.unsatisfiable_outlines.is_empty());
+    assert!(!analysis.can_generate());
+
+    // A role can move to another outline to make room
+    let analysis = analyze("Jailor | Mayor\nMayor\nMafioso", Role::values());
+    assert!(analysis.can_generate());
+}
+
+#[test]
+fn outlines_that_only_sometimes_fit_never_fail() {
+    let analysis = analyze("Jailor | Villager\nJailor\nMafioso", Role::values());
+
+    assert!(analysis.can_generate());
+    assert_eq!(analysis.failed_generations, 0);
+}
+
+#[test]
+fn constraints_nothing_can_meet_fail_every_generation() {
+    let analysis = RoleListAnalysis::new(&Settings {
+        role_list: RoleList::from_notation("Jester\nVillager\nMafioso").expect("Role list should parse"),
+        enabled_roles: Role::values().into_iter().collect(),
+        role_constraints: vec!["Jester requires Town Investigative".parse().expect("Constraint should parse")],
+        ..Default::default()
+    });
+
+    assert!(!analysis.can_generate());
+    assert!(analysis.unsatisfiable_outlines.is_empty());
+    assert_eq!(analysis.failed_generations, analysis.samples);
+}
+
+#[test]
+fn estimates_how_often_the_game_starts_over() {
+    // Nobody can kill, so town has already won
+    let analysis = analyze("Villager\nVillager\nDetective", Role::values());
+    assert_eq!(analysis.instant_game_overs, analysis.samples);
+
+    let analysis = analyze("Mafioso | Villager\nVillager\nVillager", Role::values());
+    assert!(analysis.instant_game_overs > 0);
+    assert!(analysis.instant_game_overs < analysis.samples);
+
+    // The same settings always get the same estimate
+    assert_eq!(analyze("Mafioso | Villager\nVillager\nVillager", Role::values()), analysis);
+}
+
+#[test]
+fn large_role_lists_are_analyzed_with_a_fixed_number_of_samples() {
+    let role_list = ["Any"; 30].join("\n");
+    let analysis = analyze(&role_list, Role::values());
+
+    assert!(analysis.can_generate());
+    assert_eq!(analysis.samples, SAMPLES);
+}
+
+#[test]
+fn lobby_is_sent_analysis_when_settings_change() {
+    let (host, mut packets) = client();
+    let mut lobby = Lobby::new(1);
+    let host_id = lobby.join_player(&host, &JoinCredentials::default()).expect("Lobby should have room");
+
+    let mut analyses = || {
+        let mut analyses = Vec::new();
+        while let Ok(packet) = packets.try_recv() {
+            if let ToClientPacket::RoleListAnalysis { analysis } = packet {
+                analyses.push(analysis);
+            }
+        }
+        analyses
+    };
+    assert_eq!(analyses().len(), 1);
+
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetEnabledRoles { roles: vec![Role::Villager] });
+    let Some(analysis) = analyses().pop() else {panic!("Changing enabled roles should send an analysis")};
+    assert_eq!(analysis.unsatisfiable_outlines, Vec::<u8>::new());
+
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleList {
+        role_list: RoleList::from_notation("Mafioso").expect("Role list should parse")
+    });
+    let Some(analysis) = analyses().pop() else {panic!("Changing the role list should send an analysis")};
+    assert_eq!(analysis.unsatisfiable_outlines, vec![0]);
+
+    lobby.add_bot().expect("Lobby should have room");
+    assert_eq!(analyses().len(), 1);
+}
+
+#[test]
+fn older_clients_are_not_sent_analysis() {
+    let old = Protocol { version: 6, capabilities: Default::default() };
+    assert!(old.downgrade(ToClientPacket::RoleListAnalysis { analysis: RoleListAnalysis::default() }).is_none());
+}
+
+#[tokio::test]
+async fn analysis_is_worked_out_in_the_background_and_kept() {
+    let (host, mut packets) = client();
+    let mut lobby = Lobby::new(1);
+    let host_id = lobby.join_player(&host, &JoinCredentials::default()).expect("Lobby should have room");
+    let mut analyses = || std::iter::from_fn(|| packets.try_recv().ok())
+        .filter(|packet| matches!(packet, ToClientPacket::RoleListAnalysis { .. }))
+        .count();
+
+    let role_list = RoleList::from_notation("Mafioso").expect("Role list should parse");
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleList { role_list: role_list.clone() });
+    // Only sent by a tick once it's done
+    assert_eq!(analyses(), 0);
+    let mut sent = 0;
+    for _ in 0..100 {
+        lobby.tick(Duration::ZERO);
+        sent = analyses();
+        if sent != 0 {break}
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(sent, 1);
+
+    // Nothing it depends on changed, so it's sent again straight away
+    lobby.on_client_message(&host, host_id, ToServerPacket::SetRoleList { role_list });
+    assert_eq!(analyses(), 1);
+}
@@ -1,3 +1,3 @@
 source "$HOME/.cargo/env"
 cd /mafia/server
-cargo run --release
+exec cargo run --release